@@ -0,0 +1,265 @@
+//! User-defined command aliases (`config set alias.nls "network list --output table"`),
+//! expanded git-style before clap parses the command line. Built-in commands always win,
+//! and unambiguous prefixes of built-in commands (`netw mem ls`) are left to clap's
+//! `infer_subcommands`.
+
+use std::collections::{BTreeMap, HashSet};
+use std::ffi::OsString;
+
+use clap::{Arg, Command, CommandFactory};
+
+use crate::cli::Cli;
+use crate::config;
+use crate::error::CliError;
+
+/// Replaces an alias in command position with its expansion. Global options written after
+/// the command inside an alias (`network list --output table`) are moved in front of it,
+/// where clap expects them. A config that cannot be read is left for the command itself to
+/// report.
+pub(crate) fn expand(args: Vec<OsString>) -> Result<Vec<OsString>, CliError> {
+	let root = Cli::command();
+	let Some(index) = command_position(&root, &args) else {
+		return Ok(args);
+	};
+	let Some(name) = args[index].to_str() else {
+		return Ok(args);
+	};
+	if is_builtin(&root, name) {
+		return Ok(args);
+	}
+	let Ok(cfg) = config::default_config_path().and_then(|path| config::load_config(&path)) else {
+		return Ok(args);
+	};
+	if !cfg.aliases.contains_key(name) {
+		return Ok(args);
+	}
+
+	let words = resolve(&root, &cfg.aliases, name)?;
+	let words = hoist_global_options(&root, words);
+	let mut expanded = args[..index].to_vec();
+	expanded.extend(words.into_iter().map(OsString::from));
+	expanded.extend(args[index + 1..].iter().cloned());
+	Ok(expanded)
+}
+
+/// Checks a new alias before it is saved: a usable name that does not shadow a built-in
+/// command, and a value that splits into words.
+pub(crate) fn validate(name: &str, value: &str) -> Result<(), CliError> {
+	if name.is_empty() || name.starts_with('-') || name.contains(char::is_whitespace) {
+		return Err(CliError::InvalidArgument(format!("invalid alias name '{name}'")));
+	}
+	if is_builtin(&Cli::command(), name) {
+		return Err(CliError::InvalidArgument(format!(
+			"alias '{name}' would never run: '{name}' is a built-in command"
+		)));
+	}
+	if split_words(value)?.is_empty() {
+		return Err(CliError::InvalidArgument(format!("alias '{name}' is empty")));
+	}
+	Ok(())
+}
+
+/// Expands `name`, following aliases of aliases until a built-in command is reached.
+fn resolve(root: &Command, aliases: &BTreeMap<String, String>, name: &str) -> Result<Vec<String>, CliError> {
+	let mut seen = HashSet::new();
+	let mut words = vec![name.to_string()];
+	while let Some(value) = aliases.get(&words[0]) {
+		if is_builtin(root, &words[0]) {
+			break;
+		}
+		if !seen.insert(words[0].clone()) {
+			return Err(CliError::InvalidArgument(format!("alias '{name}' expands to itself (via '{}')", words[0])));
+		}
+		let mut next = split_words(value)?;
+		if next.is_empty() {
+			return Err(CliError::InvalidArgument(format!("alias '{}' is empty", words[0])));
+		}
+		next.extend(words.drain(1..));
+		words = next;
+	}
+	Ok(words)
+}
+
+fn is_builtin(root: &Command, name: &str) -> bool {
+	root.get_subcommands().any(|sub| sub.get_name() == name || sub.get_all_aliases().any(|a| a == name))
+		|| name == "help"
+}
+
+/// Index of the first word that is not a global option (or an option's value).
+fn command_position(root: &Command, args: &[OsString]) -> Option<usize> {
+	let mut index = 1;
+	while index < args.len() {
+		let word = args[index].to_str()?;
+		if word == "--" || !word.starts_with('-') || word == "-" {
+			return (word != "--").then_some(index);
+		}
+		index += if option_takes_separate_value(root, word) { 2 } else { 1 };
+	}
+	None
+}
+
+/// Whether `word` is an option of `command` whose value is the next word.
+fn option_takes_separate_value(command: &Command, word: &str) -> bool {
+	find_option(command, word).is_some_and(|arg| arg.get_action().takes_values() && !attached_value(word))
+}
+
+fn attached_value(word: &str) -> bool {
+	if word.starts_with("--") {
+		word.contains('=')
+	} else {
+		word.len() > 2
+	}
+}
+
+fn find_option<'a>(command: &'a Command, word: &str) -> Option<&'a Arg> {
+	if let Some(long) = word.strip_prefix("--") {
+		let long = long.split('=').next().unwrap_or_default();
+		return command
+			.get_arguments()
+			.find(|arg| arg.get_long() == Some(long) || arg.get_all_aliases().is_some_and(|a| a.contains(&long)));
+	}
+	let short = word.strip_prefix('-')?.chars().next()?;
+	command.get_arguments().find(|arg| arg.get_short() == Some(short))
+}
+
+/// Moves global options that follow the subcommand path in front of it, unless the
+/// subcommand defines an option of the same name itself (e.g. `member list --org`).
+fn hoist_global_options(root: &Command, words: Vec<String>) -> Vec<String> {
+	let mut hoisted = Vec::new();
+	let mut rest = Vec::with_capacity(words.len());
+	let mut command = root;
+	let mut iter = words.into_iter();
+	while let Some(word) = iter.next() {
+		if word == "--" {
+			rest.push(word);
+			rest.extend(iter.by_ref());
+			break;
+		}
+		if !word.starts_with('-') || word == "-" {
+			if let Some(sub) = find_subcommand(command, &word) {
+				command = sub;
+			}
+			rest.push(word);
+			continue;
+		}
+
+		let own = find_option(command, &word);
+		let target = if own.is_none() && !std::ptr::eq(command, root) && find_option(root, &word).is_some() {
+			&mut hoisted
+		} else {
+			&mut rest
+		};
+		let takes_value = option_takes_separate_value(if own.is_some() { command } else { root }, &word);
+		target.push(word);
+		if takes_value && let Some(value) = iter.next() {
+			target.push(value);
+		}
+	}
+	hoisted.extend(rest);
+	hoisted
+}
+
+/// A subcommand by name, alias or unambiguous prefix (matching clap's inference).
+fn find_subcommand<'a>(command: &'a Command, word: &str) -> Option<&'a Command> {
+	if let Some(sub) = command.find_subcommand(word) {
+		return Some(sub);
+	}
+	let mut matches = command.get_subcommands().filter(|sub| sub.get_name().starts_with(word));
+	let first = matches.next()?;
+	matches.next().is_none().then_some(first)
+}
+
+/// Shell-style splitting: whitespace separates words; single and double quotes group, and
+/// a backslash escapes the next character outside single quotes.
+pub(crate) fn split_words(text: &str) -> Result<Vec<String>, CliError> {
+	let mut words = Vec::new();
+	let mut current = String::new();
+	let mut in_word = false;
+	let mut quote = None;
+	let mut chars = text.chars();
+	while let Some(c) = chars.next() {
+		match (quote, c) {
+			(Some(q), c) if c == q => quote = None,
+			(Some('\''), c) => current.push(c),
+			(_, '\\') => {
+				in_word = true;
+				current.extend(chars.next());
+			}
+			(Some(_), c) => current.push(c),
+			(None, '\'' | '"') => {
+				in_word = true;
+				quote = Some(c);
+			}
+			(None, c) if c.is_whitespace() => {
+				if in_word {
+					words.push(std::mem::take(&mut current));
+					in_word = false;
+				}
+			}
+			(None, c) => {
+				in_word = true;
+				current.push(c);
+			}
+		}
+	}
+	if quote.is_some() {
+		return Err(CliError::InvalidArgument(format!("unbalanced quote in `{text}`")));
+	}
+	if in_word {
+		words.push(current);
+	}
+	Ok(words)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn strings(words: &[&str]) -> Vec<String> {
+		words.iter().map(|w| w.to_string()).collect()
+	}
+
+	#[test]
+	fn aliases_expand_chain_and_hoist_global_options() {
+		let root = Cli::command();
+		let aliases = BTreeMap::from([
+			("nls".to_string(), "network list --output table".to_string()),
+			("mls".to_string(), "member list --org acme -v".to_string()),
+			("n".to_string(), "nls --json".to_string()),
+			("loop".to_string(), "loop2".to_string()),
+			("loop2".to_string(), "loop".to_string()),
+		]);
+
+		let words = hoist_global_options(&root, resolve(&root, &aliases, "nls").unwrap());
+		assert_eq!(words, strings(&["--output", "table", "network", "list"]));
+		// `member list` has its own --org, so only -v moves.
+		let words = hoist_global_options(&root, resolve(&root, &aliases, "mls").unwrap());
+		assert_eq!(words, strings(&["-v", "member", "list", "--org", "acme"]));
+		let words = hoist_global_options(&root, resolve(&root, &aliases, "n").unwrap());
+		assert_eq!(words, strings(&["--output", "table", "--json", "network", "list"]));
+		assert!(resolve(&root, &aliases, "loop").is_err());
+
+		let args: Vec<OsString> = ["ztnet", "-H", "http://x", "--quiet", "nls"].iter().map(OsString::from).collect();
+		assert_eq!(command_position(&root, &args), Some(4));
+		assert!(validate("network", "org list").is_err());
+		assert!(validate("nl", "'network list").is_err());
+	}
+
+	#[test]
+	fn split_words_handles_quotes_and_escapes() {
+		assert_eq!(
+			split_words(r#"network create "lab net" --description 'a "b"' x\ y ''"#).unwrap(),
+			strings(&["network", "create", "lab net", "--description", "a \"b\"", "x y", ""])
+		);
+		assert!(split_words("it's").is_err());
+	}
+
+	#[test]
+	fn unambiguous_prefixes_parse() {
+		use clap::Parser;
+		let cli = Cli::try_parse_from(["ztnet", "netw", "mem", "li", "lab"]).unwrap();
+		assert!(matches!(cli.command, crate::cli::Command::Network { .. }));
+		// `ex` could be `export` or `examples`.
+		assert!(Cli::try_parse_from(["ztnet", "ex", "hosts"]).is_err());
+	}
+}
@@ -1,46 +1,195 @@
+mod agent;
 mod api;
 mod admin;
 mod auth;
+mod bootstrap;
 mod common;
 mod config_cmd;
+mod examples;
 mod export;
+mod filter;
+mod help;
+mod host;
+mod hosts_file;
 mod member;
+mod middleware;
 mod network;
+mod network_summary;
 mod network_trpc;
 mod org;
 mod planet;
+mod powerdns;
+mod refresh_lock;
 mod resolve;
+mod retention;
+mod schema;
 mod stats;
 mod trpc;
 mod trpc_client;
 mod trpc_resolve;
+mod trpc_script;
 mod user;
+mod verify;
+mod version;
 
 use clap::CommandFactory;
 
-use crate::cli::{Cli, Command};
+use crate::cancel;
+use crate::cli::{
+	AdminBackupCommand, AdminCommand, AgentCommand, ApiCommand, AuthCommand, BootstrapArgs, Cli, Command,
+	ConfigCommand, ExportCommand, GlobalOpts, HostCommand, MemberCommand, NetworkCommand, OrgCommand, PlanetCommand,
+	StatsCommand, TrpcCommand, UserCommand,
+};
+use crate::encrypt;
 use crate::error::CliError;
+use crate::output;
+use crate::sign::Signer;
 
 pub async fn run(cli: Cli) -> Result<(), CliError> {
 	let Cli { global, command } = cli;
+	let recipients = encrypt::parse_recipients(&global.encrypt_to)?;
+	if !recipients.is_empty() && global.out.is_none() && !writes_file(&command) {
+		return Err(CliError::InvalidArgument(
+			"--encrypt-to only encrypts files; add --out FILE (or use export hosts --out / a backup download)"
+				.to_string(),
+		));
+	}
+	if global.sign_key.is_some() && global.out.is_none() && !writes_file(&command) {
+		return Err(CliError::InvalidArgument(
+			"--sign-key only signs files; add --out FILE (or use export hosts --out / a backup download)".to_string(),
+		));
+	}
+	let signer = global.sign_key.as_deref().map(Signer::load).transpose()?;
+	output::init(global.out.as_deref(), global.max_body_print, recipients, signer, !global.no_pager)?;
+	cancel::install(global.quiet);
+	crate::progress::install(global.progress);
+
+	let result = dispatch(&global, command).await;
+	output::finish(keeps_output(&result))?;
+	result
+}
 
+/// Commands that write their own output file, which `--encrypt-to` also covers.
+fn writes_file(command: &Command) -> bool {
 	match command {
+		Command::Export {
+			command: ExportCommand::Hosts(args),
+		} => args.out.is_some(),
+		Command::Admin {
+			command: AdminCommand::Backup { command },
+		} => match command {
+			AdminBackupCommand::Create(args) => args.download.is_some(),
+			AdminBackupCommand::Download(_) => true,
+			_ => false,
+		},
+		_ => false,
+	}
+}
+
+/// Whether the `--out` file holds a usable result: output is written in whole blocks, so
+/// whatever was printed before a partial failure, drift report, dry run or Ctrl-C is
+/// complete; other errors leave nothing useful.
+fn keeps_output(result: &Result<(), CliError>) -> bool {
+	match result {
+		Ok(()) => true,
+		Err(CliError::Interrupted) => output::has_output(),
+		Err(err) => matches!(
+			err,
+			CliError::DryRunPrinted | CliError::PartialFailure { .. } | CliError::Drift(_)
+		),
+	}
+}
+
+async fn dispatch(global: &GlobalOpts, command: Command) -> Result<(), CliError> {
+	// Commands that never touch the config file or the server skip the middleware chain.
+	let command = match command {
 		Command::Completion(args) => {
 			let mut cmd = Cli::command();
 			clap_complete::generate(args.shell, &mut cmd, "ztnet", &mut std::io::stdout());
-			Ok(())
+			return Ok(());
+		}
+		Command::Version(args) => return version::run(global, args).await,
+		Command::Schema(args) => return schema::run(global, args).await,
+		Command::Help(args) => return help::run(args),
+		Command::Examples(args) => return examples::run(global, args),
+		Command::Verify(args) => return verify::run(global, args),
+		Command::Auth { command } => ChainCommand::Auth(command),
+		Command::Admin { command } => ChainCommand::Admin(command),
+		Command::Config { command } => ChainCommand::Config(command),
+		Command::User { command } => ChainCommand::User(command),
+		Command::Org { command } => ChainCommand::Org(command),
+		Command::Network { command } => ChainCommand::Network(command),
+		Command::Member { command } => ChainCommand::Member(command),
+		Command::Stats { command } => ChainCommand::Stats(command),
+		Command::Planet { command } => ChainCommand::Planet(command),
+		Command::Export { command } => ChainCommand::Export(command),
+		Command::Api { command } => ChainCommand::Api(command),
+		Command::Trpc { command } => ChainCommand::Trpc(command),
+		Command::Host { command } => ChainCommand::Host(command),
+		Command::Bootstrap(args) => ChainCommand::Bootstrap(args),
+		Command::Agent { command } => ChainCommand::Agent(command),
+	};
+
+	let name = command.name();
+	let mut timing = middleware::Timing::default();
+	middleware::run_chain(global, name, &mut [&mut timing], async |ctx| match command {
+		ChainCommand::Auth(command) => auth::run(ctx, command).await,
+		ChainCommand::Admin(command) => admin::run(ctx, command).await,
+		ChainCommand::Config(command) => config_cmd::run(ctx, command).await,
+		ChainCommand::User(command) => user::run(ctx, command).await,
+		ChainCommand::Org(command) => org::run(ctx, command).await,
+		ChainCommand::Network(command) => network::run(ctx, command).await,
+		ChainCommand::Member(command) => member::run_alias(ctx, command).await,
+		ChainCommand::Stats(command) => stats::run(ctx, command).await,
+		ChainCommand::Planet(command) => planet::run(ctx, command).await,
+		ChainCommand::Export(command) => export::run(ctx, command).await,
+		ChainCommand::Api(command) => api::run(ctx, command).await,
+		ChainCommand::Trpc(command) => trpc::run(ctx, command).await,
+		ChainCommand::Host(command) => host::run(ctx, command).await,
+		ChainCommand::Bootstrap(args) => bootstrap::run(ctx, args).await,
+		ChainCommand::Agent(command) => agent::run(ctx, command).await,
+	})
+	.await
+}
+
+/// The commands that run inside the middleware chain, i.e. everything `dispatch` does not
+/// answer up front.
+enum ChainCommand {
+	Auth(AuthCommand),
+	Admin(AdminCommand),
+	Config(ConfigCommand),
+	User(UserCommand),
+	Org(OrgCommand),
+	Network(NetworkCommand),
+	Member(MemberCommand),
+	Stats(StatsCommand),
+	Planet(PlanetCommand),
+	Export(ExportCommand),
+	Api(ApiCommand),
+	Trpc(TrpcCommand),
+	Host(HostCommand),
+	Bootstrap(BootstrapArgs),
+	Agent(AgentCommand),
+}
+
+impl ChainCommand {
+	fn name(&self) -> &'static str {
+		match self {
+			Self::Auth(_) => "auth",
+			Self::Admin(_) => "admin",
+			Self::Config(_) => "config",
+			Self::User(_) => "user",
+			Self::Org(_) => "org",
+			Self::Network(_) => "network",
+			Self::Member(_) => "member",
+			Self::Stats(_) => "stats",
+			Self::Planet(_) => "planet",
+			Self::Export(_) => "export",
+			Self::Api(_) => "api",
+			Self::Trpc(_) => "trpc",
+			Self::Host(_) => "host",
+			Self::Bootstrap(_) => "bootstrap",
+			Self::Agent(_) => "agent",
 		}
-		Command::Auth { command } => auth::run(&global, command).await,
-		Command::Admin { command } => admin::run(&global, command).await,
-		Command::Config { command } => config_cmd::run(&global, command).await,
-		Command::User { command } => user::run(&global, command).await,
-		Command::Org { command } => org::run(&global, command).await,
-		Command::Network { command } => network::run(&global, command).await,
-		Command::Member { command } => member::run_alias(&global, command).await,
-		Command::Stats { command } => stats::run(&global, command).await,
-		Command::Planet { command } => planet::run(&global, command).await,
-		Command::Export { command } => export::run(&global, command).await,
-		Command::Api { command } => api::run(&global, command).await,
-		Command::Trpc { command } => trpc::run(&global, command).await,
 	}
 }
@@ -3,31 +3,42 @@ use std::time::{SystemTime, UNIX_EPOCH};
 use base64::Engine;
 use serde_json::{json, Value};
 
+use crate::cancel;
 use crate::cli::{
 	AdminBackupCommand, AdminCommand, AdminInvitesCommand, AdminMailCommand,
 	AdminMailTemplatesCommand, AdminSettingsCommand, AdminUsersCommand, GlobalOpts,
-	MailTemplateKeyArg, OutputFormat, UserRole,
+	MailTemplateKeyArg, OutputFormat, RetentionArgs, UserRole,
 };
-use crate::context::resolve_effective_config;
 use crate::error::CliError;
-use crate::http::ClientUi;
 use crate::output;
 
-use super::common::{confirm, load_config_store, print_human_or_machine};
-use super::trpc_client::{require_cookie_from_effective, TrpcClient};
-
-pub(super) async fn run(global: &GlobalOpts, command: AdminCommand) -> Result<(), CliError> {
-	let (_config_path, cfg) = load_config_store()?;
-	let effective = resolve_effective_config(global, &cfg)?;
+use super::common::{BulkReport, confirm, print_human_or_machine, read_input_file};
+use super::middleware::{trpc_anonymous, trpc_authed, CommandContext};
+use super::retention::{self, Dated};
+use super::trpc_client::{TrpcClient, date_meta};
+
+pub(super) async fn run(ctx: &CommandContext<'_>, command: AdminCommand) -> Result<(), CliError> {
+	let (global, effective) = (ctx.global, &ctx.effective);
+
+	if let AdminCommand::Settings {
+		command: AdminSettingsCommand::Get(args),
+	} = &command
+		&& args.public
+	{
+		let trpc = trpc_anonymous(global, effective)?;
+		let response = public_settings(&trpc).await?;
+		print_human_or_machine(&response, effective.output, global.no_color)?;
+		return Ok(());
+	}
 
-	let trpc = trpc_authed(global, &effective)?;
+	let trpc = trpc_authed(global, effective)?;
 
 	match command {
-		AdminCommand::Users { command } => users(global, &effective, &trpc, command).await,
-		AdminCommand::Backup { command } => backup(global, &effective, &trpc, command).await,
-		AdminCommand::Mail { command } => mail(global, &effective, &trpc, command).await,
-		AdminCommand::Settings { command } => settings(global, &effective, &trpc, command).await,
-		AdminCommand::Invites { command } => invites(global, &effective, &trpc, command).await,
+		AdminCommand::Users { command } => users(global, effective, &trpc, command).await,
+		AdminCommand::Backup { command } => backup(global, effective, &trpc, command).await,
+		AdminCommand::Mail { command } => mail(global, effective, &trpc, command).await,
+		AdminCommand::Settings { command } => settings(global, effective, &trpc, command).await,
+		AdminCommand::Invites { command } => invites(global, effective, &trpc, command).await,
 	}
 }
 
@@ -64,33 +75,77 @@ async fn users(
 			Ok(())
 		}
 		AdminUsersCommand::Update(args) => {
-			if args.role.is_none() && !args.active && !args.inactive {
+			if args.role.is_none()
+				&& !args.active
+				&& !args.inactive
+				&& !args.send_password_reset
+				&& !args.verify_email
+				&& args.expire.is_none()
+			{
 				return Err(CliError::InvalidArgument(
-					"no update fields provided (use --role and/or --active/--inactive)".to_string(),
+					"no update fields provided (use --role, --active/--inactive, --send-password-reset, --verify-email or --expire)"
+						.to_string(),
 				));
 			}
+			let expires_at = args.expire.as_deref().map(parse_expiry).transpose()?;
 
 			let mut result = serde_json::Map::new();
 
 			if let Some(role) = args.role {
 				let response = trpc
-					.call(
+					.call_step(
 						"admin.changeRole",
 						json!({ "id": &args.user, "role": user_role_to_string(role) }),
 					)
 					.await?;
-				result.insert("role".to_string(), response);
+				if let Some(response) = response {
+					result.insert("role".to_string(), response);
+				}
 			}
 
+			let mut params = serde_json::Map::new();
+			let mut dates = Vec::new();
 			if args.active || args.inactive {
-				let is_active = args.active;
+				params.insert("isActive".to_string(), Value::Bool(args.active));
+			}
+			if args.verify_email {
+				params.insert("emailVerified".to_string(), json!(now_rfc3339()));
+				dates.push("params.emailVerified");
+			}
+			if let Some(expires_at) = expires_at {
+				if !expires_at.is_null() {
+					dates.push("params.expiresAt");
+				}
+				params.insert("expiresAt".to_string(), expires_at);
+			}
+			if !params.is_empty() {
 				let response = trpc
-					.call(
+					.call_step_with_meta(
 						"admin.updateUser",
-						json!({ "id": &args.user, "params": { "isActive": is_active } }),
+						json!({ "id": &args.user, "params": params }),
+						date_meta(&dates),
 					)
 					.await?;
-				result.insert("status".to_string(), response);
+				if let Some(response) = response {
+					result.insert("status".to_string(), response);
+				}
+			}
+
+			if args.send_password_reset {
+				let user = trpc.query("admin.getUser", json!({ "userId": &args.user })).await?;
+				let email = user["email"].as_str().ok_or_else(|| {
+					CliError::InvalidArgument(format!("user '{}' has no email address to send a reset link to", args.user))
+				})?;
+				let response = trpc
+					.call_step("auth.passwordResetLink", json!({ "email": email }))
+					.await?;
+				if let Some(response) = response {
+					result.insert("passwordReset".to_string(), response);
+				}
+			}
+
+			if global.dry_run {
+				return Err(CliError::DryRunPrinted);
 			}
 
 			if matches!(effective.output, OutputFormat::Table) && result.is_empty() {
@@ -120,42 +175,84 @@ async fn backup(
 			let mut input = serde_json::Map::new();
 			input.insert("includeDatabase".to_string(), Value::Bool(!args.no_database));
 			input.insert("includeZerotier".to_string(), Value::Bool(!args.no_zerotier));
-			if let Some(name) = args.name {
+			if let Some(name) = args.name.clone() {
 				input.insert("backupName".to_string(), Value::String(name));
 			}
 
+			let wait = args.wait || args.download.is_some() || args.retention.is_set();
+			let before = if wait {
+				backup_names(&trpc.query("admin.listBackups", Value::Null).await?)
+			} else {
+				Vec::new()
+			};
+
 			let response = trpc.call("admin.createBackup", Value::Object(input)).await?;
-			print_human_or_machine(&response, effective.output, global.no_color)?;
-			Ok(())
-		}
-		AdminBackupCommand::Download(args) => {
-			let response = trpc
-				.query("admin.downloadBackup", json!({ "fileName": args.backup }))
-				.await?;
+			if !wait {
+				print_human_or_machine(&response, effective.output, global.no_color)?;
+				return Ok(());
+			}
 
-			let data = response
-				.get("data")
-				.and_then(|v| v.as_str())
-				.ok_or_else(|| CliError::InvalidArgument("backup download returned no data".to_string()))?;
+			let wait_started = std::time::Instant::now();
+			// A bare string response is a status message, not a file name.
+			let expected = response.is_object().then(|| backup_name(&response)).flatten();
+			let name = loop {
+				let listed = backup_names(&trpc.query("admin.listBackups", Value::Null).await?);
+				if let Some(name) = find_new_backup(&listed, &before, expected.as_deref(), args.name.as_deref()) {
+					break name;
+				}
+				if wait_started.elapsed() >= args.wait_timeout {
+					return Err(CliError::Timeout(format!(
+						"backup did not appear in the backup list within {}",
+						humantime::format_duration(args.wait_timeout)
+					)));
+				}
+				cancel::sleep(args.poll_interval).await?;
+			};
+			if !global.quiet {
+				eprintln!("Backup '{name}' is ready.");
+			}
 
-			let bytes = base64::engine::general_purpose::STANDARD
-				.decode(data)
-				.map_err(|err| CliError::InvalidArgument(format!("invalid base64: {err}")))?;
+			let mut bytes = None;
+			if let Some(out) = args.download.as_deref() {
+				let size = download_backup(trpc, &name, out).await?;
+				if !global.quiet {
+					eprintln!("Wrote backup to {} ({}).", out.display(), output::format_size(size));
+				}
+				bytes = Some(size);
+			}
 
-			if let Some(parent) = args.out.parent() {
-				std::fs::create_dir_all(parent)?;
+			let mut summary = json!({
+				"backup": name,
+				"out": args.download.as_ref().map(|p| p.to_string_lossy()),
+				"bytes": bytes,
+			});
+			let mut failed = 0;
+			if args.retention.is_set() {
+				let pruned = prune_backups(global, trpc, &args.retention).await?;
+				failed = pruned.iter().filter(|row| row["status"] == "failed").count();
+				summary["pruned"] = Value::from(pruned);
+			}
+			print_human_or_machine(&summary, effective.output, global.no_color)?;
+			if failed > 0 {
+				return Err(CliError::PartialFailure {
+					failed,
+					total: summary["pruned"].as_array().map_or(0, Vec::len),
+				});
 			}
-			std::fs::write(&args.out, bytes)?;
+			Ok(())
+		}
+		AdminBackupCommand::Download(args) => {
+			let size = download_backup(trpc, &args.backup, &args.out).await?;
 
 			if !global.quiet {
-				eprintln!("Wrote backup to {}.", args.out.display());
+				eprintln!("Wrote backup to {} ({}).", args.out.display(), output::format_size(size));
 			}
 
 			if matches!(effective.output, OutputFormat::Table) {
 				return Ok(());
 			}
 
-			let out = json!({ "out": args.out.to_string_lossy() });
+			let out = json!({ "out": args.out.to_string_lossy(), "bytes": size });
 			output::print_value(&out, effective.output, global.no_color)?;
 			Ok(())
 		}
@@ -191,7 +288,130 @@ async fn backup(
 			print_human_or_machine(&response, effective.output, global.no_color)?;
 			Ok(())
 		}
+		AdminBackupCommand::Prune(args) => {
+			let pruned = prune_backups(global, trpc, &args).await?;
+			if global.dry_run {
+				return Err(CliError::DryRunPrinted);
+			}
+			let mut report = BulkReport::new(false);
+			for row in pruned {
+				let failed = row["status"] == "failed";
+				report.push(row, failed)?;
+			}
+			report.finish(global, effective.output)
+		}
+	}
+}
+
+/// Deletes the backups `policy` does not keep, after one confirmation, and returns one
+/// `{backup, status, error}` row per backup it tried (`deleted` or `failed`); a failed
+/// delete does not stop the rest. Backups whose date cannot be told are kept.
+async fn prune_backups(global: &GlobalOpts, trpc: &TrpcClient, policy: &RetentionArgs) -> Result<Vec<Value>, CliError> {
+	let listed = trpc.query("admin.listBackups", Value::Null).await?;
+	let items: Vec<Dated> = listed
+		.as_array()
+		.into_iter()
+		.flatten()
+		.filter_map(|entry| {
+			let name = backup_name(entry)?;
+			let created = retention::created_at(entry, &name);
+			Some(Dated { name, created })
+		})
+		.collect();
+	let plan = retention::plan(items, policy)?;
+
+	if !global.quiet {
+		for item in plan.keep.iter().filter(|item| item.created.is_none()) {
+			eprintln!("warning: keeping '{}': its date is unknown", item.name);
+		}
+	}
+	if plan.delete.is_empty() {
+		if !global.quiet {
+			eprintln!("Nothing to prune ({} backup(s) kept).", plan.keep.len());
+		}
+		return Ok(Vec::new());
+	}
+
+	let names: Vec<String> = plan.delete.into_iter().map(|item| item.name).collect();
+	if !global.quiet {
+		eprintln!("Pruning {} backup(s), keeping {}:", names.len(), plan.keep.len());
+		for name in &names {
+			eprintln!("  {name}");
+		}
+	}
+	if !confirm(global, "Delete these backups? ")? {
+		return Ok(Vec::new());
+	}
+
+	let mut rows = Vec::with_capacity(names.len());
+	for name in names {
+		if cancel::requested() {
+			break;
+		}
+		let row = match trpc.call_step("admin.deleteBackup", json!({ "fileName": name })).await {
+			Ok(Some(_)) => json!({ "backup": name, "status": "deleted", "error": Value::Null }),
+			Ok(None) => continue,
+			Err(CliError::Interrupted) => return Err(CliError::Interrupted),
+			Err(err) => json!({ "backup": name, "status": "failed", "error": err.to_string() }),
+		};
+		rows.push(row);
+	}
+	Ok(rows)
+}
+
+/// Downloads backup `name` to `out` and returns its size in bytes.
+async fn download_backup(trpc: &TrpcClient, name: &str, out: &std::path::Path) -> Result<u64, CliError> {
+	let response = trpc
+		.query("admin.downloadBackup", json!({ "fileName": name }))
+		.await?;
+
+	let data = response
+		.get("data")
+		.and_then(|v| v.as_str())
+		.ok_or_else(|| CliError::InvalidArgument("backup download returned no data".to_string()))?;
+
+	let bytes = base64::engine::general_purpose::STANDARD
+		.decode(data)
+		.map_err(|err| CliError::InvalidArgument(format!("invalid base64: {err}")))?;
+
+	output::write_file(out, &bytes)
+}
+
+/// Backup entries are either bare file names or objects carrying one.
+fn backup_name(entry: &Value) -> Option<String> {
+	if let Some(name) = entry.as_str() {
+		return Some(name.to_string());
+	}
+	["fileName", "filename", "name"]
+		.iter()
+		.find_map(|key| entry.get(*key).and_then(|v| v.as_str()))
+		.map(str::to_string)
+}
+
+fn backup_names(list: &Value) -> Vec<String> {
+	list.as_array()
+		.into_iter()
+		.flatten()
+		.filter_map(backup_name)
+		.collect()
+}
+
+/// Prefers the name the server reported; otherwise the first entry that was not listed
+/// before the backup started (matching `--name` when one was given).
+fn find_new_backup(
+	listed: &[String],
+	before: &[String],
+	expected: Option<&str>,
+	requested: Option<&str>,
+) -> Option<String> {
+	if let Some(expected) = expected {
+		return listed.iter().find(|n| n.as_str() == expected).cloned();
 	}
+	listed
+		.iter()
+		.filter(|n| !before.contains(n))
+		.find(|n| requested.is_none_or(|r| n.contains(r)))
+		.cloned()
 }
 
 async fn mail(
@@ -247,10 +467,7 @@ async fn mail(
 				];
 
 				if matches!(effective.output, OutputFormat::Table) {
-					for k in keys {
-						println!("{k}");
-					}
-					return Ok(());
+					return output::emit(&keys.join("\n"), false);
 				}
 
 				let value = Value::Array(keys.iter().map(|k| Value::String((*k).to_string())).collect());
@@ -265,7 +482,7 @@ async fn mail(
 				Ok(())
 			}
 			AdminMailTemplatesCommand::Set(args) => {
-				let text = std::fs::read_to_string(&args.file)?;
+				let text = read_input_file(&args.file)?;
 				serde_json::from_str::<Value>(&text).map_err(|err| {
 					CliError::InvalidArgument(format!("invalid template json: {err}"))
 				})?;
@@ -290,7 +507,7 @@ async fn settings(
 	command: AdminSettingsCommand,
 ) -> Result<(), CliError> {
 	match command {
-		AdminSettingsCommand::Get => {
+		AdminSettingsCommand::Get(_) => {
 			let response = trpc.query("settings.getAllOptions", Value::Null).await?;
 			print_human_or_machine(&response, effective.output, global.no_color)?;
 			Ok(())
@@ -333,6 +550,54 @@ async fn settings(
 	}
 }
 
+/// Options the panel exposes before login. `settings.getPublicOptions` carries most of them;
+/// the `public.*` procedures fill in what older panels leave out of it. A procedure the
+/// panel doesn't have (404) is skipped; only a panel with none of them is an error.
+pub(super) async fn public_settings(trpc: &TrpcClient) -> Result<Value, CliError> {
+	let public = trpc_optional(trpc, "settings.getPublicOptions").await?;
+	let mut found = public.is_some();
+	let mut options = match public {
+		Some(Value::Object(map)) => map,
+		_ => serde_json::Map::new(),
+	};
+
+	if !options.contains_key("enableRegistration")
+		&& let Some(allowed) = trpc_optional(trpc, "public.registrationAllowed").await?
+	{
+		found = true;
+		let allowed = allowed.get("enableRegistration").cloned().unwrap_or(allowed);
+		options.insert("enableRegistration".to_string(), allowed);
+	}
+	if !options.contains_key("welcomeMessageTitle")
+		&& !options.contains_key("welcomeMessageBody")
+		&& let Some(welcome) = trpc_optional(trpc, "public.getWelcomeMessage").await?
+	{
+		found = true;
+		for (from, to) in [("title", "welcomeMessageTitle"), ("body", "welcomeMessageBody")] {
+			if let Some(value) = welcome.get(from).or_else(|| welcome.get(to)) {
+				options.insert(to.to_string(), value.clone());
+			}
+		}
+	}
+
+	if !found {
+		return Err(CliError::HttpStatus {
+			status: reqwest::StatusCode::NOT_FOUND,
+			message: "the panel exposes no public options (is this a ZTNet panel?)".to_string(),
+			body: None,
+		});
+	}
+	Ok(Value::Object(options))
+}
+
+async fn trpc_optional(trpc: &TrpcClient, procedure: &str) -> Result<Option<Value>, CliError> {
+	match trpc.query(procedure, Value::Null).await {
+		Ok(value) => Ok(Some(value)),
+		Err(CliError::HttpStatus { status, .. }) if status == reqwest::StatusCode::NOT_FOUND => Ok(None),
+		Err(err) => Err(err),
+	}
+}
+
 async fn invites(
 	global: &GlobalOpts,
 	effective: &crate::context::EffectiveConfig,
@@ -407,17 +672,105 @@ fn default_invite_secret() -> String {
 	format!("ztnet-cli-{nanos}")
 }
 
-fn trpc_authed(
-	global: &GlobalOpts,
-	effective: &crate::context::EffectiveConfig,
-) -> Result<TrpcClient, CliError> {
-	let cookie = require_cookie_from_effective(effective)?;
-	Ok(TrpcClient::new(
-		&effective.host,
-		effective.timeout,
-		effective.retries,
-		global.dry_run,
-		ClientUi::from_context(global, effective),
-	)?
-	.with_cookie(Some(cookie)))
+/// `--expire` value as the ISO timestamp sent to the server, or `null` for `never`.
+/// Bare dates mean midnight UTC; durations count from now.
+fn parse_expiry(text: &str) -> Result<Value, CliError> {
+	let text = text.trim();
+	if matches!(text.to_ascii_lowercase().as_str(), "never" | "none") {
+		return Ok(Value::Null);
+	}
+	let at = if let Ok(duration) = humantime::parse_duration(text) {
+		SystemTime::now() + duration
+	} else {
+		let full = if text.len() == 10 { format!("{text}T00:00:00Z") } else { text.to_string() };
+		humantime::parse_rfc3339_weak(&full).map_err(|_| {
+			CliError::InvalidArgument(format!(
+				"invalid --expire '{text}' (use YYYY-MM-DD, an RFC 3339 time, a duration like 90d, or never)"
+			))
+		})?
+	};
+	Ok(json!(humantime::format_rfc3339_millis(at).to_string()))
+}
+
+fn now_rfc3339() -> String {
+	humantime::format_rfc3339_millis(SystemTime::now()).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::time::Duration;
+
+	use crate::http::ClientUi;
+	use crate::mock_server::{MockServer, Response};
+
+	/// Answers tRPC queries from `routes` (procedure name to result); any other procedure
+	/// gets tRPC's 404.
+	async fn panel(routes: &'static [(&'static str, &'static str)]) -> TrpcClient {
+		let server = MockServer::start(|request| {
+			match routes.iter().find(|(name, _)| request.procedure() == Some(*name)) {
+				Some((_, data)) => Response::trpc(&serde_json::from_str(data).unwrap()),
+				None => Response::status(
+					reqwest::StatusCode::NOT_FOUND,
+					r#"{"error":{"json":{"message":"No such procedure","data":{"code":"NOT_FOUND","httpStatus":404}}}}"#,
+				),
+			}
+		})
+		.await;
+		TrpcClient::new(&server.url, Duration::from_secs(5), 0, false, ClientUi::default()).unwrap()
+	}
+
+	#[tokio::test]
+	async fn public_settings_skip_procedures_the_panel_lacks() {
+		let full = panel(&[("settings.getPublicOptions", r#"{"enableRegistration":false,"siteName":"ZTNet"}"#)]).await;
+		assert_eq!(
+			public_settings(&full).await.unwrap(),
+			json!({ "enableRegistration": false, "siteName": "ZTNet" })
+		);
+
+		let old = panel(&[
+			("public.registrationAllowed", r#"{"enableRegistration":true}"#),
+			("public.getWelcomeMessage", r#"{"title":"Hi","body":"Welcome"}"#),
+		])
+		.await;
+		assert_eq!(
+			public_settings(&old).await.unwrap(),
+			json!({ "enableRegistration": true, "welcomeMessageTitle": "Hi", "welcomeMessageBody": "Welcome" })
+		);
+
+		let none = panel(&[]).await;
+		let err = public_settings(&none).await.unwrap_err();
+		assert!(err.to_string().contains("no public options"), "{err}");
+	}
+
+	#[test]
+	fn expiry_accepts_dates_times_durations_and_never() {
+		assert_eq!(parse_expiry("2030-01-31").unwrap(), "2030-01-31T00:00:00.000Z");
+		assert_eq!(parse_expiry("2030-01-31T12:30:00Z").unwrap(), "2030-01-31T12:30:00.000Z");
+		assert_eq!(parse_expiry("Never").unwrap(), Value::Null);
+		assert!(parse_expiry("90d").unwrap().as_str().unwrap().ends_with('Z'));
+		assert!(parse_expiry("next week").is_err());
+		assert_eq!(date_meta(&["params.expiresAt"]), Some(json!({ "values": { "params.expiresAt": ["Date"] } })));
+		assert_eq!(date_meta(&[]), None);
+	}
+
+	#[test]
+	fn find_new_backup_prefers_reported_name_then_new_entries() {
+		let before = vec!["old.tar.gz".to_string()];
+		let listed = backup_names(&json!([
+			"old.tar.gz",
+			{ "fileName": "other_1700.tar.gz" },
+			{ "name": "nightly_1700.tar.gz" }
+		]));
+
+		assert_eq!(
+			find_new_backup(&listed, &before, None, Some("nightly")).as_deref(),
+			Some("nightly_1700.tar.gz")
+		);
+		assert_eq!(
+			find_new_backup(&listed, &before, None, None).as_deref(),
+			Some("other_1700.tar.gz")
+		);
+		assert_eq!(find_new_backup(&listed, &before, Some("pending.tar.gz"), None), None);
+	}
 }
@@ -0,0 +1,673 @@
+//! `ztnet agent`: a long-running process that keeps one authenticated client (with its
+//! connection pool and resolved network names) warm and serves a small HTTP/JSON API on a
+//! loopback socket, so local tools can list networks, authorize members and export hosts
+//! without paying CLI startup and name resolution on every call. Requests must carry the
+//! bearer token the agent writes to its token file when it starts.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use reqwest::{Method, StatusCode};
+use serde_json::{Value, json};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::cancel;
+use crate::cli::{AgentCommand, AgentServeArgs, GlobalOpts};
+use crate::config;
+use crate::error::CliError;
+use crate::http::HttpClient;
+
+use super::common::write_text_output;
+use super::export::{IpSelection, host_records, hosts_lines};
+use super::middleware::CommandContext;
+use super::resolve::{resolve_network_id, resolve_org_id};
+use super::trpc_resolve::is_network_id;
+
+const MAX_HEAD: usize = 16 * 1024;
+const MAX_BODY: usize = 64 * 1024;
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub(super) async fn run(ctx: &CommandContext<'_>, command: AgentCommand) -> Result<(), CliError> {
+	match command {
+		AgentCommand::Serve(args) => serve(ctx, args).await,
+		AgentCommand::SystemdUnit(args) => systemd_unit(ctx.global, &args),
+	}
+}
+
+struct Agent {
+	client: HttpClient,
+	token: String,
+	org_id: Option<String>,
+	/// Network names already resolved to ids; ids themselves are never looked up.
+	network_ids: Mutex<HashMap<String, String>>,
+}
+
+async fn serve(ctx: &CommandContext<'_>, args: AgentServeArgs) -> Result<(), CliError> {
+	let global = ctx.global;
+	if !args.listen.ip().is_loopback() {
+		return Err(CliError::InvalidArgument(format!(
+			"--listen {} is not a loopback address; the agent only serves local clients",
+			args.listen
+		)));
+	}
+	if global.dry_run {
+		return Err(CliError::InvalidArgument("agent serve cannot run with --dry-run".to_string()));
+	}
+
+	let client = ctx.client()?;
+	let org_id = match ctx.effective.org.as_deref() {
+		Some(org) => Some(resolve_org_id(&client, org).await?),
+		None => None,
+	};
+	// Fails fast on a bad token, and opens the connection the first request reuses.
+	client
+		.request_json(Method::GET, &networks_path(org_id.as_deref()), None, Default::default(), true)
+		.await?;
+
+	let token_path = match args.token_file {
+		Some(path) => path,
+		None => config::default_cache_dir()?.join("agent.token"),
+	};
+	let token = new_token()?;
+	write_token(&token_path, &token)?;
+
+	let listener = TcpListener::bind(args.listen).await?;
+	if !global.quiet {
+		eprintln!(
+			"Agent listening on http://{} (token in {}); Ctrl-C to stop.",
+			listener.local_addr()?,
+			token_path.display()
+		);
+	}
+
+	let agent = Arc::new(Agent {
+		client,
+		token,
+		org_id,
+		network_ids: Mutex::new(HashMap::new()),
+	});
+	accept_loop(listener, agent, global.quiet).await;
+
+	let _ = fs::remove_file(&token_path);
+	Ok(())
+}
+
+/// Serves each connection on its own task until Ctrl-C.
+async fn accept_loop(listener: TcpListener, agent: Arc<Agent>, quiet: bool) {
+	loop {
+		let accepted = tokio::select! {
+			_ = cancel::cancelled() => break,
+			accepted = listener.accept() => accepted,
+		};
+		match accepted {
+			Ok((stream, _)) => {
+				let agent = Arc::clone(&agent);
+				tokio::spawn(async move { agent.handle(stream).await });
+			}
+			Err(err) if !quiet => eprintln!("agent: accept failed: {err}"),
+			Err(_) => {}
+		}
+	}
+}
+
+impl Agent {
+	async fn handle(&self, mut stream: TcpStream) {
+		let response = match tokio::time::timeout(READ_TIMEOUT, read_request(&mut stream)).await {
+			Ok(Ok(request)) => self.respond(request).await,
+			Ok(Err(message)) => Response::error(StatusCode::BAD_REQUEST, message),
+			Err(_) => Response::error(StatusCode::REQUEST_TIMEOUT, "timed out reading the request"),
+		};
+		let _ = stream.write_all(&response.to_bytes()).await;
+		let _ = stream.shutdown().await;
+	}
+
+	async fn respond(&self, request: Request) -> Response {
+		if !authorized(&request, &self.token) {
+			return Response::error(StatusCode::UNAUTHORIZED, "missing or wrong bearer token");
+		}
+		let (path, query) = request.target.split_once('?').unwrap_or((&request.target, ""));
+		let Some(route) = route(path) else {
+			return Response::error(StatusCode::NOT_FOUND, format!("no such endpoint: {path}"));
+		};
+		let result = match (request.method.as_str(), route) {
+			("GET", Route::Health) => Ok(Response::json(&json!({ "ok": true }))),
+			("GET", Route::Networks) => self.networks().await,
+			("POST", Route::Authorize { network, member }) => self.authorize(&network, &member, &request.body).await,
+			("GET", Route::Hosts { network }) => self.hosts(&network, query).await,
+			(method, _) => {
+				return Response::error(StatusCode::METHOD_NOT_ALLOWED, format!("{method} is not allowed on {path}"));
+			}
+		};
+		result.unwrap_or_else(|err| Response::error(error_status(&err), err))
+	}
+
+	async fn networks(&self) -> Result<Response, CliError> {
+		let path = networks_path(self.org_id.as_deref());
+		let networks = self.client.request_json(Method::GET, &path, None, Default::default(), true).await?;
+		Ok(Response::json(&networks))
+	}
+
+	async fn authorize(&self, network: &str, member: &str, body: &[u8]) -> Result<Response, CliError> {
+		let authorized = if body.iter().all(u8::is_ascii_whitespace) {
+			true
+		} else {
+			let body: Value = serde_json::from_slice(body)
+				.map_err(|err| CliError::InvalidArgument(format!("invalid JSON body: {err}")))?;
+			match body.get("authorized") {
+				None => true,
+				Some(Value::Bool(authorized)) => *authorized,
+				Some(_) => return Err(CliError::InvalidArgument("\"authorized\" must be true or false".to_string())),
+			}
+		};
+
+		if !is_member_id(member) {
+			return Err(not_found(format!("'{member}' is not a member id (10 hex characters)")));
+		}
+		let network_id = self.network_id(network).await?;
+		let path = format!("{}/{network_id}/member/{member}", networks_path(self.org_id.as_deref()));
+		let updated = self
+			.client
+			.request_json(Method::POST, &path, Some(json!({ "authorized": authorized })), Default::default(), true)
+			.await?;
+		Ok(Response::json(&updated))
+	}
+
+	async fn hosts(&self, network: &str, query: &str) -> Result<Response, CliError> {
+		let params: HashMap<String, String> = url::form_urlencoded::parse(query.as_bytes()).into_owned().collect();
+		let flag = |name: &str| params.get(name).is_some_and(|value| !matches!(value.as_str(), "false" | "0"));
+		let zone = params.get("zone").map(|zone| zone.trim().trim_end_matches('.')).unwrap_or_default();
+		if zone.is_empty() {
+			return Err(CliError::InvalidArgument("the zone query parameter is required".to_string()));
+		}
+		let selection = IpSelection {
+			ipv4_only: flag("ipv4_only"),
+			ipv6_only: flag("ipv6_only"),
+			ipv6_first: flag("ipv6_first"),
+			one_ip_per_member: flag("one_ip_per_member"),
+		};
+		if selection.ipv4_only && selection.ipv6_only {
+			return Err(CliError::InvalidArgument("cannot combine ipv4_only with ipv6_only".to_string()));
+		}
+
+		let network_id = self.network_id(network).await?;
+		let path = format!("{}/{network_id}/member", networks_path(self.org_id.as_deref()));
+		let members = self.client.request_json(Method::GET, &path, None, Default::default(), true).await?;
+		let Some(items) = members.as_array() else {
+			return Err(CliError::InvalidArgument("expected array response".to_string()));
+		};
+		let records = host_records(items, zone, flag("include_unauthorized"), selection);
+
+		match params.get("format").map(String::as_str) {
+			None | Some("json") => Ok(Response::json(&Value::Array(records))),
+			Some("hosts") => {
+				let text: String = hosts_lines(&records).iter().map(|line| format!("{line}\n")).collect();
+				Ok(Response::text(text))
+			}
+			Some(other) => Err(CliError::InvalidArgument(format!("unknown format '{other}' (use json or hosts)"))),
+		}
+	}
+
+	/// Only ever returns a 16-hex network id, so nothing from the request path reaches the
+	/// panel URL unchecked. Names that match no network are a 404.
+	async fn network_id(&self, network: &str) -> Result<String, CliError> {
+		if is_network_id(network) {
+			return Ok(network.to_string());
+		}
+		if let Some(id) = self.network_ids.lock().ok().and_then(|ids| ids.get(network).cloned()) {
+			return Ok(id);
+		}
+		let id = resolve_network_id(&self.client, self.org_id.as_deref(), network).await?;
+		if !is_network_id(&id) || id == network {
+			return Err(not_found(format!("no network named '{network}'")));
+		}
+		if let Ok(mut ids) = self.network_ids.lock() {
+			ids.insert(network.to_string(), id.clone());
+		}
+		Ok(id)
+	}
+}
+
+fn is_member_id(value: &str) -> bool {
+	value.len() == 10 && value.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn not_found(message: String) -> CliError {
+	CliError::HttpStatus {
+		status: StatusCode::NOT_FOUND,
+		message,
+		body: None,
+	}
+}
+
+fn networks_path(org_id: Option<&str>) -> String {
+	match org_id {
+		Some(org_id) => format!("/api/v1/org/{org_id}/network"),
+		None => "/api/v1/network".to_string(),
+	}
+}
+
+#[derive(Debug, PartialEq)]
+enum Route {
+	Health,
+	Networks,
+	Authorize { network: String, member: String },
+	Hosts { network: String },
+}
+
+fn route(path: &str) -> Option<Route> {
+	let segments = path
+		.trim_matches('/')
+		.split('/')
+		.map(percent_decode)
+		.collect::<Option<Vec<_>>>()?;
+	let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+	match segments.as_slice() {
+		["v1", "health"] => Some(Route::Health),
+		["v1", "networks"] => Some(Route::Networks),
+		["v1", "networks", network, "members", member, "authorize"] => Some(Route::Authorize {
+			network: network.to_string(),
+			member: member.to_string(),
+		}),
+		["v1", "networks", network, "hosts"] => Some(Route::Hosts {
+			network: network.to_string(),
+		}),
+		_ => None,
+	}
+}
+
+fn percent_decode(segment: &str) -> Option<String> {
+	let bytes = segment.as_bytes();
+	let mut out = Vec::with_capacity(bytes.len());
+	let mut index = 0;
+	while index < bytes.len() {
+		if bytes[index] == b'%' {
+			let hex = std::str::from_utf8(bytes.get(index + 1..index + 3)?).ok()?;
+			out.push(u8::from_str_radix(hex, 16).ok()?);
+			index += 3;
+		} else {
+			out.push(bytes[index]);
+			index += 1;
+		}
+	}
+	String::from_utf8(out).ok()
+}
+
+#[derive(Debug)]
+struct Request {
+	method: String,
+	target: String,
+	/// Header names are lowercased.
+	headers: Vec<(String, String)>,
+	body: Vec<u8>,
+}
+
+async fn read_request(stream: &mut TcpStream) -> Result<Request, String> {
+	let mut buf = Vec::new();
+	let mut chunk = [0u8; 4096];
+	let head_end = loop {
+		if let Some(end) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+			break end;
+		}
+		if buf.len() > MAX_HEAD {
+			return Err("request head is too large".to_string());
+		}
+		let read = stream.read(&mut chunk).await.map_err(|err| err.to_string())?;
+		if read == 0 {
+			return Err("connection closed mid-request".to_string());
+		}
+		buf.extend_from_slice(&chunk[..read]);
+	};
+
+	let head = std::str::from_utf8(&buf[..head_end]).map_err(|_| "request head is not UTF-8".to_string())?;
+	let mut request = parse_head(head)?;
+	let length = match request.header("content-length") {
+		Some(value) => value.parse::<usize>().map_err(|_| format!("invalid Content-Length '{value}'"))?,
+		None => 0,
+	};
+	if length > MAX_BODY {
+		return Err(format!("request body is larger than {MAX_BODY} bytes"));
+	}
+
+	let mut body = buf.split_off(head_end + 4);
+	while body.len() < length {
+		let read = stream.read(&mut chunk).await.map_err(|err| err.to_string())?;
+		if read == 0 {
+			return Err("connection closed mid-body".to_string());
+		}
+		body.extend_from_slice(&chunk[..read]);
+	}
+	body.truncate(length);
+	request.body = body;
+	Ok(request)
+}
+
+fn parse_head(head: &str) -> Result<Request, String> {
+	let mut lines = head.split("\r\n");
+	let request_line = lines.next().unwrap_or_default();
+	let mut parts = request_line.split(' ');
+	let (Some(method), Some(target), Some(version), None) = (parts.next(), parts.next(), parts.next(), parts.next())
+	else {
+		return Err(format!("malformed request line '{request_line}'"));
+	};
+	if !version.starts_with("HTTP/1.") {
+		return Err(format!("unsupported protocol '{version}'"));
+	}
+
+	let headers = lines
+		.map(|line| {
+			let (name, value) = line.split_once(':').ok_or_else(|| format!("malformed header '{line}'"))?;
+			Ok((name.trim().to_ascii_lowercase(), value.trim().to_string()))
+		})
+		.collect::<Result<_, String>>()?;
+	Ok(Request {
+		method: method.to_string(),
+		target: target.to_string(),
+		headers,
+		body: Vec::new(),
+	})
+}
+
+impl Request {
+	fn header(&self, name: &str) -> Option<&str> {
+		self.headers.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+	}
+}
+
+/// Whether the request carries `Authorization: Bearer <token>`, compared in constant time.
+fn authorized(request: &Request, token: &str) -> bool {
+	let Some(given) = request.header("authorization").and_then(|value| value.strip_prefix("Bearer ")) else {
+		return false;
+	};
+	given.len() == token.len() && given.bytes().zip(token.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+fn error_status(err: &CliError) -> StatusCode {
+	match err {
+		CliError::InvalidArgument(_) | CliError::InputRejected { .. } => StatusCode::BAD_REQUEST,
+		CliError::ReadOnly(_) => StatusCode::FORBIDDEN,
+		CliError::HttpStatus { status, .. }
+			if matches!(
+				*status,
+				StatusCode::BAD_REQUEST | StatusCode::NOT_FOUND | StatusCode::CONFLICT | StatusCode::UNPROCESSABLE_ENTITY
+			) =>
+		{
+			*status
+		}
+		// Includes the panel rejecting the agent's own credentials, which the client can't fix.
+		_ => StatusCode::BAD_GATEWAY,
+	}
+}
+
+struct Response {
+	status: StatusCode,
+	content_type: &'static str,
+	body: Vec<u8>,
+}
+
+impl Response {
+	fn json(value: &Value) -> Self {
+		Self {
+			status: StatusCode::OK,
+			content_type: "application/json",
+			body: value.to_string().into_bytes(),
+		}
+	}
+
+	fn text(body: String) -> Self {
+		Self {
+			status: StatusCode::OK,
+			content_type: "text/plain; charset=utf-8",
+			body: body.into_bytes(),
+		}
+	}
+
+	fn error(status: StatusCode, message: impl Display) -> Self {
+		Self {
+			status,
+			..Self::json(&json!({ "error": message.to_string() }))
+		}
+	}
+
+	fn to_bytes(&self) -> Vec<u8> {
+		let mut out = format!(
+			"HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+			self.status.as_u16(),
+			self.status.canonical_reason().unwrap_or_default(),
+			self.content_type,
+			self.body.len()
+		)
+		.into_bytes();
+		out.extend_from_slice(&self.body);
+		out
+	}
+}
+
+fn new_token() -> Result<String, CliError> {
+	use ring::rand::SecureRandom;
+	let mut bytes = [0u8; 32];
+	ring::rand::SystemRandom::new()
+		.fill(&mut bytes)
+		.map_err(|_| io::Error::other("no system randomness available for the agent token"))?;
+	Ok(bytes.iter().map(|b| format!("{b:02x}")).collect())
+}
+
+/// Writes the token readable by the current user only (on Unix; elsewhere the file
+/// inherits the directory's permissions). An existing file is tightened before the token
+/// goes in, since `mode` only applies to newly created files.
+fn write_token(path: &Path, token: &str) -> Result<(), CliError> {
+	if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+		fs::create_dir_all(dir)?;
+	}
+	let mut options = fs::OpenOptions::new();
+	options.write(true).create(true).truncate(true);
+	#[cfg(unix)]
+	{
+		use std::os::unix::fs::OpenOptionsExt;
+		options.mode(0o600);
+	}
+	let mut file = options.open(path)?;
+	#[cfg(unix)]
+	{
+		use std::os::unix::fs::PermissionsExt;
+		file.set_permissions(fs::Permissions::from_mode(0o600))?;
+	}
+	io::Write::write_all(&mut file, format!("{token}\n").as_bytes())?;
+	Ok(())
+}
+
+fn systemd_unit(global: &GlobalOpts, args: &AgentServeArgs) -> Result<(), CliError> {
+	let exe = std::env::current_exe()?;
+	let mut command = vec![exe.display().to_string()];
+	if let Some(profile) = global.profile.as_deref() {
+		command.extend(["--profile".to_string(), profile.to_string()]);
+	}
+	command.extend(["agent", "serve", "--listen"].map(str::to_string));
+	command.push(args.listen.to_string());
+	if let Some(path) = args.token_file.as_deref() {
+		command.extend(["--token-file".to_string(), path.display().to_string()]);
+	}
+	let exec_start: Vec<String> = command.iter().map(|word| systemd_quote(word)).collect();
+
+	let unit = format!(
+		"# Save as ~/.config/systemd/user/ztnet-agent.service, then run:\n\
+		 #   systemctl --user daemon-reload && systemctl --user enable --now ztnet-agent\n\
+		 [Unit]\n\
+		 Description=ztnet agent (local ZTNet API on {listen})\n\
+		 Wants=network-online.target\n\
+		 After=network-online.target\n\
+		 \n\
+		 [Service]\n\
+		 ExecStart={exec_start}\n\
+		 Restart=on-failure\n\
+		 RestartSec=5\n\
+		 \n\
+		 [Install]\n\
+		 WantedBy=default.target\n",
+		listen = args.listen,
+		exec_start = exec_start.join(" "),
+	);
+	write_text_output(&unit, None, global)
+}
+
+/// Quotes a word for `ExecStart=` when it contains whitespace, quotes or backslashes.
+fn systemd_quote(word: &str) -> String {
+	if !word.is_empty() && !word.contains(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '\\')) {
+		return word.to_string();
+	}
+	format!("\"{}\"", word.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::http::ClientUi;
+	use crate::mock_server::{MockServer, Response};
+
+	/// A panel that knows one network, `lab`.
+	async fn mock_panel() -> MockServer {
+		MockServer::start(|request| {
+			if request.method == "GET" && request.target == "/api/v1/network" {
+				Response::json(r#"[{"id":"abcdef0123456789","name":"lab"}]"#)
+			} else {
+				Response::json(r#"{"id":"0123456789","authorized":true}"#)
+			}
+		})
+		.await
+	}
+
+	fn agent(panel: &str) -> Agent {
+		Agent {
+			client: HttpClient::new(panel, Some("key".to_string()), Duration::from_secs(5), 0, false, ClientUi::default())
+				.unwrap(),
+			token: "s3cret".to_string(),
+			org_id: None,
+			network_ids: Mutex::new(HashMap::new()),
+		}
+	}
+
+	fn post(target: &str) -> Request {
+		Request {
+			method: "POST".to_string(),
+			target: target.to_string(),
+			headers: vec![("authorization".to_string(), "Bearer s3cret".to_string())],
+			body: Vec::new(),
+		}
+	}
+
+	#[tokio::test]
+	async fn request_paths_only_reach_the_panel_as_resolved_ids() {
+		let panel = mock_panel().await;
+		let agent = agent(&panel.url);
+
+		for target in [
+			"/v1/networks/lab/members/..%2F..%2Fuser/authorize",
+			"/v1/networks/lab/members/0123456789%3Fx=1/authorize",
+			"/v1/networks/..%2F..%2Fuser/members/0123456789/authorize",
+			"/v1/networks/nope/members/0123456789/authorize",
+		] {
+			let response = agent.respond(post(target)).await;
+			assert_eq!(response.status, StatusCode::NOT_FOUND, "{target}");
+		}
+		assert!(panel.request_lines().iter().all(|line| line == "GET /api/v1/network"));
+
+		let response = agent.respond(post("/v1/networks/lab/members/0123456789/authorize")).await;
+		assert_eq!(response.status, StatusCode::OK);
+		assert_eq!(
+			panel.request_lines().last().map(String::as_str),
+			Some("POST /api/v1/network/abcdef0123456789/member/0123456789")
+		);
+	}
+
+	#[test]
+	fn requests_parse_route_and_need_the_bearer_token() {
+		let request = parse_head(
+			"POST /v1/networks/lab%20net/members/abcdef0123/authorize HTTP/1.1\r\n\
+			 Host: 127.0.0.1\r\n\
+			 Authorization: Bearer s3cret\r\n\
+			 Content-Length: 2",
+		)
+		.unwrap();
+		assert_eq!(request.method, "POST");
+		assert_eq!(request.header("content-length"), Some("2"));
+		assert_eq!(
+			route(&request.target),
+			Some(Route::Authorize {
+				network: "lab net".to_string(),
+				member: "abcdef0123".to_string(),
+			})
+		);
+		assert!(authorized(&request, "s3cret"));
+		assert!(!authorized(&request, "s3cre"));
+		assert!(!authorized(&request, "other!"));
+
+		assert_eq!(route("/v1/networks/"), Some(Route::Networks));
+		assert_eq!(route("/v1/networks/8056c2e21c000001/hosts"), Some(Route::Hosts { network: "8056c2e21c000001".to_string() }));
+		assert_eq!(route("/v1/networks/bad%zz/hosts"), None);
+		assert_eq!(route("/v2/health"), None);
+		assert!(parse_head("GET /v1/health").is_err());
+		assert!(parse_head("GET /v1/health HTTP/1.1\r\nno-colon").is_err());
+	}
+
+	async fn send(addr: std::net::SocketAddr, request: &str) -> String {
+		let mut stream = TcpStream::connect(addr).await.unwrap();
+		stream.write_all(request.as_bytes()).await.unwrap();
+		let mut response = String::new();
+		stream.read_to_string(&mut response).await.unwrap();
+		response
+	}
+
+	#[tokio::test]
+	async fn server_answers_over_tcp_and_rejects_bad_requests() {
+		let panel = mock_panel().await;
+		let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+		let addr = listener.local_addr().unwrap();
+		let server = tokio::spawn(accept_loop(listener, Arc::new(agent(&panel.url)), true));
+
+		let status = |response: String| response.lines().next().unwrap_or_default().to_string();
+		let authed = "Authorization: Bearer s3cret\r\n";
+		assert_eq!(status(send(addr, "GET /v1/health HTTP/1.1\r\n\r\n").await), "HTTP/1.1 401 Unauthorized");
+		assert_eq!(
+			status(send(addr, "GET /v1/health HTTP/1.1\r\nAuthorization: Bearer wrong!\r\n\r\n").await),
+			"HTTP/1.1 401 Unauthorized"
+		);
+		let health = send(addr, &format!("GET /v1/health HTTP/1.1\r\n{authed}\r\n")).await;
+		assert!(health.starts_with("HTTP/1.1 200 OK\r\n") && health.ends_with(r#"{"ok":true}"#), "{health}");
+		assert_eq!(status(send(addr, &format!("GET /v1/nope HTTP/1.1\r\n{authed}\r\n")).await), "HTTP/1.1 404 Not Found");
+		assert_eq!(
+			status(send(addr, &format!("DELETE /v1/networks HTTP/1.1\r\n{authed}\r\n")).await),
+			"HTTP/1.1 405 Method Not Allowed"
+		);
+		assert_eq!(status(send(addr, "hello\r\n\r\n").await), "HTTP/1.1 400 Bad Request");
+		let authorize = format!(
+			"POST /v1/networks/lab/members/0123456789/authorize HTTP/1.1\r\n{authed}Content-Length: 20\r\n\r\n{{\"authorized\":false}}"
+		);
+		assert_eq!(status(send(addr, &authorize).await), "HTTP/1.1 200 OK");
+		server.abort();
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn token_file_is_private_even_when_it_existed() {
+		use std::os::unix::fs::PermissionsExt;
+		let path = std::env::temp_dir().join(format!("ztnet-agent-token-{}", std::process::id()));
+		fs::write(&path, "old").unwrap();
+		fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+		write_token(&path, "abc").unwrap();
+		assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+		assert_eq!(fs::read_to_string(&path).unwrap(), "abc\n");
+		let _ = fs::remove_file(&path);
+	}
+
+	#[test]
+	fn systemd_quote_leaves_plain_words_alone() {
+		assert_eq!(systemd_quote("/usr/bin/ztnet"), "/usr/bin/ztnet");
+		assert_eq!(systemd_quote("my profile"), "\"my profile\"");
+		assert_eq!(systemd_quote(r#"C:\a "b""#), r#""C:\\a \"b\"""#);
+	}
+}
@@ -1,36 +1,27 @@
-use std::io::{self, Write};
+use std::io::Write;
 use std::path::PathBuf;
 
 use reqwest::Method;
 use serde_json::Value;
 
 use crate::cli::{ApiCommand, GlobalOpts};
-use crate::context::resolve_effective_config;
 use crate::error::CliError;
-use crate::http::{ClientUi, HttpClient};
+use crate::http::HttpClient;
 use crate::output;
 
-use super::common::load_config_store;
+use super::common::read_input_file;
+use super::middleware::CommandContext;
 
-pub(super) async fn run(global: &GlobalOpts, command: ApiCommand) -> Result<(), CliError> {
-	let (_config_path, cfg) = load_config_store()?;
-	let effective = resolve_effective_config(global, &cfg)?;
-
-	let client = HttpClient::new(
-		&effective.host,
-		effective.token.clone(),
-		effective.timeout,
-		effective.retries,
-		global.dry_run,
-		ClientUi::from_context(global, &effective),
-	)?;
+pub(super) async fn run(ctx: &CommandContext<'_>, command: ApiCommand) -> Result<(), CliError> {
+	let (global, effective) = (ctx.global, &ctx.effective);
+	let client = ctx.client()?;
 
 	match command {
 		ApiCommand::Request(args) => {
 			let method = parse_method(&args.method)?;
 			exec_api_request(
 				global,
-				&effective,
+				effective,
 				&client,
 				method,
 				&args.path,
@@ -39,13 +30,20 @@ pub(super) async fn run(global: &GlobalOpts, command: ApiCommand) -> Result<(),
 				args.header,
 				args.no_auth,
 				args.raw,
+				args.items_path.as_deref(),
 			)
 			.await
 		}
+		ApiCommand::Get(args) if args.paginate => {
+			let items_path = args.items_path.as_deref().unwrap_or_default();
+			let items = fetch_pages(&client, &args.path, items_path, &args.page_param, args.max_pages).await?;
+			output::print_value(&items, effective.output, global.no_color)?;
+			Ok(())
+		}
 		ApiCommand::Get(args) => {
 			exec_api_request(
 				global,
-				&effective,
+				effective,
 				&client,
 				Method::GET,
 				&args.path,
@@ -54,13 +52,14 @@ pub(super) async fn run(global: &GlobalOpts, command: ApiCommand) -> Result<(),
 				vec![],
 				false,
 				false,
+				args.items_path.as_deref(),
 			)
 			.await
 		}
 		ApiCommand::Post(args) => {
 			exec_api_request(
 				global,
-				&effective,
+				effective,
 				&client,
 				Method::POST,
 				&args.path,
@@ -69,13 +68,14 @@ pub(super) async fn run(global: &GlobalOpts, command: ApiCommand) -> Result<(),
 				vec![],
 				false,
 				false,
+				None,
 			)
 			.await
 		}
 		ApiCommand::Delete(args) => {
 			exec_api_request(
 				global,
-				&effective,
+				effective,
 				&client,
 				Method::DELETE,
 				&args.path,
@@ -84,12 +84,14 @@ pub(super) async fn run(global: &GlobalOpts, command: ApiCommand) -> Result<(),
 				vec![],
 				false,
 				false,
+				None,
 			)
 			.await
 		}
 	}
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn exec_api_request(
 	global: &GlobalOpts,
 	effective: &crate::context::EffectiveConfig,
@@ -101,6 +103,7 @@ async fn exec_api_request(
 	headers: Vec<String>,
 	no_auth: bool,
 	raw: bool,
+	items_path: Option<&str>,
 ) -> Result<(), CliError> {
 	let mut header_map = reqwest::header::HeaderMap::new();
 	for raw_header in headers {
@@ -125,7 +128,7 @@ async fn exec_api_request(
 				.map_err(|err| CliError::InvalidArgument(format!("invalid --body json: {err}")))?,
 		)
 	} else if let Some(path) = body_file {
-		let text = std::fs::read_to_string(&path)?;
+		let text = read_input_file(&path)?;
 		Some(serde_json::from_str::<Value>(&text).map_err(|err| {
 			CliError::InvalidArgument(format!("invalid --body-file json: {err}"))
 		})?)
@@ -136,7 +139,7 @@ async fn exec_api_request(
 	if raw {
 		let body_bytes = body_value
 			.as_ref()
-			.map(|v| serde_json::to_vec(v))
+			.map(serde_json::to_vec)
 			.transpose()?;
 
 		let bytes = client
@@ -150,20 +153,152 @@ async fn exec_api_request(
 			)
 			.await?;
 
-		io::stdout().write_all(&bytes)?;
+		output::writer().write_all(&bytes)?;
 		return Ok(());
 	}
 
 	let response = client
 		.request_json(method, path, body_value, header_map, include_auth)
 		.await?;
+	let response = match items_path {
+		Some(items_path) => Value::Array(select_items(&response, items_path)?.clone()),
+		None => response,
+	};
 
 	output::print_value(&response, effective.output, global.no_color)?;
 	Ok(())
 }
 
+/// Finds the array inside an envelope such as `{"data": {"items": [...]}}`. Segments are
+/// object keys or array indexes; a leading `$.` (JSONPath style) is accepted.
+fn select_items<'a>(response: &'a Value, items_path: &str) -> Result<&'a Vec<Value>, CliError> {
+	let trimmed = items_path.trim();
+	let trimmed = trimmed.strip_prefix('$').unwrap_or(trimmed).trim_start_matches('.');
+
+	let mut current = response;
+	let mut walked = Vec::new();
+	for segment in trimmed.split('.').filter(|s| !s.is_empty()) {
+		let next = match current {
+			Value::Array(items) => segment.parse::<usize>().ok().and_then(|idx| items.get(idx)),
+			other => other.get(segment),
+		};
+		walked.push(segment);
+		current = next.ok_or_else(|| {
+			CliError::InvalidArgument(format!(
+				"--items-path '{items_path}': response has nothing at '{}'",
+				walked.join(".")
+			))
+		})?;
+	}
+
+	let kind = match current {
+		Value::Array(items) => return Ok(items),
+		Value::Object(_) => "an object",
+		Value::String(_) => "a string",
+		Value::Number(_) => "a number",
+		Value::Bool(_) => "a boolean",
+		Value::Null => "null",
+	};
+	Err(CliError::InvalidArgument(format!("--items-path '{items_path}' points to {kind}, not an array")))
+}
+
+/// `api get --paginate`: requests PATH with `page_param` counting up from the value already
+/// in PATH (or 1) and concatenates the arrays at `items_path`. Stops at the first empty page,
+/// at a page identical to the previous one (the endpoint ignores the parameter) or after
+/// `max_pages`.
+async fn fetch_pages(
+	client: &HttpClient,
+	path: &str,
+	items_path: &str,
+	page_param: &str,
+	max_pages: u32,
+) -> Result<Value, CliError> {
+	let (base, query) = path.trim().split_once('?').unwrap_or((path.trim(), ""));
+	let mut start = 1u64;
+	let mut pairs = Vec::new();
+	for (key, value) in url::form_urlencoded::parse(query.as_bytes()) {
+		if key == page_param {
+			start = value.parse().map_err(|_| {
+				CliError::InvalidArgument(format!("--paginate: '{page_param}={value}' in PATH is not a page number"))
+			})?;
+		} else {
+			pairs.push((key.into_owned(), value.into_owned()));
+		}
+	}
+	let include_auth = base.starts_with("/api/v1");
+
+	let mut merged = Vec::new();
+	let mut previous: Option<Vec<Value>> = None;
+	for page in (start..).take(max_pages as usize) {
+		let query = url::form_urlencoded::Serializer::new(String::new())
+			.extend_pairs(&pairs)
+			.append_pair(page_param, &page.to_string())
+			.finish();
+		let response = client
+			.request_json(Method::GET, &format!("{base}?{query}"), None, Default::default(), include_auth)
+			.await?;
+		let items = select_items(&response, items_path)?;
+		if items.is_empty() || previous.as_ref() == Some(items) {
+			break;
+		}
+		merged.extend(items.iter().cloned());
+		previous = Some(items.clone());
+	}
+	Ok(Value::Array(merged))
+}
+
 fn parse_method(raw: &str) -> Result<Method, CliError> {
 	let raw = raw.trim().to_ascii_uppercase();
 	Method::from_bytes(raw.as_bytes())
 		.map_err(|_| CliError::InvalidArgument(format!("invalid http method: {raw}")))
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+
+	use crate::mock_server::{MockServer, Response};
+
+	#[test]
+	fn select_items_walks_objects_and_indexes() {
+		let response = json!({ "data": { "items": [1, 2] }, "pages": [{ "rows": ["a"] }] });
+		assert_eq!(select_items(&response, "data.items").unwrap(), &[json!(1), json!(2)]);
+		assert_eq!(select_items(&response, "$.pages.0.rows").unwrap(), &[json!("a")]);
+		assert_eq!(select_items(&json!([3]), "").unwrap(), &[json!(3)]);
+
+		let err = select_items(&response, "data.rows").unwrap_err();
+		assert!(err.to_string().contains("nothing at 'data.rows'"));
+		let err = select_items(&response, "data").unwrap_err();
+		assert!(err.to_string().contains("an object, not an array"));
+	}
+
+	/// Serves `?page=N&size=2` from a three-item list wrapped in `{"data": {"items": [...]}}`.
+	async fn paged_panel() -> MockServer {
+		MockServer::start(|request| {
+			let page: usize = request.query("page").and_then(|n| n.parse().ok()).unwrap_or(1);
+			let items: Vec<Value> = (1..=3).map(|id| json!({ "id": id })).skip((page - 1) * 2).take(2).collect();
+			Response::json(json!({ "data": { "items": items } }).to_string())
+		})
+		.await
+	}
+
+	#[tokio::test]
+	async fn fetch_pages_merges_until_an_empty_page() {
+		let panel = paged_panel().await;
+		let client = HttpClient::new(&panel.url, None, std::time::Duration::from_secs(5), 0, false, Default::default())
+			.unwrap();
+
+		let items = fetch_pages(&client, "/api/things?size=2", "data.items", "page", 10).await.unwrap();
+		assert_eq!(items, json!([{ "id": 1 }, { "id": 2 }, { "id": 3 }]));
+		assert_eq!(
+			panel.request_lines(),
+			["GET /api/things?size=2&page=1", "GET /api/things?size=2&page=2", "GET /api/things?size=2&page=3"]
+		);
+
+		panel.clear();
+		let items = fetch_pages(&client, "/api/things?page=2&size=2", "data.items", "page", 1).await.unwrap();
+		assert_eq!(items, json!([{ "id": 3 }]));
+		assert_eq!(panel.request_lines(), ["GET /api/things?size=2&page=2"]);
+	}
+}
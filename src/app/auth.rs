@@ -7,18 +7,22 @@ use url::Url;
 
 use crate::cli::{AuthCommand, GlobalOpts, OutputFormat};
 use crate::config;
-use crate::context::{canonical_host_key, canonical_host_key_opt};
-use crate::context::resolve_effective_config;
+use crate::context::{canonical_host_key, canonical_host_key_opt, stale_host_defaults};
 use crate::error::CliError;
 use crate::host::normalize_host_input;
-use crate::http::{ClientUi, HttpClient};
+use crate::http::{self, clock_skew_warning, Backoff, ClientUi, ConnectOverrides, HttpClient};
 use crate::output;
+use crate::redact;
+use crate::tls;
 
-use super::common::{load_config_store, print_human_or_machine, read_stdin_trimmed, redact_token};
+use super::common::{print_human_or_machine, read_stdin_trimmed};
+use super::middleware::CommandContext;
+use super::refresh_lock::RefreshLock;
 
-pub(super) async fn run(global: &GlobalOpts, command: AuthCommand) -> Result<(), CliError> {
-	let (config_path, mut cfg) = load_config_store()?;
-	let effective = resolve_effective_config(global, &cfg)?;
+pub(super) async fn run(ctx: &CommandContext<'_>, command: AuthCommand) -> Result<(), CliError> {
+	let (global, effective) = (ctx.global, &ctx.effective);
+	let config_path = &ctx.config_path;
+	let mut cfg = ctx.cfg.clone();
 
 	match command {
 		AuthCommand::SetToken(args) => {
@@ -53,12 +57,12 @@ pub(super) async fn run(global: &GlobalOpts, command: AuthCommand) -> Result<(),
 				.map(normalize_host_input)
 				.transpose()?;
 
-			if let (Some(explicit), Some(from_profile)) = (&explicit_host, &profile_host) {
-				if canonical_host_key(explicit)? != canonical_host_key(from_profile)? {
-					return Err(CliError::InvalidArgument(format!(
-						"profile '{profile}' is configured for '{from_profile}', but the target host is '{explicit}'",
-					)));
-				}
+			if let (Some(explicit), Some(from_profile)) = (&explicit_host, &profile_host)
+				&& canonical_host_key(explicit)? != canonical_host_key(from_profile)?
+			{
+				return Err(CliError::InvalidArgument(format!(
+					"profile '{profile}' is configured for '{from_profile}', but the target host is '{explicit}'",
+				)));
 			}
 
 			let host_value = explicit_host.or(profile_host).ok_or_else(|| {
@@ -106,10 +110,8 @@ pub(super) async fn run(global: &GlobalOpts, command: AuthCommand) -> Result<(),
 			}
 			profile_cfg.token = Some(token);
 
-			if cfg.host_defaults.get(&host_key).is_none() {
-				cfg.host_defaults.insert(host_key, profile.clone());
-			}
-			config::save_config(&config_path, &cfg)?;
+			cfg.host_defaults.entry(host_key).or_insert_with(|| profile.clone());
+			config::save_config(config_path, &cfg)?;
 
 			if !global.quiet {
 				eprintln!("Token saved to profile '{profile}'.");
@@ -119,7 +121,7 @@ pub(super) async fn run(global: &GlobalOpts, command: AuthCommand) -> Result<(),
 		AuthCommand::UnsetToken(args) => {
 			let profile = args.profile.unwrap_or_else(|| effective.profile.clone());
 			cfg.profile_mut(&profile).token = None;
-			config::save_config(&config_path, &cfg)?;
+			config::save_config(config_path, &cfg)?;
 
 			if !global.quiet {
 				eprintln!("Token removed from profile '{profile}'.");
@@ -150,12 +152,12 @@ pub(super) async fn run(global: &GlobalOpts, command: AuthCommand) -> Result<(),
 				.map(normalize_host_input)
 				.transpose()?;
 
-			if let (Some(explicit), Some(from_profile)) = (&explicit_host, &profile_host) {
-				if canonical_host_key(explicit)? != canonical_host_key(from_profile)? {
-					return Err(CliError::InvalidArgument(format!(
-						"profile '{profile}' is configured for '{from_profile}', but the target host is '{explicit}'",
-					)));
-				}
+			if let (Some(explicit), Some(from_profile)) = (&explicit_host, &profile_host)
+				&& canonical_host_key(explicit)? != canonical_host_key(from_profile)?
+			{
+				return Err(CliError::InvalidArgument(format!(
+					"profile '{profile}' is configured for '{from_profile}', but the target host is '{explicit}'",
+				)));
 			}
 
 			let host_value = explicit_host.clone().or(profile_host).ok_or_else(|| {
@@ -188,37 +190,59 @@ pub(super) async fn run(global: &GlobalOpts, command: AuthCommand) -> Result<(),
 				return Err(CliError::InvalidArgument("password cannot be empty".to_string()));
 			}
 
+			let (base, client) = ConnectOverrides::from_context(global, effective).build_client(
+				host_value.trim_end_matches('/'),
+				reqwest::Client::builder()
+					.timeout(effective.timeout)
+					.redirect(reqwest::redirect::Policy::none()),
+			)?;
+			let base = base.as_str();
+
+			let user_agent = effective.user_agent.clone();
 			if global.dry_run {
-				let base = host_value.trim_end_matches('/');
-				println!("POST {base}/api/auth/callback/credentials");
-				println!("content-type: application/x-www-form-urlencoded");
-				println!("(credentials omitted)");
+				print_login_dry_run(base, &email, &user_agent, args.totp.is_some());
 				return Err(CliError::DryRunPrinted);
 			}
 
-			let base = host_value.trim_end_matches('/');
-
-			let client = reqwest::Client::builder()
-				.timeout(effective.timeout)
-				.redirect(reqwest::redirect::Policy::none())
-				.build()?;
+			// Parallel jobs sharing this profile log in one at a time; a job that had to
+			// wait reuses the session the previous holder saved instead of logging in again.
+			let host_key = canonical_host_key(&host_value)?;
+			let stale_session = cfg.profile(&profile).session_cookie.clone();
+			let (_lock, waited) = RefreshLock::acquire(global, config_path, &host_key).await?;
+			let mut cfg = config::load_config(config_path)?;
+			let current_session = cfg.profile(&profile).session_cookie.clone();
+			if waited && current_session.is_some() && current_session != stale_session {
+				if !global.quiet {
+					eprintln!("Reusing the session another process just saved to profile '{profile}'.");
+				}
+				return Ok(());
+			}
 
-			let user_agent = format!("ztnet-cli/{}", env!("CARGO_PKG_VERSION"));
+			let retry = LoginRetry {
+				retries: effective.retries,
+				backoff: effective.backoff,
+				quiet: global.quiet,
+			};
 			let mut totp = args.totp.clone();
 			loop {
-				let (csrf_token, csrf_cookie_header) =
-					fetch_nextauth_csrf(&client, base, &user_agent).await?;
-				let response = nextauth_credentials_login(
-					&client,
-					base,
-					&csrf_token,
-					&csrf_cookie_header,
-					&email,
-					&password,
-					&user_agent,
-					totp.as_deref(),
-				)
-				.await?;
+				let (csrf_token, csrf_cookie_header) = retry
+					.run(CSRF_STEP, async || fetch_nextauth_csrf(&client, base, &user_agent, global).await)
+					.await?;
+				let response = retry
+					.run(CALLBACK_STEP, async || {
+						nextauth_credentials_login(
+							&client,
+							base,
+							&csrf_token,
+							&csrf_cookie_header,
+							&email,
+							&password,
+							&user_agent,
+							totp.as_deref(),
+						)
+						.await
+					})
+					.await?;
 
 				if response.ok {
 					let session = response.session_cookie.ok_or_else(|| {
@@ -234,14 +258,12 @@ pub(super) async fn run(global: &GlobalOpts, command: AuthCommand) -> Result<(),
 						profile_cfg.host = Some(host_value.to_string());
 					}
 					profile_cfg.session_cookie = Some(session);
+					profile_cfg.session_expires_at = response.session_expires_at;
 					profile_cfg.device_cookie = response.device_cookie;
 
-					let host_key = canonical_host_key(&host_value)?;
-					if cfg.host_defaults.get(&host_key).is_none() {
-						cfg.host_defaults.insert(host_key, profile.clone());
-					}
+					cfg.host_defaults.entry(host_key.clone()).or_insert_with(|| profile.clone());
 
-					config::save_config(&config_path, &cfg)?;
+					config::save_config(config_path, &cfg)?;
 
 					if !global.quiet {
 						eprintln!("Session saved to profile '{profile}'.");
@@ -264,6 +286,7 @@ pub(super) async fn run(global: &GlobalOpts, command: AuthCommand) -> Result<(),
 						));
 					}
 
+					// The lock's heartbeat keeps it from looking abandoned while this waits.
 					eprint!("Two-factor code: ");
 					std::io::Write::flush(&mut std::io::stderr())?;
 					let mut code = String::new();
@@ -291,8 +314,9 @@ pub(super) async fn run(global: &GlobalOpts, command: AuthCommand) -> Result<(),
 			let profile = args.profile.unwrap_or_else(|| effective.profile.clone());
 			let profile_cfg = cfg.profile_mut(&profile);
 			profile_cfg.session_cookie = None;
+			profile_cfg.session_expires_at = None;
 			profile_cfg.device_cookie = None;
-			config::save_config(&config_path, &cfg)?;
+			config::save_config(config_path, &cfg)?;
 
 			if !global.quiet {
 				eprintln!("Session cleared from profile '{profile}'.");
@@ -303,7 +327,7 @@ pub(super) async fn run(global: &GlobalOpts, command: AuthCommand) -> Result<(),
 			let value = json!({
 				"profile": effective.profile,
 				"host": effective.host,
-				"token": effective.token.as_deref().map(redact_token),
+				"token": effective.token.as_deref().map(redact::token),
 				"session": if effective.session_cookie.is_some() { "active" } else { "none" },
 				"device": if effective.device_cookie.is_some() { "present" } else { "none" },
 				"org": effective.org,
@@ -318,14 +342,7 @@ pub(super) async fn run(global: &GlobalOpts, command: AuthCommand) -> Result<(),
 		AuthCommand::Test(args) => {
 			let path = if args.org.is_some() { "/api/v1/org" } else { "/api/v1/network" };
 
-			let client = HttpClient::new(
-				&effective.host,
-				effective.token.clone(),
-				effective.timeout,
-				effective.retries,
-				global.dry_run,
-				ClientUi::from_context(global, &effective),
-			)?;
+			let client = ctx.client()?;
 
 			let response = client
 				.request_json(Method::GET, path, None, Default::default(), true)
@@ -340,17 +357,13 @@ pub(super) async fn run(global: &GlobalOpts, command: AuthCommand) -> Result<(),
 			Ok(())
 		}
 		AuthCommand::Profiles { command } => match command {
-			crate::cli::AuthProfilesCommand::List => {
-				let active = cfg.active_profile.clone();
-				let profiles: Vec<String> = cfg.profiles.keys().cloned().collect();
-				let value = json!({ "active_profile": active, "profiles": profiles });
-				print_human_or_machine(&value, effective.output, global.no_color)?;
-				Ok(())
+			crate::cli::AuthProfilesCommand::List(args) => {
+				auth_profiles_list(&cfg, args, effective.output, global)
 			}
 			crate::cli::AuthProfilesCommand::Use(args) => {
 				cfg.active_profile = Some(args.name.clone());
 				cfg.profile_mut(&args.name);
-				config::save_config(&config_path, &cfg)?;
+				config::save_config(config_path, &cfg)?;
 
 				if !global.quiet {
 					eprintln!("Active profile set to '{}'.", args.name);
@@ -359,13 +372,14 @@ pub(super) async fn run(global: &GlobalOpts, command: AuthCommand) -> Result<(),
 			}
 		},
 		AuthCommand::Hosts { command } => match command {
-			crate::cli::AuthHostsCommand::List => auth_hosts_list(&cfg, effective.output, global),
+			crate::cli::AuthHostsCommand::List(args) => auth_hosts_list(&cfg, args, effective.output, global),
 			crate::cli::AuthHostsCommand::SetDefault(args) => {
-				auth_hosts_set_default(global, &config_path, &mut cfg, &effective, args)
+				auth_hosts_set_default(global, config_path, &mut cfg, effective, args)
 			}
 			crate::cli::AuthHostsCommand::UnsetDefault(args) => {
-				auth_hosts_unset_default(global, &config_path, &mut cfg, &effective, args)
+				auth_hosts_unset_default(global, config_path, &mut cfg, effective, args)
 			}
+			crate::cli::AuthHostsCommand::Prune => auth_hosts_prune(global, config_path, &mut cfg, effective.output),
 		},
 	}
 }
@@ -378,10 +392,125 @@ fn auth_login_error(message: &str) -> CliError {
 	}
 }
 
+/// Logs in without 2FA prompting and returns `(session_cookie, device_cookie)`.
+pub(super) async fn session_login(
+	global: &GlobalOpts,
+	effective: &crate::context::EffectiveConfig,
+	email: &str,
+	password: &str,
+) -> Result<(String, Option<String>), CliError> {
+	let (base, client) = ConnectOverrides::from_context(global, effective).build_client(
+		effective.host.trim_end_matches('/'),
+		reqwest::Client::builder()
+			.timeout(effective.timeout)
+			.redirect(reqwest::redirect::Policy::none()),
+	)?;
+
+	let user_agent = effective.user_agent.clone();
+	let retry = LoginRetry {
+		retries: effective.retries,
+		backoff: effective.backoff,
+		quiet: global.quiet,
+	};
+	let (csrf_token, csrf_cookie_header) = retry
+		.run(CSRF_STEP, async || fetch_nextauth_csrf(&client, &base, &user_agent, global).await)
+		.await?;
+	let response = retry
+		.run(CALLBACK_STEP, async || {
+			nextauth_credentials_login(
+				&client,
+				&base,
+				&csrf_token,
+				&csrf_cookie_header,
+				email,
+				password,
+				&user_agent,
+				None,
+			)
+			.await
+		})
+		.await?;
+
+	if !response.ok {
+		let message = match response.error.as_deref() {
+			Some("incorrect-username-password") => "invalid email or password",
+			Some("second-factor-required") => "two-factor code required",
+			Some(err) => err,
+			None => "login failed",
+		};
+		return Err(auth_login_error(message));
+	}
+
+	let session = response.session_cookie.ok_or_else(|| CliError::HttpStatus {
+		status: reqwest::StatusCode::UNAUTHORIZED,
+		message: "login succeeded but server did not set a session cookie".to_string(),
+		body: None,
+	})?;
+	Ok((session, response.device_cookie))
+}
+
+const CSRF_STEP: &str = "csrf fetch (GET /api/auth/csrf)";
+const CALLBACK_STEP: &str = "credentials callback (POST /api/auth/callback/credentials)";
+
+/// Retry policy for the two NextAuth login steps, mirroring `HttpClient`'s retries and
+/// backoff. Each step is retried on its own, so a callback that hits a restarting gateway
+/// is re-sent with the csrf token and cookies already obtained instead of starting over.
+#[derive(Debug, Clone, Copy)]
+struct LoginRetry {
+	retries: u32,
+	backoff: Backoff,
+	quiet: bool,
+}
+
+impl LoginRetry {
+	async fn run<T>(
+		&self,
+		step: &'static str,
+		mut attempt: impl AsyncFnMut() -> Result<T, CliError>,
+	) -> Result<T, CliError> {
+		let mut tries = 0;
+		loop {
+			match attempt().await {
+				Ok(value) => return Ok(value),
+				Err(err) if tries < self.retries && is_transient_login_error(&err) => {
+					if !self.quiet {
+						eprintln!("warning: {step} failed ({err}); retrying ({}/{})", tries + 1, self.retries);
+					}
+					tokio::time::sleep(self.backoff.delay(tries)).await;
+					tries += 1;
+				}
+				Err(err) => {
+					return Err(CliError::LoginStep {
+						step,
+						source: Box::new(err),
+					});
+				}
+			}
+		}
+	}
+}
+
+/// Gateway hiccups (NextAuth behind a proxy that is restarting) and network blips; a
+/// rejected login is reported through `LoginResponse` and never reaches here.
+fn is_transient_login_error(err: &CliError) -> bool {
+	match err {
+		CliError::HttpStatus { status, .. } => matches!(
+			*status,
+			reqwest::StatusCode::TOO_MANY_REQUESTS
+				| reqwest::StatusCode::BAD_GATEWAY
+				| reqwest::StatusCode::SERVICE_UNAVAILABLE
+				| reqwest::StatusCode::GATEWAY_TIMEOUT
+		),
+		CliError::Request(err) => err.is_timeout() || err.is_connect(),
+		_ => false,
+	}
+}
+
 struct LoginResponse {
 	ok: bool,
 	error: Option<String>,
 	session_cookie: Option<String>,
+	session_expires_at: Option<i64>,
 	device_cookie: Option<String>,
 }
 
@@ -389,6 +518,7 @@ async fn fetch_nextauth_csrf(
 	client: &reqwest::Client,
 	base: &str,
 	user_agent: &str,
+	global: &GlobalOpts,
 ) -> Result<(String, String), CliError> {
 	let auth_base = auth_root_base(base);
 	let mut url = Url::parse(&format!("{auth_base}/api/auth/csrf/"))?;
@@ -404,8 +534,14 @@ async fn fetch_nextauth_csrf(
 			request = request.header("cookie", cookie_header);
 		}
 
-		let resp = request.send().await?;
+		let resp = request.send().await.map_err(|err| tls::request_error(err, &url))?;
 		let status = resp.status();
+		if !global.quiet
+			&& !status.is_redirection()
+			&& let Some(warning) = clock_skew_warning(resp.headers())
+		{
+			eprintln!("warning: {warning}");
+		}
 		let set_cookies = collect_set_cookie(&resp);
 		merge_set_cookie_pairs(&mut cookies, &set_cookies);
 
@@ -472,6 +608,7 @@ async fn fetch_nextauth_csrf(
 	})
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn nextauth_credentials_login(
 	client: &reqwest::Client,
 	base: &str,
@@ -504,6 +641,7 @@ async fn nextauth_credentials_login(
 	let mut method = Method::POST;
 
 	let mut cookies: BTreeMap<String, String> = parse_cookie_header_pairs(csrf_cookie_header);
+	let mut session_expires_at = None;
 
 	for _ in 0..8 {
 		let cookie_header = cookie_header_from_pairs(&cookies);
@@ -521,10 +659,13 @@ async fn nextauth_credentials_login(
 			request = request.form(&form);
 		}
 
-		let resp = request.send().await?;
+		let resp = request.send().await.map_err(|err| tls::request_error(err, &url))?;
 		let status = resp.status();
 		let set_cookies = collect_set_cookie(&resp);
 		merge_set_cookie_pairs(&mut cookies, &set_cookies);
+		if let Some(expires) = session_cookie_expiry(&set_cookies, unix_now()) {
+			session_expires_at = Some(expires);
+		}
 
 		let location = resp
 			.headers()
@@ -567,6 +708,7 @@ async fn nextauth_credentials_login(
 					ok: false,
 					error,
 					session_cookie,
+					session_expires_at,
 					device_cookie,
 				});
 			}
@@ -578,6 +720,7 @@ async fn nextauth_credentials_login(
 					ok: true,
 					error: None,
 					session_cookie,
+					session_expires_at,
 					device_cookie,
 				});
 			}
@@ -605,6 +748,7 @@ async fn nextauth_credentials_login(
 					ok: true,
 					error: None,
 					session_cookie,
+					session_expires_at,
 					device_cookie,
 				});
 			}
@@ -641,6 +785,7 @@ async fn nextauth_credentials_login(
 			ok,
 			error,
 			session_cookie,
+			session_expires_at,
 			device_cookie,
 		});
 	}
@@ -652,6 +797,33 @@ async fn nextauth_credentials_login(
 	})
 }
 
+fn print_login_dry_run(base: &str, email: &str, user_agent: &str, has_totp: bool) {
+	let auth_base = auth_root_base(base);
+	println!("GET {auth_base}/api/auth/csrf/");
+	println!("accept: application/json");
+	println!("user-agent: {user_agent}");
+	println!();
+	println!("POST {auth_base}/api/auth/callback/credentials/");
+	println!("content-type: application/x-www-form-urlencoded");
+	println!("accept: application/json");
+	println!("x-auth-return-redirect: 1");
+	println!("user-agent: {user_agent}");
+	println!("cookie: {}", redact::REDACTED);
+	println!();
+
+	let mut form = url::form_urlencoded::Serializer::new(String::new());
+	form.append_pair("csrfToken", "<from /api/auth/csrf>")
+		.append_pair("callbackUrl", &format!("{auth_base}/network"))
+		.append_pair("json", "true")
+		.append_pair("email", email)
+		.append_pair("password", redact::REDACTED)
+		.append_pair("userAgent", user_agent);
+	if has_totp {
+		form.append_pair("totpCode", redact::REDACTED);
+	}
+	println!("{}", form.finish());
+}
+
 fn auth_root_base(base: &str) -> String {
 	let trimmed = base.trim_end_matches('/');
 	trimmed
@@ -678,6 +850,33 @@ fn merge_set_cookie_pairs(out: &mut BTreeMap<String, String>, set_cookies: &[Str
 	}
 }
 
+/// Expiry (Unix seconds) of the NextAuth session cookie among `set_cookies`, from
+/// `Max-Age` when present (it takes precedence per RFC 6265) or else `Expires`.
+fn session_cookie_expiry(set_cookies: &[String], now: i64) -> Option<i64> {
+	let raw = set_cookies.iter().find(|raw| {
+		parse_set_cookie_pair(raw).is_some_and(|(name, _)| name.ends_with("next-auth.session-token"))
+	})?;
+	let mut expires = None;
+	for attr in raw.split(';').skip(1) {
+		let Some((key, value)) = attr.split_once('=') else {
+			continue;
+		};
+		match key.trim().to_ascii_lowercase().as_str() {
+			"max-age" => return value.trim().parse::<i64>().ok().map(|secs| now + secs),
+			"expires" => expires = http::parse_http_date(value.trim()),
+			_ => {}
+		}
+	}
+	expires
+}
+
+fn unix_now() -> i64 {
+	std::time::SystemTime::now()
+		.duration_since(std::time::UNIX_EPOCH)
+		.map(|d| d.as_secs() as i64)
+		.unwrap_or_default()
+}
+
 fn parse_set_cookie_pair(raw: &str) -> Option<(String, String)> {
 	let pair = raw.split(';').next()?.trim();
 	if pair.is_empty() {
@@ -739,11 +938,100 @@ fn resolve_redirect_url(current: &Url, location: &str) -> Result<Url, CliError>
 	Ok(current.join(location)?)
 }
 
+fn auth_profiles_list(
+	cfg: &crate::config::Config,
+	args: crate::cli::AuthProfilesListArgs,
+	format: OutputFormat,
+	global: &GlobalOpts,
+) -> Result<(), CliError> {
+	let value = profile_listing(cfg, &args, unix_now());
+	if matches!(format, OutputFormat::Table) {
+		let rows = value["details"].as_array().cloned().unwrap_or_default();
+		return output::print_columns(&rows, &["name", "active", "host", "token", "session", "session_expires"]);
+	}
+	output::print_value(&value, format, global.no_color)
+}
+
+/// One row per profile with its credential state, so the listing doubles as an inventory
+/// of which profiles can still authenticate. `profiles` keeps its original shape (names
+/// only, now in the requested order) for existing consumers; the state goes in `details`.
+pub(super) fn profile_listing(
+	cfg: &crate::config::Config,
+	args: &crate::cli::AuthProfilesListArgs,
+	now: i64,
+) -> serde_json::Value {
+	let mut profiles: Vec<(&String, &crate::config::ProfileConfig)> = cfg.profiles.iter().collect();
+	profiles.sort_by(|(a_name, a), (b_name, b)| {
+		compare_profiles(args.sort, a, b).then_with(|| a_name.cmp(b_name))
+	});
+	if args.reverse {
+		profiles.reverse();
+	}
+
+	let active = cfg.active_profile.clone();
+	let rows: Vec<serde_json::Value> = profiles
+		.into_iter()
+		.map(|(name, profile)| {
+			let session = match (&profile.session_cookie, profile.session_expires_at) {
+				(None, _) => "none",
+				(Some(_), Some(expires)) if expires <= now => "expired",
+				(Some(_), _) => "present",
+			};
+			json!({
+				"name": name,
+				"active": active.as_deref() == Some(name.as_str()),
+				"host": profile.host,
+				"token": profile.token.is_some(),
+				"session": session,
+				"session_expires": profile.session_expires_at.map(format_unix_time),
+			})
+		})
+		.collect();
+
+	let names: Vec<&serde_json::Value> = rows.iter().map(|row| &row["name"]).collect();
+	json!({ "active_profile": active, "profiles": names, "details": rows })
+}
+
+fn compare_profiles(
+	key: crate::cli::ProfileSortKey,
+	a: &crate::config::ProfileConfig,
+	b: &crate::config::ProfileConfig,
+) -> std::cmp::Ordering {
+	match key {
+		crate::cli::ProfileSortKey::Name => std::cmp::Ordering::Equal,
+		crate::cli::ProfileSortKey::Host => a.host.cmp(&b.host),
+		crate::cli::ProfileSortKey::SessionExpires => {
+			// Profiles without a session sort last; sessions of unknown expiry after dated ones.
+			let key = |p: &crate::config::ProfileConfig| {
+				(p.session_cookie.is_none(), p.session_expires_at.unwrap_or(i64::MAX))
+			};
+			key(a).cmp(&key(b))
+		}
+	}
+}
+
+fn format_unix_time(secs: i64) -> String {
+	let time = std::time::UNIX_EPOCH + std::time::Duration::from_secs(secs.max(0) as u64);
+	humantime::format_rfc3339_seconds(time).to_string()
+}
+
 fn auth_hosts_list(
 	cfg: &crate::config::Config,
+	args: crate::cli::AuthHostsListArgs,
 	format: OutputFormat,
 	global: &GlobalOpts,
 ) -> Result<(), CliError> {
+	warn_stale_host_defaults(global, cfg);
+	let rows = host_listing(cfg, &args);
+	output::print_value(&serde_json::Value::Array(rows), format, global.no_color)?;
+	Ok(())
+}
+
+/// One row per known host with its default profile and every profile bound to it.
+pub(super) fn host_listing(
+	cfg: &crate::config::Config,
+	args: &crate::cli::AuthHostsListArgs,
+) -> Vec<serde_json::Value> {
 	let mut hosts: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
 
 	for host in cfg.host_defaults.keys() {
@@ -760,19 +1048,33 @@ fn auth_hosts_list(
 			.insert(name.clone());
 	}
 
-	let mut rows = Vec::with_capacity(hosts.len());
-	for (host, profiles) in hosts {
-		let default_profile = cfg.host_defaults.get(&host).cloned();
-		let profiles: Vec<String> = profiles.into_iter().collect();
-		rows.push(json!({
-			"host": host,
-			"default_profile": default_profile,
-			"profiles": profiles,
-		}));
+	let mut entries: Vec<(String, Option<String>, Vec<String>)> = hosts
+		.into_iter()
+		.map(|(host, profiles)| {
+			let default_profile = cfg.host_defaults.get(&host).cloned();
+			(host, default_profile, profiles.into_iter().collect())
+		})
+		.collect();
+	// Stable: ties keep host order, which the BTreeMap already provides.
+	match args.sort {
+		crate::cli::HostSortKey::Host => {}
+		crate::cli::HostSortKey::DefaultProfile => entries.sort_by(|a, b| a.1.cmp(&b.1)),
+		crate::cli::HostSortKey::Profiles => entries.sort_by_key(|entry| entry.2.len()),
+	}
+	if args.reverse {
+		entries.reverse();
 	}
 
-	output::print_value(&serde_json::Value::Array(rows), format, global.no_color)?;
-	Ok(())
+	entries
+		.into_iter()
+		.map(|(host, default_profile, profiles)| {
+			json!({
+				"host": host,
+				"default_profile": default_profile,
+				"profiles": profiles,
+			})
+		})
+		.collect()
 }
 
 fn auth_hosts_set_default(
@@ -867,6 +1169,52 @@ fn auth_hosts_unset_default(
 	Ok(())
 }
 
+fn auth_hosts_prune(
+	global: &GlobalOpts,
+	config_path: &std::path::Path,
+	cfg: &mut crate::config::Config,
+	format: OutputFormat,
+) -> Result<(), CliError> {
+	let stale = stale_host_defaults(cfg);
+	let verb = if global.dry_run { "Would remove" } else { "Removed" };
+	for entry in &stale {
+		if !global.quiet {
+			eprintln!("{verb} {} -> {} ({}).", entry.host, entry.profile, entry.reason);
+		}
+	}
+	if !stale.is_empty() && !global.dry_run {
+		for entry in &stale {
+			cfg.host_defaults.remove(&entry.host);
+		}
+		config::save_config(config_path, cfg)?;
+	}
+	if stale.is_empty() && !global.quiet {
+		eprintln!("Every host default points at a matching profile.");
+	}
+
+	// The lines above are the human report; machine formats also get the removals.
+	if matches!(format, OutputFormat::Table) {
+		return Ok(());
+	}
+	let rows: Vec<serde_json::Value> = stale
+		.iter()
+		.map(|entry| json!({ "host": entry.host, "profile": entry.profile, "reason": entry.reason }))
+		.collect();
+	output::print_value(&serde_json::Value::Array(rows), format, global.no_color)
+}
+
+/// Non-destructive check shown by `auth hosts list` and `config list`.
+pub(super) fn warn_stale_host_defaults(global: &GlobalOpts, cfg: &crate::config::Config) {
+	let stale = stale_host_defaults(cfg);
+	if stale.is_empty() || global.quiet {
+		return;
+	}
+	for entry in &stale {
+		eprintln!("warning: host default {} -> {} is stale: {}", entry.host, entry.profile, entry.reason);
+	}
+	eprintln!("Run `ztnet auth hosts prune` to remove {}.", if stale.len() == 1 { "it" } else { "them" });
+}
+
 fn infer_profile_name(host: &str, cfg: &crate::config::Config) -> Result<String, CliError> {
 	let url = Url::parse(host.trim())
 		.map_err(|err| CliError::InvalidArgument(format!("invalid host url: {err}")))?;
@@ -949,6 +1297,17 @@ fn explicit_host_override(global: &GlobalOpts) -> Option<String> {
 		.or_else(|| env::var("API_ADDRESS").ok())
 }
 
+fn parse_error_from_location(location: &str) -> Option<String> {
+	let (_, query) = location.split_once('?')?;
+	for part in query.split('&') {
+		let (k, v) = part.split_once('=')?;
+		if k == "error" {
+			return Some(v.to_string());
+		}
+	}
+	None
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -993,6 +1352,43 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn session_cookie_expiry_prefers_max_age_over_expires() {
+		let cookies = vec![
+			"next-auth.csrf-token=x; Path=/; Max-Age=10".to_string(),
+			"__Secure-next-auth.session-token=abc; Path=/; Expires=Sun, 06 Nov 1994 08:49:37 GMT; HttpOnly"
+				.to_string(),
+		];
+		assert_eq!(session_cookie_expiry(&cookies, 0), Some(784111777));
+
+		let cookies = vec![
+			"next-auth.session-token=abc; Expires=Sun, 06 Nov 1994 08:49:37 GMT; Max-Age=3600".to_string(),
+		];
+		assert_eq!(session_cookie_expiry(&cookies, 1000), Some(4600));
+		assert_eq!(session_cookie_expiry(&["other=1".to_string()], 0), None);
+	}
+
+	#[test]
+	fn compare_profiles_puts_missing_sessions_last() {
+		use crate::cli::ProfileSortKey;
+		use crate::config::ProfileConfig;
+
+		let soon = ProfileConfig {
+			session_cookie: Some("a".to_string()),
+			session_expires_at: Some(100),
+			..Default::default()
+		};
+		let undated = ProfileConfig {
+			session_cookie: Some("b".to_string()),
+			..Default::default()
+		};
+		let none = ProfileConfig::default();
+		let key = ProfileSortKey::SessionExpires;
+		assert!(compare_profiles(key, &soon, &undated).is_lt());
+		assert!(compare_profiles(key, &undated, &none).is_lt());
+		assert!(compare_profiles(ProfileSortKey::Name, &soon, &none).is_eq());
+	}
+
 	#[test]
 	fn parse_set_cookie_pair_extracts_cookie_name_and_value() {
 		let (k, v) = parse_set_cookie_pair("next-auth.csrf-token=abc%7Cdef; Path=/; HttpOnly")
@@ -1024,15 +1420,42 @@ mod tests {
 			resolve_redirect_url(&current, "https://other.example.com/api/auth/csrf").unwrap();
 		assert_eq!(absolute.as_str(), "https://other.example.com/api/auth/csrf");
 	}
-}
 
-fn parse_error_from_location(location: &str) -> Option<String> {
-	let (_, query) = location.split_once('?')?;
-	for part in query.split('&') {
-		let (k, v) = part.split_once('=')?;
-		if k == "error" {
-			return Some(v.to_string());
-		}
+	#[test]
+	fn login_retry_retries_gateway_errors_and_names_the_step() {
+		let runtime = tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+		let retry = LoginRetry {
+			retries: 2,
+			backoff: Backoff {
+				base: std::time::Duration::ZERO,
+				max: std::time::Duration::ZERO,
+			},
+			quiet: true,
+		};
+		let status = |status| CliError::HttpStatus {
+			status,
+			message: "x".to_string(),
+			body: None,
+		};
+
+		let mut calls = 0;
+		let value = runtime
+			.block_on(retry.run(CALLBACK_STEP, async || {
+				calls += 1;
+				if calls < 3 { Err(status(reqwest::StatusCode::BAD_GATEWAY)) } else { Ok(calls) }
+			}))
+			.unwrap();
+		assert_eq!(value, 3);
+
+		let mut calls = 0;
+		let err = runtime
+			.block_on(retry.run(CSRF_STEP, async || -> Result<(), CliError> {
+				calls += 1;
+				Err(status(reqwest::StatusCode::INTERNAL_SERVER_ERROR))
+			}))
+			.unwrap_err();
+		assert_eq!(calls, 1);
+		assert!(err.to_string().starts_with("login failed at csrf fetch"));
+		assert_eq!(err.exit_code(), 1);
 	}
-	None
 }
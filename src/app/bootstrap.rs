@@ -0,0 +1,237 @@
+use reqwest::Method;
+use serde_json::{Value, json};
+
+use crate::cli::{BootstrapArgs, OutputFormat};
+use crate::config;
+use crate::context::canonical_host_key;
+use crate::error::CliError;
+use crate::http::{ClientUi, HttpClient};
+use crate::output;
+use crate::redact;
+
+use super::auth::session_login;
+use super::common::{confirm, print_kv, read_stdin_trimmed};
+use super::middleware::CommandContext;
+use super::trpc_client::{TrpcClient, cookie_from_effective};
+
+pub(super) async fn run(ctx: &CommandContext<'_>, args: BootstrapArgs) -> Result<(), CliError> {
+	let (global, effective) = (ctx.global, &ctx.effective);
+	let config_path = &ctx.config_path;
+	let mut cfg = ctx.cfg.clone();
+	let profile = args.save_as.clone().unwrap_or_else(|| effective.profile.clone());
+
+	let password = if args.password_stdin {
+		read_stdin_trimmed()?
+	} else {
+		args.password.clone().unwrap_or_default()
+	};
+	if password.trim().is_empty() {
+		return Err(CliError::InvalidArgument(
+			"missing --password (or ZTNET_PASSWORD or --password-stdin)".to_string(),
+		));
+	}
+
+	let existing = cfg.profile(&profile);
+	if existing.token.is_some() || existing.session_cookie.is_some() {
+		let prompt = format!("Profile '{profile}' already has credentials; overwrite? ");
+		if !confirm(global, &prompt)? {
+			return Ok(());
+		}
+	}
+
+	let ui = ClientUi::from_context(global, effective);
+
+	// 1. First user: on an empty database ZTNet accepts this without auth and makes it admin.
+	let rest = HttpClient::new(
+		&effective.host,
+		None,
+		effective.timeout,
+		effective.retries,
+		global.dry_run,
+		ui.clone(),
+	)?;
+	let created = rest
+		.request_json(
+			Method::POST,
+			"/api/v1/user",
+			Some(json!({
+				"email": args.email,
+				"password": password,
+				"name": args.name,
+				"generateApiToken": true,
+			})),
+			Default::default(),
+			false,
+		)
+		.await?;
+	let token = created
+		.get("apiToken")
+		.and_then(|v| v.as_str())
+		.map(str::to_string)
+		.ok_or_else(|| {
+			CliError::InvalidArgument("server created the user but returned no apiToken".to_string())
+		})?;
+	if !global.quiet {
+		eprintln!("Created admin user '{}'.", args.email);
+	}
+
+	// 2. Session for the tRPC-only features (org creation, admin commands).
+	let (session, device) = session_login(global, effective, &args.email, &password).await?;
+
+	// 3. Persist before the optional steps so a later failure leaves a usable profile.
+	store_credentials(&mut cfg, &profile, &effective.host, &token, &session, device.clone())?;
+	config::save_config(config_path, &cfg)?;
+	if !global.quiet {
+		eprintln!("Saved host, token and session to profile '{profile}'.");
+	}
+
+	let client = HttpClient::new(
+		&effective.host,
+		Some(token.clone()),
+		effective.timeout,
+		effective.retries,
+		global.dry_run,
+		ui.clone(),
+	)?;
+
+	let mut org_id = None;
+	let mut org_value = Value::Null;
+	if let Some(org_name) = args.create_org.as_deref() {
+		let mut session_effective = effective.clone();
+		session_effective.session_cookie = Some(session.clone());
+		session_effective.device_cookie = device.clone();
+		let trpc = TrpcClient::new(
+			&effective.host,
+			effective.timeout,
+			effective.retries,
+			global.dry_run,
+			ui.clone(),
+		)?
+		.with_cookie(cookie_from_effective(&session_effective));
+		let response = trpc
+			.call("org.createOrg", json!({ "orgName": org_name, "orgDescription": "" }))
+			.await?;
+		let id = match response.get("id").and_then(|v| v.as_str()) {
+			Some(id) => id.to_string(),
+			None => find_org_id(&client, org_name).await?,
+		};
+		org_value = json!({ "id": id, "name": org_name });
+		org_id = Some(id);
+		if !global.quiet {
+			eprintln!("Created organization '{org_name}'.");
+		}
+	}
+
+	let mut network_value = Value::Null;
+	if let Some(network_name) = args.create_network.as_deref() {
+		let path = match org_id.as_deref() {
+			Some(org_id) => format!("/api/v1/org/{org_id}/network"),
+			None => "/api/v1/network".to_string(),
+		};
+		network_value = client
+			.request_json(
+				Method::POST,
+				&path,
+				Some(json!({ "name": network_name })),
+				Default::default(),
+				true,
+			)
+			.await?;
+		if !global.quiet {
+			eprintln!("Created network '{network_name}'.");
+		}
+	}
+
+	let summary = json!({
+		"profile": profile,
+		"host": effective.host,
+		"user": created.get("user").cloned().unwrap_or(Value::Null),
+		"token": redact::token(&token),
+		"session": true,
+		"org": org_value,
+		"network": network_value,
+	});
+
+	if matches!(effective.output, OutputFormat::Table) {
+		return print_kv(&json!({
+			"profile": summary["profile"],
+			"host": summary["host"],
+			"email": args.email,
+			"token": summary["token"],
+			"org": summary["org"]["id"],
+			"network": summary["network"]["id"],
+		}));
+	}
+	output::print_value(&summary, effective.output, global.no_color)
+}
+
+/// Writes the new credentials into `profile`. An existing host default or active profile is kept.
+fn store_credentials(
+	cfg: &mut config::Config,
+	profile: &str,
+	host: &str,
+	token: &str,
+	session: &str,
+	device: Option<String>,
+) -> Result<(), CliError> {
+	let profile_cfg = cfg.profile_mut(profile);
+	profile_cfg.host = Some(host.to_string());
+	profile_cfg.token = Some(token.to_string());
+	profile_cfg.session_cookie = Some(session.to_string());
+	profile_cfg.session_expires_at = None;
+	profile_cfg.device_cookie = device;
+	cfg.host_defaults
+		.entry(canonical_host_key(host)?)
+		.or_insert_with(|| profile.to_string());
+	if cfg.active_profile.is_none() {
+		cfg.active_profile = Some(profile.to_string());
+	}
+	Ok(())
+}
+
+async fn find_org_id(client: &HttpClient, name: &str) -> Result<String, CliError> {
+	let orgs = client
+		.request_json(Method::GET, "/api/v1/org", None, Default::default(), true)
+		.await?;
+	orgs.as_array()
+		.into_iter()
+		.flatten()
+		.find(|o| o.get("orgName").and_then(|v| v.as_str()) == Some(name))
+		.and_then(|o| o.get("id").and_then(|v| v.as_str()))
+		.map(str::to_string)
+		.ok_or_else(|| {
+			CliError::InvalidArgument(format!("organization '{name}' was created but not found"))
+		})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn store_credentials_keeps_existing_host_default_and_active_profile() {
+		let mut cfg = config::Config::default();
+		let key = canonical_host_key("https://panel.example.com").unwrap();
+		cfg.host_defaults.insert(key.clone(), "prod".to_string());
+		cfg.active_profile = Some("prod".to_string());
+
+		store_credentials(&mut cfg, "lab", "https://panel.example.com", "tok", "sess", None).unwrap();
+
+		assert_eq!(cfg.host_defaults.get(&key).map(String::as_str), Some("prod"));
+		assert_eq!(cfg.active_profile.as_deref(), Some("prod"));
+		let lab = cfg.profile("lab");
+		assert_eq!(lab.token.as_deref(), Some("tok"));
+		assert_eq!(lab.session_cookie.as_deref(), Some("sess"));
+	}
+
+	#[test]
+	fn store_credentials_claims_an_unmapped_host() {
+		let mut cfg = config::Config::default();
+		store_credentials(&mut cfg, "lab", "https://panel.example.com", "tok", "sess", Some("dev".into())).unwrap();
+
+		let key = canonical_host_key("https://panel.example.com").unwrap();
+		assert_eq!(cfg.host_defaults.get(&key).map(String::as_str), Some("lab"));
+		assert_eq!(cfg.active_profile.as_deref(), Some("lab"));
+		assert_eq!(cfg.profile("lab").device_cookie.as_deref(), Some("dev"));
+	}
+}
@@ -1,12 +1,19 @@
-use std::io::{self, Read, Write};
-use std::path::PathBuf;
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 
-use serde_json::Value;
+use futures_util::stream::{self, StreamExt};
+use reqwest::Method;
+use serde_json::{Value, json};
 
-use crate::cli::{GlobalOpts, OutputFormat};
+use crate::cancel;
+use crate::cli::{ConfirmDefault, ErrorExitPolicy, GlobalOpts, OutputFormat};
 use crate::config::{self, Config};
 use crate::error::CliError;
+use crate::http::HttpClient;
 use crate::output;
+use crate::progress::Progress;
 
 pub(super) fn confirm(global: &GlobalOpts, prompt: &str) -> Result<bool, CliError> {
 	if global.dry_run {
@@ -21,13 +28,77 @@ pub(super) fn confirm(global: &GlobalOpts, prompt: &str) -> Result<bool, CliErro
 		));
 	}
 
-	eprint!("{prompt}[y/N]: ");
-	io::stderr().flush()?;
+	let default_yes = global.confirm_default == ConfirmDefault::Yes;
+	let choices = if default_yes { "[Y/n]" } else { "[y/N]" };
 
-	let mut input = String::new();
-	io::stdin().read_line(&mut input)?;
-	let input = input.trim().to_ascii_lowercase();
-	Ok(matches!(input.as_str(), "y" | "yes"))
+	let Some(timeout) = global.confirm_timeout else {
+		eprint!("{prompt}{choices}: ");
+		io::stderr().flush()?;
+
+		let mut input = String::new();
+		io::stdin().read_line(&mut input)?;
+		return Ok(parse_confirm_answer(&input, default_yes));
+	};
+
+	// stdin has no portable timed read, so the read happens on a helper thread. If the
+	// deadline passes, that thread stays blocked on stdin until the process exits and keeps
+	// whatever is typed later; commands prompt at most once, so nothing else reads stdin.
+	let (tx, rx) = std::sync::mpsc::channel();
+	std::thread::spawn(move || {
+		let mut input = String::new();
+		let result = io::stdin().read_line(&mut input).map(|_| input);
+		let _ = tx.send(result);
+	});
+
+	let interactive = io::stderr().is_terminal();
+	let deadline = Instant::now() + timeout;
+	if !interactive {
+		eprint!("{prompt}{choices} (timeout {}): ", humantime::format_duration(timeout));
+		io::stderr().flush()?;
+	}
+	loop {
+		let remaining = deadline.saturating_duration_since(Instant::now());
+		if interactive {
+			eprint!("\r{prompt}{choices} ({}s): ", remaining.as_secs_f64().ceil() as u64);
+			io::stderr().flush()?;
+		}
+		if remaining.is_zero() {
+			let answer = if default_yes { "yes" } else { "no" };
+			eprintln!("\nno answer after {}; assuming {answer}", humantime::format_duration(timeout));
+			return Ok(default_yes);
+		}
+		match rx.recv_timeout(remaining.min(Duration::from_secs(1))) {
+			Ok(input) => return Ok(parse_confirm_answer(&input?, default_yes)),
+			Err(std::sync::mpsc::RecvTimeoutError::Timeout) => continue,
+			Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => return Ok(false),
+		}
+	}
+}
+
+/// `input` is what `read_line` returned: an empty string means stdin was closed (EOF),
+/// which never confirms, even with `--confirm-default yes`; a bare Enter takes the default.
+fn parse_confirm_answer(input: &str, default_yes: bool) -> bool {
+	if input.is_empty() {
+		return false;
+	}
+	match input.trim().to_ascii_lowercase().as_str() {
+		"" => default_yes,
+		"y" | "yes" => true,
+		_ => false,
+	}
+}
+
+/// Applies a client-side validation result; with `--force-send` a failure only warns.
+pub(super) fn check_input(global: &GlobalOpts, result: Result<(), CliError>) -> Result<(), CliError> {
+	match result {
+		Err(err) if global.force_send => {
+			if !global.quiet {
+				eprintln!("warning: {err} (sending anyway: --force-send)");
+			}
+			Ok(())
+		}
+		other => other,
+	}
 }
 
 pub(super) fn write_text_output(
@@ -36,17 +107,20 @@ pub(super) fn write_text_output(
 	global: &GlobalOpts,
 ) -> Result<(), CliError> {
 	if let Some(path) = path {
-		if let Some(parent) = path.parent() {
-			std::fs::create_dir_all(parent)?;
-		}
-		std::fs::write(path, out)?;
+		let size = output::write_file(path, out.as_bytes())?;
 		if !global.quiet {
-			eprintln!("Wrote {} bytes to {}.", out.as_bytes().len(), path.display());
+			let note = match (output::encrypting(), output::signing()) {
+				(true, true) => " (age-encrypted, signed)",
+				(true, false) => " (age-encrypted)",
+				(false, true) => " (signed)",
+				(false, false) => "",
+			};
+			eprintln!("Wrote {}{note} to {}.", output::format_size(size), path.display());
 		}
 		return Ok(());
 	}
 
-	print!("{out}");
+	write!(output::writer(), "{out}")?;
 	Ok(())
 }
 
@@ -56,6 +130,207 @@ pub(super) fn load_config_store() -> Result<(PathBuf, Config), CliError> {
 	Ok((config_path, cfg))
 }
 
+/// Fetches `(id, path)` detail targets, at most `concurrency` at a time, preserving input
+/// order.
+///
+/// Without `skip_errors` the first failure aborts; with it, failures are returned
+/// as `{id, status, error}` entries alongside the successful details.
+pub(super) async fn fetch_details(
+	client: &HttpClient,
+	targets: Vec<(String, String)>,
+	skip_errors: bool,
+	concurrency: usize,
+) -> Result<(Vec<Value>, Vec<Value>), CliError> {
+	let progress = Progress::start("fetch details", Some(targets.len()));
+	let progress = &progress;
+	let results: Vec<(String, Result<Value, CliError>)> = stream::iter(targets)
+		.map(|(id, path)| async move {
+			progress.item_started(&id);
+			let result = client
+				.request_json(Method::GET, &path, None, Default::default(), true)
+				.await;
+			progress.item_finished(&id, result.is_ok());
+			(id, result)
+		})
+		.buffered(concurrency)
+		.collect()
+		.await;
+
+	let mut items = Vec::with_capacity(results.len());
+	let mut errors = Vec::new();
+	for (id, result) in results {
+		match result {
+			Ok(detail) => items.push(detail),
+			Err(CliError::DryRunPrinted) => return Err(CliError::DryRunPrinted),
+			Err(CliError::Interrupted) => return Err(CliError::Interrupted),
+			Err(err) if skip_errors => errors.push(detail_error(&id, &err)),
+			Err(err) => return Err(err),
+		}
+	}
+	Ok((items, errors))
+}
+
+pub(super) fn detail_error(id: &str, err: &CliError) -> Value {
+	let status = match err {
+		CliError::HttpStatus { status, .. } => Value::from(status.as_u16()),
+		_ => Value::Null,
+	};
+	json!({ "id": id, "status": status, "error": err.to_string() })
+}
+
+/// Prints a `--skip-errors` result: machine formats get `{items, errors}`, the
+/// table format prints the items and reports errors on stderr.
+pub(super) fn print_with_errors(
+	items: &Value,
+	errors: &[Value],
+	format: OutputFormat,
+	no_color: bool,
+) -> Result<(), CliError> {
+	if matches!(format, OutputFormat::Table) {
+		output::print_value(items, format, no_color)?;
+		report_errors_stderr(errors);
+		return Ok(());
+	}
+	output::print_value(&with_errors(items, errors), format, no_color)
+}
+
+/// The machine-format `--skip-errors` document (`schema detail-errors`).
+pub(super) fn with_errors(items: &Value, errors: &[Value]) -> Value {
+	json!({ "items": items, "errors": errors })
+}
+
+pub(super) fn report_errors_stderr(errors: &[Value]) {
+	for error in errors {
+		eprintln!(
+			"error: {}: {}",
+			error["id"].as_str().unwrap_or("-"),
+			error["error"].as_str().unwrap_or("-")
+		);
+	}
+}
+
+/// Per-item results of a bulk operation. Normally every result is printed at the end;
+/// with `--only-errors` successes are only counted and each failure is written to stdout
+/// as a JSON line the moment it happens, so long CI runs stay readable. Loops stop early
+/// on Ctrl-C ([`BulkReport::interrupted`]); `finish` then prints the partial report.
+pub(super) struct BulkReport {
+	only_errors: bool,
+	results: Vec<Value>,
+	total: usize,
+	failed: usize,
+	progress: Option<(Progress, Option<String>)>,
+}
+
+impl BulkReport {
+	pub(super) fn new(only_errors: bool) -> Self {
+		Self {
+			only_errors,
+			results: Vec::new(),
+			total: 0,
+			failed: 0,
+			progress: None,
+		}
+	}
+
+	/// Reports `--progress` events for `items` items: call [`BulkReport::begin`] before
+	/// each item; [`BulkReport::push`] finishes it.
+	pub(super) fn with_progress(mut self, operation: &'static str, items: usize) -> Self {
+		self.progress = Some((Progress::start(operation, Some(items)), None));
+		self
+	}
+
+	pub(super) fn begin(&mut self, item: &str) {
+		if let Some((progress, current)) = self.progress.as_mut() {
+			progress.item_started(item);
+			*current = Some(item.to_string());
+		}
+	}
+
+	/// True once Ctrl-C was pressed; bulk loops check this before each item.
+	pub(super) fn interrupted(&self) -> bool {
+		cancel::requested()
+	}
+
+	pub(super) fn push(&mut self, result: Value, failed: bool) -> Result<(), CliError> {
+		self.total += 1;
+		if failed {
+			self.failed += 1;
+		}
+		if let Some((progress, current)) = self.progress.as_mut() {
+			progress.item_finished(current.take().as_deref().unwrap_or_default(), !failed);
+		}
+		if !self.only_errors {
+			self.results.push(result);
+		} else if failed {
+			let mut writer = output::writer();
+			writeln!(writer, "{}", serde_json::to_string(&result)?)?;
+			writer.flush()?;
+		}
+		Ok(())
+	}
+
+	/// Prints the collected results (or the `--only-errors` summary) and applies the
+	/// bulk exit policy: non-zero when any item failed.
+	pub(super) fn finish(self, global: &GlobalOpts, format: OutputFormat) -> Result<(), CliError> {
+		if self.only_errors {
+			if !global.quiet {
+				eprintln!(
+					"{} succeeded, {} failed ({} total).",
+					self.total - self.failed,
+					self.failed,
+					self.total
+				);
+			}
+		} else {
+			output::print_value(&Value::Array(self.results), format, global.no_color)?;
+		}
+		if cancel::requested() {
+			if !global.quiet {
+				eprintln!("Stopped after {} item(s).", self.total);
+			}
+			return Err(CliError::Interrupted);
+		}
+		check_error_policy(ErrorExitPolicy::Any, self.failed, self.total)
+	}
+}
+
+pub(super) fn check_error_policy(
+	policy: ErrorExitPolicy,
+	failed: usize,
+	total: usize,
+) -> Result<(), CliError> {
+	let fail = match policy {
+		ErrorExitPolicy::Never => false,
+		ErrorExitPolicy::Any => failed > 0,
+		ErrorExitPolicy::All => total > 0 && failed == total,
+	};
+	if fail {
+		return Err(CliError::PartialFailure { failed, total });
+	}
+	Ok(())
+}
+
+/// Reads a `--body-file`/`--input-file`-style argument; `-` reads stdin instead, so generated
+/// JSON can be piped in. Stdin can only be consumed once per invocation.
+pub(super) fn read_input_file(path: &Path) -> Result<String, CliError> {
+	static STDIN_READ: AtomicBool = AtomicBool::new(false);
+	read_input(path, io::stdin(), &STDIN_READ)
+}
+
+fn read_input(path: &Path, mut stdin: impl Read, stdin_read: &AtomicBool) -> Result<String, CliError> {
+	if path.as_os_str() != "-" {
+		return Ok(std::fs::read_to_string(path)?);
+	}
+	if stdin_read.swap(true, Ordering::SeqCst) {
+		return Err(CliError::InvalidArgument(
+			"only one file argument per command can be read from stdin ('-')".to_string(),
+		));
+	}
+	let mut input = String::new();
+	stdin.read_to_string(&mut input)?;
+	Ok(input)
+}
+
 pub(super) fn read_stdin_trimmed() -> Result<String, CliError> {
 	let mut input = String::new();
 	io::stdin().read_to_string(&mut input)?;
@@ -68,24 +343,23 @@ pub(super) fn print_human_or_machine(
 	no_color: bool,
 ) -> Result<(), CliError> {
 	if matches!(format, OutputFormat::Table) {
-		print_kv(value);
-		return Ok(());
+		return print_kv(value);
 	}
 	output::print_value(value, format, no_color)
 }
 
-pub(super) fn print_kv(value: &Value) {
+pub(super) fn print_kv(value: &Value) -> Result<(), CliError> {
 	let Some(obj) = value.as_object() else {
-		println!("{value}");
-		return;
+		return output::emit(&value.to_string(), true);
 	};
 
 	let mut keys: Vec<&String> = obj.keys().collect();
 	keys.sort();
-	for key in keys {
-		let v = &obj[key];
-		println!("{key}: {}", render_scalar(v));
-	}
+	let lines: Vec<String> = keys
+		.into_iter()
+		.map(|key| format!("{key}: {}", output::field_text(key, &obj[key])))
+		.collect();
+	output::emit(&lines.join("\n"), true)
 }
 
 pub(super) fn render_scalar(value: &Value) -> String {
@@ -102,11 +376,119 @@ pub(super) fn opt_string(value: Option<String>) -> Value {
 	value.map(Value::String).unwrap_or(Value::Null)
 }
 
-pub(super) fn redact_token(token: &str) -> String {
-	const KEEP: usize = 4;
-	if token.len() <= KEEP * 2 {
-		return "REDACTED".to_string();
+/// "Did you mean" suggestions: labels of candidates whose key is a case-insensitive
+/// prefix/substring match or within a small edit distance of `query`, closest first.
+pub(super) fn suggest_similar<'a>(
+	query: &str,
+	candidates: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> Vec<String> {
+	let query = query.to_ascii_lowercase();
+	let max_distance = (query.chars().count() / 3).max(1);
+	let mut scored: Vec<(usize, &str)> = candidates
+		.into_iter()
+		.filter_map(|(key, label)| {
+			let key = key.to_ascii_lowercase();
+			let distance = if key.starts_with(&query) || key.contains(&query) {
+				0
+			} else {
+				edit_distance(&query, &key)
+			};
+			(distance <= max_distance).then_some((distance, label))
+		})
+		.collect();
+	scored.sort_by_key(|(distance, _)| *distance);
+
+	let mut out: Vec<String> = Vec::new();
+	for (_, label) in scored {
+		if !out.iter().any(|l| l == label) {
+			out.push(label.to_string());
+		}
+		if out.len() == 3 {
+			break;
+		}
 	}
-	format!("{}�{}", &token[..KEEP], &token[token.len() - KEEP..])
+	out
 }
 
+fn edit_distance(a: &str, b: &str) -> usize {
+	let b: Vec<char> = b.chars().collect();
+	let mut prev: Vec<usize> = (0..=b.len()).collect();
+	for (i, ca) in a.chars().enumerate() {
+		let mut cur = vec![i + 1; b.len() + 1];
+		for (j, cb) in b.iter().enumerate() {
+			let cost = usize::from(ca != *cb);
+			cur[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1);
+		}
+		prev = cur;
+	}
+	prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parse_confirm_answer_uses_default_for_empty_reply() {
+		assert!(parse_confirm_answer("\n", true));
+		assert!(!parse_confirm_answer("\n", false));
+		// EOF, e.g. a closed stdin under CI, is always a no.
+		assert!(!parse_confirm_answer("", true));
+		assert!(parse_confirm_answer(" Yes\n", false));
+		assert!(!parse_confirm_answer("nope\n", true));
+	}
+
+	#[test]
+	fn suggest_similar_prefers_close_matches() {
+		let candidates = [
+			("abcdef1234", "abcdef1234 (web)"),
+			("web", "abcdef1234 (web)"),
+			("0011223344", "0011223344 (db)"),
+			("db", "0011223344 (db)"),
+		];
+		assert_eq!(suggest_similar("abcdef1243", candidates), vec!["abcdef1234 (web)"]);
+		assert_eq!(suggest_similar("DB", candidates), vec!["0011223344 (db)"]);
+		assert!(suggest_similar("ffffffffff", candidates).is_empty());
+		assert_eq!(edit_distance("kitten", "sitting"), 3);
+	}
+
+	#[test]
+	fn bulk_report_only_errors_counts_without_collecting() {
+		let mut report = BulkReport::new(true);
+		report.push(json!({ "id": "a", "status": "updated" }), false).unwrap();
+		assert!(report.results.is_empty());
+		assert_eq!((report.total, report.failed), (1, 0));
+
+		let mut report = BulkReport::new(false);
+		report.push(json!({ "id": "a" }), false).unwrap();
+		report.push(json!({ "id": "b" }), true).unwrap();
+		assert_eq!(report.results.len(), 2);
+		assert_eq!((report.total, report.failed), (2, 1));
+	}
+
+	#[test]
+	fn read_input_takes_dash_as_stdin_once() {
+		let used = AtomicBool::new(false);
+		assert_eq!(read_input(Path::new("-"), &b"{\"a\":1}"[..], &used).unwrap(), "{\"a\":1}");
+		let err = read_input(Path::new("-"), &b"again"[..], &used).unwrap_err();
+		assert!(err.to_string().contains("only one file argument"));
+
+		let path = std::env::temp_dir().join(format!("ztnet-input-{}.json", std::process::id()));
+		std::fs::write(&path, "[]").unwrap();
+		assert_eq!(read_input(&path, &b""[..], &used).unwrap(), "[]");
+		std::fs::remove_file(&path).unwrap();
+	}
+
+	#[test]
+	fn check_error_policy_matches_policy() {
+		assert!(check_error_policy(ErrorExitPolicy::Never, 3, 3).is_ok());
+		assert!(check_error_policy(ErrorExitPolicy::Any, 0, 3).is_ok());
+		assert!(matches!(
+			check_error_policy(ErrorExitPolicy::Any, 1, 3),
+			Err(CliError::PartialFailure { failed: 1, total: 3 })
+		));
+		assert!(check_error_policy(ErrorExitPolicy::All, 2, 3).is_ok());
+		assert!(check_error_policy(ErrorExitPolicy::All, 3, 3).is_err());
+		assert!(check_error_policy(ErrorExitPolicy::All, 0, 0).is_ok());
+	}
+}
@@ -2,88 +2,145 @@ use std::time::Duration;
 
 use serde_json::{json, Value};
 
+use crate::alias;
 use crate::cli::{ConfigCommand, GlobalOpts, OutputFormat};
 use crate::config::{self, Config};
 use crate::context::canonical_host_key;
-use crate::context::resolve_effective_config;
+use crate::context::{resolve_with_sources, EffectiveConfig};
 use crate::error::CliError;
 use crate::host::{api_base_candidates, normalize_host_input};
+use crate::http::ConnectOverrides;
 use crate::multi_base;
 use crate::output;
+use crate::redact;
+use crate::tls;
 use reqwest::StatusCode;
 use url::Url;
 
 use super::common::{
-	load_config_store, opt_string, print_human_or_machine, redact_token, render_scalar,
+	opt_string, print_human_or_machine, read_input_file, read_stdin_trimmed,
+	render_scalar,
 };
+use super::middleware::CommandContext;
 
-pub(super) async fn run(global: &GlobalOpts, command: ConfigCommand) -> Result<(), CliError> {
-	let (config_path, mut cfg) = load_config_store()?;
-	let effective = resolve_effective_config(global, &cfg)?;
+pub(super) async fn run(ctx: &CommandContext<'_>, command: ConfigCommand) -> Result<(), CliError> {
+	let (global, effective) = (ctx.global, &ctx.effective);
+	let config_path = &ctx.config_path;
+	let mut cfg = ctx.cfg.clone();
 
 	match command {
 		ConfigCommand::Path => {
-			println!("{}", config_path.display());
-			Ok(())
+			output::emit(&config_path.display().to_string(), false)
 		}
 		ConfigCommand::Get(args) => {
 			let value = get_config_key(&cfg, &args.key)?;
 			if matches!(effective.output, OutputFormat::Table) {
-				println!("{}", render_scalar(&value));
-				return Ok(());
+				return output::emit(&render_scalar(&value), false);
 			}
 			output::print_value(&value, effective.output, global.no_color)?;
 			Ok(())
 		}
 		ConfigCommand::Set(args) => {
-			let key = if args.key == "host" {
-				format!("profiles.{}.host", effective.profile)
+			let batch = if let Some(path) = args.from_file.as_ref() {
+				Some(read_input_file(path)?)
+			} else if args.stdin {
+				Some(read_stdin_trimmed()?)
 			} else {
-				args.key.clone()
+				None
 			};
 
-			let mut value = args.value.clone();
-			if is_profile_host_key(&key) {
-				let normalized = normalize_host_input(&value)?;
-				if !args.no_validate && !global.dry_run {
-					let timeout = effective.timeout.min(Duration::from_secs(5));
-					let selected = select_valid_ztnet_host(&normalized, timeout).await?;
-					if selected != normalized && !global.quiet {
-						eprintln!("Host validated as '{selected}' (corrected from '{normalized}').");
-					}
-					value = selected;
-				} else {
-					value = normalized;
+			let Some(batch) = batch else {
+				let (Some(key), Some(value)) = (args.key, args.value) else {
+					return Err(CliError::InvalidArgument(
+						"config set requires KEY and VALUE (or --from-file/--stdin)".to_string(),
+					));
+				};
+				let (key, value) =
+					prepare_set_value(global, effective, &key, value, args.no_validate).await?;
+				set_config_key(&mut cfg, &key, &value, is_profile_host_key(&key))?;
+				config::save_config(config_path, &cfg)?;
+				if !global.quiet {
+					eprintln!("Set {}.", key);
 				}
+				return Ok(());
+			};
+
+			let entries = parse_batch_settings(&batch)?;
+			if entries.is_empty() {
+				return Err(CliError::InvalidArgument("no settings found in input".to_string()));
 			}
 
-			set_config_key(&mut cfg, &key, &value, is_profile_host_key(&key))?;
-			config::save_config(&config_path, &cfg)?;
+			// Validate everything against a copy so a bad entry leaves the config untouched.
+			let before = toml::to_string(&cfg).ok();
+			let mut next = cfg.clone();
+			let mut applied = Vec::with_capacity(entries.len());
+			for (key, value) in entries {
+				let (key, value) =
+					prepare_set_value(global, effective, &key, value, args.no_validate).await?;
+				set_config_key(&mut next, &key, &value, is_profile_host_key(&key))?;
+				applied.push(key);
+			}
+
+			if toml::to_string(&next).ok() == before {
+				if !global.quiet {
+					eprintln!("Config unchanged ({} keys already set).", applied.len());
+				}
+				return Ok(());
+			}
+
+			config::save_config(config_path, &next)?;
 			if !global.quiet {
-				eprintln!("Set {}.", key);
+				eprintln!("Set {} keys: {}.", applied.len(), applied.join(", "));
 			}
 			Ok(())
 		}
 		ConfigCommand::Unset(args) => {
 			unset_config_key(&mut cfg, &args.key)?;
-			config::save_config(&config_path, &cfg)?;
+			config::save_config(config_path, &cfg)?;
 			if !global.quiet {
 				eprintln!("Unset {}.", args.key);
 			}
 			Ok(())
 		}
-		ConfigCommand::List => {
-			let value = json!({
+		ConfigCommand::List(args) => {
+			super::auth::warn_stale_host_defaults(global, &cfg);
+			let mut value = json!({
 				"config_path": config_path.to_string_lossy(),
 				"profile": effective.profile,
 				"host": effective.host,
-				"token": effective.token.as_deref().map(redact_token),
+				"token": effective.token.as_deref().map(redact::token),
 				"org": effective.org,
 				"network": effective.network,
 				"output": effective.output.to_string(),
 				"timeout": humantime::format_duration(effective.timeout).to_string(),
 				"retries": effective.retries,
+				"concurrency": effective.concurrency,
+				"backoff_base": humantime::format_duration(effective.backoff.base).to_string(),
+				"backoff_max": humantime::format_duration(effective.backoff.max).to_string(),
+				"read_only": effective.read_only,
+				"cert_fingerprint": effective.cert_fingerprint,
+				"user_agent": effective.user_agent,
 			});
+			if args.explain {
+				let (_, sources) = resolve_with_sources(global, &cfg)?;
+				if matches!(effective.output, OutputFormat::Table) {
+					let rows: Vec<Value> = sources
+						.iter()
+						.map(|(key, source)| {
+							json!({ "key": key, "value": render_scalar(value.get(key).unwrap_or(&Value::Null)), "source": source })
+						})
+						.collect();
+					output::emit(&format!("config_path: {}", config_path.display()), true)?;
+					return output::print_columns(&rows, &["key", "value", "source"]);
+				}
+				for (key, source) in sources {
+					if let Some(entry) = value.get_mut(key) {
+						*entry = json!({ "value": entry.take(), "source": source });
+					} else {
+						value[key] = json!({ "source": source });
+					}
+				}
+			}
 			print_human_or_machine(&value, effective.output, global.no_color)?;
 			Ok(())
 		}
@@ -111,7 +168,7 @@ pub(super) async fn run(global: &GlobalOpts, command: ConfigCommand) -> Result<(
 				if let Some(network) = args.network {
 					profile_cfg.default_network = Some(network);
 				}
-				config::save_config(&config_path, &cfg)?;
+				config::save_config(config_path, &cfg)?;
 				if !global.quiet {
 					eprintln!("Context updated for profile '{}'.", effective.profile);
 				}
@@ -121,7 +178,7 @@ pub(super) async fn run(global: &GlobalOpts, command: ConfigCommand) -> Result<(
 				let profile_cfg = cfg.profile_mut(&effective.profile);
 				profile_cfg.default_org = None;
 				profile_cfg.default_network = None;
-				config::save_config(&config_path, &cfg)?;
+				config::save_config(config_path, &cfg)?;
 				if !global.quiet {
 					eprintln!("Context cleared for profile '{}'.", effective.profile);
 				}
@@ -131,6 +188,79 @@ pub(super) async fn run(global: &GlobalOpts, command: ConfigCommand) -> Result<(
 	}
 }
 
+async fn prepare_set_value(
+	global: &GlobalOpts,
+	effective: &EffectiveConfig,
+	key: &str,
+	value: String,
+	no_validate: bool,
+) -> Result<(String, String), CliError> {
+	let key = if key == "host" {
+		format!("profiles.{}.host", effective.profile)
+	} else {
+		key.to_string()
+	};
+
+	if !is_profile_host_key(&key) {
+		return Ok((key, value));
+	}
+
+	let normalized = normalize_host_input(&value)?;
+	if no_validate || global.dry_run {
+		return Ok((key, normalized));
+	}
+
+	let timeout = effective.timeout.min(Duration::from_secs(5));
+	let connect = ConnectOverrides::from_global(global);
+	let selected = select_valid_ztnet_host(&normalized, timeout, &connect).await?;
+	if selected != normalized && !global.quiet {
+		eprintln!("Host validated as '{selected}' (corrected from '{normalized}').");
+	}
+	Ok((key, selected))
+}
+
+/// Flattens a TOML document into dotted `config set` keys, e.g.
+/// `[profiles.prod] retries = 5` becomes `("profiles.prod.retries", "5")`.
+fn parse_batch_settings(input: &str) -> Result<Vec<(String, String)>, CliError> {
+	let table: toml::Table = input
+		.parse()
+		.map_err(|err| CliError::InvalidArgument(format!("invalid settings TOML: {err}")))?;
+
+	let mut out = Vec::new();
+	flatten_settings("", &toml::Value::Table(table), &mut out)?;
+	Ok(out)
+}
+
+fn flatten_settings(
+	prefix: &str,
+	value: &toml::Value,
+	out: &mut Vec<(String, String)>,
+) -> Result<(), CliError> {
+	let scalar = match value {
+		toml::Value::Table(table) => {
+			for (key, child) in table {
+				let key = if prefix.is_empty() {
+					key.clone()
+				} else {
+					format!("{prefix}.{key}")
+				};
+				flatten_settings(&key, child, out)?;
+			}
+			return Ok(());
+		}
+		toml::Value::String(s) => s.clone(),
+		toml::Value::Integer(n) => n.to_string(),
+		toml::Value::Boolean(b) => b.to_string(),
+		_ => {
+			return Err(CliError::InvalidArgument(format!(
+				"unsupported value type for key: {prefix}"
+			)));
+		}
+	};
+	out.push((prefix.to_string(), scalar));
+	Ok(())
+}
+
 fn get_config_key(cfg: &Config, key: &str) -> Result<Value, CliError> {
 	let parts: Vec<&str> = key.split('.').collect();
 	match parts.as_slice() {
@@ -140,6 +270,8 @@ fn get_config_key(cfg: &Config, key: &str) -> Result<Value, CliError> {
 			.map(Value::String)
 			.unwrap_or(Value::Null)),
 		["profiles"] => Ok(serde_json::to_value(&cfg.profiles)?),
+		["alias"] => Ok(serde_json::to_value(&cfg.aliases)?),
+		["alias", name] => Ok(opt_string(cfg.aliases.get(*name).cloned())),
 		["profiles", profile] => Ok(serde_json::to_value(cfg.profile(profile))?),
 		["profiles", profile, field] => {
 			let p = cfg.profile(profile);
@@ -153,10 +285,23 @@ fn get_config_key(cfg: &Config, key: &str) -> Result<Value, CliError> {
 					.map(|f| Value::String(f.to_string()))
 					.unwrap_or(Value::Null),
 				"timeout" => opt_string(p.timeout),
+				"backoff_base" => opt_string(p.backoff_base),
+				"backoff_max" => opt_string(p.backoff_max),
 				"retries" => p
 					.retries
 					.map(|n| Value::Number(n.into()))
 					.unwrap_or(Value::Null),
+				"allow_default_route" => p
+					.allow_default_route
+					.map(Value::Bool)
+					.unwrap_or(Value::Null),
+				"read_only" => p.read_only.map(Value::Bool).unwrap_or(Value::Null),
+				"cert_fingerprint" => opt_string(p.cert_fingerprint),
+				"user_agent" => opt_string(p.user_agent),
+				"concurrency" => p
+					.concurrency
+					.map(|n| Value::Number(n.into()))
+					.unwrap_or(Value::Null),
 				_ => {
 					return Err(CliError::InvalidArgument(format!(
 						"unsupported key: {key}"
@@ -181,6 +326,11 @@ fn set_config_key(
 			cfg.active_profile = Some(value.to_string());
 			Ok(())
 		}
+		["alias", name] => {
+			alias::validate(name, value)?;
+			cfg.aliases.insert(name.to_string(), value.trim().to_string());
+			Ok(())
+		}
 		["profiles", profile, field] => {
 			match *field {
 				"host" => {
@@ -225,12 +375,54 @@ fn set_config_key(
 							})?;
 							p.timeout = Some(value.to_string());
 						}
+						"backoff_base" => {
+							humantime::parse_duration(value).map_err(|_| {
+								CliError::InvalidArgument(format!("invalid backoff_base value: {value}"))
+							})?;
+							p.backoff_base = Some(value.to_string());
+						}
+						"backoff_max" => {
+							humantime::parse_duration(value).map_err(|_| {
+								CliError::InvalidArgument(format!("invalid backoff_max value: {value}"))
+							})?;
+							p.backoff_max = Some(value.to_string());
+						}
 						"retries" => {
 							let n = value.parse::<u32>().map_err(|_| {
 								CliError::InvalidArgument(format!("invalid retries value: {value}"))
 							})?;
 							p.retries = Some(n);
 						}
+						"allow_default_route" => {
+							let flag = value.trim().parse::<bool>().map_err(|_| {
+								CliError::InvalidArgument(format!(
+									"invalid allow_default_route value: {value} (expected true or false)"
+								))
+							})?;
+							p.allow_default_route = Some(flag);
+						}
+						"read_only" => {
+							let flag = value.trim().parse::<bool>().map_err(|_| {
+								CliError::InvalidArgument(format!(
+									"invalid read_only value: {value} (expected true or false)"
+								))
+							})?;
+							p.read_only = Some(flag);
+						}
+						"cert_fingerprint" => {
+							p.cert_fingerprint = Some(tls::normalize_fingerprint(value)?);
+						}
+						"user_agent" => {
+							p.user_agent = Some(value.trim().to_string()).filter(|ua| !ua.is_empty());
+						}
+						"concurrency" => {
+							let n = value.trim().parse::<u32>().ok().filter(|n| *n > 0).ok_or_else(|| {
+								CliError::InvalidArgument(format!(
+									"invalid concurrency value: {value} (expected a number of at least 1)"
+								))
+							})?;
+							p.concurrency = Some(n);
+						}
 						_ => {
 							return Err(CliError::InvalidArgument(format!(
 								"unsupported key: {key}"
@@ -252,6 +444,10 @@ fn unset_config_key(cfg: &mut Config, key: &str) -> Result<(), CliError> {
 			cfg.active_profile = None;
 			Ok(())
 		}
+		["alias", name] => {
+			cfg.aliases.remove(*name);
+			Ok(())
+		}
 		["profiles", profile, field] => {
 			match *field {
 				"host" => {
@@ -278,7 +474,14 @@ fn unset_config_key(cfg: &mut Config, key: &str) -> Result<(), CliError> {
 						"default_network" => p.default_network = None,
 						"output" => p.output = None,
 						"timeout" => p.timeout = None,
+						"backoff_base" => p.backoff_base = None,
+						"backoff_max" => p.backoff_max = None,
 						"retries" => p.retries = None,
+						"allow_default_route" => p.allow_default_route = None,
+						"read_only" => p.read_only = None,
+						"cert_fingerprint" => p.cert_fingerprint = None,
+						"user_agent" => p.user_agent = None,
+						"concurrency" => p.concurrency = None,
 						_ => {
 							return Err(CliError::InvalidArgument(format!(
 								"unsupported key: {key}"
@@ -314,14 +517,18 @@ fn is_profile_host_key(key: &str) -> bool {
 		&& parts.next().is_none()
 }
 
-async fn select_valid_ztnet_host(base: &str, timeout: Duration) -> Result<String, CliError> {
+async fn select_valid_ztnet_host(
+	base: &str,
+	timeout: Duration,
+	connect: &ConnectOverrides,
+) -> Result<String, CliError> {
 	let candidates = api_base_candidates(base);
 
-	let client = reqwest::Client::builder().timeout(timeout).build()?;
-
 	let mut last_error = None;
 	for candidate in &candidates {
-		match probe_ztnet_instance(&client, candidate).await {
+		let (probe_base, client) =
+			connect.build_client(candidate, reqwest::Client::builder().timeout(timeout))?;
+		match probe_ztnet_instance(&client, &probe_base).await {
 			Ok(()) => return Ok(candidate.clone()),
 			Err(err) => last_error = Some(err),
 		}
@@ -395,3 +602,30 @@ async fn probe_ztnet_instance(client: &reqwest::Client, base: &str) -> Result<()
 fn build_url_from_base(base: &str, path: &str) -> Result<Url, CliError> {
 	multi_base::parse_normalize_and_join_url(base, path)
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parse_batch_settings_flattens_tables_into_dotted_keys() {
+		let entries = parse_batch_settings(
+			"active_profile = \"prod\"\n[profiles.prod]\nretries = 5\noutput = \"json\"\n",
+		)
+		.unwrap();
+		assert_eq!(
+			entries,
+			vec![
+				("active_profile".to_string(), "prod".to_string()),
+				("profiles.prod.output".to_string(), "json".to_string()),
+				("profiles.prod.retries".to_string(), "5".to_string()),
+			]
+		);
+	}
+
+	#[test]
+	fn parse_batch_settings_rejects_arrays() {
+		let err = parse_batch_settings("[profiles.prod]\nretries = [1, 2]\n").unwrap_err();
+		assert!(err.to_string().contains("profiles.prod.retries"));
+	}
+}
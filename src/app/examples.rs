@@ -0,0 +1,228 @@
+//! `ztnet examples <group>`: curated multi-step recipes. Every command here is parsed
+//! against the real clap definitions in the tests, so a renamed flag breaks the build
+//! instead of the recipe.
+
+use serde_json::{Value, json};
+
+use crate::cli::{ExamplesArgs, GlobalOpts, OutputFormat};
+use crate::error::CliError;
+use crate::output;
+
+struct Recipe {
+	group: &'static str,
+	title: &'static str,
+	commands: &'static [&'static str],
+}
+
+const RECIPES: &[Recipe] = &[
+	Recipe {
+		group: "auth",
+		title: "Set up a profile with an API token and a session",
+		commands: &[
+			"ztnet --profile prod config set host https://ztnet.example.com",
+			"ztnet --profile prod auth set-token ztn_0123456789abcdef",
+			"ztnet --profile prod auth login --email admin@example.com --password 'secret'",
+			"ztnet --profile prod auth test",
+			"ztnet auth profiles use prod",
+		],
+	},
+	Recipe {
+		group: "auth",
+		title: "Pin the panel's TLS certificate",
+		commands: &["ztnet host cert show", "ztnet --yes host cert pin"],
+	},
+	Recipe {
+		group: "config",
+		title: "Work inside one organization and network by default",
+		commands: &[
+			"ztnet config context set --org my-org --network my-network",
+			"ztnet config context show",
+			"ztnet member list",
+			"ztnet config context clear",
+		],
+	},
+	Recipe {
+		group: "member",
+		title: "Approve a device that just joined",
+		commands: &[
+			"ztnet member list my-network --unauthorized",
+			"ztnet member authorize my-network abcdef1234",
+			"ztnet member update my-network abcdef1234 --name laptop",
+		],
+	},
+	Recipe {
+		group: "member",
+		title: "Pre-provision a node with a fixed IP",
+		commands: &["ztnet member add my-network abcdef1234 --name nas --authorize --ip 10.147.20.5"],
+	},
+	Recipe {
+		group: "member",
+		title: "Tag every web server, previewing first",
+		commands: &[
+			"ztnet --dry-run member tags apply my-network --filter 'name~=web' --tag tier=frontend",
+			"ztnet member tags apply my-network --filter 'name~=web' --tag tier=frontend --only-errors",
+		],
+	},
+	Recipe {
+		group: "member",
+		title: "Clean up unnamed members that never got set up",
+		commands: &[
+			"ztnet --dry-run member deauthorize my-network --all-unnamed --age 7d",
+			"ztnet --yes member deauthorize my-network --all-unnamed --age 7d",
+		],
+	},
+	Recipe {
+		group: "member",
+		title: "Move a member to another network, keeping its IPs",
+		commands: &["ztnet network move-member laptop --from old-net --to new-net --keep-ips --source stash"],
+	},
+	Recipe {
+		group: "network",
+		title: "Create a network and route a LAN through a gateway member",
+		commands: &[
+			"ztnet network create --name lab",
+			"ztnet network routes lab add --destination 192.168.1.0/24 --via 10.147.17.5",
+			"ztnet network routes lab list",
+		],
+	},
+	Recipe {
+		group: "network",
+		title: "Keep flow rules in version control",
+		commands: &[
+			"ztnet network flow-rules lab diff --file rules.conf",
+			"ztnet network update lab --org my-org --flow-rule-file rules.conf",
+		],
+	},
+	Recipe {
+		group: "network",
+		title: "Enable IPv6 and show the derived prefixes",
+		commands: &["ztnet network ipv6 lab --6plane --rfc4193 --show-prefixes"],
+	},
+	Recipe {
+		group: "org",
+		title: "Invite a team from a CSV file",
+		commands: &[
+			"ztnet --dry-run org invite send-bulk my-org --file invites.csv",
+			"ztnet org invite send-bulk my-org --file invites.csv --delay 2s",
+		],
+	},
+	Recipe {
+		group: "export",
+		title: "Resolve members by name on this machine",
+		commands: &[
+			"ztnet export hosts my-network --zone ztnet.local",
+			"ztnet export hosts my-network --zone ztnet.local --apply",
+		],
+	},
+	Recipe {
+		group: "export",
+		title: "Publish members to PowerDNS",
+		commands: &[
+			"ztnet export hosts my-network --zone zt.example.com --format powerdns-api --pdns-url http://ns1:8081 --pdns-key secret",
+		],
+	},
+	Recipe {
+		group: "admin",
+		title: "Nightly backup with retention",
+		commands: &[
+			"ztnet --yes admin backup create --name nightly --keep-daily 7 --keep-weekly 4",
+			"ztnet --dry-run admin backup prune --keep-last 3",
+		],
+	},
+	Recipe {
+		group: "trpc",
+		title: "Script several tRPC calls that build on each other",
+		commands: &[
+			"ztnet trpc list",
+			"ztnet --dry-run trpc call --procedure-file setup.yaml",
+			"ztnet trpc call --procedure-file setup.yaml",
+		],
+	},
+];
+
+pub(super) fn run(global: &GlobalOpts, args: ExamplesArgs) -> Result<(), CliError> {
+	let format = global.output.unwrap_or(if global.json {
+		OutputFormat::Json
+	} else {
+		OutputFormat::Table
+	});
+
+	let Some(group) = args.group else {
+		let groups = groups();
+		if matches!(format, OutputFormat::Table) {
+			let width = groups.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+			let mut text: String = groups
+				.iter()
+				.map(|(name, count)| format!("{name:<width$}  {count} recipe(s)\n"))
+				.collect();
+			text.push_str("\nRun `ztnet examples <GROUP>` to print them.");
+			return output::emit(&text, true);
+		}
+		let rows: Vec<Value> = groups
+			.iter()
+			.map(|(name, count)| json!({ "group": name, "recipes": count }))
+			.collect();
+		return output::print_value(&Value::Array(rows), format, global.no_color);
+	};
+
+	let recipes: Vec<&Recipe> = RECIPES.iter().filter(|r| r.group == group).collect();
+	if recipes.is_empty() {
+		let names: Vec<&str> = groups().into_iter().map(|(name, _)| name).collect();
+		return Err(CliError::InvalidArgument(format!(
+			"no examples for '{group}' (available: {})",
+			names.join(", ")
+		)));
+	}
+
+	if matches!(format, OutputFormat::Table) {
+		let blocks: Vec<String> = recipes
+			.iter()
+			.map(|recipe| format!("# {}\n{}", recipe.title, recipe.commands.join("\n")))
+			.collect();
+		return output::emit(&blocks.join("\n\n"), true);
+	}
+	let rows: Vec<Value> = recipes
+		.iter()
+		.map(|recipe| json!({ "group": recipe.group, "title": recipe.title, "commands": recipe.commands }))
+		.collect();
+	output::print_value(&Value::Array(rows), format, global.no_color)
+}
+
+/// Groups in first-appearance order with their recipe counts.
+fn groups() -> Vec<(&'static str, usize)> {
+	let mut groups: Vec<(&'static str, usize)> = Vec::new();
+	for recipe in RECIPES {
+		match groups.iter_mut().find(|(name, _)| *name == recipe.group) {
+			Some((_, count)) => *count += 1,
+			None => groups.push((recipe.group, 1)),
+		}
+	}
+	groups
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::alias::split_words;
+	use crate::cli::Cli;
+	use clap::{CommandFactory, Parser};
+
+	#[test]
+	fn every_recipe_parses_against_the_cli() {
+		let root = Cli::command();
+		for recipe in RECIPES {
+			assert!(
+				root.find_subcommand(recipe.group).is_some(),
+				"group '{}' is not a command",
+				recipe.group
+			);
+			for command in recipe.commands {
+				let words = split_words(command).unwrap();
+				assert_eq!(words[0], "ztnet", "`{command}` must start with ztnet");
+				if let Err(err) = Cli::try_parse_from(&words) {
+					panic!("recipe '{}': `{command}` does not parse:\n{err}", recipe.title);
+				}
+			}
+		}
+	}
+}
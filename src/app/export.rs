@@ -1,31 +1,28 @@
-use std::path::PathBuf;
+use std::fs;
+use std::io;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
 
 use reqwest::Method;
 use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
 
-use crate::cli::{ExportCommand, GlobalOpts};
-use crate::context::resolve_effective_config;
+use crate::cli::{ExportCommand, ExportHostsArgs, ExportHostsFormat, GlobalOpts};
 use crate::error::CliError;
-use crate::http::{ClientUi, HttpClient};
+use crate::http::HttpClient;
 
-use super::common::{load_config_store, write_text_output};
-use super::resolve::{resolve_network_id, resolve_org_id};
+use super::common::write_text_output;
+use super::hosts_file;
+use super::middleware::CommandContext;
+use super::powerdns::{self, PdnsTarget};
+use super::resolve::{network_or_context, resolve_network_id, resolve_org_id};
 
-pub(super) async fn run(global: &GlobalOpts, command: ExportCommand) -> Result<(), CliError> {
-	let (_config_path, cfg) = load_config_store()?;
-	let effective = resolve_effective_config(global, &cfg)?;
-
-	let client = HttpClient::new(
-		&effective.host,
-		effective.token.clone(),
-		effective.timeout,
-		effective.retries,
-		global.dry_run,
-		ClientUi::from_context(global, &effective),
-	)?;
+pub(super) async fn run(ctx: &CommandContext<'_>, command: ExportCommand) -> Result<(), CliError> {
+	let (global, effective) = (ctx.global, &ctx.effective);
+	let client = ctx.client()?;
 
 	match command {
-		ExportCommand::Hosts(args) => export_hosts(global, &effective, &client, args).await,
+		ExportCommand::Hosts(args) => export_hosts(global, effective, &client, args).await,
 	}
 }
 
@@ -35,24 +32,86 @@ async fn export_hosts(
 	client: &HttpClient,
 	args: crate::cli::ExportHostsArgs,
 ) -> Result<(), CliError> {
+	if (args.only_on_change || args.on_change.is_some()) && global.out.is_some() {
+		return Err(CliError::InvalidArgument(
+			"--only-on-change and --on-change need export hosts' own --out, not the global --out".to_string(),
+		));
+	}
 	if args.authorized_only && args.include_unauthorized {
 		return Err(CliError::InvalidArgument(
 			"cannot combine --authorized-only with --include-unauthorized".to_string(),
 		));
 	}
 
-	let zone = args.zone.trim().trim_end_matches('.').to_string();
-	if zone.is_empty() {
+	let zone = args
+		.zone
+		.as_deref()
+		.unwrap_or_default()
+		.trim()
+		.trim_end_matches('.')
+		.to_string();
+	if zone.is_empty() && !args.remove {
 		return Err(CliError::InvalidArgument("--zone cannot be empty".to_string()));
 	}
 
-	let org = args.org.or(effective.org.clone());
+	let apply_path = if args.apply {
+		if !matches!(args.format, ExportHostsFormat::Hosts) {
+			return Err(CliError::InvalidArgument(
+				"--apply only works with --format hosts".to_string(),
+			));
+		}
+		let path = args.hosts_file.clone().unwrap_or_else(hosts_file::system_hosts_path);
+		hosts_file::check_writable(&path)?;
+		Some(path)
+	} else {
+		None
+	};
+
+	let pdns_target = if matches!(args.format, ExportHostsFormat::PowerdnsApi) {
+		if args.out.is_some() {
+			return Err(CliError::InvalidArgument(
+				"--out, --only-on-change and --on-change cannot be used with --format powerdns-api".to_string(),
+			));
+		}
+		let (Some(url), Some(key)) = (args.pdns_url.clone(), args.pdns_key.clone()) else {
+			return Err(CliError::InvalidArgument(
+				"--format powerdns-api requires --pdns-url and --pdns-key (or PDNS_API_KEY)".to_string(),
+			));
+		};
+		Some(PdnsTarget {
+			url,
+			key,
+			server: args.pdns_server.clone(),
+			zone: zone.clone(),
+			ttl: args.pdns_ttl,
+			adopt: args.pdns_adopt,
+		})
+	} else {
+		None
+	};
+
+	let org = args.org.clone().or(effective.org.clone());
 	let org_id = match org {
 		Some(ref org) => Some(resolve_org_id(client, org).await?),
 		None => None,
 	};
 
-	let network_id = resolve_network_id(client, org_id.as_deref(), &args.network).await?;
+	let network = network_or_context(effective, args.network.as_deref())?;
+	let network_id = resolve_network_id(client, org_id.as_deref(), &network).await?;
+
+	if args.remove
+		&& let Some(path) = apply_path.as_deref()
+	{
+		let changed = hosts_file::apply_block(global, path, &network_id, None)?;
+		if !global.quiet {
+			if changed {
+				eprintln!("Removed network {network_id} from {}.", path.display());
+			} else {
+				eprintln!("{} has no block for network {network_id}.", path.display());
+			}
+		}
+		return Ok(());
+	}
 
 	let network_get_path = match org_id.as_deref() {
 		Some(org_id) => format!("/api/v1/org/{org_id}/network/{network_id}"),
@@ -76,8 +135,148 @@ async fn export_hosts(
 		return Err(CliError::InvalidArgument("expected array response".to_string()));
 	};
 
-	let include_unauthorized = args.include_unauthorized;
+	let records = host_records(items, &zone, args.include_unauthorized, IpSelection::from(&args));
+
+	if let Some(path) = apply_path {
+		let lines = hosts_lines(&records);
+		let changed = hosts_file::apply_block(global, &path, &network_id, Some(&lines))?;
+		if !global.quiet {
+			if changed {
+				eprintln!(
+					"Updated {} with {} entries for network {network_id}.",
+					path.display(),
+					lines.len()
+				);
+			} else {
+				eprintln!("{} is already up to date.", path.display());
+			}
+		}
+		return Ok(());
+	}
+
+	let text = match args.format {
+		ExportHostsFormat::Json => serde_json::to_string_pretty(&Value::Array(records))?,
+		ExportHostsFormat::Csv => {
+			let mut out = String::new();
+			out.push_str("ip,hostname,memberId,name,authorized\n");
+			for r in &records {
+				let ip = r.get("ip").and_then(|v| v.as_str()).unwrap_or("");
+				let hostname = r.get("hostname").and_then(|v| v.as_str()).unwrap_or("");
+				let member_id = r.get("memberId").and_then(|v| v.as_str()).unwrap_or("");
+				let name = r.get("name").and_then(|v| v.as_str()).unwrap_or("");
+				let authorized = r
+					.get("authorized")
+					.and_then(|v| v.as_bool())
+					.unwrap_or(false);
+
+				out.push_str(&format!(
+					"{},{},{},{},{}\n",
+					csv_escape(ip),
+					csv_escape(hostname),
+					csv_escape(member_id),
+					csv_escape(name),
+					authorized
+				));
+			}
+			out
+		}
+		ExportHostsFormat::Hosts => hosts_lines(&records).iter().map(|line| format!("{line}\n")).collect(),
+		ExportHostsFormat::OpenwrtDhcp => openwrt_domains(&records),
+		ExportHostsFormat::ReverseZone => {
+			let pairs: Vec<(&str, &str)> = records
+				.iter()
+				.filter_map(|r| Some((r.get("ip")?.as_str()?, r.get("hostname")?.as_str()?)))
+				.collect();
+			reverse_zones(&pairs)
+		}
+		ExportHostsFormat::PowerdnsApi => {
+			let pairs: Vec<(&str, &str)> = records
+				.iter()
+				.filter_map(|r| Some((r.get("ip")?.as_str()?, r.get("hostname")?.as_str()?)))
+				.collect();
+			if let Some(target) = pdns_target {
+				powerdns::push(global, effective, &target, &network_id, &pairs).await?;
+			}
+			return Ok(());
+		}
+	};
+
+	// clap makes both flags require --out.
+	let Some(out) = args.out.as_ref().filter(|_| args.only_on_change || args.on_change.is_some()) else {
+		return write_text_output(&text, args.out.as_ref(), global);
+	};
 
+	if args.only_on_change {
+		let digest_path = digest_path_for(out);
+		let digest = sha256_hex(text.as_bytes());
+		if out.exists() && read_digest(&digest_path)?.as_deref() == Some(digest.as_str()) {
+			if !global.quiet {
+				eprintln!("{} is unchanged; not rewritten.", out.display());
+			}
+			return Ok(());
+		}
+		write_text_output(&text, Some(out), global)?;
+		fs::write(&digest_path, format!("{digest}\n"))?;
+	} else {
+		write_text_output(&text, Some(out), global)?;
+	}
+
+	if let Some(command) = args.on_change.as_deref() {
+		run_hook(global, command, out)?;
+	}
+	Ok(())
+}
+
+/// Runs the `--on-change` command through the shell, with the written file in
+/// `ZTNET_EXPORT_FILE`. A non-zero exit fails the export.
+fn run_hook(global: &GlobalOpts, command: &str, out: &Path) -> Result<(), CliError> {
+	let mut shell = if cfg!(windows) {
+		let mut shell = std::process::Command::new("cmd");
+		shell.arg("/C");
+		shell
+	} else {
+		let mut shell = std::process::Command::new("sh");
+		shell.arg("-c");
+		shell
+	};
+	if !global.quiet {
+		eprintln!("Running --on-change: {command}");
+	}
+	let status = shell.arg(command).env("ZTNET_EXPORT_FILE", out).status()?;
+	if !status.success() {
+		return Err(io::Error::other(format!("--on-change command failed ({status})")).into());
+	}
+	Ok(())
+}
+
+/// The address family flags of `export hosts` (also taken by the agent's hosts endpoint).
+#[derive(Debug, Default, Clone, Copy)]
+pub(super) struct IpSelection {
+	pub ipv4_only: bool,
+	pub ipv6_only: bool,
+	pub ipv6_first: bool,
+	pub one_ip_per_member: bool,
+}
+
+impl From<&ExportHostsArgs> for IpSelection {
+	fn from(args: &ExportHostsArgs) -> Self {
+		Self {
+			ipv4_only: args.ipv4_only,
+			ipv6_only: args.ipv6_only,
+			ipv6_first: args.ipv6_first,
+			one_ip_per_member: args.one_ip_per_member,
+		}
+	}
+}
+
+/// One `{ip, hostname, memberId, name, authorized}` record per selected member address,
+/// with hostnames built from the sanitized member name (or id) under `zone`.
+pub(super) fn host_records(
+	items: &[Value],
+	zone: &str,
+	include_unauthorized: bool,
+	selection: IpSelection,
+) -> Vec<Value> {
 	let mut records = Vec::new();
 	for item in items {
 		let authorized = item.get("authorized").and_then(|v| v.as_bool()).unwrap_or(false);
@@ -110,7 +309,7 @@ async fn export_hosts(
 			})
 			.unwrap_or_default();
 
-		for ip in ips {
+		for ip in select_ips(ips, selection) {
 			records.push(json!({
 				"ip": ip,
 				"hostname": hostname,
@@ -121,47 +320,99 @@ async fn export_hosts(
 		}
 	}
 
-	match args.format {
-		crate::cli::ExportHostsFormat::Json => {
-			let value = Value::Array(records);
-			write_export_output(&value, args.out.as_ref(), global)?;
-		}
-		crate::cli::ExportHostsFormat::Csv => {
-			let mut out = String::new();
-			out.push_str("ip,hostname,memberId,name,authorized\n");
-			for r in &records {
-				let ip = r.get("ip").and_then(|v| v.as_str()).unwrap_or("");
-				let hostname = r.get("hostname").and_then(|v| v.as_str()).unwrap_or("");
-				let member_id = r.get("memberId").and_then(|v| v.as_str()).unwrap_or("");
-				let name = r.get("name").and_then(|v| v.as_str()).unwrap_or("");
-				let authorized = r
-					.get("authorized")
-					.and_then(|v| v.as_bool())
-					.unwrap_or(false);
 
-				out.push_str(&format!(
-					"{},{},{},{},{}\n",
-					csv_escape(ip),
-					csv_escape(hostname),
-					csv_escape(member_id),
-					csv_escape(name),
-					authorized
-				));
-			}
-			write_text_output(&out, args.out.as_ref(), global)?;
-		}
-		crate::cli::ExportHostsFormat::Hosts => {
-			let mut out = String::new();
-			for r in &records {
-				let ip = r.get("ip").and_then(|v| v.as_str()).unwrap_or("");
-				let hostname = r.get("hostname").and_then(|v| v.as_str()).unwrap_or("");
-				out.push_str(&format!("{ip}\t{hostname}\n"));
+	records
+}
+
+/// `ip<TAB>hostname` lines, as written to hosts files.
+pub(super) fn hosts_lines(records: &[Value]) -> Vec<String> {
+	records
+		.iter()
+		.map(|r| {
+			let ip = r.get("ip").and_then(|v| v.as_str()).unwrap_or("");
+			let hostname = r.get("hostname").and_then(|v| v.as_str()).unwrap_or("");
+			format!("{ip}\t{hostname}")
+		})
+		.collect()
+}
+
+/// Applies the address family flags to one member's addresses: `--ipv4-only/--ipv6-only`
+/// filter, IPv4 comes before IPv6 (or after, with `--ipv6-first`) with the server's order
+/// kept within a family, and `--one-ip-per-member` keeps the first that remains.
+/// Addresses that don't parse are kept last unless a family filter is set.
+fn select_ips(ips: Vec<String>, args: IpSelection) -> Vec<String> {
+	let rank = |ip: &String| match ip.parse::<IpAddr>() {
+		Ok(IpAddr::V4(_)) => Some(usize::from(args.ipv6_first)),
+		Ok(IpAddr::V6(_)) => Some(usize::from(!args.ipv6_first)),
+		Err(_) => None,
+	};
+	let mut ips: Vec<String> = ips
+		.into_iter()
+		.filter(|ip| match ip.parse::<IpAddr>() {
+			Ok(addr) => !(args.ipv4_only && addr.is_ipv6() || args.ipv6_only && addr.is_ipv4()),
+			Err(_) => !args.ipv4_only && !args.ipv6_only,
+		})
+		.collect();
+	ips.sort_by_key(|ip| rank(ip).unwrap_or(2));
+	if args.one_ip_per_member {
+		ips.truncate(1);
+	}
+	ips
+}
+
+/// Renders PTR records grouped into reverse zones: one zone per IPv4 /24 and per IPv6
+/// /64 (nibble boundary). Each zone starts with its own `$ORIGIN`, so the output can be
+/// split per zone or included as-is. Unparseable addresses are skipped.
+fn reverse_zones(records: &[(&str, &str)]) -> String {
+	let mut entries: Vec<(IpAddr, String, String, String)> = records
+		.iter()
+		.filter_map(|(ip, hostname)| {
+			let addr = ip.parse::<IpAddr>().ok()?;
+			let (origin, owner) = reverse_name(addr);
+			let target = format!("{}.", hostname.trim_end_matches('.'));
+			Some((addr, origin, owner, target))
+		})
+		.collect();
+	// Numeric order (IPv4 before IPv6) keeps each zone's addresses contiguous.
+	entries.sort();
+	entries.dedup();
+
+	let mut out = String::new();
+	let mut current_origin: Option<&str> = None;
+	for (_, origin, owner, target) in &entries {
+		if current_origin != Some(origin.as_str()) {
+			if current_origin.is_some() {
+				out.push('\n');
 			}
-			write_text_output(&out, args.out.as_ref(), global)?;
+			out.push_str(&format!("$ORIGIN {origin}\n"));
+			current_origin = Some(origin);
 		}
+		out.push_str(&format!("{owner}\tIN\tPTR\t{target}\n"));
 	}
+	out
+}
 
-	Ok(())
+/// Splits an address into `(zone origin, owner name relative to the origin)`.
+fn reverse_name(addr: IpAddr) -> (String, String) {
+	match addr {
+		IpAddr::V4(v4) => {
+			let [a, b, c, d] = v4.octets();
+			(format!("{c}.{b}.{a}.in-addr.arpa."), d.to_string())
+		}
+		IpAddr::V6(v6) => {
+			let nibbles: Vec<String> = v6
+				.octets()
+				.iter()
+				.flat_map(|byte| [byte >> 4, byte & 0xf])
+				.map(|nibble| format!("{nibble:x}"))
+				.collect();
+			let reversed = |part: &[String]| part.iter().rev().cloned().collect::<Vec<_>>().join(".");
+			(
+				format!("{}.ip6.arpa.", reversed(&nibbles[..16])),
+				reversed(&nibbles[16..]),
+			)
+		}
+	}
 }
 
 fn sanitize_hostname_label(value: &str) -> String {
@@ -191,11 +442,127 @@ fn csv_escape(value: &str) -> String {
 	}
 }
 
-fn write_export_output(
-	value: &Value,
-	out: Option<&PathBuf>,
-	global: &GlobalOpts,
-) -> Result<(), CliError> {
-	let json = serde_json::to_string_pretty(value)?;
-	write_text_output(&json, out, global)
+/// One `config domain` section per record, for `/etc/config/dhcp`.
+fn openwrt_domains(records: &[Value]) -> String {
+	let mut out = String::new();
+	for r in records {
+		let ip = r.get("ip").and_then(|v| v.as_str()).unwrap_or("");
+		let hostname = r.get("hostname").and_then(|v| v.as_str()).unwrap_or("");
+		out.push_str("config domain\n");
+		out.push_str(&format!("\toption name {}\n", uci_quote(hostname)));
+		out.push_str(&format!("\toption ip {}\n\n", uci_quote(ip)));
+	}
+	out
+}
+
+fn uci_quote(value: &str) -> String {
+	format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// `--only-on-change` keeps the hash of the last written export next to it, e.g.
+/// `hosts.sha256` for `hosts`. It covers the plain text, so `--encrypt-to` output (which
+/// differs on every run) is still compared by content.
+fn digest_path_for(out: &Path) -> PathBuf {
+	let mut name = out.file_name().unwrap_or_default().to_os_string();
+	name.push(".sha256");
+	out.with_file_name(name)
+}
+
+fn read_digest(path: &Path) -> Result<Option<String>, CliError> {
+	match fs::read_to_string(path) {
+		Ok(text) => Ok(Some(text.trim().to_string())),
+		Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+		Err(err) => Err(err.into()),
+	}
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+	Sha256::digest(bytes).iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn reverse_zones_group_by_v4_24_and_v6_64() {
+		let records = [
+			("10.0.1.7", "db.zt.example"),
+			("10.0.1.5", "web.zt.example"),
+			("10.0.2.9", "ci.zt.example"),
+			("fd00:1:2:3::a", "web.zt.example"),
+			("not-an-ip", "x.zt.example"),
+		];
+		let out = reverse_zones(&records);
+		assert_eq!(
+			out,
+			"$ORIGIN 1.0.10.in-addr.arpa.\n\
+			 5\tIN\tPTR\tweb.zt.example.\n\
+			 7\tIN\tPTR\tdb.zt.example.\n\
+			 \n\
+			 $ORIGIN 2.0.10.in-addr.arpa.\n\
+			 9\tIN\tPTR\tci.zt.example.\n\
+			 \n\
+			 $ORIGIN 3.0.0.0.2.0.0.0.1.0.0.0.0.0.d.f.ip6.arpa.\n\
+			 a.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0\tIN\tPTR\tweb.zt.example.\n"
+		);
+	}
+
+	#[test]
+	fn openwrt_dhcp_writes_quoted_domain_sections() {
+		let records = [
+			json!({ "ip": "10.0.0.5", "hostname": "web.zt.example" }),
+			json!({ "ip": "fd00::1", "hostname": "bob's.zt.example" }),
+		];
+		assert_eq!(
+			openwrt_domains(&records),
+			"config domain\n\toption name 'web.zt.example'\n\toption ip '10.0.0.5'\n\n\
+			 config domain\n\toption name 'bob'\\''s.zt.example'\n\toption ip 'fd00::1'\n\n"
+		);
+	}
+
+	#[test]
+	fn family_flags_filter_order_and_trim_member_ips() {
+		use clap::Parser;
+		let ips = || ["fd00::1", "junk", "10.0.0.2", "fd00::2", "10.0.0.1"].map(str::to_string).to_vec();
+		let args = |flags: &[&str]| {
+			let cli = crate::cli::Cli::parse_from(["ztnet", "export", "hosts", "--zone", "z"].iter().chain(flags));
+			match cli.command {
+				crate::cli::Command::Export {
+					command: ExportCommand::Hosts(args),
+				} => IpSelection::from(&args),
+				_ => unreachable!(),
+			}
+		};
+
+		assert_eq!(select_ips(ips(), args(&[])), ["10.0.0.2", "10.0.0.1", "fd00::1", "fd00::2", "junk"]);
+		assert_eq!(select_ips(ips(), args(&["--ipv6-first"])), ["fd00::1", "fd00::2", "10.0.0.2", "10.0.0.1", "junk"]);
+		assert_eq!(select_ips(ips(), args(&["--ipv6-only"])), ["fd00::1", "fd00::2"]);
+		assert_eq!(select_ips(ips(), args(&["--ipv4-only", "--one-ip-per-member"])), ["10.0.0.2"]);
+	}
+
+	#[test]
+	fn change_detection_needs_out_and_hooks_report_failures() {
+		use clap::Parser;
+		let parse = |flags: &[&str]| {
+			crate::cli::Cli::try_parse_from(["ztnet", "export", "hosts", "--zone", "z"].iter().chain(flags))
+		};
+		assert!(parse(&["--only-on-change"]).is_err());
+		assert!(parse(&["--on-change", "true"]).is_err());
+		assert!(parse(&["--only-on-change", "--on-change", "true", "--out", "hosts"]).is_ok());
+
+		if cfg!(unix) {
+			let global = crate::cli::Cli::parse_from(["ztnet", "--quiet", "version"]).global;
+			let out = Path::new("/tmp/hosts.ztnet");
+			assert!(run_hook(&global, r#"test "$ZTNET_EXPORT_FILE" = /tmp/hosts.ztnet"#, out).is_ok());
+			assert!(run_hook(&global, "exit 3", out).is_err());
+		}
+	}
+
+	#[test]
+	fn only_on_change_digest_sits_next_to_the_output() {
+		assert_eq!(digest_path_for(Path::new("/etc/ztnet/hosts")), Path::new("/etc/ztnet/hosts.sha256"));
+		assert_eq!(digest_path_for(Path::new("db.rev")), Path::new("db.rev.sha256"));
+		assert_eq!(sha256_hex(b"").len(), 64);
+	}
 }
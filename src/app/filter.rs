@@ -0,0 +1,144 @@
+use serde_json::Value;
+
+use crate::error::CliError;
+
+/// Client-side filter shared by list-style commands: comma-separated clauses that must
+/// all match, e.g. `name~=web,authorized==true`.
+///
+/// - `field~=text` case-insensitive substring match
+/// - `field==value` / `field!=value` equality (strings case-insensitive; booleans accept
+///   `true/false/1/0/yes/no`, and a missing boolean field counts as `false`)
+///
+/// Fields may be dotted paths into nested objects (`config.authorized`).
+#[derive(Debug, Clone, Default)]
+pub(super) struct Filter {
+	clauses: Vec<Clause>,
+}
+
+#[derive(Debug, Clone)]
+struct Clause {
+	field: String,
+	op: Op,
+	value: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+	Contains,
+	Eq,
+	Ne,
+}
+
+/// Alternative field names tried when the primary one is absent (networks list `nwname`
+/// on some endpoints and `name` on others).
+const FIELD_FALLBACKS: &[(&str, &str)] = &[("name", "nwname")];
+
+impl Filter {
+	pub(super) fn parse(expr: &str) -> Result<Self, CliError> {
+		let mut clauses = Vec::new();
+		for raw in expr.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+			let (field, op, value) = if let Some((k, v)) = raw.split_once("~=") {
+				(k, Op::Contains, v)
+			} else if let Some((k, v)) = raw.split_once("!=") {
+				(k, Op::Ne, v)
+			} else if let Some((k, v)) = raw.split_once("==") {
+				(k, Op::Eq, v)
+			} else {
+				return Err(CliError::InvalidArgument(format!(
+					"invalid filter clause '{raw}' (expected field~=text, field==value or field!=value)"
+				)));
+			};
+			let field = field.trim();
+			if field.is_empty() {
+				return Err(CliError::InvalidArgument(format!(
+					"invalid filter clause '{raw}': missing field name"
+				)));
+			}
+			clauses.push(Clause {
+				field: field.to_string(),
+				op,
+				value: value.trim().to_string(),
+			});
+		}
+		Ok(Self { clauses })
+	}
+
+	pub(super) fn matches(&self, item: &Value) -> bool {
+		self.clauses.iter().all(|clause| clause.matches(item))
+	}
+}
+
+impl Clause {
+	fn matches(&self, item: &Value) -> bool {
+		let actual = lookup(item, &self.field);
+		match self.op {
+			Op::Contains => actual
+				.map(cell_text)
+				.is_some_and(|text| text.to_ascii_lowercase().contains(&self.value.to_ascii_lowercase())),
+			Op::Eq => values_equal(actual, &self.value),
+			Op::Ne => !values_equal(actual, &self.value),
+		}
+	}
+}
+
+fn lookup<'a>(item: &'a Value, field: &str) -> Option<&'a Value> {
+	let direct = field
+		.split('.')
+		.try_fold(item, |value, key| value.get(key))
+		.filter(|v| !v.is_null());
+	if direct.is_some() {
+		return direct;
+	}
+	FIELD_FALLBACKS
+		.iter()
+		.find(|(primary, _)| *primary == field)
+		.and_then(|(_, fallback)| item.get(*fallback))
+		.filter(|v| !v.is_null())
+}
+
+fn values_equal(actual: Option<&Value>, expected: &str) -> bool {
+	let expected_bool = match expected.to_ascii_lowercase().as_str() {
+		"true" | "1" | "yes" => Some(true),
+		"false" | "0" | "no" => Some(false),
+		_ => None,
+	};
+	match (actual, expected_bool) {
+		(Some(Value::Bool(b)), Some(e)) => *b == e,
+		(None, Some(e)) => !e,
+		(Some(value), _) => cell_text(value).eq_ignore_ascii_case(expected),
+		(None, None) => expected.is_empty(),
+	}
+}
+
+fn cell_text(value: &Value) -> String {
+	match value {
+		Value::String(s) => s.clone(),
+		other => other.to_string(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+
+	#[test]
+	fn filter_matches_substring_equality_and_paths() {
+		let member = json!({ "name": "Web-01", "authorized": true, "config": { "ipAssignments": ["10.0.0.5"] } });
+
+		assert!(Filter::parse("name~=web").unwrap().matches(&member));
+		assert!(Filter::parse("name~=web,authorized==true").unwrap().matches(&member));
+		assert!(!Filter::parse("name~=db").unwrap().matches(&member));
+		assert!(Filter::parse("hidden==false").unwrap().matches(&member));
+		assert!(Filter::parse("authorized!=false").unwrap().matches(&member));
+		assert!(Filter::parse("config.ipAssignments~=10.0.0.").unwrap().matches(&member));
+		assert!(Filter::parse("name~=prod").unwrap().matches(&json!({ "nwname": "prod-net" })));
+		assert!(Filter::parse("").unwrap().matches(&member));
+	}
+
+	#[test]
+	fn filter_rejects_clauses_without_operator() {
+		assert!(Filter::parse("name").is_err());
+		assert!(Filter::parse("==x").is_err());
+	}
+}
@@ -0,0 +1,156 @@
+use clap::builder::PossibleValue;
+use clap::{Arg, ArgAction, CommandFactory};
+use serde_json::{Value, json};
+
+use crate::cli::{Cli, HelpArgs};
+use crate::error::CliError;
+
+const CLI_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Replaces clap's built-in `help` subcommand so the same entry point can also emit the
+/// command tree as JSON for docs generators and wrappers.
+pub(super) fn run(args: HelpArgs) -> Result<(), CliError> {
+	let mut root = Cli::command();
+	root.build();
+
+	let mut cmd = &root;
+	for name in &args.command {
+		cmd = cmd.find_subcommand(name).ok_or_else(|| {
+			CliError::InvalidArgument(format!(
+				"unknown command '{}' (run `ztnet help` for the command list)",
+				args.command.join(" ")
+			))
+		})?;
+	}
+
+	if !args.json {
+		cmd.clone().print_long_help()?;
+		return Ok(());
+	}
+
+	let mut value = command_json(cmd);
+	if args.command.is_empty() {
+		value["version"] = json!(CLI_VERSION);
+	}
+	crate::output::emit(&serde_json::to_string_pretty(&value)?, false)
+}
+
+fn command_json(cmd: &clap::Command) -> Value {
+	let args: Vec<Value> = cmd.get_arguments().filter(|a| !a.is_hide_set()).map(arg_json).collect();
+	let subcommands: Vec<Value> = cmd
+		.get_subcommands()
+		// clap adds its own `help` subcommand to every nested command; it carries no information.
+		.filter(|sub| !sub.is_hide_set() && sub.get_name() != "help")
+		.map(command_json)
+		.collect();
+
+	let mut value = json!({
+		"name": cmd.get_name(),
+		"about": cmd.get_about().map(ToString::to_string),
+		"aliases": cmd.get_visible_aliases().collect::<Vec<_>>(),
+		"args": args,
+	});
+	if let Some(long_about) = cmd.get_long_about() {
+		value["long_about"] = json!(long_about.to_string());
+	}
+	if !subcommands.is_empty() {
+		value["subcommand_required"] = json!(cmd.is_subcommand_required_set());
+		value["subcommands"] = Value::Array(subcommands);
+	}
+	value
+}
+
+fn arg_json(arg: &Arg) -> Value {
+	let possible: Vec<String> = arg
+		.get_possible_values()
+		.iter()
+		.filter(|v| !v.is_hide_set())
+		.map(PossibleValue::get_name)
+		.map(str::to_string)
+		.collect();
+	let takes_value = arg.get_num_args().is_some_and(|n| n.takes_values());
+	let kind = match arg.get_action() {
+		ArgAction::SetTrue | ArgAction::SetFalse => "bool",
+		ArgAction::Count => "count",
+		ArgAction::Help | ArgAction::HelpShort | ArgAction::HelpLong | ArgAction::Version => "action",
+		_ if !possible.is_empty() => "enum",
+		_ if takes_value => "string",
+		_ => "bool",
+	};
+
+	let mut value = json!({
+		"id": arg.get_id().as_str(),
+		"type": kind,
+		"positional": arg.is_positional(),
+		"required": arg.is_required_set(),
+		"multiple": matches!(arg.get_action(), ArgAction::Append),
+		"help": arg.get_help().map(ToString::to_string),
+	});
+	if let Some(long) = arg.get_long() {
+		value["long"] = json!(format!("--{long}"));
+	}
+	if let Some(short) = arg.get_short() {
+		value["short"] = json!(format!("-{short}"));
+	}
+	if let Some(names) = arg.get_value_names().filter(|_| takes_value) {
+		value["value_names"] = json!(names.iter().map(ToString::to_string).collect::<Vec<_>>());
+	}
+	if !possible.is_empty() {
+		value["possible_values"] = json!(possible);
+	}
+	let defaults: Vec<String> = arg
+		.get_default_values()
+		.iter()
+		.map(|v| v.to_string_lossy().into_owned())
+		.collect();
+	// Flags default to "false"; only report defaults that carry information.
+	if takes_value && !defaults.is_empty() {
+		value["default"] = json!(defaults);
+	}
+	if let Some(env) = arg.get_env() {
+		value["env"] = json!(env.to_string_lossy());
+	}
+	if let Some(aliases) = arg.get_visible_aliases() {
+		value["aliases"] = json!(aliases.iter().map(|a| format!("--{a}")).collect::<Vec<_>>());
+	}
+	value
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn command_json_describes_flags_defaults_and_subcommands() {
+		let mut root = Cli::command();
+		root.build();
+		let value = command_json(&root);
+		assert_eq!(value["name"], "ztnet");
+
+		let global_args = value["args"].as_array().unwrap();
+		let read_only = global_args.iter().find(|a| a["id"] == "read_only").unwrap();
+		assert_eq!(read_only["type"], "bool");
+		assert_eq!(read_only["env"], "ZTNET_READONLY");
+
+		let output = global_args.iter().find(|a| a["id"] == "output").unwrap();
+		assert_eq!(output["type"], "enum");
+		assert!(output["possible_values"].as_array().unwrap().contains(&json!("yaml")));
+
+		let member = value["subcommands"]
+			.as_array()
+			.unwrap()
+			.iter()
+			.find(|c| c["name"] == "member")
+			.unwrap();
+		let deauthorize = member["subcommands"]
+			.as_array()
+			.unwrap()
+			.iter()
+			.find(|c| c["name"] == "deauthorize")
+			.unwrap();
+		let age = deauthorize["args"].as_array().unwrap().iter().find(|a| a["id"] == "age").unwrap();
+		assert_eq!(age["default"], json!(["24h"]));
+		assert_eq!(age["value_names"], json!(["DURATION"]));
+		assert!(member["subcommands"].as_array().unwrap().iter().all(|c| c["name"] != "help"));
+	}
+}
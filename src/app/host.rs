@@ -0,0 +1,161 @@
+use serde_json::{Value, json};
+use url::Url;
+
+use crate::cancel;
+use crate::cli::{HostCertCommand, HostCommand, OutputFormat};
+use crate::config;
+use crate::error::CliError;
+use crate::http::ConnectOverrides;
+use crate::output;
+use crate::tls;
+
+use super::admin::public_settings;
+use super::common::{confirm, print_human_or_machine, render_scalar};
+use super::middleware::{trpc_anonymous, CommandContext};
+
+pub(super) async fn run(ctx: &CommandContext<'_>, command: HostCommand) -> Result<(), CliError> {
+	match command {
+		HostCommand::Cert { command } => match command {
+			HostCertCommand::Show => show(ctx).await,
+			HostCertCommand::Pin => pin(ctx).await,
+		},
+		HostCommand::PublicInfo => {
+			let (global, effective) = (ctx.global, &ctx.effective);
+			let trpc = trpc_anonymous(global, effective)?;
+			let response = public_settings(&trpc).await?;
+			print_human_or_machine(&response, effective.output, global.no_color)
+		}
+	}
+}
+
+async fn show(ctx: &CommandContext<'_>) -> Result<(), CliError> {
+	let (global, effective) = (ctx.global, &ctx.effective);
+	let report = inspect(ctx).await?;
+	if !matches!(effective.output, OutputFormat::Table) {
+		return output::print_value(&report, effective.output, global.no_color);
+	}
+
+	let mut lines = vec![
+		format!("host:     {} ({})", report["server_name"].as_str().unwrap_or_default(), render_scalar(&report["address"])),
+		match report["verify_error"].as_str() {
+			Some(reason) => format!("verified: no ({reason})"),
+			None => "verified: yes".to_string(),
+		},
+		match report["pin"]["matches"].as_bool() {
+			Some(true) => "pinned:   yes, the leaf matches cert_fingerprint".to_string(),
+			Some(false) => format!("pinned:   MISMATCH (profile expects {})", render_scalar(&report["pin"]["expected"])),
+			None => "pinned:   no".to_string(),
+		},
+	];
+	for (index, cert) in report["chain"].as_array().into_iter().flatten().enumerate() {
+		lines.push(String::new());
+		lines.push(format!("[{index}] {}", if index == 0 { "leaf" } else { "intermediate" }));
+		lines.push(format!("  subject:  {}", render_scalar(&cert["subject"])));
+		lines.push(format!("  issuer:   {}", render_scalar(&cert["issuer"])));
+		lines.push(format!(
+			"  valid:    {} .. {} ({})",
+			render_scalar(&cert["not_before"]),
+			render_scalar(&cert["not_after"]),
+			expiry_note(&cert["expires_in_days"])
+		));
+		if let Some(sans) = cert["sans"].as_array() {
+			let names: Vec<String> = sans.iter().map(render_scalar).collect();
+			lines.push(format!("  sans:     {}", names.join(", ")));
+		}
+		lines.push(format!("  sha256:   {}", render_scalar(&cert["sha256"])));
+	}
+	output::emit(&lines.join("\n"), true)
+}
+
+async fn pin(ctx: &CommandContext<'_>) -> Result<(), CliError> {
+	let (global, effective) = (ctx.global, &ctx.effective);
+	let report = inspect(ctx).await?;
+	let fingerprint = report["chain"][0]["sha256"].as_str().unwrap_or_default().to_string();
+	let profile = &effective.profile;
+
+	if !global.quiet {
+		eprintln!("subject: {}", render_scalar(&report["chain"][0]["subject"]));
+		eprintln!("sha256:  {fingerprint}");
+		if let Some(reason) = report["verify_error"].as_str() {
+			eprintln!("warning: the certificate does not pass normal validation ({reason})");
+		}
+	}
+	if effective.cert_fingerprint.as_deref() == Some(fingerprint.as_str()) {
+		if !global.quiet {
+			eprintln!("Profile '{profile}' already pins this certificate.");
+		}
+		return Ok(());
+	}
+	if global.dry_run {
+		eprintln!("dry-run: would set profiles.{profile}.cert_fingerprint = {fingerprint}");
+		return Err(CliError::DryRunPrinted);
+	}
+	if !confirm(global, &format!("Pin this certificate for profile '{profile}'? "))? {
+		return Ok(());
+	}
+
+	let mut cfg = config::load_config(&ctx.config_path)?;
+	cfg.profile_mut(profile).cert_fingerprint = Some(fingerprint);
+	config::save_config(&ctx.config_path, &cfg)?;
+	if !global.quiet {
+		eprintln!("Pinned. Requests for profile '{profile}' now fail if the panel presents another certificate.");
+	}
+	Ok(())
+}
+
+/// Connects to the profile's host (honouring `--resolve` and `--host-header`) and describes
+/// the certificate chain it presents.
+async fn inspect(ctx: &CommandContext<'_>) -> Result<Value, CliError> {
+	let (global, effective) = (ctx.global, &ctx.effective);
+	let url = Url::parse(&effective.host)?;
+	if url.scheme() != "https" {
+		return Err(CliError::InvalidArgument(format!(
+			"{} is not an https URL; there is no certificate to inspect",
+			effective.host
+		)));
+	}
+	let host = url
+		.host_str()
+		.ok_or_else(|| CliError::InvalidArgument(format!("host has no hostname: {}", effective.host)))?
+		.trim_start_matches('[')
+		.trim_end_matches(']')
+		.to_ascii_lowercase();
+	let port = url.port_or_known_default().unwrap_or(443);
+
+	let connect = ConnectOverrides::from_context(global, effective);
+	let addr = connect.target_addr(&host, port).await?;
+	let server_name = connect.host_header.clone().unwrap_or(host);
+	let chain = cancel::guard(tls::fetch_chain(addr, &server_name, effective.timeout)).await??;
+
+	let certificates: Vec<Value> = chain
+		.certificates
+		.iter()
+		.enumerate()
+		.map(|(index, der)| tls::describe(der, index == 0))
+		.collect();
+	let leaf = certificates[0]["sha256"].as_str().unwrap_or_default();
+	let pin = match effective.cert_fingerprint.as_deref() {
+		Some(expected) => {
+			let expected = tls::normalize_fingerprint(expected)?;
+			json!({ "expected": expected, "matches": expected == leaf })
+		}
+		None => Value::Null,
+	};
+
+	Ok(json!({
+		"server_name": server_name,
+		"address": addr.to_string(),
+		"verified": chain.verify_error.is_none(),
+		"verify_error": chain.verify_error,
+		"pin": pin,
+		"chain": certificates,
+	}))
+}
+
+fn expiry_note(days: &Value) -> String {
+	match days.as_i64() {
+		Some(days) if days < 0 => format!("EXPIRED {} day(s) ago", -days),
+		Some(days) => format!("expires in {days} day(s)"),
+		None => "expiry unknown".to_string(),
+	}
+}
@@ -0,0 +1,219 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::cli::GlobalOpts;
+use crate::config;
+use crate::error::CliError;
+
+#[cfg(windows)]
+const ELEVATION_HINT: &str = "re-run from an elevated prompt (Run as administrator)";
+#[cfg(not(windows))]
+const ELEVATION_HINT: &str = "re-run with sudo";
+
+pub(super) fn system_hosts_path() -> PathBuf {
+	#[cfg(windows)]
+	{
+		let root = std::env::var_os("SystemRoot").unwrap_or_else(|| "C:\\Windows".into());
+		return PathBuf::from(root)
+			.join("System32")
+			.join("drivers")
+			.join("etc")
+			.join("hosts");
+	}
+
+	#[cfg(not(windows))]
+	PathBuf::from("/etc/hosts")
+}
+
+/// Fails early (before any API calls) when the hosts file cannot be opened for writing.
+pub(super) fn check_writable(path: &Path) -> Result<(), CliError> {
+	match fs::OpenOptions::new().append(true).open(path) {
+		Ok(_) => Ok(()),
+		Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+		Err(err) => Err(write_error(path, err)),
+	}
+}
+
+/// Replaces the `# BEGIN ztnet <marker>` … `# END ztnet <marker>` block in `path` with
+/// `lines`, appending the block if it does not exist yet; `None` removes the block.
+/// Returns `false` when the file already had exactly this content.
+pub(super) fn apply_block(
+	global: &GlobalOpts,
+	path: &Path,
+	marker: &str,
+	lines: Option<&[String]>,
+) -> Result<bool, CliError> {
+	let existing = match fs::read_to_string(path) {
+		Ok(contents) => contents,
+		Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+		Err(err) => return Err(err.into()),
+	};
+
+	let updated = splice_block(&existing, marker, lines)?;
+	if updated == existing {
+		return Ok(false);
+	}
+
+	if !existing.is_empty() {
+		let backup = backup_hosts(&existing)?;
+		if !global.quiet {
+			eprintln!("Backed up {} to {}.", path.display(), backup.display());
+		}
+	}
+
+	write_hosts(path, &updated).map_err(|err| write_error(path, err))?;
+	Ok(true)
+}
+
+/// On Unix the new file is written next to the old one and renamed over it, so readers
+/// never see a half-written hosts file. Bind-mounted files (e.g. `/etc/hosts` in a
+/// container) cannot be renamed over; those fall back to an in-place write.
+#[cfg(unix)]
+fn write_hosts(path: &Path, contents: &str) -> io::Result<()> {
+	use std::io::Write;
+
+	let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("hosts");
+	let tmp = path.with_file_name(format!(".{file_name}.ztnet.tmp"));
+	let result = (|| {
+		let mut file = fs::File::create(&tmp)?;
+		file.write_all(contents.as_bytes())?;
+		file.sync_all()?;
+		if let Ok(meta) = fs::metadata(path) {
+			fs::set_permissions(&tmp, meta.permissions())?;
+		}
+		fs::rename(&tmp, path)
+	})();
+
+	match result {
+		Ok(()) => Ok(()),
+		Err(err) => {
+			let _ = fs::remove_file(&tmp);
+			if err.kind() == io::ErrorKind::PermissionDenied {
+				return Err(err);
+			}
+			fs::write(path, contents)
+		}
+	}
+}
+
+/// Windows keeps the hosts file's ACLs only when it is rewritten in place.
+#[cfg(not(unix))]
+fn write_hosts(path: &Path, contents: &str) -> io::Result<()> {
+	fs::write(path, contents)
+}
+
+fn splice_block(existing: &str, marker: &str, lines: Option<&[String]>) -> Result<String, CliError> {
+	let newline = if existing.contains("\r\n") || (existing.is_empty() && cfg!(windows)) {
+		"\r\n"
+	} else {
+		"\n"
+	};
+	let begin = format!("# BEGIN ztnet {marker}");
+	let end = format!("# END ztnet {marker}");
+
+	let mut kept: Vec<&str> = Vec::new();
+	let mut insert_at = None;
+	let mut inside = false;
+	for line in existing.lines() {
+		if line.trim() == begin {
+			inside = true;
+			insert_at.get_or_insert(kept.len());
+			continue;
+		}
+		if line.trim() == end {
+			inside = false;
+			continue;
+		}
+		if !inside {
+			kept.push(line);
+		}
+	}
+	if inside {
+		return Err(CliError::InvalidArgument(format!(
+			"hosts file has '{begin}' without a matching '{end}'; fix it by hand first"
+		)));
+	}
+
+	if let Some(lines) = lines {
+		let mut block = Vec::with_capacity(lines.len() + 2);
+		block.push(begin.as_str());
+		block.extend(lines.iter().map(String::as_str));
+		block.push(end.as_str());
+
+		let insert_at = insert_at.unwrap_or(kept.len());
+		kept.splice(insert_at..insert_at, block);
+	} else if insert_at.is_none() {
+		return Ok(existing.to_string());
+	}
+
+	if kept.is_empty() {
+		return Ok(String::new());
+	}
+	let mut out = kept.join(newline);
+	out.push_str(newline);
+	Ok(out)
+}
+
+fn backup_hosts(contents: &str) -> Result<PathBuf, CliError> {
+	let dir = config::default_cache_dir()?.join("backups");
+	fs::create_dir_all(&dir)?;
+	let stamp = std::time::SystemTime::now()
+		.duration_since(std::time::UNIX_EPOCH)
+		.map(|d| d.as_secs())
+		.unwrap_or_default();
+	let path = dir.join(format!("hosts-{stamp}.bak"));
+	fs::write(&path, contents)?;
+	Ok(path)
+}
+
+fn write_error(path: &Path, err: io::Error) -> CliError {
+	if err.kind() == io::ErrorKind::PermissionDenied {
+		return CliError::InvalidArgument(format!(
+			"cannot write {}: permission denied; {ELEVATION_HINT}",
+			path.display()
+		));
+	}
+	err.into()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn lines(items: &[&str]) -> Vec<String> {
+		items.iter().map(|s| s.to_string()).collect()
+	}
+
+	#[test]
+	fn splice_block_appends_then_replaces_in_place() {
+		let original = "127.0.0.1\tlocalhost\n";
+		let first = splice_block(original, "abc", Some(&lines(&["10.0.0.1\ta.zt"]))).unwrap();
+		assert_eq!(
+			first,
+			"127.0.0.1\tlocalhost\n# BEGIN ztnet abc\n10.0.0.1\ta.zt\n# END ztnet abc\n"
+		);
+
+		let edited = format!("{first}::1\tlocalhost\n");
+		let second = splice_block(&edited, "abc", Some(&lines(&["10.0.0.2\tb.zt"]))).unwrap();
+		assert_eq!(
+			second,
+			"127.0.0.1\tlocalhost\n# BEGIN ztnet abc\n10.0.0.2\tb.zt\n# END ztnet abc\n::1\tlocalhost\n"
+		);
+		assert_eq!(splice_block(&second, "abc", Some(&lines(&["10.0.0.2\tb.zt"]))).unwrap(), second);
+
+		let removed = splice_block(&second, "abc", None).unwrap();
+		assert_eq!(removed, "127.0.0.1\tlocalhost\n::1\tlocalhost\n");
+		assert_eq!(splice_block(&removed, "abc", None).unwrap(), removed);
+	}
+
+	#[test]
+	fn splice_block_keeps_crlf_and_rejects_unterminated_blocks() {
+		let crlf = "127.0.0.1 localhost\r\n";
+		let out = splice_block(crlf, "abc", Some(&lines(&["10.0.0.1 a.zt"]))).unwrap();
+		assert!(out.ends_with("# END ztnet abc\r\n"));
+		assert!(!out.replace("\r\n", "").contains('\n'));
+
+		assert!(splice_block("# BEGIN ztnet abc\n10.0.0.1 a\n", "abc", None).is_err());
+	}
+}
@@ -1,38 +1,37 @@
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
 use reqwest::Method;
 use serde_json::Value;
+use serde_json::value::RawValue;
 
 use crate::cli::{GlobalOpts, MemberCommand, NetworkMemberCommand, OutputFormat};
-use crate::context::resolve_effective_config;
 use crate::error::CliError;
-use crate::http::{ClientUi, HttpClient};
+use crate::http::HttpClient;
 use crate::output;
 
-use super::common::{confirm, load_config_store, print_human_or_machine};
-use super::resolve::{resolve_network_id, resolve_org_id};
-use super::trpc_client::{require_cookie_from_effective, TrpcClient};
+use super::common::{
+	BulkReport, check_input, confirm, print_human_or_machine,
+	print_kv, read_input_file, render_scalar, suggest_similar,
+};
+use super::filter::Filter;
+use super::middleware::{trpc_authed, CommandContext};
+use super::resolve::{network_and_member, network_or_context, resolve_network_id, resolve_org_id};
+use super::trpc_client::{cookie_from_effective, TrpcClient};
 use super::trpc_resolve::{resolve_network_org_id, resolve_personal_network_id};
 
-pub(super) async fn run_alias(global: &GlobalOpts, command: MemberCommand) -> Result<(), CliError> {
-	let (_config_path, cfg) = load_config_store()?;
-	let effective = resolve_effective_config(global, &cfg)?;
-
-	let client = HttpClient::new(
-		&effective.host,
-		effective.token.clone(),
-		effective.timeout,
-		effective.retries,
-		global.dry_run,
-		ClientUi::from_context(global, &effective),
-	)?;
+pub(super) async fn run_alias(ctx: &CommandContext<'_>, command: MemberCommand) -> Result<(), CliError> {
+	let (global, effective) = (ctx.global, &ctx.effective);
+	let client = ctx.client()?;
 
 	match command {
-		MemberCommand::List(args) => member_list(global, &effective, &client, args).await,
-		MemberCommand::Get(args) => member_get(global, &effective, &client, args).await,
-		MemberCommand::Update(args) => member_update(global, &effective, &client, args).await,
+		MemberCommand::List(args) => member_list(global, effective, &client, args).await,
+		MemberCommand::Get(args) => member_get(global, effective, &client, args).await,
+		MemberCommand::Update(args) => member_update(global, effective, &client, args).await,
 		MemberCommand::Authorize(args) => {
 			member_set_authorized(
 				global,
-				&effective,
+				effective,
 				&client,
 				args.network,
 				args.member,
@@ -41,21 +40,10 @@ pub(super) async fn run_alias(global: &GlobalOpts, command: MemberCommand) -> Re
 			)
 			.await
 		}
-		MemberCommand::Deauthorize(args) => {
-			member_set_authorized(
-				global,
-				&effective,
-				&client,
-				args.network,
-				args.member,
-				args.org,
-				false,
-			)
-			.await
-		}
-		MemberCommand::Add(args) => member_add_trpc(global, &effective, args).await,
-		MemberCommand::Tags(args) => member_tags_trpc(global, &effective, args).await,
-		MemberCommand::Delete(args) => member_delete(global, &effective, &client, args).await,
+		MemberCommand::Deauthorize(args) => member_deauthorize(global, effective, &client, args).await,
+		MemberCommand::Add(args) => member_add_trpc(global, effective, args).await,
+		MemberCommand::Tags(args) => member_tags_trpc(global, effective, args).await,
+		MemberCommand::Delete(args) => member_delete(global, effective, &client, args).await,
 	}
 }
 
@@ -81,18 +69,7 @@ pub(super) async fn run_network_member(
 			)
 			.await
 		}
-		NetworkMemberCommand::Deauthorize(args) => {
-			member_set_authorized(
-				global,
-				effective,
-				client,
-				args.network,
-				args.member,
-				args.org,
-				false,
-			)
-			.await
-		}
+		NetworkMemberCommand::Deauthorize(args) => member_deauthorize(global, effective, client, args).await,
 		NetworkMemberCommand::Delete(args) => member_delete(global, effective, client, args).await,
 		NetworkMemberCommand::Add(args) => member_add_trpc(global, effective, args).await,
 		NetworkMemberCommand::Tags(args) => member_tags_trpc(global, effective, args).await,
@@ -104,8 +81,10 @@ async fn member_add_trpc(
 	effective: &crate::context::EffectiveConfig,
 	args: crate::cli::MemberAddArgs,
 ) -> Result<(), CliError> {
+	let (network, node_id) =
+		network_and_member(effective, args.network.as_deref(), args.node_id.as_deref(), "NODE_ID")?;
 	let trpc = trpc_authed(global, effective)?;
-	let network_id = resolve_personal_network_id(&trpc, &args.network).await?;
+	let network_id = resolve_personal_network_id(&trpc, &network).await?;
 	let details = trpc
 		.query(
 			"network.getNetworkById",
@@ -114,17 +93,107 @@ async fn member_add_trpc(
 		.await?;
 	let org_id = resolve_network_org_id(&trpc, effective, args.org.as_deref(), &details).await?;
 
+	let ips = args
+		.ip
+		.iter()
+		.map(|ip| parse_member_ip(global, ip))
+		.collect::<Result<Vec<_>, _>>()?;
+
+	let Some(response) = provision_member(&trpc, &network_id, &node_id, org_id.as_deref(), &args, ips).await? else {
+		return Err(CliError::DryRunPrinted);
+	};
+	print_human_or_machine(&response, effective.output, global.no_color)?;
+	Ok(())
+}
+
+/// Creates `node_id`, then applies `--authorize` and `--ip` in one update and `--name` in a
+/// database-only one. Returns the member as the panel reports it afterwards (`None` under
+/// `--dry-run`).
+async fn provision_member(
+	trpc: &TrpcClient,
+	network_id: &str,
+	node_id: &str,
+	org_id: Option<&str>,
+	args: &crate::cli::MemberAddArgs,
+	ips: Vec<String>,
+) -> Result<Option<Value>, CliError> {
 	let mut input = serde_json::Map::new();
-	input.insert("nwid".to_string(), Value::String(network_id));
-	input.insert("id".to_string(), Value::String(args.node_id));
+	input.insert("nwid".to_string(), Value::String(network_id.to_string()));
+	input.insert("id".to_string(), Value::String(node_id.to_string()));
 	input.insert("central".to_string(), Value::Bool(false));
 	if let Some(org_id) = org_id {
-		input.insert("organizationId".to_string(), Value::String(org_id));
+		input.insert("organizationId".to_string(), Value::String(org_id.to_string()));
 	}
 
-	let response = trpc.call("networkMember.create", Value::Object(input)).await?;
-	print_human_or_machine(&response, effective.output, global.no_color)?;
-	Ok(())
+	let response = trpc.call_step("networkMember.create", Value::Object(input)).await?;
+	let provisioned = args.authorize || !ips.is_empty() || args.name.is_some();
+
+	let mut update = serde_json::Map::new();
+	if args.authorize {
+		update.insert("authorized".to_string(), Value::Bool(true));
+	}
+	if !ips.is_empty() {
+		update.insert(
+			"ipAssignments".to_string(),
+			Value::Array(ips.into_iter().map(Value::String).collect()),
+		);
+	}
+
+	if !update.is_empty() {
+		trpc.call_step("networkMember.Update", member_update_input(network_id, node_id, org_id, update))
+			.await?;
+	}
+
+	if let Some(name) = &args.name {
+		let mut update = serde_json::Map::new();
+		update.insert("name".to_string(), Value::String(name.clone()));
+		trpc.call_step(
+			"networkMember.UpdateDatabaseOnly",
+			member_update_input(network_id, node_id, org_id, update),
+		)
+		.await?;
+	}
+
+	let Some(response) = response else {
+		return Ok(None);
+	};
+	if !provisioned {
+		return Ok(Some(response));
+	}
+	trpc.query(
+		"networkMember.getMemberById",
+		serde_json::json!({ "id": node_id, "nwid": network_id, "central": false }),
+	)
+	.await
+	.map(Some)
+}
+
+fn member_update_input(
+	network_id: &str,
+	member_id: &str,
+	org_id: Option<&str>,
+	update_params: serde_json::Map<String, Value>,
+) -> Value {
+	let mut input = serde_json::Map::new();
+	input.insert("nwid".to_string(), Value::String(network_id.to_string()));
+	input.insert("memberId".to_string(), Value::String(member_id.to_string()));
+	input.insert("central".to_string(), Value::Bool(false));
+	if let Some(org_id) = org_id {
+		input.insert("organizationId".to_string(), Value::String(org_id.to_string()));
+	}
+	input.insert("updateParams".to_string(), Value::Object(update_params));
+	Value::Object(input)
+}
+
+fn parse_member_ip(global: &GlobalOpts, raw: &str) -> Result<String, CliError> {
+	match raw.trim().parse::<std::net::IpAddr>() {
+		Ok(ip) => Ok(ip.to_string()),
+		Err(_) => {
+			let err = CliError::InvalidArgument(format!("invalid --ip address: {raw}"));
+			check_input(global, Err(err))?;
+			Ok(raw.trim().to_string())
+		}
+	}
 }
 
 async fn member_tags_trpc(
@@ -132,8 +201,31 @@ async fn member_tags_trpc(
 	effective: &crate::context::EffectiveConfig,
 	args: crate::cli::MemberTagsArgs,
 ) -> Result<(), CliError> {
+	// `None` lists the member's tags; `apply` works on many members, so it only borrows the
+	// parent's NETWORK/--org when it has none of its own.
+	let set = match args.command {
+		crate::cli::MemberTagsCommand::Apply(mut apply) => {
+			if let Some(member) = args.member {
+				return Err(CliError::InvalidArgument(format!(
+					"member tags apply selects members with --filter; drop the MEMBER argument '{member}'"
+				)));
+			}
+			apply.network = apply.network.or(args.network);
+			apply.org = apply.org.or(args.org);
+			return member_tags_apply(global, effective, apply).await;
+		}
+		crate::cli::MemberTagsCommand::List => None,
+		crate::cli::MemberTagsCommand::Set(set) => Some(set),
+	};
+	let (network, member_id) = network_and_member(
+		effective,
+		args.network.as_deref(),
+		args.member.as_deref(),
+		"MEMBER (usage: member tags [NETWORK] <MEMBER> list|set)",
+	)?;
+
 	let trpc = trpc_authed(global, effective)?;
-	let network_id = resolve_personal_network_id(&trpc, &args.network).await?;
+	let network_id = resolve_personal_network_id(&trpc, &network).await?;
 	let details = trpc
 		.query(
 			"network.getNetworkById",
@@ -142,26 +234,25 @@ async fn member_tags_trpc(
 		.await?;
 	let org_id = resolve_network_org_id(&trpc, effective, args.org.as_deref(), &details).await?;
 
-	match args.command {
-		crate::cli::MemberTagsCommand::List => {
+	match set {
+		None => {
 			let member = trpc
 				.query(
 					"networkMember.getMemberById",
-					serde_json::json!({ "id": args.member, "nwid": network_id, "central": false }),
+					serde_json::json!({ "id": member_id, "nwid": network_id, "central": false }),
 				)
 				.await?;
 
 			let tags = member.get("tags").cloned().unwrap_or(Value::Null);
 
 			if matches!(effective.output, OutputFormat::Table) && tags.is_null() {
-				println!("(no tags)");
-				return Ok(());
+				return output::emit("(no tags)", true);
 			}
 
 			output::print_value(&tags, effective.output, global.no_color)?;
 			Ok(())
 		}
-		crate::cli::MemberTagsCommand::Set(set) => {
+		Some(set) => {
 			let tags = serde_json::from_str::<Value>(&set.tags).map_err(|err| {
 				CliError::InvalidArgument(format!("invalid --tags json: {err}"))
 			})?;
@@ -171,7 +262,7 @@ async fn member_tags_trpc(
 
 			let mut input = serde_json::Map::new();
 			input.insert("nwid".to_string(), Value::String(network_id));
-			input.insert("memberId".to_string(), Value::String(args.member));
+			input.insert("memberId".to_string(), Value::String(member_id));
 			input.insert("central".to_string(), Value::Bool(false));
 			if let Some(org_id) = org_id {
 				input.insert("organizationId".to_string(), Value::String(org_id));
@@ -185,19 +276,157 @@ async fn member_tags_trpc(
 	}
 }
 
-fn trpc_authed(
+async fn member_tags_apply(
 	global: &GlobalOpts,
 	effective: &crate::context::EffectiveConfig,
-) -> Result<TrpcClient, CliError> {
-	let cookie = require_cookie_from_effective(effective)?;
-	Ok(TrpcClient::new(
-		&effective.host,
-		effective.timeout,
-		effective.retries,
-		global.dry_run,
-		ClientUi::from_context(global, effective),
-	)?
-	.with_cookie(Some(cookie)))
+	args: crate::cli::MemberTagsApplyArgs,
+) -> Result<(), CliError> {
+	let filter = Filter::parse(&args.filter)?;
+	let network = network_or_context(effective, args.network.as_deref())?;
+	let trpc = trpc_authed(global, effective)?;
+	let network_id = resolve_personal_network_id(&trpc, &network).await?;
+	let details = trpc
+		.query(
+			"network.getNetworkById",
+			serde_json::json!({ "nwid": network_id, "central": false }),
+		)
+		.await?;
+	let org_id = resolve_network_org_id(&trpc, effective, args.org.as_deref(), &details).await?;
+
+	let tags_by_name = details
+		.get("network")
+		.and_then(|n| n.get("tagsByName"))
+		.cloned()
+		.unwrap_or(Value::Null);
+	let wanted = args
+		.tag
+		.iter()
+		.map(|raw| resolve_tag_assignment(&tags_by_name, raw))
+		.collect::<Result<Vec<_>, _>>()?;
+
+	let members: Vec<&Value> = details
+		.get("members")
+		.and_then(|v| v.as_array())
+		.into_iter()
+		.flatten()
+		.filter(|m| filter.matches(m))
+		.collect();
+	if members.is_empty() {
+		return Err(CliError::InvalidArgument(format!(
+			"no members of {network_id} match '{}'",
+			args.filter
+		)));
+	}
+
+	let mut report = BulkReport::new(args.only_errors).with_progress("member tags apply", members.len());
+	for member in members {
+		if report.interrupted() {
+			break;
+		}
+		let id = member.get("id").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+		report.begin(&id);
+		let name = member.get("name").cloned().unwrap_or(Value::Null);
+		let current = member.get("tags").cloned().unwrap_or(Value::Null);
+		let merged = merge_tags(&current, &wanted);
+
+		let status = if merged == current {
+			"unchanged"
+		} else if global.dry_run {
+			"would-update"
+		} else {
+			let mut input = serde_json::Map::new();
+			input.insert("nwid".to_string(), Value::String(network_id.clone()));
+			input.insert("memberId".to_string(), Value::String(id.clone()));
+			input.insert("central".to_string(), Value::Bool(false));
+			if let Some(org_id) = org_id.clone() {
+				input.insert("organizationId".to_string(), Value::String(org_id));
+			}
+			input.insert("updateParams".to_string(), serde_json::json!({ "tags": merged }));
+
+			match trpc.call("networkMember.Tags", Value::Object(input)).await {
+				Ok(_) => "updated",
+				Err(CliError::Interrupted) => break,
+				Err(err) => {
+					let result = serde_json::json!({
+						"id": id,
+						"name": name,
+						"status": "failed",
+						"error": err.to_string(),
+					});
+					report.push(result, true)?;
+					continue;
+				}
+			}
+		};
+		let result = serde_json::json!({
+			"id": id,
+			"name": name,
+			"status": status,
+			"tags": merged,
+		});
+		report.push(result, false)?;
+	}
+
+	report.finish(global, effective.output)?;
+	if global.dry_run {
+		return Err(CliError::DryRunPrinted);
+	}
+	Ok(())
+}
+
+/// Resolves `NAME=VALUE` against the network's compiled `tagsByName`; either side may
+/// also be given as a number.
+fn resolve_tag_assignment(tags_by_name: &Value, raw: &str) -> Result<(u64, u64), CliError> {
+	let (name, value) = raw.split_once('=').ok_or_else(|| {
+		CliError::InvalidArgument(format!("invalid --tag '{raw}' (expected NAME=VALUE)"))
+	})?;
+	let (name, value) = (name.trim(), value.trim());
+
+	let tag = tags_by_name.get(name);
+	let id = match name.parse::<u64>() {
+		Ok(id) => id,
+		Err(_) => tag
+			.and_then(|t| t.get("id"))
+			.and_then(|v| v.as_u64())
+			.ok_or_else(|| {
+				let known: Vec<&str> = tags_by_name
+					.as_object()
+					.map(|m| m.keys().map(String::as_str).collect())
+					.unwrap_or_default();
+				CliError::InvalidArgument(format!(
+					"unknown tag '{name}' (defined in flow rules: {})",
+					if known.is_empty() { "none".to_string() } else { known.join(", ") }
+				))
+			})?,
+	};
+	let value = match value.parse::<u64>() {
+		Ok(value) => value,
+		Err(_) => ["enums", "flags"]
+			.iter()
+			.find_map(|key| tag.and_then(|t| t.get(*key)).and_then(|m| m.get(value)))
+			.and_then(|v| v.as_u64())
+			.ok_or_else(|| {
+				CliError::InvalidArgument(format!("tag '{name}' has no value named '{value}'"))
+			})?,
+	};
+	Ok((id, value))
+}
+
+/// ZeroTier stores tags as `[[id, value], ...]`; wanted pairs replace entries with the same id.
+fn merge_tags(current: &Value, wanted: &[(u64, u64)]) -> Value {
+	let mut pairs: Vec<(u64, u64)> = current
+		.as_array()
+		.into_iter()
+		.flatten()
+		.filter_map(|pair| Some((pair.get(0)?.as_u64()?, pair.get(1)?.as_u64()?)))
+		.collect();
+	for &(id, value) in wanted {
+		match pairs.iter_mut().find(|(existing, _)| *existing == id) {
+			Some(pair) => pair.1 = value,
+			None => pairs.push((id, value)),
+		}
+	}
+	Value::Array(pairs.into_iter().map(|(id, value)| serde_json::json!([id, value])).collect())
 }
 
 async fn member_list(
@@ -206,18 +435,30 @@ async fn member_list(
 	client: &HttpClient,
 	args: crate::cli::MemberListArgs,
 ) -> Result<(), CliError> {
-	let org = args.org.or(effective.org.clone());
+	let network = network_or_context(effective, args.network.as_deref())?;
+	let org = args.org.clone().or(effective.org.clone());
 	let org_id = match org {
 		Some(ref org) => Some(resolve_org_id(client, org).await?),
 		None => None,
 	};
 
-	let network_id = resolve_network_id(client, org_id.as_deref(), &args.network).await?;
+	let network_id = resolve_network_id(client, org_id.as_deref(), &network).await?;
 	let path = match org_id.as_deref() {
 		Some(org_id) => format!("/api/v1/org/{org_id}/network/{network_id}/member"),
 		None => format!("/api/v1/network/{network_id}/member"),
 	};
 
+	if !args.fields.is_empty() {
+		// ZTNet has no server-side field selection, so project client-side. Members are parsed
+		// lazily: only the selected and filtered fields are turned into `Value`s.
+		let bytes = client
+			.request_bytes(Method::GET, &path, None, Default::default(), true, None)
+			.await?;
+		let rows = project_member_fields(&bytes, &args.fields, |item| member_matches(&args, item))?;
+		output::print_value(&Value::Array(rows), effective.output, global.no_color)?;
+		return Ok(());
+	}
+
 	let mut response = client
 		.request_json(Method::GET, &path, None, Default::default(), true)
 		.await?;
@@ -227,64 +468,207 @@ async fn member_list(
 			return Err(CliError::InvalidArgument("expected array response".to_string()));
 		};
 
-		let needle_name = args.name.as_deref().map(|s| s.to_ascii_lowercase());
-		let needle_id = args.id.as_deref();
-
 		let filtered: Vec<Value> = items
 			.iter()
-			.filter(|item| {
-				if args.authorized {
-					if item.get("authorized").and_then(|v| v.as_bool()) != Some(true) {
-						return false;
-					}
-				}
-				if args.unauthorized {
-					if item.get("authorized").and_then(|v| v.as_bool()) != Some(false) {
-						return false;
-					}
-				}
-				if let Some(ref needle) = needle_name {
-					let name = item.get("name").and_then(|v| v.as_str()).unwrap_or("");
-					if !name.to_ascii_lowercase().contains(needle) {
-						return false;
-					}
-				}
-				if let Some(needle) = needle_id {
-					let id = item.get("id").and_then(|v| v.as_str()).unwrap_or("");
-					if id != needle {
-						return false;
-					}
-				}
-				true
-			})
+			.filter(|item| member_matches(&args, item))
 			.cloned()
 			.collect();
 
 		response = Value::Array(filtered);
 	}
 
+	if args.peers || args.relayed_only {
+		let trpc = trpc_authed(global, effective)?;
+		let details = trpc
+			.query(
+				"network.getNetworkById",
+				serde_json::json!({ "nwid": network_id, "central": false }),
+			)
+			.await?;
+		let rows = with_peer_links(response, &details, args.relayed_only)?;
+		if !global.quiet {
+			eprintln!("{}", peer_link_counts(&rows));
+		}
+		if matches!(effective.output, OutputFormat::Table) {
+			return output::emit(&peer_summary(&rows), true);
+		}
+		return output::print_value(&Value::Array(rows), effective.output, global.no_color);
+	}
+
 	output::print_value(&response, effective.output, global.no_color)?;
 	Ok(())
 }
 
+/// Adds a `peer` summary to every member from the `peers` records in the tRPC network
+/// details, keeping only relayed members when `relayed_only` is set.
+fn with_peer_links(members: Value, details: &Value, relayed_only: bool) -> Result<Vec<Value>, CliError> {
+	let Value::Array(members) = members else {
+		return Err(CliError::InvalidArgument("expected array response".to_string()));
+	};
+	let peers: HashMap<&str, &Value> = details["members"]
+		.as_array()
+		.into_iter()
+		.flatten()
+		.filter_map(|member| Some((member["id"].as_str()?, &member["peers"])))
+		.collect();
+
+	let mut rows = Vec::with_capacity(members.len());
+	for mut member in members {
+		let link = peer_link(member["id"].as_str().and_then(|id| peers.get(id).copied()));
+		if relayed_only && link["link"] != "relayed" {
+			continue;
+		}
+		if let Some(obj) = member.as_object_mut() {
+			obj.insert("peer".to_string(), link);
+		}
+		rows.push(member);
+	}
+	Ok(rows)
+}
+
+/// ZeroTier's peer record reduced to what matters for connectivity: `direct` when a live
+/// physical path exists, `relayed` when the node is online but only reachable through a
+/// root, `offline` when the controller has no peer record at all.
+pub(super) fn peer_link(peers: Option<&Value>) -> Value {
+	let Some(peers) = peers.filter(|p| p.as_object().is_some_and(|o| !o.is_empty())) else {
+		return serde_json::json!({ "link": "offline", "latency_ms": null, "path": null, "version": null });
+	};
+	let paths: Vec<&Value> = peers["paths"]
+		.as_array()
+		.into_iter()
+		.flatten()
+		.filter(|path| path["active"] != false && path["expired"] != true)
+		.collect();
+	let preferred = paths.iter().find(|path| path["preferred"] == true).or(paths.first());
+	serde_json::json!({
+		"link": if paths.is_empty() { "relayed" } else { "direct" },
+		"latency_ms": peers["latency"].as_i64().filter(|ms| *ms >= 0),
+		"path": preferred.and_then(|path| path["address"].as_str()),
+		"version": peers["version"].as_str(),
+	})
+}
+
+fn peer_link_counts(rows: &[Value]) -> String {
+	let count = |link: &str| rows.iter().filter(|row| row["peer"]["link"] == link).count();
+	format!(
+		"{} direct, {} relayed, {} offline",
+		count("direct"),
+		count("relayed"),
+		count("offline")
+	)
+}
+
+/// `wg show`-style blocks, one per member.
+fn peer_summary(rows: &[Value]) -> String {
+	let blocks: Vec<String> = rows
+		.iter()
+		.map(|row| {
+			let peer = &row["peer"];
+			let name = row["name"].as_str().filter(|name| !name.is_empty());
+			let mut lines = vec![match name {
+				Some(name) => format!("peer: {} ({name})", render_scalar(&row["id"])),
+				None => format!("peer: {}", render_scalar(&row["id"])),
+			}];
+			lines.push(match peer["path"].as_str() {
+				Some(path) => format!("  link: {} via {path}", render_scalar(&peer["link"])),
+				None => format!("  link: {}", render_scalar(&peer["link"])),
+			});
+			if let Some(ms) = peer["latency_ms"].as_i64() {
+				lines.push(format!("  latency: {ms}ms"));
+			}
+			let ips: Vec<String> = row["ipAssignments"].as_array().into_iter().flatten().map(render_scalar).collect();
+			if !ips.is_empty() {
+				lines.push(format!("  allowed ips: {}", ips.join(", ")));
+			}
+			if let Some(version) = peer["version"].as_str() {
+				lines.push(format!("  version: {version}"));
+			}
+			lines.join("\n")
+		})
+		.collect();
+	blocks.join("\n\n")
+}
+
+fn member_matches(args: &crate::cli::MemberListArgs, item: &Value) -> bool {
+	if args.authorized && item.get("authorized").and_then(|v| v.as_bool()) != Some(true) {
+		return false;
+	}
+	if args.unauthorized && item.get("authorized").and_then(|v| v.as_bool()) != Some(false) {
+		return false;
+	}
+	if let Some(ref needle) = args.name {
+		let name = item.get("name").and_then(|v| v.as_str()).unwrap_or("");
+		if !name.to_ascii_lowercase().contains(&needle.to_ascii_lowercase()) {
+			return false;
+		}
+	}
+	if let Some(ref needle) = args.id {
+		let id = item.get("id").and_then(|v| v.as_str()).unwrap_or("");
+		if id != needle {
+			return false;
+		}
+	}
+	true
+}
+
+const MEMBER_FILTER_FIELDS: &[&str] = &["authorized", "name", "id"];
+
+fn project_member_fields(
+	bytes: &[u8],
+	fields: &[String],
+	keep: impl Fn(&Value) -> bool,
+) -> Result<Vec<Value>, CliError> {
+	let members: Vec<HashMap<String, &RawValue>> = serde_json::from_slice(bytes)?;
+
+	let mut rows = Vec::with_capacity(members.len());
+	for member in members {
+		let parse = |key: &str| -> Result<Value, CliError> {
+			match member.get(key) {
+				Some(raw) => Ok(serde_json::from_str(raw.get())?),
+				None => Ok(Value::Null),
+			}
+		};
+
+		let mut probe = serde_json::Map::new();
+		for key in MEMBER_FILTER_FIELDS {
+			probe.insert(key.to_string(), parse(key)?);
+		}
+		if !keep(&Value::Object(probe)) {
+			continue;
+		}
+
+		let mut row = serde_json::Map::new();
+		for field in fields {
+			let field = field.trim();
+			if !field.is_empty() {
+				row.insert(field.to_string(), parse(field)?);
+			}
+		}
+		rows.push(Value::Object(row));
+	}
+	Ok(rows)
+}
+
 async fn member_get(
 	global: &GlobalOpts,
 	effective: &crate::context::EffectiveConfig,
 	client: &HttpClient,
 	args: crate::cli::MemberGetArgs,
 ) -> Result<(), CliError> {
+	let (network, member) =
+		network_and_member(effective, args.network.as_deref(), args.member.as_deref(), "MEMBER")?;
 	let org = args.org.or(effective.org.clone());
 	let org_id = match org {
 		Some(ref org) => Some(resolve_org_id(client, org).await?),
 		None => None,
 	};
 
-	let network_id = resolve_network_id(client, org_id.as_deref(), &args.network).await?;
+	let network_id = resolve_network_id(client, org_id.as_deref(), &network).await?;
 
 	// Some deployments don't support a stable REST GET-by-id endpoint for members (400/405).
 	// Prefer GET-by-id when it works, but fall back to list+filter for consistent behavior.
 	let response = if let Some(org_id) = org_id.as_deref() {
-		let path = format!("/api/v1/org/{org_id}/network/{network_id}/member/{}", args.member);
+		let path = format!("/api/v1/org/{org_id}/network/{network_id}/member/{member}");
 		match client
 			.request_json(Method::GET, &path, None, Default::default(), true)
 			.await
@@ -292,18 +676,207 @@ async fn member_get(
 			Ok(v) => v,
 			Err(CliError::HttpStatus { status, .. })
 				if status == reqwest::StatusCode::BAD_REQUEST
+					|| status == reqwest::StatusCode::NOT_FOUND
 					|| status == reqwest::StatusCode::METHOD_NOT_ALLOWED =>
 			{
-				member_get_via_list(client, Some(org_id), &network_id, &args.member).await?
+				// A 404 is re-checked against the list so the error can suggest close matches.
+				member_get_via_list(client, Some(org_id), &network_id, &member).await?
 			}
 			Err(err) => return Err(err),
 		}
 	} else {
-		member_get_via_list(client, None, &network_id, &args.member).await?
+		member_get_via_list(client, None, &network_id, &member).await?
 	};
 
-	print_human_or_machine(&response, effective.output, global.no_color)?;
-	Ok(())
+	if !matches!(effective.output, OutputFormat::Table) {
+		return output::print_value(&response, effective.output, global.no_color);
+	}
+
+	let has_tags = response
+		.get("tags")
+		.and_then(|v| v.as_array())
+		.is_some_and(|tags| !tags.is_empty());
+	let tags_by_name = if has_tags {
+		network_tags_by_name(global, effective, client, org_id.as_deref(), &network_id).await
+	} else {
+		Value::Null
+	};
+	print_member_sections(&response, &tags_by_name)
+}
+
+/// Best-effort lookup of the network's compiled tag definitions, used only to label tag
+/// ids in table output. The REST network document carries them on most versions; the
+/// tRPC network view is tried when a session is available.
+async fn network_tags_by_name(
+	global: &GlobalOpts,
+	effective: &crate::context::EffectiveConfig,
+	client: &HttpClient,
+	org_id: Option<&str>,
+	network_id: &str,
+) -> Value {
+	let path = match org_id {
+		Some(org_id) => format!("/api/v1/org/{org_id}/network/{network_id}"),
+		None => format!("/api/v1/network/{network_id}"),
+	};
+	if let Ok(network) = client
+		.request_json(Method::GET, &path, None, Default::default(), true)
+		.await
+		&& let Some(tags) = network
+			.get("tagsByName")
+			.or_else(|| network.get("network").and_then(|n| n.get("tagsByName")))
+			.filter(|v| v.is_object())
+	{
+		return tags.clone();
+	}
+
+	if cookie_from_effective(effective).is_none() {
+		return Value::Null;
+	}
+	let Ok(trpc) = trpc_authed(global, effective) else {
+		return Value::Null;
+	};
+	trpc.query(
+		"network.getNetworkById",
+		serde_json::json!({ "nwid": network_id, "central": false }),
+	)
+	.await
+	.ok()
+	.and_then(|details| details.get("network")?.get("tagsByName").cloned())
+	.unwrap_or(Value::Null)
+}
+
+const MEMBER_SECTIONS: &[(&str, &[(&str, &str)])] = &[
+	(
+		"Identity",
+		&[
+			("id", "Member ID"),
+			("name", "Name"),
+			("description", "Description"),
+			("address", "Address"),
+			("identity", "Identity"),
+		],
+	),
+	(
+		"Network",
+		&[
+			("nwid", "Network"),
+			("ipAssignments", "IP assignments"),
+			("noAutoAssignIps", "No auto-assign IPs"),
+			("activeBridge", "Active bridge"),
+			("tags", "Tags"),
+			("capabilities", "Capabilities"),
+		],
+	),
+	(
+		"Status",
+		&[
+			("authorized", "Authorized"),
+			("online", "Online"),
+			("conStatus", "Connection"),
+			("physicalAddress", "Physical address"),
+			("lastSeen", "Last seen"),
+			("creationTime", "Created"),
+			("lastAuthorizedTime", "Last authorized"),
+			("lastDeauthorizedTime", "Last deauthorized"),
+		],
+	),
+];
+
+fn print_member_sections(member: &Value, tags_by_name: &Value) -> Result<(), CliError> {
+	let Some(obj) = member.as_object() else {
+		return print_kv(member);
+	};
+
+	let mut sections: Vec<(&str, Vec<(String, String)>)> = Vec::new();
+	for (title, fields) in MEMBER_SECTIONS {
+		let rows: Vec<(String, String)> = fields
+			.iter()
+			.filter_map(|(key, label)| {
+				let value = obj.get(*key).filter(|v| !v.is_null())?;
+				Some((label.to_string(), member_field_text(key, value, tags_by_name)))
+			})
+			.collect();
+		if !rows.is_empty() {
+			sections.push((title, rows));
+		}
+	}
+
+	let mut other: Vec<(String, String)> = obj
+		.iter()
+		.filter(|(key, value)| {
+			!value.is_null()
+				&& !MEMBER_SECTIONS
+					.iter()
+					.any(|(_, fields)| fields.iter().any(|(k, _)| k == key))
+		})
+		.map(|(key, value)| (key.clone(), render_scalar(value)))
+		.collect();
+	other.sort();
+	if !other.is_empty() {
+		sections.push(("Other", other));
+	}
+
+	let width = sections
+		.iter()
+		.flat_map(|(_, rows)| rows.iter().map(|(label, _)| label.len()))
+		.max()
+		.unwrap_or(0);
+	let blocks: Vec<String> = sections
+		.iter()
+		.map(|(title, rows)| {
+			let mut block = title.to_string();
+			for (label, text) in rows {
+				block.push_str(&format!("\n  {label:<width$}  {text}"));
+			}
+			block
+		})
+		.collect();
+	output::emit(&blocks.join("\n\n"), true)
+}
+
+fn member_field_text(key: &str, value: &Value, tags_by_name: &Value) -> String {
+	match key {
+		"ipAssignments" => match value.as_array() {
+			Some(ips) if ips.is_empty() => "(none)".to_string(),
+			Some(ips) => ips.iter().map(render_scalar).collect::<Vec<_>>().join(", "),
+			None => render_scalar(value),
+		},
+		"tags" => match value.as_array() {
+			Some(tags) if tags.is_empty() => "(none)".to_string(),
+			Some(tags) => tags
+				.iter()
+				.map(|pair| describe_tag(pair, tags_by_name))
+				.collect::<Vec<_>>()
+				.join(", "),
+			None => render_scalar(value),
+		},
+		_ => render_scalar(value),
+	}
+}
+
+/// Renders a `[id, value]` tag pair as `name=value`, naming the value after its enum or
+/// flag entry when the flow rules define one.
+fn describe_tag(pair: &Value, tags_by_name: &Value) -> String {
+	let (Some(id), Some(value)) = (
+		pair.get(0).and_then(|v| v.as_u64()),
+		pair.get(1).and_then(|v| v.as_u64()),
+	) else {
+		return render_scalar(pair);
+	};
+	let Some((name, tag)) = tags_by_name
+		.as_object()
+		.and_then(|tags| tags.iter().find(|(_, tag)| tag.get("id").and_then(|v| v.as_u64()) == Some(id)))
+	else {
+		return format!("{id}={value}");
+	};
+	let value_name = ["enums", "flags"].iter().find_map(|key| {
+		tag.get(*key)?
+			.as_object()?
+			.iter()
+			.find(|(_, v)| v.as_u64() == Some(value))
+			.map(|(label, _)| label.clone())
+	});
+	format!("{name}={}", value_name.unwrap_or_else(|| value.to_string()))
 }
 
 async fn member_get_via_list(
@@ -329,11 +902,38 @@ async fn member_get_via_list(
 		.iter()
 		.find(|item| item.get("id").and_then(|v| v.as_str()) == Some(member_id))
 		.cloned()
-		.ok_or(CliError::HttpStatus {
-			status: reqwest::StatusCode::NOT_FOUND,
-			message: "member not found".to_string(),
-			body: None,
+		.ok_or_else(|| member_not_found(network_id, member_id, items))
+}
+
+fn member_not_found(network_id: &str, member_id: &str, items: &[Value]) -> CliError {
+	// Both ids and names are matched, since members are often referred to by hostname.
+	let candidates: Vec<(String, String)> = items
+		.iter()
+		.filter_map(|item| {
+			let id = item.get("id").and_then(|v| v.as_str())?;
+			let name = item.get("name").and_then(|v| v.as_str()).filter(|s| !s.is_empty());
+			let label = match name {
+				Some(name) => format!("{id} ({name})"),
+				None => id.to_string(),
+			};
+			Some(std::iter::once((id.to_string(), label.clone())).chain(name.map(|n| (n.to_string(), label))))
 		})
+		.flatten()
+		.collect();
+	let suggestions = suggest_similar(
+		member_id,
+		candidates.iter().map(|(key, label)| (key.as_str(), label.as_str())),
+	);
+
+	let mut message = format!("member '{member_id}' is not in network {network_id}");
+	if !suggestions.is_empty() {
+		message.push_str(&format!("\n\n  did you mean: {}", suggestions.join(", ")));
+	}
+	CliError::HttpStatus {
+		status: reqwest::StatusCode::NOT_FOUND,
+		message,
+		body: None,
+	}
 }
 
 async fn member_update(
@@ -342,19 +942,64 @@ async fn member_update(
 	client: &HttpClient,
 	args: crate::cli::MemberUpdateArgs,
 ) -> Result<(), CliError> {
-	let org = args.org.or(effective.org.clone());
+	let (network, member) =
+		network_and_member(effective, args.network.as_deref(), args.member.as_deref(), "MEMBER")?;
+	let org = args.org.clone().or(effective.org.clone());
 	let org_id = match org {
 		Some(ref org) => Some(resolve_org_id(client, org).await?),
 		None => None,
 	};
 
-	let network_id = resolve_network_id(client, org_id.as_deref(), &args.network).await?;
+	let network_id = resolve_network_id(client, org_id.as_deref(), &network).await?;
+
+	// Validated up front so a bad --ip fails before the REST part is sent.
+	let advanced = if args.has_advanced_fields() {
+		Some(advanced_update_params(global, &args)?)
+	} else {
+		None
+	};
+	let rest_fields = args.name.is_some()
+		|| (org_id.is_none() && args.description.is_some())
+		|| args.authorized
+		|| args.unauthorized;
+	if let Some(update) = advanced {
+		if rest_fields {
+			member_update_rest(client, org_id.as_deref(), &network_id, &member, args).await?;
+		}
+		let trpc = trpc_authed(global, effective)?;
+		if trpc
+			.call_step("networkMember.Update", member_update_input(&network_id, &member, org_id.as_deref(), update))
+			.await?
+			.is_none()
+		{
+			return Err(CliError::DryRunPrinted);
+		}
+		let response = trpc
+			.query(
+				"networkMember.getMemberById",
+				serde_json::json!({ "id": member, "nwid": network_id, "central": false }),
+			)
+			.await?;
+		return print_human_or_machine(&response, effective.output, global.no_color);
+	}
 
+	let response = member_update_rest(client, org_id.as_deref(), &network_id, &member, args).await?;
+	print_human_or_machine(&response, effective.output, global.no_color)?;
+	Ok(())
+}
+
+async fn member_update_rest(
+	client: &HttpClient,
+	org_id: Option<&str>,
+	network_id: &str,
+	member: &str,
+	args: crate::cli::MemberUpdateArgs,
+) -> Result<Value, CliError> {
 	let body = if let Some(body) = args.body {
 		serde_json::from_str::<Value>(&body)
 			.map_err(|err| CliError::InvalidArgument(format!("invalid --body json: {err}")))?
 	} else if let Some(path) = args.body_file {
-		let text = std::fs::read_to_string(&path)?;
+		let text = read_input_file(&path)?;
 		serde_json::from_str::<Value>(&text)
 			.map_err(|err| CliError::InvalidArgument(format!("invalid --body-file json: {err}")))?
 	} else {
@@ -362,10 +1007,8 @@ async fn member_update(
 		if let Some(name) = args.name {
 			map.insert("name".to_string(), Value::String(name));
 		}
-		if org_id.is_none() {
-			if let Some(description) = args.description {
-				map.insert("description".to_string(), Value::String(description));
-			}
+		if org_id.is_none() && let Some(description) = args.description {
+			map.insert("description".to_string(), Value::String(description));
 		}
 		if args.authorized {
 			map.insert("authorized".to_string(), Value::Bool(true));
@@ -381,28 +1024,47 @@ async fn member_update(
 		Value::Object(map)
 	};
 
-	let path = match org_id.as_deref() {
-		Some(org_id) => format!(
-			"/api/v1/org/{org_id}/network/{network_id}/member/{}",
-			args.member
-		),
-		None => format!("/api/v1/network/{network_id}/member/{}", args.member),
+	let path = match org_id {
+		Some(org_id) => format!("/api/v1/org/{org_id}/network/{network_id}/member/{member}"),
+		None => format!("/api/v1/network/{network_id}/member/{member}"),
 	};
 
-	let response = client
+	client
 		.request_json(Method::POST, &path, Some(body), Default::default(), true)
-		.await?;
+		.await
+}
 
-	print_human_or_machine(&response, effective.output, global.no_color)?;
-	Ok(())
+/// `updateParams` for `networkMember.Update` from the flags the REST endpoint can't set.
+fn advanced_update_params(
+	global: &GlobalOpts,
+	args: &crate::cli::MemberUpdateArgs,
+) -> Result<serde_json::Map<String, Value>, CliError> {
+	let mut update = serde_json::Map::new();
+	if args.allow_bridging || args.deny_bridging {
+		update.insert("activeBridge".to_string(), Value::Bool(args.allow_bridging));
+	}
+	if args.no_auto_assign_ips || args.auto_assign_ips {
+		update.insert("noAutoAssignIps".to_string(), Value::Bool(args.no_auto_assign_ips));
+	}
+	if args.clear_ips {
+		update.insert("ipAssignments".to_string(), Value::Array(Vec::new()));
+	} else if !args.ip.is_empty() {
+		let ips = args
+			.ip
+			.iter()
+			.map(|ip| parse_member_ip(global, ip).map(Value::String))
+			.collect::<Result<Vec<_>, _>>()?;
+		update.insert("ipAssignments".to_string(), Value::Array(ips));
+	}
+	Ok(update)
 }
 
 async fn member_set_authorized(
 	global: &GlobalOpts,
 	effective: &crate::context::EffectiveConfig,
 	client: &HttpClient,
-	network: String,
-	member: String,
+	network: Option<String>,
+	member: Option<String>,
 	org: Option<String>,
 	authorized: bool,
 ) -> Result<(), CliError> {
@@ -416,43 +1078,760 @@ async fn member_set_authorized(
 		unauthorized: !authorized,
 		body: None,
 		body_file: None,
+		allow_bridging: false,
+		deny_bridging: false,
+		no_auto_assign_ips: false,
+		auto_assign_ips: false,
+		ip: Vec::new(),
+		clear_ips: false,
 	};
 	member_update(global, effective, client, update).await
 }
 
-async fn member_delete(
+async fn member_deauthorize(
 	global: &GlobalOpts,
 	effective: &crate::context::EffectiveConfig,
 	client: &HttpClient,
-	args: crate::cli::MemberDeleteArgs,
+	args: crate::cli::MemberDeauthorizeArgs,
+) -> Result<(), CliError> {
+	if args.all_unnamed {
+		return member_deauthorize_unnamed(global, effective, client, args).await;
+	}
+	member_set_authorized(global, effective, client, args.network, args.member, args.org, false).await
+}
+
+/// `deauthorize --all-unnamed`: previews authorized members that never got a name and
+/// joined at least `--age` ago, then deauthorizes them after a single confirmation.
+async fn member_deauthorize_unnamed(
+	global: &GlobalOpts,
+	effective: &crate::context::EffectiveConfig,
+	client: &HttpClient,
+	args: crate::cli::MemberDeauthorizeArgs,
 ) -> Result<(), CliError> {
+	let network = network_or_context(effective, args.network.as_deref())?;
 	let org = args.org.or(effective.org.clone());
 	let org_id = match org {
 		Some(ref org) => Some(resolve_org_id(client, org).await?),
 		None => None,
 	};
+	let network_id = resolve_network_id(client, org_id.as_deref(), &network).await?;
+	let base = match org_id.as_deref() {
+		Some(org_id) => format!("/api/v1/org/{org_id}/network/{network_id}/member"),
+		None => format!("/api/v1/network/{network_id}/member"),
+	};
+
+	let members = client
+		.request_json(Method::GET, &base, None, Default::default(), true)
+		.await?;
+	let now = SystemTime::now();
+	let candidates: Vec<&Value> = members
+		.as_array()
+		.into_iter()
+		.flatten()
+		.filter(|m| is_stale_unnamed(m, now, args.age))
+		.collect();
 
-	let network_id = resolve_network_id(client, org_id.as_deref(), &args.network).await?;
+	let age = humantime::format_duration(args.age);
+	if candidates.is_empty() {
+		if !global.quiet {
+			eprintln!("No authorized unnamed members older than {age} in network {network_id}.");
+		}
+		return Ok(());
+	}
 
+	if !global.quiet {
+		eprintln!("Unnamed members that joined more than {age} ago:");
+		for member in &candidates {
+			let id = member.get("id").and_then(|v| v.as_str()).unwrap_or_default();
+			let joined = member
+				.get("creationTime")
+				.and_then(member_timestamp)
+				.map(|t| humantime::format_rfc3339_seconds(t).to_string())
+				.unwrap_or_default();
+			eprintln!("  {id}  joined {joined}");
+		}
+	}
 	let prompt = format!(
-		"Delete (stash) member '{}' from network '{}'? ",
-		args.member, network_id
+		"Deauthorize {} member(s) in network '{network_id}'? ",
+		candidates.len()
 	);
+	if !global.dry_run && !confirm(global, &prompt)? {
+		return Ok(());
+	}
+
+	let mut report = BulkReport::new(false).with_progress("member deauthorize", candidates.len());
+	for member in candidates {
+		if report.interrupted() {
+			break;
+		}
+		let id = member.get("id").and_then(|v| v.as_str()).unwrap_or_default();
+		report.begin(id);
+		if global.dry_run {
+			report.push(serde_json::json!({ "id": id, "status": "would-deauthorize" }), false)?;
+			continue;
+		}
+		let body = serde_json::json!({ "authorized": false });
+		match client
+			.request_json(Method::POST, &format!("{base}/{id}"), Some(body), Default::default(), true)
+			.await
+		{
+			Ok(_) => report.push(serde_json::json!({ "id": id, "status": "deauthorized" }), false)?,
+			Err(CliError::Interrupted) => break,
+			Err(err) => report.push(
+				serde_json::json!({ "id": id, "status": "failed", "error": err.to_string() }),
+				true,
+			)?,
+		}
+	}
+
+	report.finish(global, effective.output)?;
+	if global.dry_run {
+		return Err(CliError::DryRunPrinted);
+	}
+	Ok(())
+}
+
+/// Authorized, nameless, and joined at least `age` before `now`. Members without a
+/// usable `creationTime` are never selected.
+fn is_stale_unnamed(member: &Value, now: SystemTime, age: Duration) -> bool {
+	let authorized = member.get("authorized").and_then(|v| v.as_bool()).unwrap_or(false);
+	let unnamed = member
+		.get("name")
+		.and_then(|v| v.as_str())
+		.is_none_or(|name| name.trim().is_empty());
+	let old_enough = member
+		.get("creationTime")
+		.and_then(member_timestamp)
+		.and_then(|joined| now.duration_since(joined).ok())
+		.is_some_and(|elapsed| elapsed >= age);
+	authorized && unnamed && old_enough
+}
+
+/// Member timestamps are epoch milliseconds from the controller, or RFC 3339 strings
+/// from the database.
+fn member_timestamp(value: &Value) -> Option<SystemTime> {
+	match value {
+		Value::Number(ms) => Some(UNIX_EPOCH + Duration::from_millis(ms.as_u64()?)),
+		Value::String(text) => humantime::parse_rfc3339_weak(text).ok(),
+		_ => None,
+	}
+}
+
+async fn member_delete(
+	global: &GlobalOpts,
+	effective: &crate::context::EffectiveConfig,
+	client: &HttpClient,
+	args: crate::cli::MemberDeleteArgs,
+) -> Result<(), CliError> {
+	let (network, member) =
+		network_and_member(effective, args.network.as_deref(), args.member.as_deref(), "MEMBER")?;
+	let org = args.org.or(effective.org.clone());
+	let org_id = match org {
+		Some(ref org) => Some(resolve_org_id(client, org).await?),
+		None => None,
+	};
+
+	let network_id = resolve_network_id(client, org_id.as_deref(), &network).await?;
+	// The REST endpoint only stashes; the permanent delete is a tRPC follow-up and needs a
+	// session, so check for one before anything is changed.
+	let trpc = if args.permanent {
+		Some(trpc_authed(global, effective)?)
+	} else {
+		None
+	};
+
+	let prompt = if args.permanent {
+		format!("Permanently delete member '{member}' from network '{network_id}'? This cannot be undone. ")
+	} else {
+		format!("Stash member '{member}' on network '{network_id}'? It can be restored later (--permanent deletes it). ")
+	};
 	if !confirm(global, &prompt)? {
 		return Ok(());
 	}
 
 	let path = match org_id.as_deref() {
-		Some(org_id) => format!(
-			"/api/v1/org/{org_id}/network/{network_id}/member/{}",
-			args.member
-		),
-		None => format!("/api/v1/network/{network_id}/member/{}", args.member),
+		Some(org_id) => format!("/api/v1/org/{org_id}/network/{network_id}/member/{member}"),
+		None => format!("/api/v1/network/{network_id}/member/{member}"),
 	};
 
-	let response = client
+	let response = match client
 		.request_json(Method::DELETE, &path, None, Default::default(), true)
-		.await?;
+		.await
+	{
+		Ok(response) => Some(response),
+		Err(CliError::DryRunPrinted) if trpc.is_some() => {
+			// Blank line between the two previewed requests.
+			output::emit("", false)?;
+			None
+		}
+		Err(err) => return Err(err),
+	};
+
+	if let Some(trpc) = trpc {
+		let mut input = serde_json::Map::new();
+		input.insert("nwid".to_string(), Value::String(network_id.clone()));
+		input.insert("id".to_string(), Value::String(member.clone()));
+		input.insert("central".to_string(), Value::Bool(false));
+		if let Some(ref org_id) = org_id {
+			input.insert("organizationId".to_string(), Value::String(org_id.clone()));
+		}
+		if trpc.call_step("networkMember.delete", Value::Object(input)).await?.is_none() {
+			return Err(CliError::DryRunPrinted);
+		}
+	}
+	let Some(response) = response else {
+		return Err(CliError::DryRunPrinted);
+	};
+
+	if !global.quiet {
+		if args.permanent {
+			eprintln!("Deleted member '{member}' from network '{network_id}'.");
+		} else {
+			eprintln!(
+				"Stashed member '{member}' on network '{network_id}'; it can be restored from the web UI. Use --permanent to delete it for good."
+			);
+		}
+	}
 	print_human_or_machine(&response, effective.output, global.no_color)?;
 	Ok(())
 }
+
+/// Re-creates a member on another network: name, tags (matched by tag name, so ids may
+/// differ between networks) and, with `--keep-ips`, its IP assignments. The source member
+/// is kept, deauthorized or stashed afterwards.
+pub(super) async fn move_member(
+	global: &GlobalOpts,
+	effective: &crate::context::EffectiveConfig,
+	args: crate::cli::NetworkMoveMemberArgs,
+) -> Result<(), CliError> {
+	let from = network_or_context(effective, args.from.as_deref())?;
+	let trpc = trpc_authed(global, effective)?;
+
+	let from_id = resolve_personal_network_id(&trpc, &from).await?;
+	let to_id = resolve_personal_network_id(&trpc, &args.to).await?;
+	if from_id == to_id {
+		return Err(CliError::InvalidArgument(
+			"--from and --to are the same network".to_string(),
+		));
+	}
+	let from_details = trpc
+		.query("network.getNetworkById", serde_json::json!({ "nwid": from_id, "central": false }))
+		.await?;
+	let to_details = trpc
+		.query("network.getNetworkById", serde_json::json!({ "nwid": to_id, "central": false }))
+		.await?;
+	let from_org = resolve_network_org_id(&trpc, effective, args.org.as_deref(), &from_details).await?;
+	let to_org = resolve_network_org_id(&trpc, effective, args.org.as_deref(), &to_details).await?;
+
+	let members = from_details
+		.get("members")
+		.and_then(|v| v.as_array())
+		.map(Vec::as_slice)
+		.unwrap_or_default();
+	let wanted = args.member.trim();
+	let member = members
+		.iter()
+		.find(|m| m.get("id").and_then(|v| v.as_str()).is_some_and(|id| id.eq_ignore_ascii_case(wanted)))
+		.or_else(|| {
+			members
+				.iter()
+				.find(|m| m.get("name").and_then(|v| v.as_str()) == Some(wanted))
+		})
+		.ok_or_else(|| member_not_found(&from_id, wanted, members))?;
+	let member_id = member.get("id").and_then(|v| v.as_str()).unwrap_or(wanted).to_string();
+	let name = member.get("name").and_then(|v| v.as_str()).filter(|s| !s.is_empty());
+
+	let tags_by_name = |details: &Value| {
+		details
+			.get("network")
+			.and_then(|n| n.get("tagsByName"))
+			.cloned()
+			.unwrap_or(Value::Null)
+	};
+	let (tags, dropped_tags) = translate_tags(
+		member.get("tags").unwrap_or(&Value::Null),
+		&tags_by_name(&from_details),
+		&tags_by_name(&to_details),
+	);
+	let ips: Vec<Value> = if args.keep_ips {
+		member
+			.get("ipAssignments")
+			.and_then(|v| v.as_array())
+			.cloned()
+			.unwrap_or_default()
+	} else {
+		Vec::new()
+	};
+
+	if args.source == crate::cli::MoveSourceAction::Stash && !global.dry_run {
+		let prompt = format!("Move member '{member_id}' to {to_id} and stash it on {from_id}? ");
+		if !confirm(global, &prompt)? {
+			return Ok(());
+		}
+	}
+
+	let mut create = serde_json::Map::new();
+	create.insert("nwid".to_string(), Value::String(to_id.clone()));
+	create.insert("id".to_string(), Value::String(member_id.clone()));
+	create.insert("central".to_string(), Value::Bool(false));
+	if let Some(ref org_id) = to_org {
+		create.insert("organizationId".to_string(), Value::String(org_id.clone()));
+	}
+	let created = trpc.call_step("networkMember.create", Value::Object(create)).await?;
+
+	let mut update = serde_json::Map::new();
+	if !args.no_authorize {
+		update.insert("authorized".to_string(), Value::Bool(true));
+	}
+	if !ips.is_empty() {
+		update.insert("ipAssignments".to_string(), Value::Array(ips.clone()));
+	}
+	if !update.is_empty() {
+		trpc.call_step(
+			"networkMember.Update",
+			member_update_input(&to_id, &member_id, to_org.as_deref(), update),
+		)
+		.await?;
+	}
+	if let Some(name) = name {
+		let mut update = serde_json::Map::new();
+		update.insert("name".to_string(), Value::String(name.to_string()));
+		trpc.call_step(
+			"networkMember.UpdateDatabaseOnly",
+			member_update_input(&to_id, &member_id, to_org.as_deref(), update),
+		)
+		.await?;
+	}
+	if !tags.is_empty() {
+		let mut update = serde_json::Map::new();
+		update.insert("tags".to_string(), serde_json::json!(tags));
+		trpc.call_step(
+			"networkMember.Tags",
+			member_update_input(&to_id, &member_id, to_org.as_deref(), update),
+		)
+		.await?;
+	}
+
+	match args.source {
+		crate::cli::MoveSourceAction::Keep => {}
+		crate::cli::MoveSourceAction::Deauthorize => {
+			let mut update = serde_json::Map::new();
+			update.insert("authorized".to_string(), Value::Bool(false));
+			trpc.call_step(
+				"networkMember.Update",
+				member_update_input(&from_id, &member_id, from_org.as_deref(), update),
+			)
+			.await?;
+		}
+		crate::cli::MoveSourceAction::Stash => {
+			let mut input = serde_json::Map::new();
+			input.insert("nwid".to_string(), Value::String(from_id.clone()));
+			input.insert("id".to_string(), Value::String(member_id.clone()));
+			input.insert("central".to_string(), Value::Bool(false));
+			if let Some(ref org_id) = from_org {
+				input.insert("organizationId".to_string(), Value::String(org_id.clone()));
+			}
+			trpc.call_step("networkMember.stash", Value::Object(input)).await?;
+		}
+	}
+
+	if created.is_none() {
+		return Err(CliError::DryRunPrinted);
+	}
+
+	let summary = serde_json::json!({
+		"member": member_id,
+		"from": from_id,
+		"to": to_id,
+		"name": name,
+		"authorized": !args.no_authorize,
+		"ipAssignments": if args.keep_ips { Value::Array(ips) } else { Value::String("assigned by target".to_string()) },
+		"tags": tags,
+		"droppedTags": dropped_tags,
+		"source": match args.source {
+			crate::cli::MoveSourceAction::Keep => "kept",
+			crate::cli::MoveSourceAction::Deauthorize => "deauthorized",
+			crate::cli::MoveSourceAction::Stash => "stashed",
+		},
+	});
+	print_human_or_machine(&summary, effective.output, global.no_color)?;
+	Ok(())
+}
+
+/// Maps `[[id, value], ...]` tags from one network's tag ids to another's by tag name,
+/// translating named enum/flag values the same way. Tags the target network doesn't
+/// define are returned as dropped `name=value` labels.
+fn translate_tags(tags: &Value, from_by_name: &Value, to_by_name: &Value) -> (Vec<(u64, u64)>, Vec<String>) {
+	let named = |by_name: &Value, id: u64| {
+		by_name.as_object().and_then(|m| {
+			m.iter()
+				.find(|(_, tag)| tag.get("id").and_then(|v| v.as_u64()) == Some(id))
+				.map(|(name, tag)| (name.clone(), tag.clone()))
+		})
+	};
+	let value_name = |tag: &Value, value: u64| {
+		["enums", "flags"].iter().find_map(|key| {
+			tag.get(*key)?
+				.as_object()?
+				.iter()
+				.find(|(_, v)| v.as_u64() == Some(value))
+				.map(|(label, _)| label.clone())
+		})
+	};
+
+	let mut carried = Vec::new();
+	let mut dropped = Vec::new();
+	for pair in tags.as_array().into_iter().flatten() {
+		let (Some(id), Some(value)) = (
+			pair.get(0).and_then(|v| v.as_u64()),
+			pair.get(1).and_then(|v| v.as_u64()),
+		) else {
+			continue;
+		};
+		let Some((name, from_tag)) = named(from_by_name, id) else {
+			dropped.push(format!("{id}={value}"));
+			continue;
+		};
+		let label = value_name(&from_tag, value);
+		let Some(to_tag) = to_by_name.get(&name) else {
+			dropped.push(format!("{name}={}", label.unwrap_or_else(|| value.to_string())));
+			continue;
+		};
+		let Some(to_id) = to_tag.get("id").and_then(|v| v.as_u64()) else {
+			continue;
+		};
+		let to_value = match label {
+			Some(ref label) => ["enums", "flags"]
+				.iter()
+				.find_map(|key| to_tag.get(*key).and_then(|m| m.get(label)).and_then(|v| v.as_u64())),
+			None => Some(value),
+		};
+		match to_value {
+			Some(to_value) => carried.push((to_id, to_value)),
+			None => dropped.push(format!("{name}={}", label.unwrap_or_default())),
+		}
+	}
+	(carried, dropped)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+
+	use crate::mock_server::{MockServer, Request, Response};
+
+	#[test]
+	fn advanced_member_flags_map_to_trpc_update_params() {
+		use clap::Parser;
+		let cli = crate::cli::Cli::try_parse_from([
+			"ztnet", "member", "update", "net", "abcdef0123", "--deny", "--no-auto-assign-ips", "--ip", "10.0.0.9",
+		])
+		.unwrap();
+		let crate::cli::Command::Member {
+			command: crate::cli::MemberCommand::Update(args),
+		} = cli.command
+		else {
+			panic!("expected member update");
+		};
+		assert!(args.has_advanced_fields());
+		let update = advanced_update_params(&cli.global, &args).unwrap();
+		assert_eq!(
+			Value::Object(update),
+			json!({ "activeBridge": false, "noAutoAssignIps": true, "ipAssignments": ["10.0.0.9"] })
+		);
+
+		let cli = crate::cli::Cli::try_parse_from(["ztnet", "member", "update", "net", "m", "--allow-bridging", "--clear-ips"]).unwrap();
+		let crate::cli::Command::Member {
+			command: crate::cli::MemberCommand::Update(args),
+		} = cli.command
+		else {
+			panic!("expected member update");
+		};
+		let update = advanced_update_params(&cli.global, &args).unwrap();
+		assert_eq!(Value::Object(update), json!({ "activeBridge": true, "ipAssignments": [] }));
+		assert!(advanced_update_params(&cli.global, &crate::cli::MemberUpdateArgs { ip: vec!["nope".into()], clear_ips: false, ..args }).is_err());
+	}
+
+	#[test]
+	fn project_member_fields_filters_and_projects_without_full_parse() {
+		let body = br#"[
+			{"id":"a1","name":"alpha","authorized":true,"ipAssignments":["10.0.0.1"],"peers":{"latency":3}},
+			{"id":"b2","name":"beta","authorized":false,"ipAssignments":[],"peers":{"latency":9}}
+		]"#;
+		let fields = vec!["id".to_string(), "ipAssignments".to_string(), "missing".to_string()];
+
+		let rows = project_member_fields(body, &fields, |item| item["authorized"] == true).unwrap();
+		assert_eq!(
+			rows,
+			vec![json!({ "id": "a1", "ipAssignments": ["10.0.0.1"], "missing": null })]
+		);
+	}
+
+	#[test]
+	fn peer_links_classify_direct_relayed_and_offline() {
+		let details = json!({ "members": [
+			{ "id": "a1", "peers": { "latency": 12, "version": "1.14.0", "paths": [
+				{ "address": "198.51.100.7/9993", "active": true, "expired": false, "preferred": false },
+				{ "address": "203.0.113.5/9993", "active": true, "expired": false, "preferred": true }
+			] } },
+			{ "id": "b2", "peers": { "latency": -1, "paths": [
+				{ "address": "192.0.2.1/9993", "active": false, "expired": true }
+			] } },
+			{ "id": "c3", "peers": {} }
+		] });
+		let members = json!([
+			{ "id": "a1", "name": "web" },
+			{ "id": "b2", "name": "nas" },
+			{ "id": "c3", "name": "" },
+			{ "id": "d4" }
+		]);
+
+		let rows = with_peer_links(members.clone(), &details, false).unwrap();
+		assert_eq!(
+			rows[0]["peer"],
+			json!({ "link": "direct", "latency_ms": 12, "path": "203.0.113.5/9993", "version": "1.14.0" })
+		);
+		assert_eq!(rows[1]["peer"]["link"], "relayed");
+		assert_eq!(rows[1]["peer"]["latency_ms"], Value::Null);
+		assert_eq!(rows[2]["peer"]["link"], "offline");
+		assert_eq!(rows[3]["peer"]["link"], "offline");
+		assert_eq!(peer_link_counts(&rows), "1 direct, 1 relayed, 2 offline");
+
+		let relayed = with_peer_links(members, &details, true).unwrap();
+		assert_eq!(relayed.len(), 1);
+		assert_eq!(peer_summary(&relayed), "peer: b2 (nas)\n  link: relayed");
+	}
+
+	#[test]
+	fn member_fields_render_tag_names_and_ip_lists() {
+		let tags_by_name = json!({
+			"tier": { "id": 1000, "enums": { "frontend": 10 } },
+			"role": { "id": 2000, "flags": { "db": 1 } }
+		});
+		let tags = json!([[1000, 10], [2000, 1], [2000, 4], [3000, 7]]);
+		assert_eq!(
+			member_field_text("tags", &tags, &tags_by_name),
+			"tier=frontend, role=db, role=4, 3000=7"
+		);
+		assert_eq!(member_field_text("tags", &json!([]), &Value::Null), "(none)");
+		assert_eq!(
+			member_field_text("ipAssignments", &json!(["10.0.0.1", "fd00::1"]), &Value::Null),
+			"10.0.0.1, fd00::1"
+		);
+	}
+
+	#[test]
+	fn tag_assignments_resolve_names_and_merge_by_id() {
+		let tags_by_name = serde_json::json!({
+			"tier": { "id": 1000, "enums": { "frontend": 10, "backend": 20 } },
+			"role": { "id": 2000, "flags": { "db": 1 } }
+		});
+		assert_eq!(resolve_tag_assignment(&tags_by_name, "tier=frontend").unwrap(), (1000, 10));
+		assert_eq!(resolve_tag_assignment(&tags_by_name, "role=db").unwrap(), (2000, 1));
+		assert_eq!(resolve_tag_assignment(&tags_by_name, "3000=7").unwrap(), (3000, 7));
+		assert!(resolve_tag_assignment(&tags_by_name, "tier=edge").is_err());
+		assert!(resolve_tag_assignment(&tags_by_name, "zone=a").is_err());
+
+		let current = serde_json::json!([[1000, 20], [3000, 1]]);
+		assert_eq!(
+			merge_tags(&current, &[(1000, 10), (2000, 1)]),
+			serde_json::json!([[1000, 10], [3000, 1], [2000, 1]])
+		);
+		assert_eq!(merge_tags(&Value::Null, &[(1000, 10)]), serde_json::json!([[1000, 10]]));
+	}
+
+	#[test]
+	fn stale_unnamed_requires_authorized_nameless_and_old() {
+		let now = UNIX_EPOCH + Duration::from_secs(10 * 86_400);
+		let day = Duration::from_secs(86_400);
+		let joined_ms = 7 * 86_400 * 1000;
+
+		let stale = serde_json::json!({ "id": "a", "name": " ", "authorized": true, "creationTime": joined_ms });
+		assert!(is_stale_unnamed(&stale, now, day));
+		assert!(!is_stale_unnamed(&stale, now, 4 * day));
+
+		let named = serde_json::json!({ "id": "b", "name": "web", "authorized": true, "creationTime": joined_ms });
+		assert!(!is_stale_unnamed(&named, now, day));
+		let pending = serde_json::json!({ "id": "c", "authorized": false, "creationTime": joined_ms });
+		assert!(!is_stale_unnamed(&pending, now, day));
+		let undated = serde_json::json!({ "id": "d", "authorized": true });
+		assert!(!is_stale_unnamed(&undated, now, day));
+
+		let rfc3339 = serde_json::json!({ "authorized": true, "creationTime": "1970-01-08T00:00:00Z" });
+		assert!(is_stale_unnamed(&rfc3339, now, day));
+	}
+
+	#[test]
+	fn translate_tags_maps_by_name_and_drops_unknown() {
+		let from = json!({
+			"tier": { "id": 1000, "enums": { "frontend": 10, "backend": 20 } },
+			"zone": { "id": 3000 }
+		});
+		let to = json!({ "tier": { "id": 5, "enums": { "frontend": 1 } } });
+		let tags = json!([[1000, 10], [1000, 20], [3000, 7], [9999, 1]]);
+
+		let (carried, dropped) = translate_tags(&tags, &from, &to);
+		assert_eq!(carried, vec![(5, 1)]);
+		assert_eq!(dropped, vec!["tier=backend", "zone=7", "9999=1"]);
+	}
+
+	/// A panel answering every call with `{"id": <name>}`, where the name is the tRPC procedure
+	/// or `METHOD /path` for REST.
+	async fn recording_panel() -> MockServer {
+		MockServer::start(|request| Response::trpc(&json!({ "id": call_name(request) }))).await
+	}
+
+	fn call_name(request: &Request) -> String {
+		match request.procedure() {
+			Some(procedure) => procedure.to_string(),
+			None => format!("{} {}", request.method, request.path()),
+		}
+	}
+
+	/// `(name, input)` for every call so far; the input is the tRPC input or the REST body.
+	fn calls(panel: &MockServer) -> Vec<(String, Value)> {
+		let input = |r: &Request| match r.procedure() {
+			Some(_) => r.trpc_input(),
+			None => serde_json::from_str(&r.body).unwrap_or(Value::Null),
+		};
+		panel.requests().iter().map(|r| (call_name(r), input(r))).collect()
+	}
+
+	fn trpc(url: &str) -> TrpcClient {
+		TrpcClient::new(url, Duration::from_secs(5), 0, false, crate::http::ClientUi::default()).unwrap()
+	}
+
+	fn add_args(extra: &[&str]) -> crate::cli::MemberAddArgs {
+		use clap::Parser;
+		let argv = ["ztnet", "member", "add", "net", "abcdef0123"].iter().chain(extra);
+		match crate::cli::Cli::try_parse_from(argv).unwrap().command {
+			crate::cli::Command::Member { command: crate::cli::MemberCommand::Add(args) } => args,
+			_ => panic!("expected member add"),
+		}
+	}
+
+	#[tokio::test]
+	async fn provision_member_creates_then_authorizes_names_and_refetches() {
+		let panel = recording_panel().await;
+		let args = add_args(&["--name", "web-1", "--authorize", "--ip", "10.0.0.5"]);
+		let trpc = trpc(&panel.url);
+		let member = provision_member(&trpc, "nw1", "abcdef0123", Some("org1"), &args, vec!["10.0.0.5".to_string()])
+			.await
+			.unwrap();
+		assert_eq!(member, Some(json!({ "id": "networkMember.getMemberById" })));
+
+		let calls = calls(&panel);
+		let procedures: Vec<&str> = calls.iter().map(|(p, _)| p.as_str()).collect();
+		assert_eq!(
+			procedures,
+			[
+				"networkMember.create",
+				"networkMember.Update",
+				"networkMember.UpdateDatabaseOnly",
+				"networkMember.getMemberById",
+			]
+		);
+		assert_eq!(calls[0].1, json!({ "nwid": "nw1", "id": "abcdef0123", "central": false, "organizationId": "org1" }));
+		assert_eq!(calls[1].1["memberId"], "abcdef0123");
+		assert_eq!(calls[1].1["updateParams"], json!({ "authorized": true, "ipAssignments": ["10.0.0.5"] }));
+		assert_eq!(calls[2].1["updateParams"], json!({ "name": "web-1" }));
+	}
+
+	#[tokio::test]
+	async fn provision_member_without_options_only_creates() {
+		let panel = recording_panel().await;
+		let trpc = trpc(&panel.url);
+		let member = provision_member(&trpc, "nw1", "abcdef0123", None, &add_args(&[]), Vec::new()).await.unwrap();
+		assert_eq!(member, Some(json!({ "id": "networkMember.create" })));
+		assert_eq!(calls(&panel).len(), 1);
+	}
+
+	type DeleteCall = (GlobalOpts, crate::context::EffectiveConfig, crate::cli::MemberDeleteArgs);
+
+	fn delete_call(url: &str, session: Option<&str>, extra: &[&str]) -> DeleteCall {
+		use clap::Parser;
+		let argv = ["ztnet", "--yes", "--quiet", "member", "delete", "nw1", "abcdef0123"].iter().chain(extra);
+		let cli = crate::cli::Cli::try_parse_from(argv).unwrap();
+		let crate::cli::Command::Member { command: crate::cli::MemberCommand::Delete(args) } = cli.command else {
+			panic!("expected member delete");
+		};
+		let effective = crate::context::EffectiveConfig {
+			profile: "default".to_string(),
+			host: url.to_string(),
+			token: Some("t".to_string()),
+			session_cookie: session.map(str::to_string),
+			device_cookie: None,
+			org: None,
+			network: None,
+			output: crate::cli::OutputFormat::Json,
+			timeout: Duration::from_secs(5),
+			retries: 0,
+			backoff: Default::default(),
+			allow_default_route: false,
+			read_only: false,
+			cert_fingerprint: None,
+			user_agent: String::new(),
+			concurrency: 1,
+		};
+		(cli.global, effective, args)
+	}
+
+	fn rest(url: &str) -> HttpClient {
+		HttpClient::new(url, Some("t".to_string()), Duration::from_secs(5), 0, false, crate::http::ClientUi::default())
+			.unwrap()
+	}
+
+	#[tokio::test]
+	async fn member_delete_permanent_stashes_then_deletes_over_trpc() {
+		let panel = recording_panel().await;
+		let (global, effective, args) = delete_call(&panel.url, Some("next-auth.session-token=s"), &["--permanent"]);
+		member_delete(&global, &effective, &rest(&panel.url), args).await.unwrap();
+
+		let calls = calls(&panel);
+		let names: Vec<&str> = calls.iter().map(|(name, _)| name.as_str()).collect();
+		assert_eq!(
+			names,
+			["GET /api/v1/network", "DELETE /api/v1/network/nw1/member/abcdef0123", "networkMember.delete"]
+		);
+		assert_eq!(calls[2].1, json!({ "nwid": "nw1", "id": "abcdef0123", "central": false }));
+	}
+
+	#[tokio::test]
+	async fn member_delete_permanent_needs_a_session_before_stashing() {
+		let panel = recording_panel().await;
+		let (global, effective, args) = delete_call(&panel.url, None, &["--permanent"]);
+		assert!(member_delete(&global, &effective, &rest(&panel.url), args).await.is_err());
+		assert!(calls(&panel).iter().all(|(name, _)| !name.starts_with("DELETE")));
+
+		let (global, effective, args) = delete_call(&panel.url, None, &[]);
+		member_delete(&global, &effective, &rest(&panel.url), args).await.unwrap();
+		assert_eq!(calls(&panel).last().unwrap().0, "DELETE /api/v1/network/nw1/member/abcdef0123");
+	}
+
+	#[test]
+	fn member_tags_apply_accepts_the_network_before_or_after_apply() {
+		use clap::Parser;
+		let parse = |argv: &[&str]| {
+			let argv = ["ztnet", "member", "tags"].iter().chain(argv);
+			match crate::cli::Cli::try_parse_from(argv).unwrap().command {
+				crate::cli::Command::Member { command: crate::cli::MemberCommand::Tags(args) } => args,
+				_ => panic!("expected member tags"),
+			}
+		};
+
+		let args = parse(&["LAB", "apply", "--filter", "name~=web", "--tag", "role=web"]);
+		assert_eq!(args.network.as_deref(), Some("LAB"));
+		assert!(args.member.is_none());
+		let crate::cli::MemberTagsCommand::Apply(apply) = args.command else {
+			panic!("expected apply");
+		};
+		assert!(apply.network.is_none());
+
+		let args = parse(&["apply", "LAB", "--filter", "name~=web", "--tag", "role=web"]);
+		let crate::cli::MemberTagsCommand::Apply(apply) = args.command else {
+			panic!("expected apply");
+		};
+		assert_eq!(apply.network.as_deref(), Some("LAB"));
+	}
+}
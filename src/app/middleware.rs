@@ -0,0 +1,184 @@
+use std::path::PathBuf;
+use std::time::Instant;
+
+use crate::cli::GlobalOpts;
+use crate::config::Config;
+use crate::context::{resolve_effective_config, EffectiveConfig};
+use crate::error::CliError;
+use crate::http::{ClientUi, HttpClient};
+use crate::output;
+
+use super::common::load_config_store;
+use super::trpc_client::{require_cookie_from_effective, TrpcClient};
+
+/// Config-backed state shared by every command handler: the loaded config file, the
+/// effective settings after flag/env/profile precedence, and client constructors.
+pub(super) struct CommandContext<'a> {
+	pub global: &'a GlobalOpts,
+	pub config_path: PathBuf,
+	pub cfg: Config,
+	pub effective: EffectiveConfig,
+}
+
+impl<'a> CommandContext<'a> {
+	pub(super) fn load(global: &'a GlobalOpts) -> Result<Self, CliError> {
+		let (config_path, cfg) = load_config_store()?;
+		let effective = resolve_effective_config(global, &cfg)?;
+		Ok(Self {
+			global,
+			config_path,
+			cfg,
+			effective,
+		})
+	}
+
+	/// REST client authenticated with the effective API token (if any).
+	pub(super) fn client(&self) -> Result<HttpClient, CliError> {
+		http_client(self.global, &self.effective, self.effective.token.clone())
+	}
+
+	/// REST client that never sends the API token, for tRPC and public endpoints.
+	pub(super) fn anonymous_client(&self) -> Result<HttpClient, CliError> {
+		http_client(self.global, &self.effective, None)
+	}
+}
+
+fn http_client(
+	global: &GlobalOpts,
+	effective: &EffectiveConfig,
+	token: Option<String>,
+) -> Result<HttpClient, CliError> {
+	HttpClient::new(
+		&effective.host,
+		token,
+		effective.timeout,
+		effective.retries,
+		global.dry_run,
+		ClientUi::from_context(global, effective),
+	)
+}
+
+/// tRPC client carrying the profile's session; fails with a login hint when none is stored.
+pub(super) fn trpc_authed(global: &GlobalOpts, effective: &EffectiveConfig) -> Result<TrpcClient, CliError> {
+	let cookie = require_cookie_from_effective(effective)?;
+	Ok(TrpcClient::new(
+		&effective.host,
+		effective.timeout,
+		effective.retries,
+		global.dry_run,
+		ClientUi::from_context(global, effective),
+	)?
+	.with_cookie(Some(cookie)))
+}
+
+/// tRPC client without a session, for the panel's public procedures.
+pub(super) fn trpc_anonymous(global: &GlobalOpts, effective: &EffectiveConfig) -> Result<TrpcClient, CliError> {
+	TrpcClient::new(
+		&effective.host,
+		effective.timeout,
+		effective.retries,
+		global.dry_run,
+		ClientUi::from_context(global, effective),
+	)
+}
+
+/// Cross-cutting work around a command. `before` hooks run in chain order and may abort
+/// the command; `after` hooks run in reverse order and see the command's result.
+pub(super) trait Middleware {
+	fn before(&mut self, _ctx: &CommandContext<'_>, _command: &str) -> Result<(), CliError> {
+		Ok(())
+	}
+
+	fn after(&mut self, _ctx: &CommandContext<'_>, _command: &str, _result: &Result<(), CliError>) {}
+}
+
+/// Reports the wall-clock time of the command on stderr with `-v`.
+#[derive(Default)]
+pub(super) struct Timing {
+	started: Option<Instant>,
+}
+
+impl Middleware for Timing {
+	fn before(&mut self, _ctx: &CommandContext<'_>, _command: &str) -> Result<(), CliError> {
+		self.started = Some(Instant::now());
+		Ok(())
+	}
+
+	fn after(&mut self, ctx: &CommandContext<'_>, command: &str, result: &Result<(), CliError>) {
+		let Some(started) = self.started else {
+			return;
+		};
+		if ctx.global.verbose == 0 || ctx.global.quiet {
+			return;
+		}
+		let outcome = match result {
+			Ok(()) | Err(CliError::DryRunPrinted) => "ok",
+			Err(CliError::Interrupted) => "interrupted",
+			Err(_) => "failed",
+		};
+		eprintln!("{command}: {outcome} in {}", output::format_duration(started.elapsed()));
+	}
+}
+
+/// Runs `execute` with a freshly loaded context, wrapped by the middleware chain.
+pub(super) async fn run_chain<'a>(
+	global: &'a GlobalOpts,
+	command: &str,
+	chain: &mut [&mut dyn Middleware],
+	execute: impl AsyncFnOnce(&CommandContext<'a>) -> Result<(), CliError>,
+) -> Result<(), CliError> {
+	let ctx = CommandContext::load(global)?;
+	for middleware in chain.iter_mut() {
+		middleware.before(&ctx, command)?;
+	}
+	let result = execute(&ctx).await;
+	for middleware in chain.iter_mut().rev() {
+		middleware.after(&ctx, command, &result);
+	}
+	result
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use clap::Parser;
+	use reqwest::Method;
+
+	use crate::mock_server::{MockServer, Response};
+
+	#[tokio::test]
+	async fn context_clients_carry_the_effective_settings() {
+		let server = MockServer::start(|_| Response::json("{}")).await;
+		let cli = crate::cli::Cli::parse_from([
+			"ztnet",
+			"--host",
+			server.url.as_str(),
+			"--token",
+			"t0ken",
+			"--user-agent",
+			"probe/1.0",
+			"--retries",
+			"0",
+			"version",
+		]);
+		let cfg = Config::default();
+		let ctx = CommandContext {
+			global: &cli.global,
+			config_path: PathBuf::new(),
+			effective: resolve_effective_config(&cli.global, &cfg).unwrap(),
+			cfg,
+		};
+
+		let client = ctx.client().unwrap();
+		client.request_json(Method::GET, "/api/v1/network", None, Default::default(), true).await.unwrap();
+		let request = server.requests().pop().unwrap();
+		assert_eq!(request.header("user-agent"), Some("probe/1.0"));
+		assert_eq!(request.header("x-ztnet-auth"), Some("t0ken"));
+
+		let anonymous = ctx.anonymous_client().unwrap();
+		anonymous.request_json(Method::GET, "/api/planet", None, Default::default(), false).await.unwrap();
+		let request = server.requests().pop().unwrap();
+		assert_eq!(request.header("user-agent"), Some("probe/1.0"));
+		assert_eq!(request.header("x-ztnet-auth"), None);
+	}
+}
@@ -1,69 +1,91 @@
+use std::collections::HashMap;
+
+use futures_util::stream::{self, StreamExt};
 use reqwest::Method;
 use serde_json::{json, Value};
 
-use crate::cli::{GlobalOpts, NetworkCommand, OutputFormat};
-use crate::context::resolve_effective_config;
+use crate::cli::{ErrorExitPolicy, NetworkCommand, OutputFormat};
 use crate::error::CliError;
-use crate::http::{ClientUi, HttpClient};
+use crate::http::HttpClient;
 use crate::output;
 
-use super::common::{load_config_store, print_human_or_machine};
+use super::common::{
+	check_error_policy, detail_error, fetch_details, print_human_or_machine, print_with_errors,
+	read_input_file, report_errors_stderr,
+};
+use super::filter::Filter;
 use super::member;
+use super::middleware::CommandContext;
+use super::network_summary;
 use super::network_trpc;
-use super::resolve::{extract_network_id, resolve_network_id, resolve_org_id};
-
-pub(super) async fn run(global: &GlobalOpts, command: NetworkCommand) -> Result<(), CliError> {
-	let (_config_path, cfg) = load_config_store()?;
-	let effective = resolve_effective_config(global, &cfg)?;
+use super::resolve::{extract_network_id, network_or_context, resolve_network_id, resolve_org_id};
 
-	let client = HttpClient::new(
-		&effective.host,
-		effective.token.clone(),
-		effective.timeout,
-		effective.retries,
-		global.dry_run,
-		ClientUi::from_context(global, &effective),
-	)?;
+pub(super) async fn run(ctx: &CommandContext<'_>, command: NetworkCommand) -> Result<(), CliError> {
+	let (global, effective) = (ctx.global, &ctx.effective);
+	let client = ctx.client()?;
 
 	match command {
 		NetworkCommand::List(args) => {
-			let org = args.org.or(effective.org.clone());
-			let org_id = match org {
-				Some(ref org) => Some(resolve_org_id(&client, org).await?),
-				None => None,
+			let scopes = if args.all {
+				all_list_scopes(&client).await?
+			} else {
+				let org = if args.mine { None } else { args.org.or(effective.org.clone()) };
+				let org_id = match org {
+					Some(ref org) => Some(resolve_org_id(&client, org).await?),
+					None => None,
+				};
+				vec![ListScope { org_id, owner: None }]
 			};
 
-			let path = match org_id.as_deref() {
-				Some(org_id) => format!("/api/v1/org/{org_id}/network"),
-				None => "/api/v1/network".to_string(),
+			let lists: Vec<Result<Value, CliError>> = stream::iter(scopes.iter().map(|scope| {
+				let path = scope.path(None);
+				let client = &client;
+				async move {
+					client
+						.request_json(Method::GET, &path, None, Default::default(), true)
+						.await
+				}
+			}))
+			.buffered(effective.concurrency)
+			.collect()
+			.await;
+
+			let (mut response, scope_of, scope_errors) = if args.all {
+				let (mut merged, scope_of, errors) = merge_scope_lists(&scopes, lists)?;
+				annotate_owners(&mut merged, &scopes, &scope_of);
+				(merged, scope_of, errors)
+			} else {
+				let list = lists.into_iter().next().unwrap_or(Ok(Value::Null))?;
+				(list, HashMap::new(), Vec::new())
 			};
 
-			let mut response = client
-				.request_json(Method::GET, &path, None, Default::default(), true)
-				.await?;
-
 			if let Some(filter) = args.filter.as_deref() {
 				response = filter_network_list(response, filter)?;
 			}
 
+			let mut detail_errors = Vec::new();
+			let mut detail_total = 0;
 			if args.details {
 				let Some(networks) = response.as_array() else {
 					return Err(CliError::InvalidArgument("expected array response".to_string()));
 				};
 
-				let mut detailed = Vec::with_capacity(networks.len());
-				for net in networks {
-					let Some(id) = extract_network_id(net) else { continue };
-					let detail_path = match org_id.as_deref() {
-						Some(org_id) => format!("/api/v1/org/{org_id}/network/{id}"),
-						None => format!("/api/v1/network/{id}"),
-					};
-					let detail = client
-						.request_json(Method::GET, &detail_path, None, Default::default(), true)
-						.await?;
-					detailed.push(detail);
-				}
+				let targets: Vec<(String, String)> = networks
+					.iter()
+					.filter_map(extract_network_id)
+					.map(|id| {
+						let scope = &scopes[scope_of.get(id).copied().unwrap_or(0)];
+						(id.to_string(), scope.path(Some(id)))
+					})
+					.collect();
+				detail_total = targets.len();
+
+				let (detailed, errors) = fetch_details(&client, targets, args.skip_errors, effective.concurrency).await?;
 				response = Value::Array(detailed);
+				if args.all {
+					annotate_owners(&mut response, &scopes, &scope_of);
+				}
+				detail_errors = errors;
 			}
 
 			if args.ids_only {
@@ -78,15 +100,24 @@ pub(super) async fn run(global: &GlobalOpts, command: NetworkCommand) -> Result<
 					.unwrap_or_default();
 
 				if matches!(effective.output, OutputFormat::Table) {
-					for id in ids {
-						println!("{id}");
+					if !ids.is_empty() {
+						output::emit(&ids.join("\n"), false)?;
 					}
-					return Ok(());
+				} else {
+					let value = Value::Array(ids.into_iter().map(Value::String).collect());
+					output::print_value(&value, effective.output, global.no_color)?;
 				}
+				report_errors_stderr(&scope_errors);
+				report_errors_stderr(&detail_errors);
+				check_error_policy(ErrorExitPolicy::Any, scope_errors.len(), scopes.len())?;
+				return check_error_policy(args.error_exit, detail_errors.len(), detail_total);
+			}
 
-				let value = Value::Array(ids.into_iter().map(Value::String).collect());
-				output::print_value(&value, effective.output, global.no_color)?;
-				return Ok(());
+			if args.skip_errors || !scope_errors.is_empty() {
+				let errors: Vec<Value> = scope_errors.iter().chain(&detail_errors).cloned().collect();
+				print_with_errors(&response, &errors, effective.output, global.no_color)?;
+				check_error_policy(ErrorExitPolicy::Any, scope_errors.len(), scopes.len())?;
+				return check_error_policy(args.error_exit, detail_errors.len(), detail_total);
 			}
 
 			output::print_value(&response, effective.output, global.no_color)?;
@@ -123,7 +154,8 @@ pub(super) async fn run(global: &GlobalOpts, command: NetworkCommand) -> Result<
 				None => None,
 			};
 
-			let network_id = resolve_network_id(&client, org_id.as_deref(), &args.network).await?;
+			let network = network_or_context(effective, args.network.as_deref())?;
+			let network_id = resolve_network_id(&client, org_id.as_deref(), &network).await?;
 			let path = match org_id.as_deref() {
 				Some(org_id) => format!("/api/v1/org/{org_id}/network/{network_id}"),
 				None => format!("/api/v1/network/{network_id}"),
@@ -137,14 +169,15 @@ pub(super) async fn run(global: &GlobalOpts, command: NetworkCommand) -> Result<
 		}
 		NetworkCommand::Update(args) => {
 			let org_id = resolve_org_id(&client, &args.org).await?;
-			let network_id = resolve_network_id(&client, Some(&org_id), &args.network).await?;
+			let network = network_or_context(effective, args.network.as_deref())?;
+			let network_id = resolve_network_id(&client, Some(&org_id), &network).await?;
 			let path = format!("/api/v1/org/{org_id}/network/{network_id}");
 
 			let body = if let Some(body) = args.body {
 				serde_json::from_str::<Value>(&body)
 					.map_err(|err| CliError::InvalidArgument(format!("invalid --body json: {err}")))?
 			} else if let Some(path) = args.body_file {
-				let text = std::fs::read_to_string(&path)?;
+				let text = read_input_file(&path)?;
 				serde_json::from_str::<Value>(&text).map_err(|err| {
 					CliError::InvalidArgument(format!("invalid --body-file json: {err}"))
 				})?
@@ -159,15 +192,17 @@ pub(super) async fn run(global: &GlobalOpts, command: NetworkCommand) -> Result<
 			Ok(())
 		}
 		NetworkCommand::Member { command } => {
-			member::run_network_member(global, &effective, &client, command).await
+			member::run_network_member(global, effective, &client, command).await
 		}
-		NetworkCommand::Delete(args) => network_trpc::delete(global, &effective, args).await,
-		NetworkCommand::Routes(args) => network_trpc::routes(global, &effective, args).await,
-		NetworkCommand::IpPool(args) => network_trpc::ip_pool(global, &effective, args).await,
-		NetworkCommand::Dns(args) => network_trpc::dns(global, &effective, args).await,
-		NetworkCommand::Ipv6(args) => network_trpc::ipv6(global, &effective, args).await,
-		NetworkCommand::Multicast(args) => network_trpc::multicast(global, &effective, args).await,
-		NetworkCommand::FlowRules(args) => network_trpc::flow_rules(global, &effective, args).await,
+		NetworkCommand::Delete(args) => network_trpc::delete(global, effective, args).await,
+		NetworkCommand::Routes(args) => network_trpc::routes(global, effective, &client, args).await,
+		NetworkCommand::IpPool(args) => network_trpc::ip_pool(global, effective, &client, args).await,
+		NetworkCommand::Dns(args) => network_trpc::dns(global, effective, args).await,
+		NetworkCommand::Ipv6(args) => network_trpc::ipv6(global, effective, args).await,
+		NetworkCommand::Multicast(args) => network_trpc::multicast(global, effective, args).await,
+		NetworkCommand::FlowRules(args) => network_trpc::flow_rules(global, effective, args).await,
+		NetworkCommand::MoveMember(args) => member::move_member(global, effective, args).await,
+		NetworkCommand::Summary(args) => network_summary::run(global, effective, args).await,
 	}
 }
 
@@ -176,53 +211,10 @@ fn filter_network_list(response: Value, expr: &str) -> Result<Value, CliError> {
 		return Ok(response);
 	};
 
-	let mut name_contains: Option<String> = None;
-	let mut private_is: Option<bool> = None;
-
-	for raw in expr.split(',').map(str::trim).filter(|s| !s.is_empty()) {
-		if let Some((k, v)) = raw.split_once("~=") {
-			if k.trim().eq_ignore_ascii_case("name") {
-				name_contains = Some(v.trim().to_string());
-			}
-			continue;
-		}
-		if let Some((k, v)) = raw.split_once("==") {
-			if k.trim().eq_ignore_ascii_case("private") {
-				private_is = Some(matches!(
-					v.trim().to_ascii_lowercase().as_str(),
-					"true" | "1" | "yes"
-				));
-			}
-			continue;
-		}
-	}
-
+	let filter = Filter::parse(expr)?;
 	let filtered: Vec<Value> = items
 		.iter()
-		.filter(|item| {
-			if let Some(ref needle) = name_contains {
-				let name = item
-					.get("name")
-					.and_then(|v| v.as_str())
-					.or_else(|| item.get("nwname").and_then(|v| v.as_str()))
-					.unwrap_or("");
-				if !name
-					.to_ascii_lowercase()
-					.contains(&needle.to_ascii_lowercase())
-				{
-					return false;
-				}
-			}
-
-			if let Some(expected) = private_is {
-				let actual = item.get("private").and_then(|v| v.as_bool()).unwrap_or(false);
-				if actual != expected {
-					return false;
-				}
-			}
-
-			true
-		})
+		.filter(|item| filter.matches(item))
 		.cloned()
 		.collect();
 
@@ -251,7 +243,7 @@ fn build_network_update_body(args: &crate::cli::NetworkUpdateArgs) -> Result<Val
 		let rule = if let Some(rule) = args.flow_rule.clone() {
 			rule
 		} else if let Some(path) = args.flow_rule_file.as_ref() {
-			std::fs::read_to_string(path)?
+			read_input_file(path)?
 		} else {
 			unreachable!()
 		};
@@ -280,3 +272,144 @@ fn build_network_update_body(args: &crate::cli::NetworkUpdateArgs) -> Result<Val
 
 	Ok(Value::Object(body))
 }
+
+/// Where `network list` reads from: the personal scope (`org_id` unset) or one organization.
+/// `owner` is only set with `--all`, where it labels each network.
+struct ListScope {
+	org_id: Option<String>,
+	owner: Option<String>,
+}
+
+impl ListScope {
+	fn path(&self, network_id: Option<&str>) -> String {
+		let base = match self.org_id.as_deref() {
+			Some(org_id) => format!("/api/v1/org/{org_id}/network"),
+			None => "/api/v1/network".to_string(),
+		};
+		match network_id {
+			Some(id) => format!("{base}/{id}"),
+			None => base,
+		}
+	}
+}
+
+/// The personal scope followed by every organization the account belongs to.
+async fn all_list_scopes(client: &HttpClient) -> Result<Vec<ListScope>, CliError> {
+	let orgs = client
+		.request_json(Method::GET, "/api/v1/org", None, Default::default(), true)
+		.await?;
+	let mut scopes = vec![ListScope {
+		org_id: None,
+		owner: Some("personal".to_string()),
+	}];
+	for org in orgs.as_array().into_iter().flatten() {
+		let Some(id) = org.get("id").and_then(|v| v.as_str()) else {
+			continue;
+		};
+		let name = org
+			.get("orgName")
+			.and_then(|v| v.as_str())
+			.or_else(|| org.get("name").and_then(|v| v.as_str()))
+			.unwrap_or(id);
+		scopes.push(ListScope {
+			org_id: Some(id.to_string()),
+			owner: Some(name.to_string()),
+		});
+	}
+	Ok(scopes)
+}
+
+/// Network id -> index of the [`ListScope`] it was listed under.
+type ScopeIndex = HashMap<String, usize>;
+
+/// Concatenates the `--all` scope lists. A network listed by several scopes is kept once,
+/// under the first scope that returned it. A scope that fails becomes an error entry
+/// instead of aborting the listing; only when every scope fails is the first error returned.
+fn merge_scope_lists(
+	scopes: &[ListScope],
+	lists: Vec<Result<Value, CliError>>,
+) -> Result<(Value, ScopeIndex, Vec<Value>), CliError> {
+	let mut merged = Vec::new();
+	let mut scope_of = ScopeIndex::new();
+	let mut errors = Vec::new();
+	let mut first_error = None;
+	let total = lists.len();
+	for (idx, list) in lists.into_iter().enumerate() {
+		let list = match list {
+			Ok(list) => list,
+			Err(err @ (CliError::DryRunPrinted | CliError::Interrupted)) => return Err(err),
+			Err(err) => {
+				let scope = &scopes[idx];
+				let label = scope.org_id.as_deref().or(scope.owner.as_deref()).unwrap_or("personal");
+				errors.push(detail_error(label, &err));
+				first_error.get_or_insert(err);
+				continue;
+			}
+		};
+		for network in list.as_array().cloned().unwrap_or_default() {
+			if let Some(id) = extract_network_id(&network) {
+				if scope_of.contains_key(id) {
+					continue;
+				}
+				scope_of.insert(id.to_string(), idx);
+			}
+			merged.push(network);
+		}
+	}
+	if let Some(err) = first_error
+		&& errors.len() == total
+	{
+		return Err(err);
+	}
+	Ok((Value::Array(merged), scope_of, errors))
+}
+
+fn annotate_owners(networks: &mut Value, scopes: &[ListScope], scope_of: &ScopeIndex) {
+	for network in networks.as_array_mut().into_iter().flatten() {
+		let owner = extract_network_id(network)
+			.and_then(|id| scope_of.get(id))
+			.and_then(|idx| scopes[*idx].owner.clone());
+		if let (Some(owner), Some(obj)) = (owner, network.as_object_mut()) {
+			obj.insert("owner".to_string(), Value::String(owner));
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn scopes() -> Vec<ListScope> {
+		vec![
+			ListScope { org_id: None, owner: Some("personal".to_string()) },
+			ListScope { org_id: Some("org-a".to_string()), owner: Some("Alpha".to_string()) },
+			ListScope { org_id: Some("org-b".to_string()), owner: Some("Beta".to_string()) },
+		]
+	}
+
+	#[test]
+	fn merge_scope_lists_keeps_each_network_once_and_collects_failed_scopes() {
+		let scopes = scopes();
+		let lists = vec![
+			Ok(json!([{ "nwid": "n1" }])),
+			Ok(json!([{ "nwid": "n1" }, { "nwid": "n2" }])),
+			Err(CliError::InvalidArgument("boom".to_string())),
+		];
+		let (mut merged, scope_of, errors) = merge_scope_lists(&scopes, lists).unwrap();
+		annotate_owners(&mut merged, &scopes, &scope_of);
+		assert_eq!(merged, json!([{ "nwid": "n1", "owner": "personal" }, { "nwid": "n2", "owner": "Alpha" }]));
+		assert_eq!(errors.len(), 1);
+		assert_eq!(errors[0]["id"], "org-b");
+		assert!(errors[0]["error"].as_str().unwrap().contains("boom"));
+	}
+
+	#[test]
+	fn merge_scope_lists_fails_when_every_scope_fails() {
+		let lists = (0..3).map(|i| Err(CliError::InvalidArgument(format!("down {i}")))).collect();
+		let err = merge_scope_lists(&scopes(), lists).unwrap_err();
+		assert!(err.to_string().contains("down 0"));
+
+		let lists = vec![Ok(json!([])), Err(CliError::Interrupted), Ok(json!([]))];
+		assert!(matches!(merge_scope_lists(&scopes(), lists), Err(CliError::Interrupted)));
+	}
+}
@@ -0,0 +1,222 @@
+//! `network summary`: one report of a network's settings, routes, IP pools, DNS and members,
+//! as tables, JSON, or Markdown for wikis and scheduled documentation commits.
+
+use serde_json::{Value, json};
+
+use crate::cli::{GlobalOpts, NetworkSummaryArgs, OutputFormat};
+use crate::context::EffectiveConfig;
+use crate::error::CliError;
+use crate::output;
+
+use super::common::{print_kv, render_scalar};
+use super::member::peer_link;
+use super::middleware::trpc_authed;
+use super::network_trpc::{extract_ip_pools, extract_network_routes, get_network_details};
+use super::resolve::network_or_context;
+use super::trpc_resolve::resolve_personal_network_id;
+
+pub(super) async fn run(
+	global: &GlobalOpts,
+	effective: &EffectiveConfig,
+	args: NetworkSummaryArgs,
+) -> Result<(), CliError> {
+	let network = network_or_context(effective, args.network.as_deref())?;
+	let trpc = trpc_authed(global, effective)?;
+	let network_id = resolve_personal_network_id(&trpc, &network).await?;
+	let details = get_network_details(&trpc, &network_id).await?;
+	let summary = summarize(&network_id, &details)?;
+
+	if args.markdown {
+		return output::emit(&markdown(&summary), false);
+	}
+	if !matches!(effective.output, OutputFormat::Table) {
+		return output::print_value(&summary, effective.output, global.no_color);
+	}
+
+	print_kv(&summary["network"])?;
+	for (title, key, columns) in [
+		("Routes", "routes", &["target", "via"][..]),
+		("IP pools", "ip_pools", &["ipRangeStart", "ipRangeEnd"][..]),
+		("Members", "members", &["id", "name", "authorized", "ips", "link"][..]),
+	] {
+		output::emit(&format!("\n{title}"), true)?;
+		output::print_columns(summary[key].as_array().map(Vec::as_slice).unwrap_or_default(), columns)?;
+	}
+	Ok(())
+}
+
+/// The report's data. Lists are sorted so repeated runs over an unchanged network are
+/// identical.
+fn summarize(network_id: &str, details: &Value) -> Result<Value, CliError> {
+	let network = &details["network"];
+	let mut routes = extract_network_routes(details)?;
+	routes.sort_by_key(|route| render_scalar(&route["target"]));
+	let mut pools = extract_ip_pools(details)?;
+	pools.sort_by_key(|pool| render_scalar(&pool["ipRangeStart"]));
+
+	let mut members: Vec<Value> = details["members"]
+		.as_array()
+		.into_iter()
+		.flatten()
+		.map(|member| {
+			json!({
+				"id": member["id"],
+				"name": member["name"],
+				"authorized": member["authorized"].as_bool().unwrap_or(false),
+				"ips": member["ipAssignments"].as_array().cloned().unwrap_or_default(),
+				"link": peer_link(Some(&member["peers"]))["link"],
+			})
+		})
+		.collect();
+	members.sort_by_key(|member| render_scalar(&member["id"]));
+
+	let v6: Vec<&str> = ["rfc4193", "6plane", "zt"]
+		.into_iter()
+		.filter(|mode| network["v6AssignMode"][*mode] == true)
+		.collect();
+
+	Ok(json!({
+		"network": {
+			"id": network_id,
+			"name": network["name"],
+			"description": network["description"],
+			"private": network["private"],
+			"mtu": network["mtu"],
+			"ipv6": v6.join(", "),
+			"multicastLimit": network["multicastLimit"],
+			"enableBroadcast": network["enableBroadcast"],
+		},
+		"routes": routes,
+		"ip_pools": pools,
+		"dns": {
+			"domain": network["dns"]["domain"],
+			"servers": network["dns"]["servers"].as_array().cloned().unwrap_or_default(),
+		},
+		"members": members,
+	}))
+}
+
+/// Markdown with an HTML anchor per section (`network-<id>`, `network-<id>-routes`, ...), so
+/// links survive renames and several networks can share one page. Peer links change from
+/// minute to minute and are left out to keep documentation diffs quiet.
+fn markdown(summary: &Value) -> String {
+	let network = &summary["network"];
+	let id = render_scalar(&network["id"]);
+	let name = network["name"].as_str().filter(|name| !name.is_empty()).unwrap_or(&id);
+	let mut out = vec![format!("<a id=\"network-{id}\"></a>\n\n# Network {} (`{id}`)", cell(&json!(name)))];
+
+	out.push(table(
+		&["Setting", "Value"],
+		[
+			("Description", &network["description"]),
+			("Private", &network["private"]),
+			("MTU", &network["mtu"]),
+			("IPv6 assignment", &network["ipv6"]),
+			("Multicast limit", &network["multicastLimit"]),
+			("Broadcast", &network["enableBroadcast"]),
+		]
+		.into_iter()
+		.map(|(setting, value)| vec![setting.to_string(), cell(value)])
+		.collect(),
+	));
+
+	let mut section = |anchor: &str, title: String, headers: &[&str], rows: Vec<Vec<String>>| {
+		out.push(format!("<a id=\"network-{id}-{anchor}\"></a>\n\n## {title}"));
+		out.push(if rows.is_empty() { "_None._".to_string() } else { table(headers, rows) });
+	};
+	let rows = |key: &str, fields: &[&str]| -> Vec<Vec<String>> {
+		summary[key]
+			.as_array()
+			.into_iter()
+			.flatten()
+			.map(|row| fields.iter().map(|field| cell(&row[*field])).collect())
+			.collect()
+	};
+
+	section("routes", "Routes".to_string(), &["Destination", "Via"], rows("routes", &["target", "via"]));
+	section(
+		"ip-pools",
+		"IP pools".to_string(),
+		&["Start", "End"],
+		rows("ip_pools", &["ipRangeStart", "ipRangeEnd"]),
+	);
+	let dns = &summary["dns"];
+	let dns_rows = if dns["domain"].is_null() && dns["servers"].as_array().is_none_or(Vec::is_empty) {
+		Vec::new()
+	} else {
+		vec![vec![cell(&dns["domain"]), cell(&dns["servers"])]]
+	};
+	section("dns", "DNS".to_string(), &["Domain", "Servers"], dns_rows);
+
+	let members = summary["members"].as_array().map(Vec::as_slice).unwrap_or_default();
+	let authorized = members.iter().filter(|member| member["authorized"] == true).count();
+	section(
+		"members",
+		format!("Members ({} total, {authorized} authorized)", members.len()),
+		&["ID", "Name", "Authorized", "IPs"],
+		rows("members", &["id", "name", "authorized", "ips"]),
+	);
+
+	out.join("\n\n") + "\n"
+}
+
+fn table(headers: &[&str], rows: Vec<Vec<String>>) -> String {
+	let mut lines = vec![
+		format!("| {} |", headers.join(" | ")),
+		format!("|{}|", vec!["---"; headers.len()].join("|")),
+	];
+	lines.extend(rows.into_iter().map(|row| format!("| {} |", row.join(" | "))));
+	lines.join("\n")
+}
+
+/// One table cell: lists joined with commas, booleans as yes/no, `|` and newlines escaped.
+fn cell(value: &Value) -> String {
+	let text = match value {
+		Value::Bool(true) => "yes".to_string(),
+		Value::Bool(false) => "no".to_string(),
+		Value::Array(items) => items.iter().map(render_scalar).collect::<Vec<_>>().join(", "),
+		Value::Null => String::new(),
+		other => render_scalar(other),
+	};
+	text.replace('|', "\\|").replace(['\r', '\n'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn markdown_report_is_sorted_anchored_and_escaped() {
+		let details = json!({
+			"network": {
+				"name": "lab|1",
+				"private": true,
+				"v6AssignMode": { "rfc4193": true, "6plane": false, "zt": false },
+				"routes": [
+					{ "target": "192.168.1.0/24", "via": "10.0.0.5" },
+					{ "target": "10.0.0.0/24", "via": null }
+				],
+				"ipAssignmentPools": [],
+				"dns": { "domain": "lab.zt", "servers": ["10.0.0.53"] }
+			},
+			"members": [
+				{ "id": "bb", "name": "nas", "authorized": false, "ipAssignments": [], "peers": {} },
+				{ "id": "aa", "name": "web", "authorized": true, "ipAssignments": ["10.0.0.1", "10.0.0.2"],
+				  "peers": { "latency": 4, "paths": [{ "address": "192.0.2.1/9993", "active": true }] } }
+			]
+		});
+		let summary = summarize("8056c2e21c000001", &details).unwrap();
+		assert_eq!(summary["members"][0]["link"], "direct");
+		assert_eq!(summary["network"]["ipv6"], "rfc4193");
+
+		let md = markdown(&summary);
+		assert!(md.starts_with("<a id=\"network-8056c2e21c000001\"></a>\n\n# Network lab\\|1 (`8056c2e21c000001`)"));
+		assert!(md.contains("| Private | yes |"));
+		assert!(md.contains("| 10.0.0.0/24 |  |\n| 192.168.1.0/24 | 10.0.0.5 |"));
+		assert!(md.contains("<a id=\"network-8056c2e21c000001-ip-pools\"></a>\n\n## IP pools\n\n_None._"));
+		assert!(md.contains("| lab.zt | 10.0.0.53 |"));
+		assert!(md.contains("## Members (2 total, 1 authorized)"));
+		assert!(md.contains("| aa | web | yes | 10.0.0.1, 10.0.0.2 |\n| bb | nas | no |  |"));
+		assert!(!md.contains("direct"));
+	}
+}
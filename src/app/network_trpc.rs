@@ -7,11 +7,13 @@ use crate::cli::{
 };
 use crate::context::EffectiveConfig;
 use crate::error::CliError;
-use crate::http::ClientUi;
+use crate::http::HttpClient;
 use crate::output;
 
-use super::common::confirm;
-use super::trpc_client::{require_cookie_from_effective, TrpcClient};
+use super::common::{check_input, confirm, read_input_file};
+use super::middleware::trpc_authed;
+use super::resolve::{network_or_context, resolve_network_id, resolve_org_id};
+use super::trpc_client::{cookie_from_effective, TrpcClient};
 use super::trpc_resolve::{resolve_network_org_id, resolve_personal_network_id};
 
 pub(super) async fn delete(
@@ -54,13 +56,24 @@ pub(super) async fn delete(
 	Ok(())
 }
 
+const DEFAULT_ROUTE_V4: &str = "0.0.0.0/0";
+
 pub(super) async fn routes(
 	global: &GlobalOpts,
 	effective: &EffectiveConfig,
+	client: &HttpClient,
 	args: NetworkRoutesArgs,
 ) -> Result<(), CliError> {
+	let network = network_or_context(effective, args.network.as_deref())?;
+	if matches!(args.command, NetworkRoutesCommand::List) && rest_fallback_available(effective) {
+		let details = rest_network_details(client, effective, &network, args.org.as_deref()).await?;
+		let routes = extract_network_routes(&details)?;
+		output::print_value(&Value::Array(routes), effective.output, global.no_color)?;
+		return Ok(());
+	}
+
 	let trpc = trpc_authed(global, effective)?;
-	let network_id = resolve_personal_network_id(&trpc, &args.network).await?;
+	let network_id = resolve_personal_network_id(&trpc, &network).await?;
 	let details = get_network_details(&trpc, &network_id).await?;
 	let org_id = resolve_network_org_id(&trpc, effective, args.org.as_deref(), &details).await?;
 
@@ -72,25 +85,49 @@ pub(super) async fn routes(
 			Ok(())
 		}
 		NetworkRoutesCommand::Add(add) => {
-			let destination = add.destination.trim().to_string();
-			if destination.is_empty() {
-				return Err(CliError::InvalidArgument(
-					"--destination cannot be empty".to_string(),
-				));
+			let destination = if add.replace_default {
+				DEFAULT_ROUTE_V4.to_string()
+			} else {
+				add.destination.as_deref().unwrap_or_default().trim().to_string()
+			};
+			check_input(global, validate_cidr(&destination, "--destination"))?;
+
+			let is_default = parse_cidr(&destination).is_some_and(|(_, prefix)| prefix == 0);
+			if is_default {
+				if !add.allow_default && !effective.allow_default_route {
+					return Err(CliError::InvalidArgument(format!(
+						"'{destination}' is a default route: every member that accepts it sends all \
+						 its traffic through this network (full tunnel). Pass --allow-default, or set \
+						 profiles.{}.allow_default_route = true",
+						effective.profile
+					)));
+				}
+				if !global.quiet {
+					eprintln!(
+						"warning: default route {destination} makes this network a full tunnel for \
+						 members that enable allowDefault; make sure the gateway forwards and NATs traffic"
+					);
+				}
 			}
 
-			if routes.iter().any(|r| {
-				r.get("target").and_then(|v| v.as_str()) == Some(destination.as_str())
-			}) {
-				return Err(CliError::InvalidArgument(format!(
-					"route '{destination}' already exists"
-				)));
+			if add.replace_default {
+				let before = routes.len();
+				routes.retain(|r| route_target(r) != Some(DEFAULT_ROUTE_V4));
+				if routes.len() != before && !global.quiet {
+					eprintln!("Replacing existing default route.");
+				}
 			}
 
+			check_input(global, check_route_conflict(&routes, &destination))?;
+
 			let via = match add.via.as_deref().map(str::trim) {
 				Some("") | None => Value::Null,
 				Some("lan") => Value::Null,
-				Some(v) => Value::String(v.to_string()),
+				Some(v) => {
+					check_input(global, validate_ip(v, "--via"))?;
+					check_input(global, check_via_in_managed_ranges(&routes, v))?;
+					Value::String(v.to_string())
+				}
 			};
 
 			routes.push(json!({ "target": destination, "via": via }));
@@ -128,10 +165,19 @@ pub(super) async fn routes(
 pub(super) async fn ip_pool(
 	global: &GlobalOpts,
 	effective: &EffectiveConfig,
+	client: &HttpClient,
 	args: NetworkIpPoolArgs,
 ) -> Result<(), CliError> {
+	let network = network_or_context(effective, args.network.as_deref())?;
+	if matches!(args.command, NetworkIpPoolCommand::List) && rest_fallback_available(effective) {
+		let details = rest_network_details(client, effective, &network, args.org.as_deref()).await?;
+		let pools = extract_ip_pools(&details)?;
+		output::print_value(&Value::Array(pools), effective.output, global.no_color)?;
+		return Ok(());
+	}
+
 	let trpc = trpc_authed(global, effective)?;
-	let network_id = resolve_personal_network_id(&trpc, &args.network).await?;
+	let network_id = resolve_personal_network_id(&trpc, &network).await?;
 	let details = get_network_details(&trpc, &network_id).await?;
 	let org_id = resolve_network_org_id(&trpc, effective, args.org.as_deref(), &details).await?;
 
@@ -148,6 +194,7 @@ pub(super) async fn ip_pool(
 		}
 		NetworkIpPoolCommand::Add(change) => {
 			let (start, end) = pool_range(&change)?;
+			check_input(global, validate_pool_range(&start, &end))?;
 			let mut pools = extract_ip_pools(&details)?;
 
 			if pools.iter().any(|p| pool_matches(p, &start, &end)) {
@@ -176,6 +223,56 @@ pub(super) async fn ip_pool(
 				return Err(CliError::InvalidArgument("pool not found".to_string()));
 			}
 
+			let response = trpc
+				.call(
+					"network.advancedIpAssignment",
+					advanced_ip_assignment_input(network_id, org_id, pools),
+				)
+				.await?;
+
+			output::print_value(&response, effective.output, global.no_color)?;
+			Ok(())
+		}
+		NetworkIpPoolCommand::Set(set) => {
+			let ranges = pool_set_ranges(global, &set)?;
+			let current: Vec<(String, String)> = extract_ip_pools(&details)?
+				.iter()
+				.filter_map(|p| {
+					Some((p["ipRangeStart"].as_str()?.to_string(), p["ipRangeEnd"].as_str()?.to_string()))
+				})
+				.collect();
+			let outside = addresses_outside(&details, &current, &ranges);
+			let pools: Vec<Value> = ranges
+				.iter()
+				.map(|(start, end)| json!({ "ipRangeStart": start, "ipRangeEnd": end }))
+				.collect();
+
+			if set.simulate {
+				if matches!(effective.output, OutputFormat::Table) {
+					if !global.quiet {
+						match outside.len() {
+							0 => eprintln!("Every member address stays inside the new pools."),
+							n => eprintln!("{n} member address(es) would fall outside the new pools:"),
+						}
+					}
+					return output::print_columns(&outside, &["member", "name", "ip"]);
+				}
+				let report = json!({ "pools": pools, "outside": outside });
+				return output::print_value(&report, effective.output, global.no_color);
+			}
+
+			if !outside.is_empty() {
+				if !global.quiet {
+					eprintln!("{} member address(es) would fall outside the new pools:", outside.len());
+					for row in &outside {
+						eprintln!("  {}  {}", row["member"].as_str().unwrap_or("-"), row["ip"].as_str().unwrap_or("-"));
+					}
+				}
+				if !global.dry_run && !confirm(global, "Replace the pools anyway? ")? {
+					return Ok(());
+				}
+			}
+
 			let response = trpc
 				.call(
 					"network.advancedIpAssignment",
@@ -189,13 +286,74 @@ pub(super) async fn ip_pool(
 	}
 }
 
+/// The `(start, end)` ranges of `ip-pool set`, in flag order: `--cidr` pools first, then
+/// `--start/--end` pairs. Duplicates are dropped.
+fn pool_set_ranges(
+	global: &GlobalOpts,
+	args: &crate::cli::NetworkIpPoolSetArgs,
+) -> Result<Vec<(String, String)>, CliError> {
+	let mut ranges = Vec::new();
+	for cidr in &args.cidr {
+		ranges.push(cidr_to_range(cidr)?);
+	}
+	if args.start.len() != args.end.len() {
+		return Err(CliError::InvalidArgument(format!(
+			"got {} --start and {} --end values; give one --end for every --start",
+			args.start.len(),
+			args.end.len()
+		)));
+	}
+	for (start, end) in args.start.iter().zip(&args.end) {
+		let (start, end) = (start.trim().to_string(), end.trim().to_string());
+		check_input(global, validate_pool_range(&start, &end))?;
+		ranges.push((start, end));
+	}
+	let mut seen = std::collections::HashSet::new();
+	ranges.retain(|range| seen.insert(range.clone()));
+	Ok(ranges)
+}
+
+/// Member addresses that a current pool covers but no range in `ranges` does, as
+/// `{member, name, ip}`. Addresses no pool covers today (IPv6, manual assignments) are
+/// unaffected by the change and not reported.
+fn addresses_outside(details: &Value, current: &[(String, String)], ranges: &[(String, String)]) -> Vec<Value> {
+	use std::net::IpAddr;
+	let parse = |ranges: &[(String, String)]| -> Vec<(IpAddr, IpAddr)> {
+		ranges
+			.iter()
+			.filter_map(|(start, end)| Some((start.parse().ok()?, end.parse().ok()?)))
+			.collect()
+	};
+	let covers = |ranges: &[(IpAddr, IpAddr)], ip: IpAddr| {
+		ranges
+			.iter()
+			.any(|(start, end)| start.is_ipv4() == ip.is_ipv4() && *start <= ip && ip <= *end)
+	};
+	let (current, ranges) = (parse(current), parse(ranges));
+
+	let mut outside = Vec::new();
+	for member in details["members"].as_array().into_iter().flatten() {
+		for ip in member["ipAssignments"].as_array().into_iter().flatten().filter_map(|v| v.as_str()) {
+			if ip.parse().is_ok_and(|ip| covers(&current, ip) && !covers(&ranges, ip)) {
+				outside.push(json!({
+					"member": member["id"],
+					"name": member["name"],
+					"ip": ip,
+				}));
+			}
+		}
+	}
+	outside
+}
+
 pub(super) async fn dns(
 	global: &GlobalOpts,
 	effective: &EffectiveConfig,
 	args: NetworkDnsArgs,
 ) -> Result<(), CliError> {
+	let network = network_or_context(effective, args.network.as_deref())?;
 	let trpc = trpc_authed(global, effective)?;
-	let network_id = resolve_personal_network_id(&trpc, &args.network).await?;
+	let network_id = resolve_personal_network_id(&trpc, &network).await?;
 	let details = get_network_details(&trpc, &network_id).await?;
 	let org_id = resolve_network_org_id(&trpc, effective, args.org.as_deref(), &details).await?;
 
@@ -210,6 +368,9 @@ pub(super) async fn dns(
 				"dns update requires --servers (comma-separated)".to_string(),
 			));
 		}
+		for server in &args.servers {
+			check_input(global, validate_ip(server, "--servers"))?;
+		}
 		json!({ "dns": { "domain": domain, "servers": args.servers } })
 	};
 
@@ -226,8 +387,9 @@ pub(super) async fn ipv6(
 	effective: &EffectiveConfig,
 	args: NetworkIpv6Args,
 ) -> Result<(), CliError> {
+	let network = network_or_context(effective, args.network.as_deref())?;
 	let trpc = trpc_authed(global, effective)?;
-	let network_id = resolve_personal_network_id(&trpc, &args.network).await?;
+	let network_id = resolve_personal_network_id(&trpc, &network).await?;
 	let details = get_network_details(&trpc, &network_id).await?;
 	let org_id = resolve_network_org_id(&trpc, effective, args.org.as_deref(), &details).await?;
 
@@ -248,27 +410,96 @@ pub(super) async fn ipv6(
 		v6.insert("zt".to_string(), Value::Bool(false));
 	}
 
-	if v6.is_empty() {
+	if v6.is_empty() && !args.show_prefixes {
 		return Err(CliError::InvalidArgument(
 			"no ipv6 options provided (use --6plane/--no-6plane, --rfc4193/--no-rfc4193, --zt/--no-zt)".to_string(),
 		));
 	}
 
-	let response = trpc
-		.call("network.ipv6", ipv6_input(network_id, org_id, v6))
-		.await?;
+	// Current modes with this call's toggles applied on top.
+	let mut modes = details
+		.get("network")
+		.and_then(|n| n.get("v6AssignMode"))
+		.and_then(Value::as_object)
+		.cloned()
+		.unwrap_or_default();
+	modes.extend(v6.clone());
 
-	output::print_value(&response, effective.output, global.no_color)?;
+	let response = if v6.is_empty() {
+		None
+	} else {
+		Some(trpc.call("network.ipv6", ipv6_input(network_id.clone(), org_id, v6)).await?)
+	};
+
+	if !args.show_prefixes {
+		output::print_value(response.as_ref().unwrap_or(&Value::Null), effective.output, global.no_color)?;
+		return Ok(());
+	}
+
+	let prefixes = ipv6_prefixes(&network_id, &modes)?;
+	if matches!(effective.output, OutputFormat::Table) {
+		if let Some(response) = &response {
+			output::print_value(response, effective.output, global.no_color)?;
+		}
+		let lines: Vec<String> = prefixes.iter().map(|(mode, prefix)| format!("{mode:<8} {prefix}")).collect();
+		return output::emit(&lines.join("\n"), true);
+	}
+
+	let value = json!({
+		"network": network_id,
+		"v6AssignMode": modes,
+		"prefixes": prefixes.iter().cloned().collect::<serde_json::Map<_, _>>(),
+		"result": response,
+	});
+	output::print_value(&value, effective.output, global.no_color)?;
 	Ok(())
 }
 
+/// Prefixes ZeroTier derives from a network ID for the enabled IPv6 modes:
+///
+/// - RFC4193: `fd` + the 64-bit network ID + `9993`, a /88 (each member adds its
+///   40-bit node ID).
+/// - 6plane: `fc` + the network ID's two 32-bit halves XORed together, a /40 (each
+///   member owns the /80 formed by appending its node ID).
+fn ipv6_prefixes(
+	network_id: &str,
+	modes: &serde_json::Map<String, Value>,
+) -> Result<Vec<(String, Value)>, CliError> {
+	let nwid = u64::from_str_radix(network_id, 16)
+		.ok()
+		.filter(|_| network_id.len() == 16)
+		.ok_or_else(|| CliError::InvalidArgument(format!("invalid network id: {network_id}")))?;
+	let enabled = |mode: &str| modes.get(mode).and_then(Value::as_bool).unwrap_or(false);
+
+	let mut prefixes = Vec::new();
+	if enabled("rfc4193") {
+		let mut octets = [0u8; 16];
+		octets[0] = 0xfd;
+		octets[1..9].copy_from_slice(&nwid.to_be_bytes());
+		octets[9] = 0x99;
+		octets[10] = 0x93;
+		let prefix = format!("{}/88", std::net::Ipv6Addr::from(octets));
+		prefixes.push(("rfc4193".to_string(), Value::String(prefix)));
+	}
+	if enabled("6plane") {
+		let folded = ((nwid >> 32) as u32) ^ (nwid as u32);
+		let mut octets = [0u8; 16];
+		octets[0] = 0xfc;
+		octets[1..5].copy_from_slice(&folded.to_be_bytes());
+		let prefix = format!("{}/40", std::net::Ipv6Addr::from(octets));
+		prefixes.push(("6plane".to_string(), Value::String(prefix)));
+	}
+	Ok(prefixes)
+}
+
 pub(super) async fn multicast(
 	global: &GlobalOpts,
 	effective: &EffectiveConfig,
 	args: NetworkMulticastArgs,
 ) -> Result<(), CliError> {
+	let network = network_or_context(effective, args.network.as_deref())?;
 	let trpc = trpc_authed(global, effective)?;
-	let network_id = resolve_personal_network_id(&trpc, &args.network).await?;
+	let network_id = resolve_personal_network_id(&trpc, &network).await?;
 	let details = get_network_details(&trpc, &network_id).await?;
 	let org_id = resolve_network_org_id(&trpc, effective, args.org.as_deref(), &details).await?;
 
@@ -301,8 +532,9 @@ pub(super) async fn flow_rules(
 	effective: &EffectiveConfig,
 	args: NetworkFlowRulesArgs,
 ) -> Result<(), CliError> {
+	let network = network_or_context(effective, args.network.as_deref())?;
 	let trpc = trpc_authed(global, effective)?;
-	let network_id = resolve_personal_network_id(&trpc, &args.network).await?;
+	let network_id = resolve_personal_network_id(&trpc, &network).await?;
 
 	match args.command {
 		NetworkFlowRulesCommand::Get(get) => {
@@ -313,30 +545,150 @@ pub(super) async fn flow_rules(
 				)
 				.await?;
 
-			output::print_value(&response, effective.output, global.no_color)?;
+			if !get.compiled {
+				output::print_value(&response, effective.output, global.no_color)?;
+				return Ok(());
+			}
+
+			let details = get_network_details(&trpc, &network_id).await?;
+			let network = details.get("network").unwrap_or(&Value::Null);
+			let value = json!({
+				"source": flow_rule_source(&response),
+				"rules": network.get("rules").cloned().unwrap_or(Value::Null),
+				"capabilities": network.get("capabilities").cloned().unwrap_or(Value::Null),
+				"tags": network.get("tags").cloned().unwrap_or(Value::Null),
+			});
+			output::print_value(&value, effective.output, global.no_color)?;
 			Ok(())
 		}
+		NetworkFlowRulesCommand::Diff(diff) => {
+			let local = read_input_file(&diff.file)?;
+			let response = trpc
+				.query(
+					"network.getFlowRule",
+					json!({ "nwid": network_id, "central": false, "reset": false }),
+				)
+				.await?;
+			let live = flow_rule_source(&response).ok_or_else(|| {
+				CliError::InvalidArgument("server returned no flow rules source".to_string())
+			})?;
+
+			let lines = line_diff(&normalize_rules(live), &normalize_rules(&local));
+			let drift = lines.iter().any(|l| !l.starts_with(' '));
+
+			if matches!(effective.output, crate::cli::OutputFormat::Table) {
+				if drift {
+					let header = format!("--- live ({network_id})\n+++ {}", diff.file.display());
+					output::emit(&format!("{header}\n{}", lines.join("\n")), true)?;
+				} else if !global.quiet {
+					eprintln!("Flow rules match {}.", diff.file.display());
+				}
+			} else {
+				let value = json!({
+					"network": network_id,
+					"file": diff.file.display().to_string(),
+					"drift": drift,
+					"diff": lines,
+				});
+				output::print_value(&value, effective.output, global.no_color)?;
+			}
+
+			if drift {
+				return Err(CliError::Drift(format!(
+					"live flow rules differ from {}",
+					diff.file.display()
+				)));
+			}
+			Ok(())
+		}
+	}
+}
+
+fn flow_rule_source(response: &Value) -> Option<&str> {
+	response
+		.as_str()
+		.or_else(|| response.get("flowRule").and_then(|v| v.as_str()))
+}
+
+// Ignores line-ending and trailing-whitespace differences so CRLF checkouts don't count as drift.
+fn normalize_rules(source: &str) -> Vec<String> {
+	let mut lines: Vec<String> = source.lines().map(|l| l.trim_end().to_string()).collect();
+	while lines.last().is_some_and(|l| l.is_empty()) {
+		lines.pop();
+	}
+	lines
+}
+
+/// Line diff (LCS) with `' '`, `'-'` (live only) and `'+'` (file only) prefixes.
+fn line_diff(live: &[String], file: &[String]) -> Vec<String> {
+	let (n, m) = (live.len(), file.len());
+	let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+	for i in (0..n).rev() {
+		for j in (0..m).rev() {
+			lcs[i][j] = if live[i] == file[j] {
+				lcs[i + 1][j + 1] + 1
+			} else {
+				lcs[i + 1][j].max(lcs[i][j + 1])
+			};
+		}
 	}
+
+	let mut out = Vec::new();
+	let (mut i, mut j) = (0, 0);
+	while i < n && j < m {
+		if live[i] == file[j] {
+			out.push(format!(" {}", live[i]));
+			i += 1;
+			j += 1;
+		} else if lcs[i + 1][j] >= lcs[i][j + 1] {
+			out.push(format!("-{}", live[i]));
+			i += 1;
+		} else {
+			out.push(format!("+{}", file[j]));
+			j += 1;
+		}
+	}
+	out.extend(live[i..].iter().map(|l| format!("-{l}")));
+	out.extend(file[j..].iter().map(|l| format!("+{l}")));
+	out
 }
 
-fn trpc_authed(global: &GlobalOpts, effective: &EffectiveConfig) -> Result<TrpcClient, CliError> {
-	let cookie = require_cookie_from_effective(effective)?;
-	Ok(TrpcClient::new(
-		&effective.host,
-		effective.timeout,
-		effective.retries,
-		global.dry_run,
-		ClientUi::from_context(global, effective),
-	)?
-	.with_cookie(Some(cookie)))
+// Read-only subcommands can be served by the REST API when only a token is configured.
+fn rest_fallback_available(effective: &EffectiveConfig) -> bool {
+	cookie_from_effective(effective).is_none() && effective.token.is_some()
 }
 
-async fn get_network_details(trpc: &TrpcClient, nwid: &str) -> Result<Value, CliError> {
+// Wraps the REST network object like `network.getNetworkById` so the extract_* helpers work on both.
+async fn rest_network_details(
+	client: &HttpClient,
+	effective: &EffectiveConfig,
+	network: &str,
+	org: Option<&str>,
+) -> Result<Value, CliError> {
+	let org = org.map(str::to_string).or(effective.org.clone());
+	let org_id = match org {
+		Some(ref org) => Some(resolve_org_id(client, org).await?),
+		None => None,
+	};
+
+	let network_id = resolve_network_id(client, org_id.as_deref(), network).await?;
+	let path = match org_id.as_deref() {
+		Some(org_id) => format!("/api/v1/org/{org_id}/network/{network_id}"),
+		None => format!("/api/v1/network/{network_id}"),
+	};
+
+	let network = client
+		.request_json(reqwest::Method::GET, &path, None, Default::default(), true)
+		.await?;
+	Ok(json!({ "network": network }))
+}
+
+pub(super) async fn get_network_details(trpc: &TrpcClient, nwid: &str) -> Result<Value, CliError> {
 	trpc.query("network.getNetworkById", json!({ "nwid": nwid, "central": false }))
 		.await
 }
 
-fn extract_network_routes(details: &Value) -> Result<Vec<Value>, CliError> {
+pub(super) fn extract_network_routes(details: &Value) -> Result<Vec<Value>, CliError> {
 	let routes = details
 		.get("network")
 		.and_then(|n| n.get("routes"))
@@ -356,7 +708,7 @@ fn extract_network_routes(details: &Value) -> Result<Vec<Value>, CliError> {
 	Ok(normalized)
 }
 
-fn extract_ip_pools(details: &Value) -> Result<Vec<Value>, CliError> {
+pub(super) fn extract_ip_pools(details: &Value) -> Result<Vec<Value>, CliError> {
 	let pools = details
 		.get("network")
 		.and_then(|n| n.get("ipAssignmentPools"))
@@ -383,7 +735,7 @@ fn pool_matches(pool: &Value, start: &str, end: &str) -> bool {
 
 fn pool_range(args: &crate::cli::NetworkIpPoolChangeArgs) -> Result<(String, String), CliError> {
 	if let Some(cidr) = args.cidr.as_deref() {
-		return cidr_to_ipv4_range(cidr);
+		return cidr_to_range(cidr);
 	}
 
 	let start = args
@@ -397,34 +749,167 @@ fn pool_range(args: &crate::cli::NetworkIpPoolChangeArgs) -> Result<(String, Str
 		.ok_or_else(|| CliError::InvalidArgument("missing --end".to_string()))?
 		.trim();
 
-	if start.is_empty() || end.is_empty() {
+	Ok((start.to_string(), end.to_string()))
+}
+
+fn validate_ip(value: &str, flag: &str) -> Result<(), CliError> {
+	value
+		.trim()
+		.parse::<std::net::IpAddr>()
+		.map(|_| ())
+		.map_err(|_| CliError::InvalidArgument(format!("invalid {flag} address: {value}")))
+}
+
+fn validate_cidr(value: &str, flag: &str) -> Result<(), CliError> {
+	parse_cidr(value)
+		.map(|_| ())
+		.ok_or_else(|| CliError::InvalidArgument(format!("invalid {flag} CIDR: {value}")))
+}
+
+fn parse_cidr(value: &str) -> Option<(std::net::IpAddr, u8)> {
+	let (ip, prefix) = value.trim().split_once('/')?;
+	let ip = ip.parse::<std::net::IpAddr>().ok()?;
+	let prefix = prefix.parse::<u8>().ok()?;
+	let max = if ip.is_ipv4() { 32 } else { 128 };
+	(prefix <= max).then_some((ip, prefix))
+}
+
+/// Whether `ip` falls inside `net/prefix`; addresses of different families never match.
+fn cidr_contains(net: std::net::IpAddr, prefix: u8, ip: std::net::IpAddr) -> bool {
+	use std::net::IpAddr;
+	let (net, ip, bits) = match (net, ip) {
+		(IpAddr::V4(n), IpAddr::V4(i)) => (u32::from(n) as u128, u32::from(i) as u128, 32),
+		(IpAddr::V6(n), IpAddr::V6(i)) => (u128::from(n), u128::from(i), 128),
+		_ => return false,
+	};
+	let shift = bits - u32::from(prefix);
+	if shift >= bits {
+		return true;
+	}
+	(net >> shift) == (ip >> shift)
+}
+
+fn route_target(route: &Value) -> Option<&str> {
+	route.get("target").and_then(|v| v.as_str())
+}
+
+fn describe_route(route: &Value) -> String {
+	let target = route_target(route).unwrap_or("-");
+	match route.get("via").and_then(|v| v.as_str()) {
+		Some(via) => format!("{target} via {via}"),
+		None => format!("{target} (lan)"),
+	}
+}
+
+/// Rejects a destination that duplicates or overlaps an existing route. Default routes only
+/// conflict with another default route, since more specific routes are meant to win over them.
+fn check_route_conflict(routes: &[Value], destination: &str) -> Result<(), CliError> {
+	let Some((ip, prefix)) = parse_cidr(destination) else {
+		return Ok(());
+	};
+	for route in routes {
+		let Some((other_ip, other_prefix)) = route_target(route).and_then(parse_cidr) else {
+			continue;
+		};
+		if ip.is_ipv4() != other_ip.is_ipv4() {
+			continue;
+		}
+		if (prefix == 0) != (other_prefix == 0) {
+			continue;
+		}
+		let overlaps = if prefix <= other_prefix {
+			cidr_contains(ip, prefix, other_ip)
+		} else {
+			cidr_contains(other_ip, other_prefix, ip)
+		};
+		if overlaps {
+			let kind = if prefix == other_prefix && cidr_contains(ip, prefix, other_ip) {
+				"duplicates"
+			} else {
+				"overlaps"
+			};
+			return Err(CliError::InvalidArgument(format!(
+				"route '{destination}' {kind} existing route {}",
+				describe_route(route)
+			)));
+		}
+	}
+	Ok(())
+}
+
+/// A gateway must be reachable on the network itself, i.e. inside one of its LAN routes.
+fn check_via_in_managed_ranges(routes: &[Value], via: &str) -> Result<(), CliError> {
+	let Ok(via_ip) = via.trim().parse::<std::net::IpAddr>() else {
+		return Ok(());
+	};
+	let managed: Vec<&str> = routes
+		.iter()
+		.filter(|r| r.get("via").is_none_or(Value::is_null))
+		.filter_map(route_target)
+		.collect();
+	if managed.is_empty() {
+		return Ok(());
+	}
+	let inside = managed.iter().filter_map(|t| parse_cidr(t)).any(|(net, prefix)| {
+		prefix != 0 && cidr_contains(net, prefix, via_ip)
+	});
+	if inside {
+		return Ok(());
+	}
+	Err(CliError::InvalidArgument(format!(
+		"--via {via} is not inside the network's managed ranges ({})",
+		managed.join(", ")
+	)))
+}
+
+fn validate_pool_range(start: &str, end: &str) -> Result<(), CliError> {
+	let parse = |value: &str| {
+		value.parse::<std::net::IpAddr>().map_err(|_| {
+			CliError::InvalidArgument(format!("invalid pool address: {value}"))
+		})
+	};
+	let (start_ip, end_ip) = (parse(start)?, parse(end)?);
+	if start_ip.is_ipv4() != end_ip.is_ipv4() {
 		return Err(CliError::InvalidArgument(
-			"--start/--end cannot be empty".to_string(),
+			"pool start and end must be the same address family".to_string(),
 		));
 	}
-
-	Ok((start.to_string(), end.to_string()))
+	if start_ip > end_ip {
+		return Err(CliError::InvalidArgument(format!(
+			"pool start {start} is after end {end}"
+		)));
+	}
+	Ok(())
 }
 
-fn cidr_to_ipv4_range(cidr: &str) -> Result<(String, String), CliError> {
+/// The pool range a `--cidr` covers. IPv4 pools leave out the network and broadcast
+/// addresses (except for /31 and /32); IPv6 pools span the whole prefix.
+fn cidr_to_range(cidr: &str) -> Result<(String, String), CliError> {
 	let (ip, prefix) = cidr
 		.trim()
 		.split_once('/')
-		.ok_or_else(|| CliError::InvalidArgument("invalid CIDR".to_string()))?;
+		.ok_or_else(|| CliError::InvalidArgument(format!("invalid --cidr '{cidr}' (expected ADDRESS/PREFIX)")))?;
 
-	let ip = ip.trim().parse::<std::net::Ipv4Addr>().map_err(|_| {
-		CliError::InvalidArgument("CIDR must be a valid IPv4 address".to_string())
+	let ip = ip.trim().parse::<std::net::IpAddr>().map_err(|_| {
+		CliError::InvalidArgument(format!("invalid --cidr '{cidr}': not an IP address"))
 	})?;
 
-	let prefix = prefix.trim().parse::<u32>().map_err(|_| {
-		CliError::InvalidArgument("CIDR prefix must be a number".to_string())
+	let max = if ip.is_ipv4() { 32 } else { 128 };
+	let prefix = prefix.trim().parse::<u32>().ok().filter(|p| *p <= max).ok_or_else(|| {
+		CliError::InvalidArgument(format!("invalid --cidr '{cidr}': prefix must be 0-{max}"))
 	})?;
-	if prefix > 32 {
-		return Err(CliError::InvalidArgument(
-			"CIDR prefix must be <= 32".to_string(),
-		));
-	}
 
+	let ip = match ip {
+		std::net::IpAddr::V4(ip) => ip,
+		std::net::IpAddr::V6(ip) => {
+			let mask = u128::MAX.checked_shl(128 - prefix).unwrap_or(0);
+			let network = u128::from(ip) & mask;
+			return Ok((
+				std::net::Ipv6Addr::from(network).to_string(),
+				std::net::Ipv6Addr::from(network | !mask).to_string(),
+			));
+		}
+	};
 	let ip_u32 = u32::from(ip);
 	let mask = if prefix == 0 { 0 } else { u32::MAX << (32 - prefix) };
 	let network = ip_u32 & mask;
@@ -493,3 +978,118 @@ fn multicast_input(nwid: String, org_id: Option<String>, update_params: serde_js
 	input.insert("updateParams".to_string(), Value::Object(update_params));
 	Value::Object(input)
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn pool_set_reports_pooled_addresses_the_new_ranges_drop() {
+		let details = json!({ "members": [
+			{ "id": "aa", "name": "web", "ipAssignments": ["10.0.0.5", "10.0.1.9"] },
+			{ "id": "bb", "name": "db", "ipAssignments": ["10.0.2.1", "fd00::1", "192.168.1.4", "junk"] },
+		] });
+		let current = [("10.0.0.1".to_string(), "10.0.2.254".to_string())];
+		let ranges = [
+			("10.0.0.1".to_string(), "10.0.0.254".to_string()),
+			("10.0.2.1".to_string(), "10.0.2.1".to_string()),
+		];
+		// fd00::1 and the manual 192.168.1.4 sit outside every pool already, so they stay quiet.
+		let outside = addresses_outside(&details, &current, &ranges);
+		assert_eq!(outside, vec![json!({ "member": "aa", "name": "web", "ip": "10.0.1.9" })]);
+		assert!(addresses_outside(&details, &[], &ranges).is_empty());
+	}
+
+	#[test]
+	fn validate_cidr_accepts_v4_and_v6() {
+		assert!(validate_cidr("10.0.0.0/24", "--destination").is_ok());
+		assert!(validate_cidr("fd00::/64", "--destination").is_ok());
+		assert!(validate_cidr("10.0.0.0/33", "--destination").is_err());
+		assert!(validate_cidr("10.0.0.0", "--destination").is_err());
+	}
+
+	#[test]
+	fn route_conflicts_and_gateway_ranges() {
+		let routes = vec![
+			json!({ "target": "10.147.17.0/24", "via": null }),
+			json!({ "target": "192.168.1.0/24", "via": "10.147.17.5" }),
+		];
+
+		let err = check_route_conflict(&routes, "192.168.1.0/24").unwrap_err();
+		assert!(err.to_string().contains("duplicates existing route 192.168.1.0/24 via 10.147.17.5"));
+		let err = check_route_conflict(&routes, "192.168.0.0/16").unwrap_err();
+		assert!(err.to_string().contains("overlaps"));
+		assert!(check_route_conflict(&routes, "192.168.2.0/24").is_ok());
+		assert!(check_route_conflict(&routes, "0.0.0.0/0").is_ok());
+		assert!(check_route_conflict(&[json!({ "target": "0.0.0.0/0", "via": "10.147.17.1" })], "0.0.0.0/0").is_err());
+
+		assert!(check_via_in_managed_ranges(&routes, "10.147.17.1").is_ok());
+		let err = check_via_in_managed_ranges(&routes, "10.0.0.1").unwrap_err();
+		assert!(err.to_string().contains("10.147.17.0/24"));
+	}
+
+	#[test]
+	fn line_diff_marks_changed_lines() {
+		let live = normalize_rules("drop not ethertype ipv4;\r\naccept;\n\n");
+		let file = normalize_rules("drop not ethertype ipv4;\nbreak;\naccept;\n");
+		assert_eq!(
+			line_diff(&live, &file),
+			vec![" drop not ethertype ipv4;", "+break;", " accept;"]
+		);
+		assert!(line_diff(&live, &live).iter().all(|l| l.starts_with(' ')));
+	}
+
+	#[test]
+	fn validate_pool_range_rejects_reversed_or_mixed_ranges() {
+		assert!(validate_pool_range("10.0.0.10", "10.0.0.20").is_ok());
+		assert!(validate_pool_range("10.0.0.20", "10.0.0.10").is_err());
+		assert!(validate_pool_range("10.0.0.1", "fd00::1").is_err());
+		assert!(validate_pool_range("", "10.0.0.1").is_err());
+	}
+
+	#[test]
+	fn cidr_pools_cover_v4_hosts_and_whole_v6_prefixes() {
+		let range = |cidr: &str| cidr_to_range(cidr).unwrap();
+		assert_eq!(range("10.0.0.0/24"), ("10.0.0.1".to_string(), "10.0.0.254".to_string()));
+		assert_eq!(range("10.0.0.7/32"), ("10.0.0.7".to_string(), "10.0.0.7".to_string()));
+		assert_eq!(range("fd00:1::5/64"), ("fd00:1::".to_string(), "fd00:1::ffff:ffff:ffff:ffff".to_string()));
+		assert!(cidr_to_range("10.0.0.0/33").is_err());
+		assert!(cidr_to_range("fd00::/129").is_err());
+	}
+
+	#[test]
+	fn force_send_turns_every_input_check_into_a_warning() {
+		use clap::Parser;
+		let global = |force: &str| crate::cli::Cli::parse_from(["ztnet", "--quiet", force, "version"]).global;
+		let (strict, forced) = (global("--yes"), global("--force-send"));
+		let routes = [json!({ "target": "10.0.0.0/24", "via": null })];
+		let checks = || {
+			[
+				validate_cidr("", "--destination"),
+				validate_ip("10.0.0.300", "--via"),
+				validate_pool_range("10.0.0.20", "10.0.0.10"),
+				check_route_conflict(&routes, "10.0.0.0/25"),
+				check_via_in_managed_ranges(&routes, "192.168.1.1"),
+			]
+		};
+		assert!(checks().into_iter().all(|check| check_input(&strict, check).is_err()));
+		assert!(checks().into_iter().all(|check| check_input(&forced, check).is_ok()));
+	}
+
+	#[test]
+	fn ipv6_prefixes_follow_zerotier_derivation() {
+		let modes = json!({ "6plane": true, "rfc4193": true, "zt": false });
+		let prefixes = ipv6_prefixes("8056c2e21c000001", modes.as_object().unwrap()).unwrap();
+		assert_eq!(
+			prefixes,
+			vec![
+				("rfc4193".to_string(), json!("fd80:56c2:e21c:0:199:9300::/88")),
+				("6plane".to_string(), json!("fc9c:56c2:e300::/40")),
+			]
+		);
+
+		let none = json!({ "6plane": false });
+		assert!(ipv6_prefixes("8056c2e21c000001", none.as_object().unwrap()).unwrap().is_empty());
+		assert!(ipv6_prefixes("xyz", none.as_object().unwrap()).is_err());
+	}
+}
@@ -1,29 +1,27 @@
+use std::collections::HashSet;
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use reqwest::Method;
 use serde_json::Value;
 
+use crate::cancel;
 use crate::cli::{GlobalOpts, OrgCommand, OrgRole, OutputFormat};
-use crate::context::resolve_effective_config;
 use crate::error::CliError;
-use crate::http::{ClientUi, HttpClient};
 use crate::output;
 
-use super::common::{load_config_store, print_human_or_machine};
+use super::common::{
+	BulkReport, check_error_policy, check_input, fetch_details, print_human_or_machine, print_with_errors,
+	read_input_file, report_errors_stderr, suggest_similar,
+};
+use super::middleware::{trpc_authed, CommandContext};
 use super::resolve::resolve_org_id;
-use super::trpc_client::{require_cookie_from_effective, TrpcClient};
+use super::retention;
+use super::trpc_client::TrpcClient;
 use super::trpc_resolve::resolve_org_id as resolve_org_id_trpc;
 
-pub(super) async fn run(global: &GlobalOpts, command: OrgCommand) -> Result<(), CliError> {
-	let (_config_path, cfg) = load_config_store()?;
-	let effective = resolve_effective_config(global, &cfg)?;
-
-	let client = HttpClient::new(
-		&effective.host,
-		effective.token.clone(),
-		effective.timeout,
-		effective.retries,
-		global.dry_run,
-		ClientUi::from_context(global, &effective),
-	)?;
+pub(super) async fn run(ctx: &CommandContext<'_>, command: OrgCommand) -> Result<(), CliError> {
+	let (global, effective) = (ctx.global, &ctx.effective);
+	let client = ctx.client()?;
 
 	match command {
 		OrgCommand::List(args) => {
@@ -31,28 +29,23 @@ pub(super) async fn run(global: &GlobalOpts, command: OrgCommand) -> Result<(),
 				.request_json(Method::GET, "/api/v1/org", None, Default::default(), true)
 				.await?;
 
+			let mut detail_errors = Vec::new();
+			let mut detail_total = 0;
 			if args.details {
 				let Some(orgs) = response.as_array() else {
 					return Err(CliError::InvalidArgument("expected array response".to_string()));
 				};
 
-				let mut detailed = Vec::with_capacity(orgs.len());
-				for org in orgs {
-					let Some(id) = org.get("id").and_then(|v| v.as_str()) else {
-						continue;
-					};
-					let detail = client
-						.request_json(
-							Method::GET,
-							&format!("/api/v1/org/{id}"),
-							None,
-							Default::default(),
-							true,
-						)
-						.await?;
-					detailed.push(detail);
-				}
+				let targets: Vec<(String, String)> = orgs
+					.iter()
+					.filter_map(|org| org.get("id").and_then(|v| v.as_str()))
+					.map(|id| (id.to_string(), format!("/api/v1/org/{id}")))
+					.collect();
+				detail_total = targets.len();
+
+				let (detailed, errors) = fetch_details(&client, targets, args.skip_errors, effective.concurrency).await?;
 				response = Value::Array(detailed);
+				detail_errors = errors;
 			}
 
 			if args.ids_only {
@@ -66,15 +59,20 @@ pub(super) async fn run(global: &GlobalOpts, command: OrgCommand) -> Result<(),
 					.unwrap_or_default();
 
 				if matches!(effective.output, OutputFormat::Table) {
-					for id in ids {
-						println!("{id}");
+					if !ids.is_empty() {
+						output::emit(&ids.join("\n"), false)?;
 					}
-					return Ok(());
+				} else {
+					let value = Value::Array(ids.into_iter().map(Value::String).collect());
+					output::print_value(&value, effective.output, global.no_color)?;
 				}
+				report_errors_stderr(&detail_errors);
+				return check_error_policy(args.error_exit, detail_errors.len(), detail_total);
+			}
 
-				let value = Value::Array(ids.into_iter().map(Value::String).collect());
-				output::print_value(&value, effective.output, global.no_color)?;
-				return Ok(());
+			if args.skip_errors {
+				print_with_errors(&response, &detail_errors, effective.output, global.no_color)?;
+				return check_error_policy(args.error_exit, detail_errors.len(), detail_total);
 			}
 
 			output::print_value(&response, effective.output, global.no_color)?;
@@ -106,11 +104,51 @@ pub(super) async fn run(global: &GlobalOpts, command: OrgCommand) -> Result<(),
 						true,
 					)
 					.await?;
-				output::print_value(&response, effective.output, global.no_color)?;
+
+				let Some(users) = response.as_array() else {
+					return Err(CliError::InvalidArgument("expected array response".to_string()));
+				};
+
+				let role = args.role.map(role_to_string);
+				let email = args.email.as_deref().map(str::to_ascii_lowercase);
+				// One flat row per user; the role sits in the user's org membership.
+				let rows: Vec<Value> = users
+					.iter()
+					.map(|user| {
+						let role = user
+							.get("role")
+							.or_else(|| user.pointer("/memberships/0/role"))
+							.cloned()
+							.unwrap_or(Value::Null);
+						serde_json::json!({
+							"id": user.get("userId").or_else(|| user.get("id")),
+							"name": user.get("name"),
+							"email": user.get("email"),
+							"role": role,
+						})
+					})
+					.filter(|row| {
+						role.is_none_or(|role| {
+							row["role"].as_str().is_some_and(|r| r.eq_ignore_ascii_case(role))
+						})
+					})
+					.filter(|row| {
+						email.as_deref().is_none_or(|email| {
+							row["email"]
+								.as_str()
+								.is_some_and(|e| e.to_ascii_lowercase().contains(email))
+						})
+					})
+					.collect();
+
+				if matches!(effective.output, OutputFormat::Table) {
+					return output::print_columns(&rows, &["id", "name", "email", "role"]);
+				}
+				output::print_value(&Value::Array(rows), effective.output, global.no_color)?;
 				Ok(())
 			}
 			crate::cli::OrgUsersCommand::Add(args) => {
-				let trpc = trpc_authed(global, &effective)?;
+				let trpc = trpc_authed(global, effective)?;
 				let org_id = resolve_org_id_trpc(&trpc, &args.org).await?;
 
 				let users = trpc
@@ -177,7 +215,7 @@ pub(super) async fn run(global: &GlobalOpts, command: OrgCommand) -> Result<(),
 				Ok(())
 			}
 			crate::cli::OrgUsersCommand::Role(args) => {
-				let trpc = trpc_authed(global, &effective)?;
+				let trpc = trpc_authed(global, effective)?;
 				let org_id = resolve_org_id_trpc(&trpc, &args.org).await?;
 
 				let user_id = if args.user.contains('@') {
@@ -238,7 +276,7 @@ pub(super) async fn run(global: &GlobalOpts, command: OrgCommand) -> Result<(),
 			}
 		},
 		OrgCommand::Invite { command } => {
-			let trpc = trpc_authed(global, &effective)?;
+			let trpc = trpc_authed(global, effective)?;
 			match command {
 				crate::cli::OrgInviteCommand::Create(args) => {
 					let org_id = resolve_org_id_trpc(&trpc, &args.org).await?;
@@ -292,10 +330,82 @@ pub(super) async fn run(global: &GlobalOpts, command: OrgCommand) -> Result<(),
 					print_human_or_machine(&response, effective.output, global.no_color)?;
 					Ok(())
 				}
+				crate::cli::OrgInviteCommand::SendBulk(args) => {
+					let text = read_input_file(&args.file)?;
+					let rows = parse_invite_csv(&text, args.role)?;
+					if rows.is_empty() {
+						return Err(CliError::InvalidArgument(format!(
+							"{} contains no invitations",
+							args.file.display()
+						)));
+					}
+					let org_id = resolve_org_id_trpc(&trpc, &args.org).await?;
+
+					if global.dry_run {
+						let preview: Vec<Value> = rows
+							.iter()
+							.map(|row| invite_result(row, "would-send", None))
+							.collect();
+						output::print_value(&Value::Array(preview), effective.output, global.no_color)?;
+						return Err(CliError::DryRunPrinted);
+					}
+
+					let total = rows.len();
+					let mut report = BulkReport::new(args.only_errors).with_progress("org invite send-bulk", total);
+					for (i, row) in rows.iter().enumerate() {
+						if i > 0 && !args.delay.is_zero() && cancel::sleep(args.delay).await.is_err() {
+							break;
+						}
+						if report.interrupted() {
+							break;
+						}
+						report.begin(&row.email);
+						let sent = trpc
+							.call(
+								"org.inviteUserByMail",
+								serde_json::json!({
+									"organizationId": org_id,
+									"role": role_to_string(row.role),
+									"email": row.email,
+								}),
+							)
+							.await;
+						let (result, failed) = match sent {
+							Ok(_) => (invite_result(row, "sent", None), false),
+							Err(CliError::Interrupted) => break,
+							Err(err) => (invite_result(row, "failed", Some(err.to_string())), true),
+						};
+						if !global.quiet && !args.only_errors && matches!(effective.output, OutputFormat::Table) {
+							eprintln!(
+								"[{}/{total}] {} {}",
+								i + 1,
+								row.email,
+								result["status"].as_str().unwrap_or("-")
+							);
+						}
+						report.push(result, failed)?;
+					}
+
+					report.finish(global, effective.output)
+				}
+				crate::cli::OrgInviteCommand::Resend(args) => {
+					let org_id = resolve_org_id_trpc(&trpc, &args.org).await?;
+					if let Some(invite) = args.invite.as_deref() {
+						let response = trpc
+							.call(
+								"org.resendInvite",
+								serde_json::json!({ "organizationId": org_id, "invitationId": invitation_id(invite) }),
+							)
+							.await?;
+						print_human_or_machine(&response, effective.output, global.no_color)?;
+						return Ok(());
+					}
+					resend_pending(global, effective.output, &trpc, &org_id, &args).await
+				}
 			}
 		}
 		OrgCommand::Settings { command } => {
-			let trpc = trpc_authed(global, &effective)?;
+			let trpc = trpc_authed(global, effective)?;
 			match command {
 				crate::cli::OrgSettingsCommand::Get(args) => {
 					let org_id = resolve_org_id_trpc(&trpc, &args.org).await?;
@@ -340,9 +450,9 @@ pub(super) async fn run(global: &GlobalOpts, command: OrgCommand) -> Result<(),
 			}
 		}
 		OrgCommand::Webhooks { command } => {
-			let trpc = trpc_authed(global, &effective)?;
 			match command {
 				crate::cli::OrgWebhooksCommand::List(args) => {
+					let trpc = trpc_authed(global, effective)?;
 					let org_id = resolve_org_id_trpc(&trpc, &args.org).await?;
 					let response = trpc
 						.query("org.getOrgWebhooks", serde_json::json!({ "organizationId": org_id }))
@@ -356,7 +466,18 @@ pub(super) async fn run(global: &GlobalOpts, command: OrgCommand) -> Result<(),
 							"webhook add requires at least one --event".to_string(),
 						));
 					}
+					let mut events = Vec::with_capacity(args.event.len());
+					for event in &args.event {
+						match webhook_event(event) {
+							Ok(name) => events.push(name),
+							Err(err) => {
+								check_input(global, Err(err))?;
+								events.push(event.clone());
+							}
+						}
+					}
 
+					let trpc = trpc_authed(global, effective)?;
 					let org_id = resolve_org_id_trpc(&trpc, &args.org).await?;
 					let response = trpc
 						.call(
@@ -365,7 +486,7 @@ pub(super) async fn run(global: &GlobalOpts, command: OrgCommand) -> Result<(),
 								"organizationId": org_id,
 								"webhookUrl": args.url,
 								"webhookName": args.name,
-								"hookType": args.event,
+								"hookType": events,
 							}),
 						)
 						.await?;
@@ -373,6 +494,7 @@ pub(super) async fn run(global: &GlobalOpts, command: OrgCommand) -> Result<(),
 					Ok(())
 				}
 				crate::cli::OrgWebhooksCommand::Delete(args) => {
+					let trpc = trpc_authed(global, effective)?;
 					let org_id = resolve_org_id_trpc(&trpc, &args.org).await?;
 					let response = trpc
 						.call(
@@ -386,10 +508,11 @@ pub(super) async fn run(global: &GlobalOpts, command: OrgCommand) -> Result<(),
 					print_human_or_machine(&response, effective.output, global.no_color)?;
 					Ok(())
 				}
+				crate::cli::OrgWebhooksCommand::Events => print_webhook_events(global, effective.output),
 			}
 		}
 		OrgCommand::Logs(args) => {
-			let trpc = trpc_authed(global, &effective)?;
+			let trpc = trpc_authed(global, effective)?;
 			let org_id = resolve_org_id_trpc(&trpc, &args.org).await?;
 			let response = trpc
 				.query("org.getLogs", serde_json::json!({ "organizationId": org_id }))
@@ -397,9 +520,406 @@ pub(super) async fn run(global: &GlobalOpts, command: OrgCommand) -> Result<(),
 			output::print_value(&response, effective.output, global.no_color)?;
 			Ok(())
 		}
+		OrgCommand::Notifications { command } => {
+			let trpc = trpc_authed(global, effective)?;
+			match command {
+				crate::cli::OrgNotificationsCommand::List => {
+					let response = trpc.query("org.getOrgNotifications", Value::Null).await?;
+					output::print_value(&response, effective.output, global.no_color)?;
+					Ok(())
+				}
+				crate::cli::OrgNotificationsCommand::Watch(args) => {
+					watch_notifications(global, effective, &trpc, args).await
+				}
+			}
+		}
 	}
 }
 
+/// Polls `org.getOrgNotifications` until interrupted and, when the org reports unread
+/// messages, prints the ones not seen in the previous fetch. `seen` only keeps the ids of
+/// the latest feed, so it stays as small as the panel's own message window. Transient
+/// failures are reported and retried on the next tick.
+async fn watch_notifications(
+	global: &GlobalOpts,
+	effective: &crate::context::EffectiveConfig,
+	trpc: &TrpcClient,
+	args: crate::cli::OrgNotificationsWatchArgs,
+) -> Result<(), CliError> {
+	let org_id = resolve_org_id_trpc(trpc, &args.org).await?;
+	let input = serde_json::json!({ "organizationId": org_id });
+
+	let initial = trpc.query("org.getMessages", input.clone()).await?;
+	let initial = message_list(&initial);
+	let mut seen: HashSet<String> = initial.iter().filter_map(message_key).collect();
+	let backlog = &initial[initial.len().saturating_sub(args.tail)..];
+	print_messages(backlog, effective.output)?;
+	if args.ack && !backlog.is_empty() {
+		trpc.call("org.markMessagesAsRead", input.clone()).await?;
+	}
+
+	if !global.quiet {
+		eprintln!(
+			"Watching messages for org {org_id} every {} (Ctrl-C to stop)...",
+			humantime::format_duration(args.interval)
+		);
+	}
+
+	loop {
+		cancel::sleep(args.interval).await?;
+
+		let poll = async {
+			let notifications = trpc.query("org.getOrgNotifications", Value::Null).await?;
+			if !has_unread(&notifications, &org_id) {
+				return Ok(None);
+			}
+			trpc.query("org.getMessages", input.clone()).await.map(Some)
+		};
+		let response = match poll.await {
+			Ok(Some(response)) => response,
+			Ok(None) => continue,
+			Err(err) if is_transient(&err) => {
+				if !global.quiet {
+					eprintln!("warning: poll failed: {err}");
+				}
+				continue;
+			}
+			Err(err) => return Err(err),
+		};
+
+		let (fresh, latest) = split_fresh(message_list(&response), &seen);
+		seen = latest;
+		if fresh.is_empty() {
+			continue;
+		}
+		print_messages(&fresh, effective.output)?;
+		if args.ack {
+			trpc.call("org.markMessagesAsRead", input.clone()).await?;
+		}
+	}
+}
+
+/// `org invite resend --all-pending`: resends unused invitations created at least
+/// `--older-than` ago, one at a time. The client already waits out 429s (honouring
+/// `Retry-After`); when the panel keeps refusing, the rest are reported as `skipped`
+/// rather than hammered one by one.
+async fn resend_pending(
+	global: &GlobalOpts,
+	format: OutputFormat,
+	trpc: &TrpcClient,
+	org_id: &str,
+	args: &crate::cli::OrgInviteResendArgs,
+) -> Result<(), CliError> {
+	let invites = trpc.query("org.getInvites", serde_json::json!({ "organizationId": org_id })).await?;
+	let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or_default();
+	let stale = stale_invites(&invites, now - args.older_than.as_secs() as i64);
+	if stale.is_empty() {
+		if !global.quiet {
+			eprintln!(
+				"No pending invitations older than {}.",
+				humantime::format_duration(args.older_than)
+			);
+		}
+		return Ok(());
+	}
+
+	if global.dry_run {
+		let preview: Vec<Value> = stale.iter().map(|invite| resend_result(invite, "would-resend", None)).collect();
+		output::print_value(&Value::Array(preview), format, global.no_color)?;
+		return Err(CliError::DryRunPrinted);
+	}
+
+	let total = stale.len();
+	let mut report = BulkReport::new(args.only_errors).with_progress("org invite resend", total);
+	let mut rate_limited = false;
+	for (i, invite) in stale.iter().enumerate() {
+		let email = invite["email"].as_str().unwrap_or_default();
+		if rate_limited {
+			report.begin(email);
+			report.push(resend_result(invite, "skipped", Some("rate limited".to_string())), true)?;
+			continue;
+		}
+		if i > 0 && !args.delay.is_zero() && cancel::sleep(args.delay).await.is_err() {
+			break;
+		}
+		if report.interrupted() {
+			break;
+		}
+		report.begin(email);
+		let sent = trpc
+			.call(
+				"org.resendInvite",
+				serde_json::json!({ "organizationId": org_id, "invitationId": invite["id"] }),
+			)
+			.await;
+		let (result, failed) = match sent {
+			Ok(_) => (resend_result(invite, "sent", None), false),
+			Err(CliError::Interrupted) => break,
+			Err(err) => {
+				rate_limited = matches!(err, CliError::RateLimited);
+				(resend_result(invite, "failed", Some(err.to_string())), true)
+			}
+		};
+		if !global.quiet && !args.only_errors && matches!(format, OutputFormat::Table) {
+			eprintln!(
+				"[{}/{total}] {} {}",
+				i + 1,
+				invite["email"].as_str().unwrap_or("-"),
+				result["status"].as_str().unwrap_or("-")
+			);
+		}
+		report.push(result, failed)?;
+	}
+	if rate_limited && !global.quiet {
+		eprintln!("The panel is rate limiting invitation emails; re-run later or raise --delay.");
+	}
+
+	report.finish(global, format)
+}
+
+/// Unused invitations created before `cutoff` (Unix seconds), oldest first, as
+/// `{id, email}`. Entries may wrap the invitation in an `invitation` object;
+/// ones without a creation date are left alone.
+fn stale_invites(invites: &Value, cutoff: i64) -> Vec<Value> {
+	let mut stale: Vec<(i64, Value)> = invites
+		.as_array()
+		.into_iter()
+		.flatten()
+		.filter_map(|entry| {
+			let invite = entry.get("invitation").filter(|v| v.is_object()).unwrap_or(entry);
+			if invite.get("used").and_then(Value::as_bool) == Some(true) {
+				return None;
+			}
+			let created = retention::created_at(invite, "").filter(|created| *created <= cutoff)?;
+			let id = invite.get("id").or_else(|| entry.get("id"))?.clone();
+			Some((created, serde_json::json!({ "id": id, "email": invite.get("email") })))
+		})
+		.collect();
+	stale.sort_by_key(|(created, _)| *created);
+	stale.into_iter().map(|(_, invite)| invite).collect()
+}
+
+fn resend_result(invite: &Value, status: &str, error: Option<String>) -> Value {
+	serde_json::json!({
+		"id": invite["id"],
+		"email": invite["email"],
+		"status": status,
+		"error": error,
+	})
+}
+
+/// Invitation ids are numeric in ZTNet; anything else is passed through as typed.
+fn invitation_id(raw: &str) -> Value {
+	raw.trim().parse::<u64>().map(Value::from).unwrap_or_else(|_| Value::String(raw.to_string()))
+}
+
+fn is_transient(err: &CliError) -> bool {
+	match err {
+		CliError::Request(_) | CliError::Unreachable { .. } | CliError::RateLimited => true,
+		CliError::HttpStatus { status, .. } => status.is_server_error(),
+		_ => false,
+	}
+}
+
+fn message_list(response: &Value) -> Vec<Value> {
+	response
+		.as_array()
+		.or_else(|| response.get("messages").and_then(|v| v.as_array()))
+		.cloned()
+		.unwrap_or_default()
+}
+
+/// Whether a `getOrgNotifications` response flags `org_id` as having unread messages.
+/// Shapes that do not mention the org count as unread so the feed is still checked.
+fn has_unread(notifications: &Value, org_id: &str) -> bool {
+	let entry = match notifications {
+		Value::Array(orgs) => orgs.iter().find(|o| {
+			["id", "orgId", "organizationId"]
+				.iter()
+				.any(|k| o.get(k).and_then(|v| v.as_str()) == Some(org_id))
+		}),
+		Value::Object(map) => map
+			.get(org_id)
+			.or_else(|| map.get("orgUnreadInfo").and_then(|info| info.get(org_id))),
+		_ => None,
+	};
+	let Some(entry) = entry else {
+		return true;
+	};
+	match entry {
+		Value::Bool(unread) => *unread,
+		_ => match (entry.get("hasUnreadMessages"), entry.get("unreadCount")) {
+			(Some(Value::Bool(unread)), _) => *unread,
+			(_, Some(Value::Number(count))) => count.as_u64() != Some(0),
+			_ => true,
+		},
+	}
+}
+
+/// Splits a fetched feed into messages missing from `seen` and the ids of the whole feed,
+/// which replace `seen` for the next poll.
+fn split_fresh(messages: Vec<Value>, seen: &HashSet<String>) -> (Vec<Value>, HashSet<String>) {
+	let latest = messages.iter().filter_map(message_key).collect();
+	let fresh = messages
+		.into_iter()
+		.filter(|m| message_key(m).is_none_or(|key| !seen.contains(&key)))
+		.collect();
+	(fresh, latest)
+}
+
+fn message_key(message: &Value) -> Option<String> {
+	match message.get("id")? {
+		Value::String(id) => Some(id.clone()),
+		Value::Number(id) => Some(id.to_string()),
+		_ => None,
+	}
+}
+
+/// One line per message: `[time] author: text` in table mode, NDJSON otherwise so the
+/// stream can be piped into `jq`.
+fn print_messages(messages: &[Value], format: OutputFormat) -> Result<(), CliError> {
+	use std::io::Write;
+
+	let mut stdout = output::writer();
+	for message in messages {
+		let line = if matches!(format, OutputFormat::Table) {
+			format_message(message)
+		} else {
+			serde_json::to_string(message)?
+		};
+		writeln!(stdout, "{line}")?;
+	}
+	stdout.flush()?;
+	Ok(())
+}
+
+fn format_message(message: &Value) -> String {
+	let time = message
+		.get("createdAt")
+		.and_then(|v| v.as_str())
+		.unwrap_or("-");
+	let user = message.get("user");
+	let author = user
+		.and_then(|u| u.get("name"))
+		.and_then(|v| v.as_str())
+		.filter(|s| !s.is_empty())
+		.or_else(|| user.and_then(|u| u.get("email")).and_then(|v| v.as_str()))
+		.unwrap_or("system");
+	let text = message
+		.get("content")
+		.or_else(|| message.get("message"))
+		.and_then(|v| v.as_str())
+		.unwrap_or("");
+	format!("[{time}] {author}: {text}")
+}
+
+#[derive(Debug)]
+pub(super) struct InviteRow {
+	line: usize,
+	email: String,
+	role: OrgRole,
+}
+
+/// Parses `email[,role]` lines. A first line whose first cell is `email` is a header;
+/// blank lines and `#` comments are skipped. All rows are validated before any is sent.
+pub(super) fn parse_invite_csv(text: &str, default_role: OrgRole) -> Result<Vec<InviteRow>, CliError> {
+	let mut rows = Vec::new();
+	for (idx, raw) in text.lines().enumerate() {
+		let line = idx + 1;
+		let raw = raw.trim();
+		if raw.is_empty() || raw.starts_with('#') {
+			continue;
+		}
+		let cells: Vec<&str> = raw
+			.split(',')
+			.map(|cell| cell.trim().trim_matches('"').trim())
+			.collect();
+		if line == 1 && cells[0].eq_ignore_ascii_case("email") {
+			continue;
+		}
+
+		let email = cells[0];
+		if !email.contains('@') || email.contains(char::is_whitespace) {
+			return Err(CliError::InvalidArgument(format!(
+				"line {line}: invalid email '{email}'"
+			)));
+		}
+		let role = match cells.get(1).copied().filter(|r| !r.is_empty()) {
+			Some(role) => parse_org_role(role).ok_or_else(|| {
+				CliError::InvalidArgument(format!(
+					"line {line}: unknown role '{role}' (expected read-only, user or admin)"
+				))
+			})?,
+			None => default_role,
+		};
+		rows.push(InviteRow {
+			line,
+			email: email.to_string(),
+			role,
+		});
+	}
+	Ok(rows)
+}
+
+fn parse_org_role(raw: &str) -> Option<OrgRole> {
+	match raw.to_ascii_lowercase().replace(['_', ' '], "-").as_str() {
+		"read-only" | "readonly" => Some(OrgRole::ReadOnly),
+		"user" => Some(OrgRole::User),
+		"admin" => Some(OrgRole::Admin),
+		_ => None,
+	}
+}
+
+pub(super) fn invite_result(row: &InviteRow, status: &str, error: Option<String>) -> Value {
+	serde_json::json!({
+		"line": row.line,
+		"email": row.email,
+		"role": role_to_string(row.role),
+		"status": status,
+		"error": error,
+	})
+}
+
+/// Webhook event types (`hookType`) accepted by ZTNet's `org.addOrgWebhooks`.
+const WEBHOOK_EVENTS: &[(&str, &str)] = &[
+	("ORGANIZATION_INVITE", "A user was invited to the organization"),
+	("ORG_MEMBER_REMOVED", "A user was removed from the organization"),
+	("ORG_MEMBER_ROLE_CHANGED", "A user's organization role changed"),
+	("NETWORK_JOIN", "A node asked to join an organization network"),
+	("NETWORK_CREATED", "An organization network was created"),
+	("NETWORK_DELETED", "An organization network was deleted"),
+	("MEMBER_CONFIG_CHANGED", "A network member's configuration changed"),
+	("MEMBER_DELETED", "A network member was deleted"),
+];
+
+fn print_webhook_events(global: &GlobalOpts, format: OutputFormat) -> Result<(), CliError> {
+	let rows: Vec<Value> = WEBHOOK_EVENTS
+		.iter()
+		.map(|(event, description)| serde_json::json!({ "event": event, "description": description }))
+		.collect();
+	if matches!(format, OutputFormat::Table) {
+		return output::print_columns(&rows, &["event", "description"]);
+	}
+	output::print_value(&Value::Array(rows), format, global.no_color)
+}
+
+/// Maps `--event` input to a known event type; case, `-` and `.` are forgiven
+/// (`network-join` is `NETWORK_JOIN`).
+fn webhook_event(raw: &str) -> Result<String, CliError> {
+	let normalized = raw.trim().to_ascii_uppercase().replace(['-', '.'], "_");
+	if let Some((event, _)) = WEBHOOK_EVENTS.iter().find(|(event, _)| *event == normalized) {
+		return Ok(event.to_string());
+	}
+
+	let suggestions = suggest_similar(&normalized, WEBHOOK_EVENTS.iter().map(|(event, _)| (*event, *event)));
+	let mut message = format!("unknown webhook event '{raw}'");
+	if !suggestions.is_empty() {
+		message.push_str(&format!("\n\n  did you mean: {}", suggestions.join(", ")));
+	}
+	message.push_str("\n\n  Run: ztnet org webhooks events");
+	Err(CliError::InvalidArgument(message))
+}
+
 fn role_to_string(role: OrgRole) -> &'static str {
 	match role {
 		OrgRole::ReadOnly => "READ_ONLY",
@@ -408,14 +928,103 @@ fn role_to_string(role: OrgRole) -> &'static str {
 	}
 }
 
-fn trpc_authed(global: &GlobalOpts, effective: &crate::context::EffectiveConfig) -> Result<TrpcClient, CliError> {
-	let cookie = require_cookie_from_effective(effective)?;
-	Ok(TrpcClient::new(
-		&effective.host,
-		effective.timeout,
-		effective.retries,
-		global.dry_run,
-		ClientUi::from_context(global, effective),
-	)?
-	.with_cookie(Some(cookie)))
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+
+	#[test]
+	fn format_message_prefers_name_then_email() {
+		let message = json!({
+			"id": 7,
+			"createdAt": "2024-05-01T10:00:00.000Z",
+			"content": "maintenance tonight",
+			"user": { "name": "", "email": "ops@example.com" }
+		});
+		assert_eq!(
+			format_message(&message),
+			"[2024-05-01T10:00:00.000Z] ops@example.com: maintenance tonight"
+		);
+		assert_eq!(message_key(&message).as_deref(), Some("7"));
+		assert_eq!(message_list(&json!({ "messages": [message] })).len(), 1);
+	}
+
+	#[test]
+	fn stale_invites_skip_used_recent_and_undated() {
+		let invites = serde_json::json!([
+			{ "id": 3, "email": "new@x.io", "createdAt": "2024-01-30T00:00:00Z" },
+			{ "id": 2, "email": "used@x.io", "used": true, "createdAt": "2024-01-01T00:00:00Z" },
+			{ "invitation": { "id": 1, "email": "old@x.io", "createdAt": "2024-01-02T00:00:00Z" } },
+			{ "id": 4, "email": "older@x.io", "createdAt": "2024-01-01T00:00:00Z" },
+			{ "id": 5, "email": "undated@x.io" },
+		]);
+		// 2024-01-27T00:00:00Z
+		let stale = stale_invites(&invites, 1_706_313_600);
+		assert_eq!(
+			stale,
+			vec![
+				serde_json::json!({ "id": 4, "email": "older@x.io" }),
+				serde_json::json!({ "id": 1, "email": "old@x.io" }),
+			]
+		);
+		assert_eq!(invitation_id("42"), serde_json::json!(42));
+		assert_eq!(invitation_id("abc"), serde_json::json!("abc"));
+	}
+
+	#[test]
+	fn parse_invite_csv_reads_header_roles_and_defaults() {
+		let text = "email,role\nann@x.io,admin\n\n# comment\n\"bob@x.io\", Read_Only\ncarl@x.io\n";
+		let rows = parse_invite_csv(text, OrgRole::User).unwrap();
+		let summary: Vec<_> = rows
+			.iter()
+			.map(|r| (r.line, r.email.as_str(), role_to_string(r.role)))
+			.collect();
+		assert_eq!(
+			summary,
+			vec![
+				(2, "ann@x.io", "ADMIN"),
+				(5, "bob@x.io", "READ_ONLY"),
+				(6, "carl@x.io", "USER"),
+			]
+		);
+
+		let err = parse_invite_csv("ann@x.io\nnot-an-email\n", OrgRole::User).unwrap_err();
+		assert!(err.to_string().contains("line 2"));
+		assert!(parse_invite_csv("ann@x.io,owner", OrgRole::User).is_err());
+	}
+
+	#[test]
+	fn webhook_event_normalizes_and_suggests() {
+		assert_eq!(webhook_event("network-join").unwrap(), "NETWORK_JOIN");
+		assert_eq!(webhook_event("member_deleted").unwrap(), "MEMBER_DELETED");
+
+		let err = webhook_event("NETWORK_JION").unwrap_err().to_string();
+		assert!(err.contains("did you mean: NETWORK_JOIN"), "{err}");
+		assert!(webhook_event("banana").is_err());
+	}
+
+	#[test]
+	fn has_unread_reads_per_org_flags_and_defaults_to_checking() {
+		let list = json!([
+			{ "id": "org1", "hasUnreadMessages": false },
+			{ "id": "org2", "unreadCount": 3 },
+		]);
+		assert!(!has_unread(&list, "org1"));
+		assert!(has_unread(&list, "org2"));
+		assert!(!has_unread(&json!({ "org1": false }), "org1"));
+		assert!(!has_unread(&json!({ "orgUnreadInfo": { "org1": { "unreadCount": 0 } } }), "org1"));
+		assert!(has_unread(&list, "org3"));
+		assert!(has_unread(&Value::Null, "org1"));
+	}
+
+	#[test]
+	fn split_fresh_prints_unseen_and_forgets_dropped_ids() {
+		let seen: HashSet<String> = ["1", "2"].iter().map(|s| s.to_string()).collect();
+		let feed = vec![json!({ "id": 2 }), json!({ "id": 3 }), json!({ "content": "no id" })];
+		let (fresh, latest) = split_fresh(feed, &seen);
+		assert_eq!(fresh, vec![json!({ "id": 3 }), json!({ "content": "no id" })]);
+		let mut latest: Vec<_> = latest.into_iter().collect();
+		latest.sort();
+		assert_eq!(latest, ["2", "3"]);
+	}
 }
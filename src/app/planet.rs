@@ -3,16 +3,14 @@ use std::path::PathBuf;
 
 use reqwest::Method;
 
-use crate::cli::{GlobalOpts, PlanetCommand};
-use crate::context::resolve_effective_config;
+use crate::cli::PlanetCommand;
 use crate::error::CliError;
-use crate::http::{ClientUi, HttpClient};
+use crate::output;
 
-use super::common::load_config_store;
+use super::middleware::CommandContext;
 
-pub(super) async fn run(global: &GlobalOpts, command: PlanetCommand) -> Result<(), CliError> {
-	let (_config_path, cfg) = load_config_store()?;
-	let effective = resolve_effective_config(global, &cfg)?;
+pub(super) async fn run(ctx: &CommandContext<'_>, command: PlanetCommand) -> Result<(), CliError> {
+	let global = ctx.global;
 
 	match command {
 		PlanetCommand::Download(args) => {
@@ -28,23 +26,14 @@ pub(super) async fn run(global: &GlobalOpts, command: PlanetCommand) -> Result<(
 				Some(args.out.unwrap_or_else(|| PathBuf::from("planet")))
 			};
 
-			if let Some(ref out_path) = out_path {
-				if out_path.exists() && !args.force {
-					return Err(CliError::InvalidArgument(format!(
-						"output file already exists: {} (pass --force to overwrite)",
-						out_path.display()
-					)));
-				}
+			if let Some(ref out_path) = out_path && out_path.exists() && !args.force {
+				return Err(CliError::InvalidArgument(format!(
+					"output file already exists: {} (pass --force to overwrite)",
+					out_path.display()
+				)));
 			}
 
-			let client = HttpClient::new(
-				&effective.host,
-				None,
-				effective.timeout,
-				effective.retries,
-				global.dry_run,
-				ClientUi::from_context(global, &effective),
-			)?;
+			let client = ctx.anonymous_client()?;
 
 			let bytes = client
 				.request_bytes(
@@ -63,7 +52,7 @@ pub(super) async fn run(global: &GlobalOpts, command: PlanetCommand) -> Result<(
 				}
 				std::fs::write(&out_path, &bytes)?;
 				if !global.quiet {
-					eprintln!("Wrote {} bytes to {}.", bytes.len(), out_path.display());
+					eprintln!("Wrote {} to {}.", output::format_size(bytes.len() as u64), out_path.display());
 				}
 				return Ok(());
 			}
@@ -0,0 +1,335 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::net::IpAddr;
+
+use reqwest::{Method, StatusCode};
+use serde_json::{json, Value};
+use url::Url;
+
+use crate::cancel;
+use crate::cli::{GlobalOpts, OutputFormat};
+use crate::context::EffectiveConfig;
+use crate::error::CliError;
+use crate::http::{should_retry_error, should_retry_status, ClientUi, ConnectOverrides};
+use crate::output;
+use crate::redact;
+
+/// Rrsets written by the CLI carry this account in their comment, so later runs can tell
+/// their own records apart from hand-made ones in the same zone.
+const COMMENT_ACCOUNT: &str = "ztnet-cli";
+
+pub(super) struct PdnsTarget {
+	pub url: String,
+	pub key: String,
+	pub server: String,
+	pub zone: String,
+	pub ttl: u32,
+	pub adopt: bool,
+}
+
+/// Upserts one A/AAAA rrset per hostname into a PowerDNS authoritative zone and deletes
+/// rrsets this network wrote on an earlier run whose member is gone. Records are only
+/// touched when their content, TTL or ownership changed, and rrsets without the ownership
+/// comment are left alone unless `adopt` is set.
+pub(super) async fn push(
+	global: &GlobalOpts,
+	effective: &EffectiveConfig,
+	target: &PdnsTarget,
+	network_id: &str,
+	records: &[(&str, &str)],
+) -> Result<(), CliError> {
+	let zone = format!("{}.", target.zone.trim_end_matches('.'));
+	let zone_url = Url::parse(&format!(
+		"{}/api/v1/servers/{}/zones/{zone}",
+		target.url.trim_end_matches('/'),
+		target.server
+	))?;
+	let marker = comment_for(network_id);
+	let desired = desired_rrsets(records, &zone, target.ttl, &marker);
+
+	if global.dry_run {
+		// The zone is never read here, so the PATCH shows every rrset a first run would send.
+		let key = redact::header("X-API-Key", &target.key);
+		let body = serde_json::to_string_pretty(&json!({ "rrsets": desired }))?;
+		output::emit(
+			&format!("GET {zone_url}\nX-API-Key: {key}\n\nPATCH {zone_url}\nX-API-Key: {key}\n\n{body}"),
+			false,
+		)?;
+		return Err(CliError::DryRunPrinted);
+	}
+
+	let ui = ClientUi::from_context(global, effective);
+	// The panel's Host override and certificate pin don't apply to the PowerDNS server.
+	let connect = ConnectOverrides {
+		host_header: None,
+		cert_pin: None,
+		..ui.connect.clone()
+	};
+	let (_, client) =
+		connect.build_client(&target.url, reqwest::Client::builder().timeout(effective.timeout))?;
+	let send = |method: Method, body: Option<Value>| {
+		let mut request = client.request(method, zone_url.clone()).header("X-API-Key", &target.key);
+		if let Some(body) = body {
+			request = request.json(&body);
+		}
+		request
+	};
+
+	let existing = send_with_retries(&ui, &connect, effective.retries, &zone_url, || send(Method::GET, None)).await?;
+	let existing = pdns_json(Method::GET, &zone_url, existing).await?;
+	let plan = plan_changes(&desired, &existing, &marker, target.adopt);
+
+	let summary = json!({
+		"zone": zone,
+		"upserted": plan.upserts.iter().map(rrset_label).collect::<Vec<_>>(),
+		"deleted": plan.deletes.iter().map(rrset_label).collect::<Vec<_>>(),
+		"skipped": plan.skipped.iter().map(rrset_label).collect::<Vec<_>>(),
+		"unchanged": plan.unchanged,
+	});
+	let rrsets: Vec<Value> = plan.upserts.iter().chain(plan.deletes.iter()).cloned().collect();
+
+	if !rrsets.is_empty() {
+		ui.check_writable(&Method::PATCH, zone_url.as_str())?;
+		let body = json!({ "rrsets": rrsets });
+		let response = send_with_retries(&ui, &connect, effective.retries, &zone_url, || {
+			send(Method::PATCH, Some(body.clone()))
+		})
+		.await?;
+		pdns_json(Method::PATCH, &zone_url, response).await?;
+	}
+
+	if !plan.skipped.is_empty() && !global.quiet {
+		eprintln!(
+			"Skipped {} rrset(s) that ztnet-cli did not create (pass --pdns-adopt to take them over): {}",
+			plan.skipped.len(),
+			plan.skipped.iter().map(rrset_label).collect::<Vec<_>>().join(", ")
+		);
+	}
+
+	if matches!(effective.output, OutputFormat::Table) {
+		if !global.quiet {
+			eprintln!(
+				"PowerDNS zone {zone}: {} upserted, {} deleted, {} unchanged.",
+				plan.upserts.len(),
+				plan.deletes.len(),
+				plan.unchanged
+			);
+		}
+		return Ok(());
+	}
+	output::print_value(&summary, effective.output, global.no_color)?;
+	Ok(())
+}
+
+/// Sends a request built fresh for every attempt, retrying 429/5xx responses and transient
+/// connection errors the way panel requests are retried. Both requests are idempotent:
+/// the PATCH only carries REPLACE and DELETE changes.
+async fn send_with_retries(
+	ui: &ClientUi,
+	connect: &ConnectOverrides,
+	retries: u32,
+	url: &Url,
+	request: impl Fn() -> reqwest::RequestBuilder,
+) -> Result<reqwest::Response, CliError> {
+	let mut attempt = 0;
+	loop {
+		match cancel::guard(request().send()).await? {
+			Ok(response) if should_retry_status(response.status()) && attempt < retries => {}
+			Ok(response) => return Ok(response),
+			Err(err) if attempt < retries && should_retry_error(&err) => {}
+			Err(err) => return Err(crate::diagnose::connection_error(err, url, connect).await),
+		}
+		cancel::sleep(ui.backoff.delay(attempt)).await?;
+		attempt += 1;
+	}
+}
+
+async fn pdns_json(method: Method, url: &Url, response: reqwest::Response) -> Result<Value, CliError> {
+	let status = response.status();
+	let text = response.text().await?;
+	if status.is_success() {
+		if text.trim().is_empty() {
+			return Ok(Value::Null);
+		}
+		return Ok(serde_json::from_str(&text)?);
+	}
+
+	let detail = serde_json::from_str::<Value>(&text)
+		.ok()
+		.and_then(|v| v.get("error").and_then(|e| e.as_str()).map(str::to_string))
+		.unwrap_or_else(|| status.canonical_reason().unwrap_or("error").to_string());
+	let hint = match status {
+		StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => "\n\n  hint: check --pdns-key (PDNS_API_KEY)",
+		StatusCode::NOT_FOUND | StatusCode::UNPROCESSABLE_ENTITY => {
+			"\n\n  hint: check --zone and --pdns-server; the zone must already exist"
+		}
+		_ => "",
+	};
+	Err(CliError::HttpStatus {
+		status,
+		message: format!("PowerDNS {method} {}: {detail}{hint}", url.path()),
+		body: Some(text),
+	})
+}
+
+fn comment_for(network_id: &str) -> String {
+	format!("managed by ztnet-cli for network {network_id}")
+}
+
+fn desired_rrsets(records: &[(&str, &str)], zone: &str, ttl: u32, marker: &str) -> Vec<Value> {
+	let mut grouped: BTreeMap<(String, &str), BTreeSet<String>> = BTreeMap::new();
+	for (ip, hostname) in records {
+		let Ok(addr) = ip.parse::<IpAddr>() else {
+			continue;
+		};
+		let name = format!("{}.", hostname.trim_end_matches('.'));
+		if !name.ends_with(zone) {
+			continue;
+		}
+		let kind = if addr.is_ipv4() { "A" } else { "AAAA" };
+		grouped.entry((name, kind)).or_default().insert(addr.to_string());
+	}
+
+	grouped
+		.into_iter()
+		.map(|((name, kind), contents)| {
+			json!({
+				"name": name,
+				"type": kind,
+				"ttl": ttl,
+				"changetype": "REPLACE",
+				"records": contents
+					.into_iter()
+					.map(|content| json!({ "content": content, "disabled": false }))
+					.collect::<Vec<_>>(),
+				"comments": [{ "content": marker, "account": COMMENT_ACCOUNT }],
+			})
+		})
+		.collect()
+}
+
+struct Plan {
+	upserts: Vec<Value>,
+	deletes: Vec<Value>,
+	/// Desired rrsets whose name and type are already taken by a record without our comment.
+	skipped: Vec<Value>,
+	unchanged: usize,
+}
+
+fn plan_changes(desired: &[Value], existing_zone: &Value, marker: &str, adopt: bool) -> Plan {
+	let existing: Vec<&Value> = existing_zone
+		.get("rrsets")
+		.and_then(|v| v.as_array())
+		.into_iter()
+		.flatten()
+		.collect();
+	let key = |rrset: &Value| {
+		(
+			rrset.get("name").and_then(|v| v.as_str()).unwrap_or_default().to_ascii_lowercase(),
+			rrset.get("type").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+		)
+	};
+	let contents = |rrset: &Value| -> BTreeSet<String> {
+		rrset
+			.get("records")
+			.and_then(|v| v.as_array())
+			.into_iter()
+			.flatten()
+			.filter_map(|r| r.get("content").and_then(|c| c.as_str()).map(str::to_string))
+			.collect()
+	};
+	let owned = |rrset: &Value| {
+		rrset
+			.get("comments")
+			.and_then(|v| v.as_array())
+			.into_iter()
+			.flatten()
+			.any(|c| c.get("content").and_then(|v| v.as_str()) == Some(marker))
+	};
+
+	let mut plan = Plan {
+		upserts: Vec::new(),
+		deletes: Vec::new(),
+		skipped: Vec::new(),
+		unchanged: 0,
+	};
+	for want in desired {
+		let current = existing.iter().find(|rrset| key(rrset) == key(want));
+		if !adopt && current.is_some_and(|rrset| !owned(rrset)) {
+			plan.skipped.push(want.clone());
+			continue;
+		}
+		let same = current.is_some_and(|rrset| {
+			owned(rrset) && contents(rrset) == contents(want) && rrset.get("ttl") == want.get("ttl")
+		});
+		if same {
+			plan.unchanged += 1;
+		} else {
+			plan.upserts.push(want.clone());
+		}
+	}
+
+	let wanted: BTreeSet<(String, String)> = desired.iter().map(key).collect();
+	for rrset in existing {
+		let (name, kind) = key(rrset);
+		if matches!(kind.as_str(), "A" | "AAAA") && owned(rrset) && !wanted.contains(&(name.clone(), kind.clone())) {
+			plan.deletes.push(json!({ "name": name, "type": kind, "changetype": "DELETE" }));
+		}
+	}
+	plan
+}
+
+fn rrset_label(rrset: &Value) -> String {
+	format!(
+		"{} {}",
+		rrset.get("name").and_then(|v| v.as_str()).unwrap_or_default(),
+		rrset.get("type").and_then(|v| v.as_str()).unwrap_or_default()
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn plan_upserts_changed_and_deletes_only_owned_leftovers() {
+		let marker = comment_for("abc");
+		let records = [
+			("10.0.0.1", "web.zt.example.com"),
+			("fd00::1", "web.zt.example.com"),
+			("10.0.0.2", "db.zt.example.com"),
+			("10.0.0.9", "outside.other.org"),
+		];
+		let desired = desired_rrsets(&records, "zt.example.com.", 300, &marker);
+		assert_eq!(desired.len(), 3);
+
+		let owned = json!([{ "content": marker, "account": COMMENT_ACCOUNT }]);
+		let existing = json!({ "rrsets": [
+			{ "name": "web.zt.example.com.", "type": "A", "ttl": 300,
+			  "records": [{ "content": "10.0.0.1" }], "comments": owned },
+			{ "name": "db.zt.example.com.", "type": "A", "ttl": 300,
+			  "records": [{ "content": "10.0.0.7" }], "comments": owned },
+			{ "name": "gone.zt.example.com.", "type": "A", "ttl": 300,
+			  "records": [{ "content": "10.0.0.3" }], "comments": owned },
+			{ "name": "manual.zt.example.com.", "type": "A", "ttl": 300,
+			  "records": [{ "content": "10.0.0.4" }], "comments": [] },
+			{ "name": "web.zt.example.com.", "type": "AAAA", "ttl": 300,
+			  "records": [{ "content": "fd00::9" }], "comments": [] },
+		]});
+
+		let plan = plan_changes(&desired, &existing, &marker, false);
+		assert_eq!(plan.unchanged, 1);
+		let upserted: Vec<String> = plan.upserts.iter().map(rrset_label).collect();
+		assert_eq!(upserted, vec!["db.zt.example.com. A"]);
+		let skipped: Vec<String> = plan.skipped.iter().map(rrset_label).collect();
+		assert_eq!(skipped, vec!["web.zt.example.com. AAAA"]);
+		assert_eq!(
+			plan.deletes,
+			vec![json!({ "name": "gone.zt.example.com.", "type": "A", "changetype": "DELETE" })]
+		);
+
+		let adopted = plan_changes(&desired, &existing, &marker, true);
+		let upserted: Vec<String> = adopted.upserts.iter().map(rrset_label).collect();
+		assert_eq!(upserted, vec!["db.zt.example.com. A", "web.zt.example.com. AAAA"]);
+		assert!(adopted.skipped.is_empty());
+	}
+}
@@ -0,0 +1,208 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime};
+
+use crate::cli::GlobalOpts;
+use crate::error::CliError;
+
+/// How long to wait for another process to finish its login before giving up.
+const WAIT_TIMEOUT: Duration = Duration::from_secs(90);
+/// The holder touches the lock file this often, including while it waits at the
+/// two-factor prompt.
+const HEARTBEAT: Duration = Duration::from_secs(5);
+/// A lock not touched for this long belongs to a process that died mid-login. It is well
+/// under `WAIT_TIMEOUT`, so waiters recover from a crashed holder instead of timing out.
+const STALE_AFTER: Duration = Duration::from_secs(30);
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+const _: () = assert!(HEARTBEAT.as_millis() * 3 <= STALE_AFTER.as_millis());
+const _: () = assert!(STALE_AFTER.as_millis() < WAIT_TIMEOUT.as_millis());
+
+/// Cross-process lock serializing session refreshes for one host, so parallel CI jobs
+/// sharing a profile perform a single login instead of a rate-limited storm. The lock is
+/// a file created exclusively next to the config file and removed on drop; a background
+/// thread keeps its mtime fresh while it is held.
+pub(super) struct RefreshLock {
+	path: PathBuf,
+	heartbeat: Option<(mpsc::Sender<()>, JoinHandle<()>)>,
+}
+
+impl RefreshLock {
+	/// Blocks until the lock for `host_key` is free. Returns the lock together with
+	/// whether another process held it first (and may have refreshed the session already).
+	pub(super) async fn acquire(
+		global: &GlobalOpts,
+		config_path: &Path,
+		host_key: &str,
+	) -> Result<(Self, bool), CliError> {
+		let dir = config_path
+			.parent()
+			.map(|parent| parent.join("locks"))
+			.unwrap_or_else(|| PathBuf::from("locks"));
+		fs::create_dir_all(&dir)?;
+		let path = dir.join(lock_file_name(host_key));
+
+		let started = std::time::Instant::now();
+		let mut waited = false;
+		loop {
+			if try_create(&path)? {
+				let heartbeat = start_heartbeat(path.clone());
+				return Ok((Self { path, heartbeat: Some(heartbeat) }, waited));
+			}
+			if is_stale(&path) {
+				take_over_stale(&path)?;
+				continue;
+			}
+			if started.elapsed() >= WAIT_TIMEOUT {
+				return Err(CliError::Timeout(format!(
+					"another process is logging in to {host_key} (lock file {}); remove it if no login is running",
+					path.display()
+				)));
+			}
+			if !waited && !global.quiet {
+				eprintln!("Waiting for another process to finish logging in to {host_key}...");
+			}
+			waited = true;
+			tokio::time::sleep(POLL_INTERVAL).await;
+		}
+	}
+}
+
+impl Drop for RefreshLock {
+	fn drop(&mut self) {
+		if let Some((stop, thread)) = self.heartbeat.take() {
+			drop(stop);
+			let _ = thread.join();
+		}
+		let _ = fs::remove_file(&self.path);
+	}
+}
+
+/// A std thread rather than a task: the two-factor prompt blocks the runtime's thread.
+fn start_heartbeat(path: PathBuf) -> (mpsc::Sender<()>, JoinHandle<()>) {
+	let (stop, stopped) = mpsc::channel::<()>();
+	let thread = std::thread::spawn(move || {
+		while let Err(mpsc::RecvTimeoutError::Timeout) = stopped.recv_timeout(HEARTBEAT) {
+			let _ = fs::File::options()
+				.write(true)
+				.open(&path)
+				.and_then(|file| file.set_modified(SystemTime::now()));
+		}
+	});
+	(stop, thread)
+}
+
+/// Removes a stale lock without racing other waiters. The lock is first renamed to a name
+/// only this process uses, so of two waiters that both saw it stale only one gets it. If
+/// the renamed file turns out to be fresh, another waiter had already replaced the stale
+/// lock with its own, which is put back.
+fn take_over_stale(path: &Path) -> io::Result<()> {
+	let mut claimed = path.as_os_str().to_os_string();
+	claimed.push(format!(".stale-{}", std::process::id()));
+	let claimed = PathBuf::from(claimed);
+	match fs::rename(path, &claimed) {
+		Ok(()) => {}
+		Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+		Err(err) => return Err(err),
+	}
+	if !is_stale(&claimed) {
+		// Fails only if yet another lock appeared meanwhile; that one is left alone.
+		let _ = fs::hard_link(&claimed, path);
+	}
+	fs::remove_file(&claimed)
+}
+
+fn try_create(path: &Path) -> io::Result<bool> {
+	match fs::OpenOptions::new().write(true).create_new(true).open(path) {
+		Ok(mut file) => {
+			let _ = writeln!(file, "{}", std::process::id());
+			Ok(true)
+		}
+		Err(err) if err.kind() == io::ErrorKind::AlreadyExists => Ok(false),
+		Err(err) => Err(err),
+	}
+}
+
+fn is_stale(path: &Path) -> bool {
+	fs::metadata(path)
+		.and_then(|meta| meta.modified())
+		.ok()
+		.and_then(|modified| SystemTime::now().duration_since(modified).ok())
+		.is_some_and(|age| age > STALE_AFTER)
+}
+
+fn lock_file_name(host_key: &str) -> String {
+	let name: String = host_key
+		.trim_start_matches("https://")
+		.trim_start_matches("http://")
+		.chars()
+		.map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '-') { c } else { '_' })
+		.collect();
+	format!("login-{name}.lock")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn lock_dir(name: &str) -> PathBuf {
+		let dir = std::env::temp_dir().join(format!("ztnet-lock-{name}-{}", std::process::id()));
+		let _ = fs::remove_dir_all(&dir);
+		fs::create_dir_all(dir.join("locks")).unwrap();
+		dir
+	}
+
+	fn age(path: &Path, by: Duration) {
+		let file = fs::File::options().write(true).open(path).unwrap();
+		file.set_modified(SystemTime::now() - by).unwrap();
+	}
+
+	#[tokio::test]
+	async fn stale_lock_is_taken_over_and_released_on_drop() {
+		use clap::Parser;
+		let global = crate::cli::Cli::parse_from(["ztnet", "--quiet", "version"]).global;
+		let dir = lock_dir("stale");
+		let path = dir.join("locks").join(lock_file_name("https://panel.test"));
+		fs::write(&path, "1\n").unwrap();
+		age(&path, STALE_AFTER + Duration::from_secs(1));
+
+		let (lock, waited) = RefreshLock::acquire(&global, &dir.join("config.toml"), "https://panel.test")
+			.await
+			.unwrap();
+		assert!(!waited);
+		assert_eq!(fs::read_to_string(&path).unwrap(), format!("{}\n", std::process::id()));
+		assert!(!is_stale(&path));
+		drop(lock);
+		assert!(!path.exists());
+		fs::remove_dir_all(&dir).unwrap();
+	}
+
+	#[test]
+	fn taking_over_a_lock_that_was_just_replaced_puts_it_back() {
+		let dir = lock_dir("race");
+		let path = dir.join("locks").join("login-panel.test.lock");
+
+		// Another waiter already swapped the stale lock for its own fresh one.
+		fs::write(&path, "4242\n").unwrap();
+		take_over_stale(&path).unwrap();
+		assert_eq!(fs::read_to_string(&path).unwrap(), "4242\n");
+
+		age(&path, STALE_AFTER + Duration::from_secs(1));
+		take_over_stale(&path).unwrap();
+		assert!(!path.exists());
+		assert_eq!(fs::read_dir(dir.join("locks")).unwrap().count(), 0);
+
+		// The other waiter removed it first.
+		take_over_stale(&path).unwrap();
+		fs::remove_dir_all(&dir).unwrap();
+	}
+
+	#[test]
+	fn lock_file_name_is_filesystem_safe() {
+		assert_eq!(lock_file_name("https://ztnet.example.com"), "login-ztnet.example.com.lock");
+		assert_eq!(lock_file_name("http://[::1]:3000"), "login-___1__3000.lock");
+	}
+}
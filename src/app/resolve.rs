@@ -1,9 +1,42 @@
 use reqwest::Method;
 use serde_json::Value;
 
+use crate::context::EffectiveConfig;
 use crate::error::CliError;
 use crate::http::HttpClient;
 
+/// Positional `[NETWORK]`, falling back to the context network (`--network` or
+/// `config context set --network`).
+pub(super) fn network_or_context(effective: &EffectiveConfig, network: Option<&str>) -> Result<String, CliError> {
+	if let Some(network) = network.map(str::trim).filter(|n| !n.is_empty()) {
+		return Ok(network.to_string());
+	}
+	effective.network.clone().ok_or_else(|| {
+		CliError::InvalidArgument(format!(
+			"missing NETWORK and profile '{}' has no context network\n\n  Pass NETWORK, or run: ztnet config context set --network <NETWORK>",
+			effective.profile
+		))
+	})
+}
+
+/// `[NETWORK] <MEMBER>` positionals: a single value is the member, and the network
+/// comes from the context.
+pub(super) fn network_and_member(
+	effective: &EffectiveConfig,
+	network: Option<&str>,
+	member: Option<&str>,
+	member_label: &str,
+) -> Result<(String, String), CliError> {
+	let (network, member) = match (network, member) {
+		(network, Some(member)) => (network, member),
+		(Some(member), None) => (None, member),
+		(None, None) => {
+			return Err(CliError::InvalidArgument(format!("missing {member_label}")));
+		}
+	};
+	Ok((network_or_context(effective, network)?, member.to_string()))
+}
+
 pub(super) async fn resolve_org_id(client: &HttpClient, org: &str) -> Result<String, CliError> {
 	let org = org.trim();
 	if org.is_empty() {
@@ -33,10 +66,8 @@ pub(super) async fn resolve_org_id(client: &HttpClient, org: &str) -> Result<Str
 			.and_then(|v| v.as_str())
 			.or_else(|| o.get("name").and_then(|v| v.as_str()));
 
-		if let (Some(id), Some(name)) = (id, name) {
-			if name.eq_ignore_ascii_case(org) {
-				matches.push(id.to_string());
-			}
+		if let (Some(id), Some(name)) = (id, name) && name.eq_ignore_ascii_case(org) {
+			matches.push(id.to_string());
 		}
 	}
 
@@ -84,10 +115,8 @@ pub(super) async fn resolve_network_id(
 			.and_then(|v| v.as_str())
 			.or_else(|| n.get("nwname").and_then(|v| v.as_str()));
 
-		if let (Some(id), Some(name)) = (id, name) {
-			if name.eq_ignore_ascii_case(network) {
-				matches.push(id.to_string());
-			}
+		if let (Some(id), Some(name)) = (id, name) && name.eq_ignore_ascii_case(network) {
+			matches.push(id.to_string());
 		}
 	}
 
@@ -107,3 +136,46 @@ pub(super) fn extract_network_id(value: &Value) -> Option<&str> {
 		.or_else(|| value.get("nwid").and_then(|v| v.as_str()))
 }
 
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn positional_network_falls_back_to_context() {
+		let mut effective = EffectiveConfig {
+			profile: "default".to_string(),
+			host: "https://ztnet.example.com".to_string(),
+			token: None,
+			session_cookie: None,
+			device_cookie: None,
+			org: None,
+			network: None,
+			output: crate::cli::OutputFormat::Json,
+			timeout: std::time::Duration::from_secs(1),
+			retries: 0,
+			backoff: Default::default(),
+			allow_default_route: false,
+			read_only: false,
+			cert_fingerprint: None,
+			user_agent: String::new(),
+			concurrency: 1,
+		};
+
+		let err = network_and_member(&effective, Some("abc123"), None, "MEMBER").unwrap_err();
+		assert!(err.to_string().contains("config context set --network"));
+		assert!(network_and_member(&effective, None, None, "MEMBER").is_err());
+
+		effective.network = Some("ctx-net".to_string());
+		assert_eq!(network_or_context(&effective, None).unwrap(), "ctx-net");
+		assert_eq!(network_or_context(&effective, Some("other")).unwrap(), "other");
+		assert_eq!(
+			network_and_member(&effective, Some("abc123"), None, "MEMBER").unwrap(),
+			("ctx-net".to_string(), "abc123".to_string())
+		);
+		assert_eq!(
+			network_and_member(&effective, Some("net"), Some("abc123"), "MEMBER").unwrap(),
+			("net".to_string(), "abc123".to_string())
+		);
+	}
+}
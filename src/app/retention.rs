@@ -0,0 +1,195 @@
+//! `--keep-last/--keep-daily/--keep-weekly` retention: which items of a time-stamped
+//! series (admin backups) survive a prune.
+
+use std::collections::HashSet;
+use std::time::UNIX_EPOCH;
+
+use serde_json::Value;
+
+use crate::cli::RetentionArgs;
+use crate::error::CliError;
+
+const DAY_SECS: i64 = 86_400;
+
+/// An item and when it was created (Unix seconds), if that could be determined.
+#[derive(Debug, Clone)]
+pub(super) struct Dated {
+	pub name: String,
+	pub created: Option<i64>,
+}
+
+#[derive(Debug, Default)]
+pub(super) struct Plan {
+	pub keep: Vec<Dated>,
+	pub delete: Vec<Dated>,
+}
+
+impl RetentionArgs {
+	pub(super) fn is_set(&self) -> bool {
+		self.keep_last.is_some() || self.keep_daily.is_some() || self.keep_weekly.is_some()
+	}
+}
+
+/// Keeps the newest `keep_last` items, plus the newest item of each of the last
+/// `keep_daily` days and `keep_weekly` weeks (UTC, weeks start on Monday) that have one.
+/// Items without a known date are always kept.
+pub(super) fn plan(items: Vec<Dated>, policy: &RetentionArgs) -> Result<Plan, CliError> {
+	if !policy.is_set() {
+		return Err(CliError::InvalidArgument(
+			"pass at least one of --keep-last, --keep-daily or --keep-weekly".to_string(),
+		));
+	}
+
+	let (mut dated, undated): (Vec<Dated>, Vec<Dated>) = items.into_iter().partition(|i| i.created.is_some());
+	dated.sort_by_key(|item| std::cmp::Reverse(item.created));
+
+	let mut keep = vec![false; dated.len()];
+	for flag in keep.iter_mut().take(policy.keep_last.unwrap_or(0)) {
+		*flag = true;
+	}
+	for (count, bucket) in [(policy.keep_daily, day as fn(i64) -> i64), (policy.keep_weekly, week)] {
+		let Some(count) = count else { continue };
+		let mut seen = HashSet::new();
+		for (index, item) in dated.iter().enumerate() {
+			if seen.len() >= count {
+				break;
+			}
+			if seen.insert(bucket(item.created.unwrap_or_default())) {
+				keep[index] = true;
+			}
+		}
+	}
+
+	let mut plan = Plan {
+		keep: undated,
+		delete: Vec::new(),
+	};
+	for (item, kept) in dated.into_iter().zip(keep) {
+		if kept {
+			plan.keep.push(item);
+		} else {
+			plan.delete.push(item);
+		}
+	}
+	Ok(plan)
+}
+
+fn day(secs: i64) -> i64 {
+	secs.div_euclid(DAY_SECS)
+}
+
+/// 1970-01-01 was a Thursday; shifting by three days makes weeks start on Monday.
+fn week(secs: i64) -> i64 {
+	(day(secs) + 3).div_euclid(7)
+}
+
+/// Creation time of a listed entry: an explicit date field when the server sends one,
+/// otherwise a Unix timestamp or `YYYY-MM-DD[THH-MM-SS]` embedded in the file name.
+pub(super) fn created_at(entry: &Value, name: &str) -> Option<i64> {
+	for key in ["createdAt", "created", "timestamp", "date", "mtime", "modifiedAt"] {
+		match entry.get(key) {
+			Some(Value::Number(n)) => return n.as_i64().map(from_epoch_number),
+			Some(Value::String(s)) => {
+				if let Some(secs) = parse_rfc3339(s) {
+					return Some(secs);
+				}
+			}
+			_ => {}
+		}
+	}
+	timestamp_in_name(name)
+}
+
+/// Unix seconds, or milliseconds when the number is too large to be seconds.
+fn from_epoch_number(n: i64) -> i64 {
+	if n > 100_000_000_000 { n / 1000 } else { n }
+}
+
+fn parse_rfc3339(text: &str) -> Option<i64> {
+	let time = humantime::parse_rfc3339_weak(text.trim()).ok()?;
+	time.duration_since(UNIX_EPOCH).ok().map(|d| d.as_secs() as i64)
+}
+
+fn timestamp_in_name(name: &str) -> Option<i64> {
+	let bytes = name.as_bytes();
+	let mut start = 0;
+	while start < bytes.len() {
+		if !bytes[start].is_ascii_digit() {
+			start += 1;
+			continue;
+		}
+		let end = start + bytes[start..].iter().take_while(|b| b.is_ascii_digit()).count();
+		let digits = &name[start..end];
+		if digits.len() == 4 && let Some(secs) = date_at(&name[start..]) {
+			return Some(secs);
+		}
+		if matches!(digits.len(), 10 | 13) {
+			return digits.parse().ok().map(from_epoch_number);
+		}
+		start = end;
+	}
+	None
+}
+
+/// `YYYY-MM-DD`, optionally followed by `THH-MM-SS` / `_HH:MM:SS` style times.
+fn date_at(text: &str) -> Option<i64> {
+	let date = text.get(..10)?;
+	let time = text
+		.get(11..19)
+		.filter(|t| t.as_bytes().iter().enumerate().all(|(i, b)| if i % 3 == 2 { !b.is_ascii_digit() } else { b.is_ascii_digit() }))
+		.map(|t| t.replace(|c: char| !c.is_ascii_digit(), ":"))
+		.unwrap_or_else(|| "00:00:00".to_string());
+	parse_rfc3339(&format!("{date}T{time}Z"))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+
+	fn days_ago(n: i64) -> Dated {
+		// Days counted back from Wednesday 2024-01-31 12:00 UTC.
+		Dated {
+			name: format!("b{n}"),
+			created: Some(1_706_702_400 - n * DAY_SECS),
+		}
+	}
+
+	#[test]
+	fn plan_keeps_last_daily_and_weekly_and_undated() {
+		let items: Vec<Dated> = (0..30).map(days_ago).chain([Dated { name: "x".into(), created: None }]).collect();
+		let policy = RetentionArgs {
+			keep_last: Some(2),
+			keep_daily: Some(3),
+			keep_weekly: Some(3),
+		};
+		let plan = plan(items, &policy).unwrap();
+		let mut kept: Vec<&str> = plan.keep.iter().map(|i| i.name.as_str()).collect();
+		kept.sort();
+		// b0..b2 are the last two and last three days; b3 (Sunday) and b10 close the two
+		// previous weeks.
+		assert_eq!(kept, ["b0", "b1", "b10", "b2", "b3", "x"]);
+		assert_eq!(plan.delete.len(), 25);
+
+		assert!(super::plan(Vec::new(), &RetentionArgs::default()).is_err());
+	}
+
+	#[test]
+	fn zero_keep_counts_are_rejected() {
+		use clap::Parser;
+		for flag in ["--keep-last", "--keep-daily", "--keep-weekly"] {
+			assert!(crate::cli::Cli::try_parse_from(["ztnet", "admin", "backup", "prune", flag, "0"]).is_err());
+			assert!(crate::cli::Cli::try_parse_from(["ztnet", "admin", "backup", "prune", flag, "1"]).is_ok());
+		}
+	}
+
+	#[test]
+	fn creation_time_from_fields_or_file_name() {
+		assert_eq!(created_at(&json!({ "createdAt": "2024-01-31T12:00:00Z" }), "a"), Some(1_706_702_400));
+		assert_eq!(created_at(&json!({ "mtime": 1_706_702_400_000i64 }), "a"), Some(1_706_702_400));
+		assert_eq!(created_at(&json!("n"), "nightly_1706702400.tar.gz"), Some(1_706_702_400));
+		assert_eq!(created_at(&json!("n"), "backup-2024-01-31T12-00-00.tar.gz"), Some(1_706_702_400));
+		assert_eq!(created_at(&json!("n"), "backup-2024-01-31.tar.gz"), Some(1_706_659_200));
+		assert_eq!(created_at(&json!("n"), "manual.tar.gz"), None);
+	}
+}
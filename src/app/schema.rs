@@ -0,0 +1,425 @@
+use serde_json::{Value, json};
+
+use crate::cli::{GlobalOpts, OutputFormat, SchemaArgs};
+use crate::error::CliError;
+use crate::output;
+
+const CLI_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Documents whose shape is owned by the CLI (built here rather than passed through from
+/// the ZTNet API), so their schemas stay stable across server releases.
+struct SchemaDoc {
+	name: &'static str,
+	commands: &'static str,
+	description: &'static str,
+	schema: fn() -> Value,
+}
+
+const DOCUMENTS: &[SchemaDoc] = &[
+	SchemaDoc {
+		name: "auth-hosts",
+		commands: "auth hosts list",
+		description: "Configured hosts with their default and bound profiles",
+		schema: auth_hosts,
+	},
+	SchemaDoc {
+		name: "auth-profiles",
+		commands: "auth profiles list",
+		description: "Profiles with their host and credential state, plus the active profile",
+		schema: auth_profiles,
+	},
+	SchemaDoc {
+		name: "detail-errors",
+		commands: "network list --details --skip-errors, org list --details --skip-errors",
+		description: "Detail results with per-item errors (machine output formats)",
+		schema: detail_errors,
+	},
+	SchemaDoc {
+		name: "export-hosts",
+		commands: "export hosts --format json",
+		description: "One record per member IP with its generated hostname",
+		schema: export_hosts,
+	},
+	SchemaDoc {
+		name: "invite-results",
+		commands: "org invite send-bulk",
+		description: "Per-row outcome of a bulk invite",
+		schema: invite_results,
+	},
+	SchemaDoc {
+		name: "version-report",
+		commands: "version --check-server",
+		description: "CLI version, inferred server version and feature availability",
+		schema: version_report,
+	},
+];
+
+pub(super) async fn run(global: &GlobalOpts, args: SchemaArgs) -> Result<(), CliError> {
+	let Some(name) = args.document else {
+		let format = global.output.unwrap_or(if global.json {
+			OutputFormat::Json
+		} else {
+			OutputFormat::Table
+		});
+		if matches!(format, OutputFormat::Table) {
+			let width = DOCUMENTS.iter().map(|doc| doc.name.len()).max().unwrap_or(0);
+			let lines: Vec<String> = DOCUMENTS
+				.iter()
+				.map(|doc| format!("{:<width$}  {}\n{:<width$}  ({})", doc.name, doc.description, "", doc.commands))
+				.collect();
+			return output::emit(&lines.join("\n"), true);
+		}
+		let rows: Vec<Value> = DOCUMENTS
+			.iter()
+			.map(|doc| json!({ "name": doc.name, "commands": doc.commands, "description": doc.description }))
+			.collect();
+		return output::print_value(&Value::Array(rows), format, global.no_color);
+	};
+
+	let doc = DOCUMENTS.iter().find(|doc| doc.name == name).ok_or_else(|| {
+		let names: Vec<&str> = DOCUMENTS.iter().map(|doc| doc.name).collect();
+		CliError::InvalidArgument(format!(
+			"unknown schema document '{name}' (available: {})",
+			names.join(", ")
+		))
+	})?;
+
+	// JSON Schema is itself JSON; table output makes no sense here.
+	let format = match global.output {
+		Some(OutputFormat::Table) | None => OutputFormat::Json,
+		Some(format) => format,
+	};
+	output::print_value(&document_schema(doc), format, global.no_color)
+}
+
+fn document_schema(doc: &SchemaDoc) -> Value {
+	let mut schema = json!({
+		"$schema": "https://json-schema.org/draft/2020-12/schema",
+		"$id": format!("https://github.com/JKamsker/ztnet-cli/schemas/{}.json", doc.name),
+		"title": doc.name,
+		"description": doc.description,
+		"x-ztnet-cli-version": CLI_VERSION,
+	});
+	if let (Some(target), Value::Object(body)) = (schema.as_object_mut(), (doc.schema)()) {
+		target.extend(body);
+	}
+	schema
+}
+
+fn string() -> Value {
+	json!({ "type": "string" })
+}
+
+fn nullable_string() -> Value {
+	json!({ "type": ["string", "null"] })
+}
+
+fn string_array() -> Value {
+	json!({ "type": "array", "items": string() })
+}
+
+fn object(properties: Value, required: &[&str]) -> Value {
+	json!({
+		"type": "object",
+		"properties": properties,
+		"required": required,
+	})
+}
+
+fn auth_hosts() -> Value {
+	json!({
+		"type": "array",
+		"items": object(
+			json!({
+				"host": string(),
+				"default_profile": nullable_string(),
+				"profiles": string_array(),
+			}),
+			&["host", "default_profile", "profiles"],
+		),
+	})
+}
+
+fn auth_profiles() -> Value {
+	object(
+		json!({
+			"active_profile": nullable_string(),
+			"profiles": string_array(),
+			"details": {
+				"type": "array",
+				"items": object(
+					json!({
+						"name": string(),
+						"active": { "type": "boolean" },
+						"host": nullable_string(),
+						"token": { "type": "boolean" },
+						"session": { "enum": ["present", "expired", "none"] },
+						"session_expires": { "type": ["string", "null"], "format": "date-time" },
+					}),
+					&["name", "active", "host", "token", "session", "session_expires"],
+				),
+			},
+		}),
+		&["active_profile", "profiles", "details"],
+	)
+}
+
+fn detail_errors() -> Value {
+	object(
+		json!({
+			"items": { "type": "array", "items": { "type": "object" } },
+			"errors": {
+				"type": "array",
+				"items": object(
+					json!({
+						"id": string(),
+						"status": { "type": ["integer", "null"] },
+						"error": string(),
+					}),
+					&["id", "status", "error"],
+				),
+			},
+		}),
+		&["items", "errors"],
+	)
+}
+
+fn export_hosts() -> Value {
+	json!({
+		"type": "array",
+		"items": object(
+			json!({
+				"ip": string(),
+				"hostname": string(),
+				"memberId": string(),
+				"name": string(),
+				"authorized": { "type": "boolean" },
+			}),
+			&["ip", "hostname", "memberId", "name", "authorized"],
+		),
+	})
+}
+
+fn invite_results() -> Value {
+	json!({
+		"type": "array",
+		"items": object(
+			json!({
+				"line": { "type": "integer", "minimum": 1 },
+				"email": string(),
+				"role": { "enum": ["READ_ONLY", "USER", "ADMIN"] },
+				"status": { "enum": ["would-send", "sent", "failed"] },
+				"error": nullable_string(),
+			}),
+			&["line", "email", "role", "status", "error"],
+		),
+	})
+}
+
+fn version_report() -> Value {
+	object(
+		json!({
+			"cli_version": string(),
+			"server": object(
+				json!({
+					"host": string(),
+					"version": nullable_string(),
+					"version_source": string(),
+				}),
+				&["host", "version", "version_source"],
+			),
+			"features": {
+				"type": "array",
+				"items": object(
+					json!({
+						"feature": string(),
+						"commands": string(),
+						"probe": string(),
+						"since": string(),
+						"status": { "enum": ["available", "unavailable", "unknown"] },
+						"error": string(),
+					}),
+					&["feature", "commands", "probe", "since", "status"],
+				),
+			},
+		}),
+		&["cli_version", "server", "features"],
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn documents_are_unique_and_well_formed() {
+		for (idx, doc) in DOCUMENTS.iter().enumerate() {
+			assert!(
+				DOCUMENTS[..idx].iter().all(|other| other.name != doc.name),
+				"duplicate schema {}",
+				doc.name
+			);
+			let schema = document_schema(doc);
+			assert_eq!(schema["title"], doc.name);
+			assert!(schema["type"].is_string(), "{} has no type", doc.name);
+		}
+	}
+
+	#[test]
+	fn required_fields_are_declared() {
+		fn check(schema: &Value) {
+			if let Some(required) = schema["required"].as_array() {
+				for field in required {
+					let field = field.as_str().unwrap();
+					assert!(schema["properties"].get(field).is_some(), "{field} not declared");
+				}
+			}
+			if let Some(props) = schema["properties"].as_object() {
+				props.values().for_each(check);
+			}
+			if let Some(items) = schema.get("items") {
+				check(items);
+			}
+		}
+		for doc in DOCUMENTS {
+			check(&(doc.schema)());
+		}
+	}
+
+	/// Checks the subset of JSON Schema these documents use: `type`, `enum`, `minimum`,
+	/// `properties`, `required` and `items`.
+	fn validate(schema: &Value, value: &Value, path: &str) -> Result<(), String> {
+		if let Some(types) = schema.get("type") {
+			let types: Vec<&str> = match types {
+				Value::String(t) => vec![t.as_str()],
+				other => other.as_array().unwrap().iter().filter_map(|t| t.as_str()).collect(),
+			};
+			let matches = |t: &str| match t {
+				"object" => value.is_object(),
+				"array" => value.is_array(),
+				"string" => value.is_string(),
+				"integer" => value.is_i64() || value.is_u64(),
+				"boolean" => value.is_boolean(),
+				"null" => value.is_null(),
+				other => panic!("unsupported type {other}"),
+			};
+			if !types.iter().any(|t| matches(t)) {
+				return Err(format!("{path}: {value} is not {types:?}"));
+			}
+		}
+		if let Some(allowed) = schema.get("enum").and_then(|v| v.as_array())
+			&& !allowed.contains(value)
+		{
+			return Err(format!("{path}: {value} is not one of {allowed:?}"));
+		}
+		if let Some(min) = schema.get("minimum").and_then(|v| v.as_i64())
+			&& value.as_i64().is_some_and(|n| n < min)
+		{
+			return Err(format!("{path}: {value} is below {min}"));
+		}
+		for field in schema.get("required").and_then(|v| v.as_array()).into_iter().flatten() {
+			let field = field.as_str().unwrap();
+			if value.get(field).is_none() {
+				return Err(format!("{path}: missing required '{field}'"));
+			}
+		}
+		if let (Some(props), Some(object)) = (schema.get("properties").and_then(|v| v.as_object()), value.as_object()) {
+			for (key, sub) in props {
+				if let Some(field) = object.get(key) {
+					validate(sub, field, &format!("{path}.{key}"))?;
+				}
+			}
+		}
+		if let (Some(items), Some(array)) = (schema.get("items"), value.as_array()) {
+			for (idx, item) in array.iter().enumerate() {
+				validate(items, item, &format!("{path}[{idx}]"))?;
+			}
+		}
+		Ok(())
+	}
+
+	/// Representative output of each document, built by the code that prints it.
+	fn sample(name: &str) -> Vec<Value> {
+		use crate::config::{Config, ProfileConfig};
+		let mut cfg = Config { active_profile: Some("prod".to_string()), ..Default::default() };
+		cfg.profiles.insert(
+			"prod".to_string(),
+			ProfileConfig {
+				host: Some("https://ztnet.example.com".to_string()),
+				token: Some("t".to_string()),
+				session_cookie: Some("s".to_string()),
+				session_expires_at: Some(1_700_000_000),
+				..Default::default()
+			},
+		);
+		cfg.profiles.insert("lab".to_string(), ProfileConfig::default());
+		cfg.host_defaults.insert("https://ztnet.example.com".to_string(), "prod".to_string());
+
+		match name {
+			"auth-hosts" => {
+				let args = crate::cli::AuthHostsListArgs { sort: crate::cli::HostSortKey::Host, reverse: false };
+				vec![Value::Array(super::super::auth::host_listing(&cfg, &args))]
+			}
+			"auth-profiles" => {
+				let args = crate::cli::AuthProfilesListArgs { sort: crate::cli::ProfileSortKey::Name, reverse: false };
+				vec![
+					super::super::auth::profile_listing(&cfg, &args, 1_800_000_000),
+					super::super::auth::profile_listing(&Config::default(), &args, 0),
+				]
+			}
+			"detail-errors" => {
+				let err = CliError::InvalidArgument("boom".to_string());
+				let errors = [super::super::common::detail_error("abc", &err)];
+				vec![super::super::common::with_errors(&json!([{ "id": "def" }]), &errors)]
+			}
+			"export-hosts" => {
+				let members = [
+					json!({
+						"id": "m1",
+						"name": "Web 1",
+						"authorized": true,
+						"ipAssignments": ["10.0.0.1", "fd00::1"],
+					}),
+					json!({ "id": "m2", "authorized": false, "ipAssignments": ["10.0.0.2"] }),
+				];
+				let selection = super::super::export::IpSelection::default();
+				vec![Value::Array(super::super::export::host_records(&members, "zt.example", true, selection))]
+			}
+			"invite-results" => {
+				use super::super::org::{invite_result, parse_invite_csv};
+				let csv = "email,role\nann@example.com,admin\nbob@example.com\n";
+				let rows = parse_invite_csv(csv, crate::cli::OrgRole::User).unwrap();
+				vec![Value::Array(vec![
+					invite_result(&rows[0], "sent", None),
+					invite_result(&rows[1], "failed", Some("rejected".to_string())),
+				])]
+			}
+			"version-report" => {
+				use super::super::version::{ProbeStatus, server_report};
+				let results = (0..32)
+					.map(|i| match i % 3 {
+						0 => (ProbeStatus::Available, None),
+						1 => (ProbeStatus::Unavailable, None),
+						_ => (ProbeStatus::Unknown, Some("timed out".to_string())),
+					})
+					.collect();
+				vec![server_report("https://ztnet.example.com", results)]
+			}
+			other => panic!("no sample output for schema '{other}'"),
+		}
+	}
+
+	#[test]
+	fn command_outputs_match_their_schemas() {
+		for doc in DOCUMENTS {
+			let schema = (doc.schema)();
+			for value in sample(doc.name) {
+				if let Err(err) = validate(&schema, &value, "$") {
+					panic!("{} output does not match its schema: {err}\n{value:#}", doc.name);
+				}
+			}
+		}
+		let broken = json!([{ "host": "h", "default_profile": 1, "profiles": [] }]);
+		assert!(validate(&auth_hosts(), &broken, "$").unwrap_err().contains("$[0].default_profile"));
+	}
+}
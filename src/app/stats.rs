@@ -1,24 +1,14 @@
 use reqwest::Method;
 
-use crate::cli::{GlobalOpts, StatsCommand};
-use crate::context::resolve_effective_config;
+use crate::cli::StatsCommand;
 use crate::error::CliError;
-use crate::http::{ClientUi, HttpClient};
 
-use super::common::{load_config_store, print_human_or_machine};
+use super::common::print_human_or_machine;
+use super::middleware::CommandContext;
 
-pub(super) async fn run(global: &GlobalOpts, command: StatsCommand) -> Result<(), CliError> {
-	let (_config_path, cfg) = load_config_store()?;
-	let effective = resolve_effective_config(global, &cfg)?;
-
-	let client = HttpClient::new(
-		&effective.host,
-		effective.token.clone(),
-		effective.timeout,
-		effective.retries,
-		global.dry_run,
-		ClientUi::from_context(global, &effective),
-	)?;
+pub(super) async fn run(ctx: &CommandContext<'_>, command: StatsCommand) -> Result<(), CliError> {
+	let (global, effective) = (ctx.global, &ctx.effective);
+	let client = ctx.client()?;
 
 	match command {
 		StatsCommand::Get => {
@@ -1,27 +1,21 @@
 use reqwest::Method;
 use serde_json::{json, Value};
 
-use crate::cli::{GlobalOpts, TrpcCommand};
-use crate::context::resolve_effective_config;
+use crate::cli::TrpcCommand;
+use crate::config::Config;
+use crate::context::{canonical_host_key, EffectiveConfig};
 use crate::error::CliError;
-use crate::http::{ClientUi, HttpClient};
+use crate::host::normalize_host_input;
 use crate::output;
 
-use super::common::{load_config_store, print_human_or_machine};
+use super::common::{print_human_or_machine, read_input_file};
+use super::middleware::CommandContext;
 use super::trpc_client::cookie_from_effective;
+use super::trpc_script;
 
-pub(super) async fn run(global: &GlobalOpts, command: TrpcCommand) -> Result<(), CliError> {
-	let (_config_path, cfg) = load_config_store()?;
-	let effective = resolve_effective_config(global, &cfg)?;
-
-	let client = HttpClient::new(
-		&effective.host,
-		None,
-		effective.timeout,
-		effective.retries,
-		global.dry_run,
-		ClientUi::from_context(global, &effective),
-	)?;
+pub(super) async fn run(ctx: &CommandContext<'_>, command: TrpcCommand) -> Result<(), CliError> {
+	let (global, effective) = (ctx.global, &ctx.effective);
+	let client = ctx.anonymous_client()?;
 
 	match command {
 		TrpcCommand::List => {
@@ -42,12 +36,27 @@ pub(super) async fn run(global: &GlobalOpts, command: TrpcCommand) -> Result<(),
 			Ok(())
 		}
 		TrpcCommand::Call(args) => {
+			let cookie = if let Some(cookie) = args.cookie {
+				Some(cookie)
+			} else if let Some(path) = args.cookie_file {
+				Some(read_input_file(&path)?.trim().to_string())
+			} else if let Some(name) = args.cookie_from_profile.as_deref() {
+				Some(cookie_from_profile(&ctx.cfg, effective, name)?)
+			} else {
+				cookie_from_effective(effective)
+			};
+
+			if let Some(path) = args.procedure_file.as_deref() {
+				return trpc_script::run(ctx, path, cookie).await;
+			}
+			let procedure = args.procedure.unwrap_or_default();
+
 			let input = if let Some(input) = args.input {
 				serde_json::from_str::<Value>(&input).map_err(|err| {
 					CliError::InvalidArgument(format!("invalid --input json: {err}"))
 				})?
 			} else if let Some(path) = args.input_file {
-				let text = std::fs::read_to_string(&path)?;
+				let text = read_input_file(&path)?;
 				serde_json::from_str::<Value>(&text).map_err(|err| {
 					CliError::InvalidArgument(format!("invalid --input-file json: {err}"))
 				})?
@@ -55,14 +64,6 @@ pub(super) async fn run(global: &GlobalOpts, command: TrpcCommand) -> Result<(),
 				Value::Null
 			};
 
-			let cookie = if let Some(cookie) = args.cookie {
-				Some(cookie)
-			} else if let Some(path) = args.cookie_file {
-				Some(std::fs::read_to_string(&path)?.trim().to_string())
-			} else {
-				cookie_from_effective(&effective)
-			};
-
 			let mut headers = reqwest::header::HeaderMap::new();
 			if let Some(cookie) = cookie {
 				headers.insert(
@@ -74,7 +75,7 @@ pub(super) async fn run(global: &GlobalOpts, command: TrpcCommand) -> Result<(),
 			}
 
 			let body = json!({ "0": { "json": input } });
-			let path = format!("/api/trpc/{}?batch=1", args.procedure);
+			let path = format!("/api/trpc/{procedure}?batch=1");
 
 			let response = client
 				.request_json(Method::POST, &path, Some(body), headers, false)
@@ -85,3 +86,96 @@ pub(super) async fn run(global: &GlobalOpts, command: TrpcCommand) -> Result<(),
 		}
 	}
 }
+
+/// Borrows another profile's session for this call. Sessions are only valid on the host
+/// that issued them, so the profile must point at the same host as the current target.
+fn cookie_from_profile(cfg: &Config, effective: &EffectiveConfig, name: &str) -> Result<String, CliError> {
+	let profile = cfg
+		.profiles
+		.get(name)
+		.ok_or_else(|| CliError::InvalidArgument(format!("profile '{name}' not found")))?;
+
+	let profile_host = profile
+		.host
+		.as_deref()
+		.map(normalize_host_input)
+		.transpose()?
+		.ok_or_else(|| CliError::InvalidArgument(format!("profile '{name}' has no host configured")))?;
+	if canonical_host_key(&profile_host)? != canonical_host_key(&effective.host)? {
+		return Err(CliError::InvalidArgument(format!(
+			"profile '{name}' is configured for '{profile_host}', but the target host is '{}'",
+			effective.host
+		)));
+	}
+
+	let borrowed = EffectiveConfig {
+		session_cookie: profile.session_cookie.clone(),
+		device_cookie: profile.device_cookie.clone(),
+		..effective.clone()
+	};
+	cookie_from_effective(&borrowed).ok_or_else(|| {
+		CliError::InvalidArgument(format!(
+			"profile '{name}' has no stored session\n\n  Run: ztnet --profile {name} auth login"
+		))
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::config::ProfileConfig;
+
+	#[test]
+	fn cookie_from_profile_requires_same_host_and_session() {
+		let mut cfg = Config::default();
+		cfg.profiles.insert(
+			"other".to_string(),
+			ProfileConfig {
+				host: Some("https://ztnet.example.com".to_string()),
+				session_cookie: Some("abc".to_string()),
+				..Default::default()
+			},
+		);
+		cfg.profiles.insert(
+			"elsewhere".to_string(),
+			ProfileConfig {
+				host: Some("https://other.example.com".to_string()),
+				session_cookie: Some("abc".to_string()),
+				..Default::default()
+			},
+		);
+		cfg.profiles.insert(
+			"token-only".to_string(),
+			ProfileConfig {
+				host: Some("https://ztnet.example.com".to_string()),
+				token: Some("t".to_string()),
+				..Default::default()
+			},
+		);
+
+		let effective = EffectiveConfig {
+			profile: "default".to_string(),
+			host: "https://ztnet.example.com".to_string(),
+			token: None,
+			session_cookie: None,
+			device_cookie: None,
+			org: None,
+			network: None,
+			output: crate::cli::OutputFormat::Json,
+			timeout: std::time::Duration::from_secs(1),
+			retries: 0,
+			backoff: Default::default(),
+			allow_default_route: false,
+			read_only: false,
+			cert_fingerprint: None,
+			user_agent: String::new(),
+			concurrency: 1,
+		};
+
+		let cookie = cookie_from_profile(&cfg, &effective, "other").unwrap();
+		assert!(cookie.contains("next-auth.session-token=abc"));
+		assert!(cookie_from_profile(&cfg, &effective, "elsewhere").is_err());
+		assert!(cookie_from_profile(&cfg, &effective, "token-only").is_err());
+		assert!(cookie_from_profile(&cfg, &effective, "missing").is_err());
+	}
+}
@@ -1,3 +1,4 @@
+use std::io::Write;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::time::Duration;
 
@@ -7,10 +8,15 @@ use reqwest::{Method, StatusCode};
 use serde_json::{json, Value};
 use url::Url;
 
+use crate::cancel;
 use crate::context::EffectiveConfig;
-use crate::error::CliError;
-use crate::http::{print_host_autofix_banner, ClientUi};
+use crate::diagnose;
+use crate::error::{AuthMethod, CliError, FieldIssue};
+use crate::http::{command_prefix, print_host_autofix_banner, write_dry_run, ClientUi, RequestCompression};
 use crate::multi_base::{self, BaseCandidate};
+use crate::output;
+use crate::redact;
+use crate::tls;
 
 #[derive(Debug)]
 pub(super) struct TrpcClient {
@@ -21,6 +27,7 @@ pub(super) struct TrpcClient {
 	dry_run: bool,
 	client: reqwest::Client,
 	cookie: Option<String>,
+	compression: RequestCompression,
 	ui: ClientUi,
 }
 
@@ -32,9 +39,11 @@ impl TrpcClient {
 		dry_run: bool,
 		ui: ClientUi,
 	) -> Result<Self, CliError> {
-		let bases = multi_base::build_base_candidates(base_url)?;
+		let (base_url, client) = ui
+			.connect
+			.build_client(base_url, reqwest::Client::builder().timeout(timeout))?;
+		let bases = multi_base::build_base_candidates(&base_url)?;
 
-		let client = reqwest::Client::builder().timeout(timeout).build()?;
 		Ok(Self {
 			bases,
 			active_base: AtomicUsize::new(0),
@@ -43,6 +52,7 @@ impl TrpcClient {
 			dry_run,
 			client,
 			cookie: None,
+			compression: RequestCompression::new(&ui.connect),
 			ui,
 		})
 	}
@@ -73,13 +83,15 @@ impl TrpcClient {
 			Some(serde_json::to_string(&json!({ "json": input }))?)
 		};
 
-		if self.dry_run {
+		// With a session, reads still run under --dry-run so composite flows can resolve IDs and
+		// preview the exact mutation they would send; only writes are intercepted.
+		if self.dry_run && self.cookie.is_none() {
 			let base_idx = self.active_base.load(Ordering::Relaxed);
 			let mut url = self.build_url_for_base(base_idx, &path)?;
 			if let Some(input) = input_param.as_deref() {
 				url.query_pairs_mut().append_pair("input", input);
 			}
-			print_dry_run_no_body(&Method::GET, &url, &headers);
+			write_dry_run(&mut output::writer(), &Method::GET, &url, None, &headers, None)?;
 			return Err(CliError::DryRunPrinted);
 		}
 
@@ -101,9 +113,22 @@ impl TrpcClient {
 	}
 
 	pub(super) async fn mutation(&self, procedure: &str, input: Value) -> Result<Value, CliError> {
+		self.mutation_with_meta(procedure, input, None).await
+	}
+
+	/// A mutation whose input carries superjson `meta`, e.g. from [`date_meta`].
+	pub(super) async fn mutation_with_meta(
+		&self,
+		procedure: &str,
+		input: Value,
+		meta: Option<Value>,
+	) -> Result<Value, CliError> {
 		let path = format!("api/trpc/{}?batch=1", procedure.trim());
 
-		let body = json!({ "0": { "json": input } });
+		let mut body = json!({ "0": { "json": input } });
+		if let Some(meta) = meta {
+			body["0"]["meta"] = meta;
+		}
 		let body_bytes = Bytes::from(serde_json::to_vec(&body)?);
 
 		let mut headers = HeaderMap::new();
@@ -122,9 +147,16 @@ impl TrpcClient {
 		if self.dry_run {
 			let base_idx = self.active_base.load(Ordering::Relaxed);
 			let url = self.build_url_for_base(base_idx, &path)?;
-			print_dry_run(&Method::POST, &url, &headers, &body);
+			let mut preview = body.clone();
+			redact::json(&mut preview);
+			let mut out = output::writer();
+			writeln!(out, "# trpc mutation: {}", procedure.trim())?;
+			let preview = serde_json::to_vec(&preview)?;
+			write_dry_run(&mut out, &Method::POST, &url, None, &headers, Some(&preview))?;
 			return Err(CliError::DryRunPrinted);
 		}
+		self.ui
+			.check_writable(&Method::POST, &format!("tRPC mutation {}", procedure.trim()))?;
 
 		multi_base::try_with_base_fallback(
 			&self.bases,
@@ -138,6 +170,29 @@ impl TrpcClient {
 		.await
 	}
 
+	/// One mutation of a multi-step flow: under `--dry-run` the preview is printed and `None`
+	/// returned, so every following step is previewed as well.
+	pub(super) async fn call_step(&self, procedure: &str, input: Value) -> Result<Option<Value>, CliError> {
+		self.call_step_with_meta(procedure, input, None).await
+	}
+
+	pub(super) async fn call_step_with_meta(
+		&self,
+		procedure: &str,
+		input: Value,
+		meta: Option<Value>,
+	) -> Result<Option<Value>, CliError> {
+		match self.mutation_with_meta(procedure, input, meta).await {
+			Ok(value) => Ok(Some(value)),
+			Err(CliError::DryRunPrinted) => {
+				// Blank line between consecutive previews, in the same sink as the previews.
+				output::emit("", false)?;
+				Ok(None)
+			}
+			Err(err) => Err(err),
+		}
+	}
+
 	// Backwards-compat: keep `.call()` but treat it as a mutation.
 	pub(super) async fn call(&self, procedure: &str, input: Value) -> Result<Value, CliError> {
 		self.mutation(procedure, input).await
@@ -163,17 +218,23 @@ impl TrpcClient {
 		headers: &HeaderMap,
 		body_bytes: Bytes,
 	) -> Result<Value, CliError> {
-		let mut backoff = Duration::from_millis(200);
+		let gzipped = self.compression.encode(&body_bytes);
 		for attempt in 0..=self.retries {
 			let request = self
 				.client
 				.request(Method::POST, url.clone())
-				.headers(headers.clone())
-				.body(body_bytes.clone());
+				.headers(headers.clone());
+			let request = match gzipped {
+				Some(ref gzipped) => request.header("content-encoding", "gzip").body(gzipped.clone()),
+				None => request.body(body_bytes.clone()),
+			};
 
-			match request.send().await {
+			match cancel::guard(request.send()).await? {
 				Ok(resp) => {
 					let status = resp.status();
+					if gzipped.is_some() && self.compression.rejected(status) {
+						return Box::pin(self.call_with_url(url, headers, body_bytes)).await;
+					}
 					let retry_after = resp
 						.headers()
 						.get("retry-after")
@@ -184,11 +245,10 @@ impl TrpcClient {
 
 					if should_retry_status(status) && attempt < self.retries {
 						if status == StatusCode::TOO_MANY_REQUESTS {
-							tokio::time::sleep(retry_after.unwrap_or(backoff)).await;
+							cancel::sleep(retry_after.unwrap_or_else(|| self.ui.backoff.delay(attempt))).await?;
 						} else {
-							tokio::time::sleep(backoff).await;
+							cancel::sleep(self.ui.backoff.delay(attempt)).await?;
 						}
-						backoff = (backoff * 2).min(Duration::from_secs(5));
 						continue;
 					}
 
@@ -196,11 +256,10 @@ impl TrpcClient {
 				}
 				Err(err) => {
 					if attempt < self.retries && should_retry_error(&err) {
-						tokio::time::sleep(backoff).await;
-						backoff = (backoff * 2).min(Duration::from_secs(5));
+						cancel::sleep(self.ui.backoff.delay(attempt)).await?;
 						continue;
 					}
-					return Err(CliError::Request(err));
+					return Err(diagnose::connection_error(err, &url, &self.ui.connect).await);
 				}
 			}
 		}
@@ -209,14 +268,13 @@ impl TrpcClient {
 	}
 
 	async fn query_with_url(&self, url: Url, headers: &HeaderMap) -> Result<Value, CliError> {
-		let mut backoff = Duration::from_millis(200);
 		for attempt in 0..=self.retries {
 			let request = self
 				.client
 				.request(Method::GET, url.clone())
 				.headers(headers.clone());
 
-			match request.send().await {
+			match cancel::guard(request.send()).await? {
 				Ok(resp) => {
 					let status = resp.status();
 					let retry_after = resp
@@ -229,11 +287,10 @@ impl TrpcClient {
 
 					if should_retry_status(status) && attempt < self.retries {
 						if status == StatusCode::TOO_MANY_REQUESTS {
-							tokio::time::sleep(retry_after.unwrap_or(backoff)).await;
+							cancel::sleep(retry_after.unwrap_or_else(|| self.ui.backoff.delay(attempt))).await?;
 						} else {
-							tokio::time::sleep(backoff).await;
+							cancel::sleep(self.ui.backoff.delay(attempt)).await?;
 						}
-						backoff = (backoff * 2).min(Duration::from_secs(5));
 						continue;
 					}
 
@@ -241,11 +298,10 @@ impl TrpcClient {
 				}
 				Err(err) => {
 					if attempt < self.retries && should_retry_error(&err) {
-						tokio::time::sleep(backoff).await;
-						backoff = (backoff * 2).min(Duration::from_secs(5));
+						cancel::sleep(self.ui.backoff.delay(attempt)).await?;
 						continue;
 					}
-					return Err(CliError::Request(err));
+					return Err(diagnose::connection_error(err, &url, &self.ui.connect).await);
 				}
 			}
 		}
@@ -254,6 +310,17 @@ impl TrpcClient {
 	}
 }
 
+/// superjson metadata marking the dotted input `paths` (e.g. `params.expiresAt`) as `Date`s.
+/// Procedures validating `z.date()` reject a bare ISO string without it.
+pub(super) fn date_meta(paths: &[&str]) -> Option<Value> {
+	if paths.is_empty() {
+		return None;
+	}
+	let values: serde_json::Map<String, Value> =
+		paths.iter().map(|path| (path.to_string(), json!(["Date"]))).collect();
+	Some(json!({ "values": values }))
+}
+
 fn should_try_host_autofix(err: &CliError) -> bool {
 	if multi_base::should_try_host_autofix_basic(err) {
 		return true;
@@ -262,26 +329,6 @@ fn should_try_host_autofix(err: &CliError) -> bool {
 	matches!(err, CliError::HttpStatus { message, .. } if message == "invalid json response")
 }
 
-#[cfg(test)]
-mod tests {
-	use super::*;
-
-	#[test]
-	fn trpc_join_preserves_base_path_prefix() {
-		let client = TrpcClient::new(
-			"https://example.com/api",
-			Duration::from_secs(1),
-			0,
-			true,
-			ClientUi::default(),
-		)
-		.unwrap();
-
-		let url = client.build_url_for_base(0, "api/trpc/foo?batch=1").unwrap();
-		assert_eq!(url.as_str(), "https://example.com/api/api/trpc/foo?batch=1");
-	}
-}
-
 pub(super) fn cookie_from_effective(effective: &EffectiveConfig) -> Option<String> {
 	let session = effective.session_cookie.as_deref()?.trim();
 	if session.is_empty() {
@@ -304,7 +351,22 @@ pub(super) fn cookie_from_effective(effective: &EffectiveConfig) -> Option<Strin
 }
 
 pub(super) fn require_cookie_from_effective(effective: &EffectiveConfig) -> Result<String, CliError> {
-	cookie_from_effective(effective).ok_or(CliError::SessionRequired)
+	if let Some(cookie) = cookie_from_effective(effective) {
+		return Ok(cookie);
+	}
+
+	if effective.token.is_none() {
+		return Err(CliError::SessionRequired);
+	}
+
+	Err(CliError::AuthMethodMissing {
+		required: AuthMethod::Session,
+		available: AuthMethod::Token,
+		fix: format!(
+			"{} auth login --email <EMAIL> --password <PASSWORD>",
+			command_prefix(Some(&effective.profile))
+		),
+	})
 }
 
 fn parse_trpc_http_response(status: StatusCode, bytes: &[u8]) -> Result<Value, CliError> {
@@ -317,7 +379,7 @@ fn parse_trpc_http_response(status: StatusCode, bytes: &[u8]) -> Result<Value, C
 	let value = match parsed {
 		Ok(v) => v,
 		Err(_) => {
-			let body = String::from_utf8_lossy(bytes).to_string();
+			let body = redact::body(&String::from_utf8_lossy(bytes));
 			return Err(CliError::HttpStatus {
 				status,
 				message: "invalid json response".to_string(),
@@ -347,6 +409,19 @@ fn parse_trpc_envelope(http_status: StatusCode, value: Value) -> Result<Value, C
 	};
 
 	if let Some(err) = obj.get("error") {
+		// With the superjson transformer the error object sits under `json`.
+		let err = err.get("json").unwrap_or(err);
+		if let Some(issues) = validation_issues(err) {
+			return Err(CliError::InputRejected {
+				procedure: err
+					.get("data")
+					.and_then(|d| d.get("path"))
+					.and_then(|v| v.as_str())
+					.map(str::to_string),
+				issues,
+			});
+		}
+
 		let message = err
 			.get("message")
 			.and_then(|v| v.as_str())
@@ -389,45 +464,211 @@ fn parse_trpc_envelope(http_status: StatusCode, value: Value) -> Result<Value, C
 	Ok(data.clone())
 }
 
+/// Per-field messages of a rejected (Zod-validated) input: tRPC puts the Zod issues,
+/// JSON-encoded, in the error message, and ZTNet's error formatter adds a flattened
+/// `data.zodError` with top-level fields only.
+fn validation_issues(err: &Value) -> Option<Vec<FieldIssue>> {
+	let data = err.get("data");
+	if data.and_then(|d| d.get("code")).and_then(|v| v.as_str()) != Some("BAD_REQUEST") {
+		return None;
+	}
+
+	let parsed = err
+		.get("message")
+		.and_then(|v| v.as_str())
+		.and_then(|message| serde_json::from_str::<Vec<Value>>(message).ok());
+	if let Some(raw) = parsed {
+		let issues: Vec<FieldIssue> = raw
+			.iter()
+			.filter_map(|issue| {
+				let message = issue.get("message")?.as_str()?.to_string();
+				let path = issue.get("path").and_then(|p| p.as_array()).cloned().unwrap_or_default();
+				Some(field_issue(&path, message))
+			})
+			.collect();
+		if !issues.is_empty() {
+			return Some(issues);
+		}
+	}
+
+	let zod = data.and_then(|d| d.get("zodError"))?;
+	let mut issues = Vec::new();
+	for message in zod.get("formErrors").and_then(|v| v.as_array()).into_iter().flatten() {
+		issues.push(field_issue(&[], message.as_str().unwrap_or_default().to_string()));
+	}
+	for (field, messages) in zod.get("fieldErrors").and_then(|v| v.as_object()).into_iter().flatten() {
+		for message in messages.as_array().into_iter().flatten() {
+			issues.push(field_issue(&[Value::String(field.clone())], message.as_str().unwrap_or_default().to_string()));
+		}
+	}
+	(!issues.is_empty()).then_some(issues)
+}
+
+/// Input fields the CLI fills from a flag, by their trailing path (most specific first).
+const FIELD_FLAGS: &[(&str, &str)] = &[
+	("routes.target", "--destination"),
+	("routes.via", "--via"),
+	("routes", "--destination/--via"),
+	("ipAssignmentPools.ipRangeStart", "--start/--cidr"),
+	("ipAssignmentPools.ipRangeEnd", "--end/--cidr"),
+	("ipAssignmentPools", "--start/--end/--cidr"),
+	("dns.domain", "--domain"),
+	("dns.servers", "--servers"),
+	("multicastLimit", "--limit"),
+	("enableBroadcast", "--enable/--disable"),
+	("v6AssignMode.6plane", "--6plane/--no-6plane"),
+	("v6AssignMode.rfc4193", "--rfc4193/--no-rfc4193"),
+	("v6AssignMode.zt", "--zt/--no-zt"),
+	("activeBridge", "--allow-bridging/--deny-bridging"),
+	("noAutoAssignIps", "--auto-assign-ips/--no-auto-assign-ips"),
+	("ipAssignments", "--ip"),
+	("organizationId", "--org"),
+	("name", "--name"),
+	("description", "--description"),
+	("email", "--email"),
+];
+
+fn field_issue(path: &[Value], message: String) -> FieldIssue {
+	let mut rendered = String::new();
+	let mut named = Vec::new();
+	for segment in path {
+		match segment {
+			Value::Number(index) => rendered.push_str(&format!("[{index}]")),
+			other => {
+				let name = other.as_str().map(str::to_string).unwrap_or_else(|| other.to_string());
+				if !rendered.is_empty() {
+					rendered.push('.');
+				}
+				rendered.push_str(&name);
+				named.push(name);
+			}
+		}
+	}
+	let named = named.join(".");
+	let flag = FIELD_FLAGS
+		.iter()
+		.find(|(field, _)| named == *field || named.ends_with(&format!(".{field}")))
+		.map(|(_, flag)| *flag);
+	FieldIssue {
+		path: rendered,
+		message,
+		flag,
+	}
+}
+
 fn should_retry_status(status: StatusCode) -> bool {
 	status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
 }
 
 fn should_retry_error(err: &reqwest::Error) -> bool {
-	err.is_timeout() || err.is_connect() || err.is_request()
+	(err.is_timeout() || err.is_connect() || err.is_request()) && tls::pin_mismatch(err).is_none()
 }
 
-fn print_dry_run(method: &Method, url: &Url, headers: &HeaderMap, body: &Value) {
-	println!("{method} {url}");
+#[cfg(test)]
+mod tests {
+	use super::*;
 
-	for (name, value) in headers.iter() {
-		if name.as_str().eq_ignore_ascii_case("cookie") {
-			println!("{name}: REDACTED");
-			continue;
-		}
+	use crate::mock_server::{MockServer, Response};
 
-		if let Ok(value) = value.to_str() {
-			println!("{name}: {value}");
-		}
+	#[test]
+	fn trpc_join_preserves_base_path_prefix() {
+		let client = TrpcClient::new(
+			"https://example.com/api",
+			Duration::from_secs(1),
+			0,
+			true,
+			ClientUi::default(),
+		)
+		.unwrap();
+
+		let url = client.build_url_for_base(0, "api/trpc/foo?batch=1").unwrap();
+		assert_eq!(url.as_str(), "https://example.com/api/api/trpc/foo?batch=1");
 	}
 
-	if let Ok(pretty) = serde_json::to_string_pretty(body) {
-		println!();
-		println!("{pretty}");
+	#[tokio::test]
+	async fn dry_run_reads_need_a_session_to_reach_the_panel() {
+		let panel = MockServer::start(|_| Response::trpc(&json!({ "id": "n1" }))).await;
+		let client = |cookie: Option<&str>| {
+			TrpcClient::new(&panel.url, Duration::from_secs(5), 0, true, ClientUi::default())
+				.unwrap()
+				.with_cookie(cookie.map(str::to_string))
+		};
+
+		let err = client(None).query("network.getUserNetworks", json!({})).await.unwrap_err();
+		assert!(matches!(err, CliError::DryRunPrinted));
+		assert!(panel.requests().is_empty());
+
+		let value = client(Some("session=abc")).query("network.getUserNetworks", json!({})).await.unwrap();
+		assert_eq!(value, json!({ "id": "n1" }));
+		assert_eq!(panel.requests().len(), 1);
 	}
-}
 
-fn print_dry_run_no_body(method: &Method, url: &Url, headers: &HeaderMap) {
-	println!("{method} {url}");
+	#[test]
+	fn zod_errors_become_field_issues_with_flag_hints() {
+		let issues = json!([
+			{ "code": "invalid_string", "message": "invalid CIDR", "path": ["updateParams", "routes", 0, "target"] },
+			{ "code": "custom", "message": "required", "path": ["nwid"] },
+		]);
+		let body = json!([{ "error": { "json": {
+			"message": issues.to_string(),
+			"code": -32600,
+			"data": { "code": "BAD_REQUEST", "httpStatus": 400, "path": "network.managedRoutes" },
+		} } }]);
+		let err = parse_trpc_envelope(StatusCode::BAD_REQUEST, body).unwrap_err();
+		assert_eq!(err.exit_code(), 5);
+		assert_eq!(
+			err.to_string(),
+			"the server rejected the input to network.managedRoutes:\n  updateParams.routes[0].target: invalid CIDR (check --destination)\n  nwid: required"
+		);
 
-	for (name, value) in headers.iter() {
-		if name.as_str().eq_ignore_ascii_case("cookie") {
-			println!("{name}: REDACTED");
-			continue;
+		let flattened = json!({ "error": {
+			"message": "bad input",
+			"data": { "code": "BAD_REQUEST", "zodError": { "formErrors": [], "fieldErrors": { "email": ["Invalid email"] } } },
+		} });
+		match parse_trpc_envelope(StatusCode::BAD_REQUEST, flattened).unwrap_err() {
+			CliError::InputRejected { issues, .. } => {
+				assert_eq!(issues[0].path, "email");
+				assert_eq!(issues[0].flag, Some("--email"));
+			}
+			other => panic!("unexpected error: {other}"),
 		}
 
-		if let Ok(value) = value.to_str() {
-			println!("{name}: {value}");
+		let other = json!({ "error": { "message": "not found", "data": { "code": "NOT_FOUND", "httpStatus": 404 } } });
+		assert!(matches!(parse_trpc_envelope(StatusCode::OK, other).unwrap_err(), CliError::HttpStatus { .. }));
+	}
+
+	#[test]
+	fn require_cookie_reports_missing_session_when_only_token_is_configured() {
+		let effective = EffectiveConfig {
+			profile: "prod".to_string(),
+			host: "https://example.com".to_string(),
+			token: Some("token".to_string()),
+			session_cookie: None,
+			device_cookie: None,
+			org: None,
+			network: None,
+			output: crate::cli::OutputFormat::Json,
+			timeout: Duration::from_secs(1),
+			retries: 0,
+			backoff: Default::default(),
+			allow_default_route: false,
+			read_only: false,
+			cert_fingerprint: None,
+			user_agent: String::new(),
+			concurrency: 1,
+		};
+
+		match require_cookie_from_effective(&effective).unwrap_err() {
+			CliError::AuthMethodMissing {
+				required,
+				available,
+				fix,
+			} => {
+				assert_eq!(required, AuthMethod::Session);
+				assert_eq!(available, AuthMethod::Token);
+				assert!(fix.starts_with("ztnet --profile prod auth login"));
+			}
+			other => panic!("expected AuthMethodMissing, got {other:?}"),
 		}
 	}
 }
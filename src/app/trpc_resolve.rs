@@ -27,10 +27,8 @@ pub(super) async fn resolve_org_id(trpc: &TrpcClient, org: &str) -> Result<Strin
 	for item in items {
 		let id = item.get("id").and_then(|v| v.as_str());
 		let name = item.get("orgName").and_then(|v| v.as_str());
-		if let (Some(id), Some(name)) = (id, name) {
-			if name.eq_ignore_ascii_case(org) {
-				matches.push(id.to_string());
-			}
+		if let (Some(id), Some(name)) = (id, name) && name.eq_ignore_ascii_case(org) {
+			matches.push(id.to_string());
 		}
 	}
 
@@ -70,15 +68,11 @@ pub(super) async fn resolve_personal_network_id(
 	for item in items {
 		let id = item.get("nwid").and_then(|v| v.as_str());
 		let name = item.get("name").and_then(|v| v.as_str());
-		if let Some(id) = id {
-			if id == network {
-				return Ok(id.to_string());
-			}
+		if let Some(id) = id && id == network {
+			return Ok(id.to_string());
 		}
-		if let (Some(id), Some(name)) = (id, name) {
-			if name.eq_ignore_ascii_case(network) {
-				matches.push(id.to_string());
-			}
+		if let (Some(id), Some(name)) = (id, name) && name.eq_ignore_ascii_case(network) {
+			matches.push(id.to_string());
 		}
 	}
 
@@ -112,12 +106,10 @@ pub(super) async fn resolve_network_org_id(
 
 	match inferred {
 		Some(inferred) => {
-			if let Some(explicit) = explicit {
-				if explicit != inferred {
-					return Err(CliError::InvalidArgument(
-						"network belongs to a different org than --org".to_string(),
-					));
-				}
+			if let Some(explicit) = explicit && explicit != inferred {
+				return Err(CliError::InvalidArgument(
+					"network belongs to a different org than --org".to_string(),
+				));
 			}
 			Ok(Some(inferred))
 		}
@@ -125,7 +117,7 @@ pub(super) async fn resolve_network_org_id(
 	}
 }
 
-fn is_network_id(value: &str) -> bool {
+pub(super) fn is_network_id(value: &str) -> bool {
 	value.len() == 16 && value.chars().all(|c| c.is_ascii_hexdigit())
 }
 
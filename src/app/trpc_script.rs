@@ -0,0 +1,246 @@
+//! `trpc call --procedure-file`: a list of tRPC calls run in order, where each step's
+//! input may reference the output of earlier steps as `{{steps.<id>.<path>}}`.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::cli::OutputFormat;
+use crate::error::CliError;
+use crate::http::ClientUi;
+use crate::output;
+
+use super::common::read_input_file;
+use super::middleware::CommandContext;
+use super::trpc_client::TrpcClient;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Script {
+	steps: Vec<Step>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Step {
+	/// Name later steps use to reference this one; defaults to the 1-based position.
+	id: Option<String>,
+	procedure: String,
+	#[serde(default)]
+	input: Value,
+	/// Send as a query (GET) instead of a mutation.
+	#[serde(default)]
+	query: bool,
+	/// Print the request instead of sending it.
+	#[serde(default)]
+	dry_run: bool,
+	/// Record a failure and carry on with the next step instead of stopping.
+	#[serde(default)]
+	continue_on_error: bool,
+}
+
+pub(super) async fn run(ctx: &CommandContext<'_>, path: &Path, cookie: Option<String>) -> Result<(), CliError> {
+	let (global, effective) = (ctx.global, &ctx.effective);
+	let script = load(path)?;
+
+	let client = |dry_run: bool| -> Result<TrpcClient, CliError> {
+		Ok(TrpcClient::new(
+			&effective.host,
+			effective.timeout,
+			effective.retries,
+			dry_run,
+			ClientUi::from_context(global, effective),
+		)?
+		.with_cookie(cookie.clone()))
+	};
+	let live = client(global.dry_run)?;
+	let preview = client(true)?;
+
+	// `None` marks a step that was previewed or failed, so it has no output to reference.
+	let mut outputs: HashMap<String, Option<Value>> = HashMap::new();
+	let mut rows = Vec::with_capacity(script.steps.len());
+	let mut failed = 0;
+	let mut stopped = false;
+	for (index, step) in script.steps.iter().enumerate() {
+		let id = step_id(step, index);
+		if stopped {
+			rows.push(json!({ "id": id, "procedure": step.procedure, "status": "skipped" }));
+			continue;
+		}
+
+		let previewed = step.dry_run || global.dry_run;
+		let client = if step.dry_run { &preview } else { &live };
+		let result = match substitute(&step.input, &outputs, previewed) {
+			Ok(input) if step.query => client.query(&step.procedure, input).await.map(Some),
+			Ok(input) => client.call_step(&step.procedure, input).await,
+			Err(err) => Err(err),
+		};
+		match result {
+			Ok(Some(value)) => {
+				rows.push(json!({ "id": id, "procedure": step.procedure, "status": "ok", "output": value }));
+				outputs.insert(id, Some(value));
+			}
+			Ok(None) | Err(CliError::DryRunPrinted) => {
+				rows.push(json!({ "id": id, "procedure": step.procedure, "status": "dry-run" }));
+				outputs.insert(id, None);
+			}
+			Err(CliError::Interrupted) => return Err(CliError::Interrupted),
+			Err(err) => {
+				failed += 1;
+				stopped = !step.continue_on_error;
+				if !global.quiet {
+					eprintln!("step '{id}' ({}) failed: {err}", step.procedure);
+				}
+				rows.push(json!({
+					"id": id,
+					"procedure": step.procedure,
+					"status": "failed",
+					"error": err.to_string(),
+				}));
+				outputs.insert(id, None);
+			}
+		}
+	}
+
+	if matches!(effective.output, OutputFormat::Table) {
+		output::print_columns(&rows, &["id", "procedure", "status", "error"])?;
+	} else {
+		output::print_value(&json!({ "steps": rows }), effective.output, global.no_color)?;
+	}
+	if failed > 0 {
+		return Err(CliError::PartialFailure {
+			failed,
+			total: script.steps.len(),
+		});
+	}
+	Ok(())
+}
+
+fn load(path: &Path) -> Result<Script, CliError> {
+	let text = read_input_file(path)?;
+	// YAML is a superset of JSON, so one parser covers both.
+	let script: Script = serde_yaml::from_str(&text).map_err(|err| {
+		CliError::InvalidArgument(format!("invalid procedure file {}: {err}", path.display()))
+	})?;
+	if script.steps.is_empty() {
+		return Err(CliError::InvalidArgument(format!("procedure file {} has no steps", path.display())));
+	}
+
+	let mut seen = HashMap::new();
+	for (index, step) in script.steps.iter().enumerate() {
+		if let Some(previous) = seen.insert(step_id(step, index), index) {
+			return Err(CliError::InvalidArgument(format!(
+				"procedure file {}: steps {} and {} share the id '{}'",
+				path.display(),
+				previous + 1,
+				index + 1,
+				step_id(step, index)
+			)));
+		}
+	}
+	Ok(script)
+}
+
+fn step_id(step: &Step, index: usize) -> String {
+	step.id.clone().unwrap_or_else(|| (index + 1).to_string())
+}
+
+/// Replaces `{{steps.<id>.<path>}}` placeholders in every string of `input`. A string that
+/// is exactly one placeholder takes the referenced value as is (numbers, objects, ...);
+/// placeholders inside longer strings are spliced in as text. In a `preview`, references to
+/// steps without output (themselves previewed) are left as written.
+fn substitute(input: &Value, outputs: &HashMap<String, Option<Value>>, preview: bool) -> Result<Value, CliError> {
+	match input {
+		Value::String(text) => {
+			let trimmed = text.trim();
+			if let Some(expr) = trimmed.strip_prefix("{{").and_then(|rest| rest.strip_suffix("}}"))
+				&& !expr.contains("{{")
+			{
+				return lookup(expr, outputs, preview);
+			}
+
+			let mut out = String::with_capacity(text.len());
+			let mut rest = text.as_str();
+			while let Some(start) = rest.find("{{") {
+				let end = rest[start..].find("}}").ok_or_else(|| {
+					CliError::InvalidArgument(format!("unterminated placeholder in \"{text}\""))
+				})?;
+				out.push_str(&rest[..start]);
+				match lookup(&rest[start + 2..start + end], outputs, preview)? {
+					Value::String(value) => out.push_str(&value),
+					value => out.push_str(&value.to_string()),
+				}
+				rest = &rest[start + end + 2..];
+			}
+			out.push_str(rest);
+			Ok(Value::String(out))
+		}
+		Value::Array(items) => items
+			.iter()
+			.map(|item| substitute(item, outputs, preview))
+			.collect::<Result<_, _>>()
+			.map(Value::Array),
+		Value::Object(map) => map
+			.iter()
+			.map(|(key, value)| Ok((key.clone(), substitute(value, outputs, preview)?)))
+			.collect::<Result<_, CliError>>()
+			.map(Value::Object),
+		other => Ok(other.clone()),
+	}
+}
+
+fn lookup(expr: &str, outputs: &HashMap<String, Option<Value>>, preview: bool) -> Result<Value, CliError> {
+	let expr = expr.trim();
+	let invalid = |reason: String| CliError::InvalidArgument(format!("{{{{{expr}}}}}: {reason}"));
+	let mut parts = expr.split('.');
+	if parts.next() != Some("steps") {
+		return Err(invalid("placeholders must start with steps.<id>".to_string()));
+	}
+	let id = parts.next().filter(|id| !id.is_empty()).ok_or_else(|| invalid("missing step id".to_string()))?;
+	let output = match outputs.get(id) {
+		Some(Some(output)) => output,
+		Some(None) if preview => return Ok(Value::String(format!("{{{{{expr}}}}}"))),
+		Some(None) => return Err(invalid(format!("step '{id}' has no output (dry run or failed)"))),
+		None => return Err(invalid(format!("no earlier step with id '{id}'"))),
+	};
+
+	let mut current = output;
+	for part in parts {
+		let next = match current {
+			Value::Array(items) => part.parse::<usize>().ok().and_then(|i| items.get(i)),
+			Value::Object(map) => map.get(part),
+			_ => None,
+		};
+		current = next.ok_or_else(|| invalid(format!("output of step '{id}' has no '{part}'")))?;
+	}
+	Ok(current.clone())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn placeholders_keep_types_or_splice_as_text() {
+		let mut outputs = HashMap::new();
+		outputs.insert("create".to_string(), Some(json!({ "id": "abc", "routes": [{ "via": 3 }] })));
+		outputs.insert("preview".to_string(), None);
+
+		let input = json!({
+			"nwid": "{{ steps.create.id }}",
+			"via": "{{steps.create.routes.0.via}}",
+			"label": "net-{{steps.create.id}}-{{steps.create.routes.0.via}}",
+			"keep": [1, true],
+		});
+		let out = substitute(&input, &outputs, false).unwrap();
+		assert_eq!(out, json!({ "nwid": "abc", "via": 3, "label": "net-abc-3", "keep": [1, true] }));
+
+		assert!(substitute(&json!("{{steps.preview.id}}"), &outputs, false).is_err());
+		assert_eq!(substitute(&json!("{{steps.preview.id}}"), &outputs, true).unwrap(), "{{steps.preview.id}}");
+		assert!(substitute(&json!("{{steps.missing.id}}"), &outputs, true).is_err());
+		assert!(substitute(&json!("{{steps.create.nope}}"), &outputs, false).is_err());
+		assert!(substitute(&json!("x {{steps.create.id"), &outputs, false).is_err());
+	}
+}
@@ -1,18 +1,18 @@
 use reqwest::Method;
 use serde_json::Value;
 
-use crate::cli::{GlobalOpts, OutputFormat, UserCommand};
+use crate::cli::{OutputFormat, UserCommand};
 use crate::config;
-use crate::context::resolve_effective_config;
 use crate::error::CliError;
-use crate::http::{ClientUi, HttpClient};
 use crate::output;
 
-use super::common::{load_config_store, print_kv};
+use super::common::print_kv;
+use super::middleware::CommandContext;
 
-pub(super) async fn run(global: &GlobalOpts, command: UserCommand) -> Result<(), CliError> {
-	let (config_path, mut cfg) = load_config_store()?;
-	let effective = resolve_effective_config(global, &cfg)?;
+pub(super) async fn run(ctx: &CommandContext<'_>, command: UserCommand) -> Result<(), CliError> {
+	let (global, effective) = (ctx.global, &ctx.effective);
+	let config_path = &ctx.config_path;
+	let mut cfg = ctx.cfg.clone();
 
 	match command {
 		UserCommand::Create(args) => {
@@ -29,14 +29,7 @@ pub(super) async fn run(global: &GlobalOpts, command: UserCommand) -> Result<(),
 				body.insert("generateApiToken".to_string(), Value::Bool(true));
 			}
 
-			let client = HttpClient::new(
-				&effective.host,
-				effective.token.clone(),
-				effective.timeout,
-				effective.retries,
-				global.dry_run,
-				ClientUi::from_context(global, &effective),
-			)?;
+			let client = ctx.client()?;
 
 			let include_auth = !args.no_auth && effective.token.is_some();
 			let response = client
@@ -63,7 +56,7 @@ pub(super) async fn run(global: &GlobalOpts, command: UserCommand) -> Result<(),
 			if args.store_token {
 				let token = api_token.clone().expect("checked above");
 				cfg.profile_mut(&effective.profile).token = Some(token);
-				config::save_config(&config_path, &cfg)?;
+				config::save_config(config_path, &cfg)?;
 				if !global.quiet {
 					eprintln!("Token stored in profile '{}'.", effective.profile);
 				}
@@ -75,12 +68,7 @@ pub(super) async fn run(global: &GlobalOpts, command: UserCommand) -> Result<(),
 			}
 
 			if matches!(effective.output, OutputFormat::Table) {
-				if let Some(user) = response.get("user") {
-					print_kv(user);
-				} else {
-					print_kv(&response);
-				}
-				return Ok(());
+				return print_kv(response.get("user").unwrap_or(&response));
 			}
 
 			output::print_value(&response, effective.output, global.no_color)?;
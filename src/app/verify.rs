@@ -0,0 +1,42 @@
+use serde_json::json;
+
+use crate::cli::{GlobalOpts, OutputFormat, VerifyArgs};
+use crate::error::CliError;
+use crate::output;
+use crate::sign::{self, PublicKey};
+
+use super::common::read_input_file;
+
+pub(super) fn run(global: &GlobalOpts, args: VerifyArgs) -> Result<(), CliError> {
+	let key = match (&args.pubkey, &args.pubkey_file) {
+		(Some(key), _) => key.clone(),
+		(None, Some(path)) => read_input_file(path)?,
+		(None, None) => return Err(CliError::InvalidArgument("pass --pubkey or --pubkey-file".to_string())),
+	};
+	let key = PublicKey::parse(&key)?;
+	let signature_path = args.signature.clone().unwrap_or_else(|| sign::signature_path(&args.file));
+	let minisig = std::fs::read_to_string(&signature_path).map_err(|err| {
+		CliError::InvalidArgument(format!("cannot read signature {}: {err}", signature_path.display()))
+	})?;
+	let data = std::fs::read(&args.file)?;
+	let trusted = key.verify(&data, &minisig)?;
+
+	let format = global.output.unwrap_or(if global.json {
+		OutputFormat::Json
+	} else {
+		OutputFormat::Table
+	});
+	if matches!(format, OutputFormat::Table) {
+		return output::emit(
+			&format!("Signature and trusted comment verified.\ntrusted comment: {trusted}"),
+			true,
+		);
+	}
+	let report = json!({
+		"file": args.file.display().to_string(),
+		"signature": signature_path.display().to_string(),
+		"verified": true,
+		"trusted_comment": trusted,
+	});
+	output::print_value(&report, format, global.no_color)
+}
@@ -0,0 +1,311 @@
+use reqwest::Method;
+use serde_json::{Value, json};
+
+use crate::cli::{GlobalOpts, OutputFormat, VersionArgs};
+use crate::error::CliError;
+use crate::http::{ClientUi, HttpClient};
+
+use super::middleware::CommandContext;
+
+const CLI_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[derive(Debug, Clone, Copy)]
+enum Probe {
+	Rest(&'static str),
+	Trpc(&'static str),
+}
+
+impl Probe {
+	fn path(self) -> String {
+		match self {
+			Probe::Rest(path) => path.to_string(),
+			Probe::Trpc(procedure) => format!("/api/trpc/{procedure}"),
+		}
+	}
+
+	fn label(self) -> String {
+		match self {
+			Probe::Rest(path) => format!("GET {path}"),
+			Probe::Trpc(procedure) => format!("trpc {procedure}"),
+		}
+	}
+}
+
+struct CompatEntry {
+	feature: &'static str,
+	commands: &'static str,
+	probe: Probe,
+	// Earliest ZTNet release known to ship the probed endpoint.
+	since: &'static str,
+}
+
+// Probes are sent without credentials: unknown endpoints answer 404, while
+// known ones answer 401/400/405 before any handler runs.
+const COMPAT_TABLE: &[CompatEntry] = &[
+	CompatEntry {
+		feature: "REST network API",
+		commands: "network, member",
+		probe: Probe::Rest("/api/v1/network"),
+		since: "0.5.0",
+	},
+	CompatEntry {
+		feature: "REST organization API",
+		commands: "org list/get, network --org, member --org",
+		probe: Probe::Rest("/api/v1/org"),
+		since: "0.6.0",
+	},
+	CompatEntry {
+		feature: "Managed routes",
+		commands: "network routes",
+		probe: Probe::Trpc("network.managedRoutes"),
+		since: "0.4.0",
+	},
+	CompatEntry {
+		feature: "Flow rules",
+		commands: "network flow-rules",
+		probe: Probe::Trpc("network.getFlowRule"),
+		since: "0.4.0",
+	},
+	CompatEntry {
+		feature: "Member tags",
+		commands: "member tags",
+		probe: Probe::Trpc("networkMember.Tags"),
+		since: "0.4.0",
+	},
+	CompatEntry {
+		feature: "Organizations",
+		commands: "org users, org invite",
+		probe: Probe::Trpc("org.getOrgIdbyUserid"),
+		since: "0.5.0",
+	},
+	CompatEntry {
+		feature: "Organization webhooks",
+		commands: "org webhooks",
+		probe: Probe::Trpc("org.getOrgWebhooks"),
+		since: "0.5.0",
+	},
+	CompatEntry {
+		feature: "Organization settings",
+		commands: "org settings",
+		probe: Probe::Trpc("org.getOrganizationSettings"),
+		since: "0.6.0",
+	},
+	CompatEntry {
+		feature: "Mail templates",
+		commands: "admin mail templates",
+		probe: Probe::Trpc("admin.getMailTemplates"),
+		since: "0.5.0",
+	},
+	CompatEntry {
+		feature: "Backups",
+		commands: "admin backup",
+		probe: Probe::Trpc("admin.listBackups"),
+		since: "0.7.0",
+	},
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ProbeStatus {
+	Available,
+	Unavailable,
+	Unknown,
+}
+
+impl ProbeStatus {
+	fn as_str(self) -> &'static str {
+		match self {
+			ProbeStatus::Available => "available",
+			ProbeStatus::Unavailable => "unavailable",
+			ProbeStatus::Unknown => "unknown",
+		}
+	}
+}
+
+pub(super) async fn run(global: &GlobalOpts, args: VersionArgs) -> Result<(), CliError> {
+	if !args.check_server {
+		let format = global.output.unwrap_or(if global.json {
+			OutputFormat::Json
+		} else {
+			OutputFormat::Table
+		});
+		if matches!(format, OutputFormat::Table) {
+			return crate::output::emit(&format!("ztnet {CLI_VERSION}"), false);
+		}
+		let value = json!({ "cli_version": CLI_VERSION });
+		return crate::output::print_value(&value, format, global.no_color);
+	}
+
+	let ctx = CommandContext::load(global)?;
+	let effective = &ctx.effective;
+
+	let client = HttpClient::new(
+		&effective.host,
+		None,
+		effective.timeout,
+		0,
+		global.dry_run,
+		ClientUi::from_context(global, effective),
+	)?;
+
+	let mut results = Vec::with_capacity(COMPAT_TABLE.len());
+	let mut first_error = None;
+	for entry in COMPAT_TABLE {
+		results.push(match probe(&client, entry.probe).await {
+			Ok(status) => (status, None),
+			Err(CliError::DryRunPrinted) => return Err(CliError::DryRunPrinted),
+			Err(err) => {
+				let message = err.to_string();
+				first_error.get_or_insert(err);
+				(ProbeStatus::Unknown, Some(message))
+			}
+		});
+	}
+
+	if results.iter().all(|(s, _)| *s == ProbeStatus::Unknown)
+		&& let Some(err) = first_error
+	{
+		return Err(err);
+	}
+
+	let report = server_report(&effective.host, results);
+	if matches!(effective.output, OutputFormat::Table) {
+		return crate::output::emit(&render_report(&report), true);
+	}
+	crate::output::print_value(&report, effective.output, global.no_color)
+}
+
+/// The `--check-server` document: one row per [`COMPAT_TABLE`] entry, in table order, from
+/// its probe status and error, plus the server version those statuses imply.
+pub(super) fn server_report(host: &str, results: Vec<(ProbeStatus, Option<String>)>) -> Value {
+	let mut features = Vec::with_capacity(COMPAT_TABLE.len());
+	let mut statuses = Vec::with_capacity(COMPAT_TABLE.len());
+	for (entry, (status, error)) in COMPAT_TABLE.iter().zip(results) {
+		statuses.push(status);
+
+		let mut row = json!({
+			"feature": entry.feature,
+			"commands": entry.commands,
+			"probe": entry.probe.label(),
+			"since": entry.since,
+			"status": status.as_str(),
+		});
+		if let Some(error) = error {
+			row["error"] = Value::String(error);
+		}
+		features.push(row);
+	}
+
+	let server_version = infer_server_version(&statuses);
+	json!({
+		"cli_version": CLI_VERSION,
+		"server": {
+			"host": host,
+			"version": server_version.as_ref().map(|v| format!(">= {v}")),
+			"version_source": "inferred from available endpoints",
+		},
+		"features": features,
+	})
+}
+
+async fn probe(client: &HttpClient, probe: Probe) -> Result<ProbeStatus, CliError> {
+	let path = probe.path();
+	match client
+		.request_bytes(Method::GET, &path, None, Default::default(), false, None)
+		.await
+	{
+		Ok(_) => Ok(ProbeStatus::Available),
+		Err(CliError::HttpStatus { status, .. }) => Ok(classify_status(status.as_u16())),
+		Err(err) => Err(err),
+	}
+}
+
+fn classify_status(status: u16) -> ProbeStatus {
+	match status {
+		404 => ProbeStatus::Unavailable,
+		500..=599 => ProbeStatus::Unknown,
+		_ => ProbeStatus::Available,
+	}
+}
+
+fn infer_server_version(statuses: &[ProbeStatus]) -> Option<String> {
+	COMPAT_TABLE
+		.iter()
+		.zip(statuses)
+		.filter(|(_, status)| **status == ProbeStatus::Available)
+		.filter_map(|(entry, _)| parse_version(entry.since).map(|v| (v, entry.since)))
+		.max_by_key(|(v, _)| *v)
+		.map(|(_, since)| since.to_string())
+}
+
+fn parse_version(value: &str) -> Option<(u32, u32, u32)> {
+	let mut parts = value.trim().trim_start_matches('v').split('.');
+	let major = parts.next()?.parse().ok()?;
+	let minor = parts.next().unwrap_or("0").parse().ok()?;
+	let patch = parts.next().unwrap_or("0").parse().ok()?;
+	Some((major, minor, patch))
+}
+
+fn render_report(report: &Value) -> String {
+	let mut lines = vec![
+		format!("ztnet {CLI_VERSION}"),
+		format!("server: {}", report["server"]["host"].as_str().unwrap_or("-")),
+		match report["server"]["version"].as_str() {
+			Some(version) => format!("server version: {version} (inferred from available endpoints)"),
+			None => "server version: unknown".to_string(),
+		},
+		String::new(),
+	];
+
+	let Some(features) = report["features"].as_array() else {
+		return lines.join("\n");
+	};
+	let width = features
+		.iter()
+		.filter_map(|f| f["feature"].as_str())
+		.map(str::len)
+		.max()
+		.unwrap_or(0);
+	for feature in features {
+		let name = feature["feature"].as_str().unwrap_or("-");
+		let status = feature["status"].as_str().unwrap_or("-");
+		let commands = feature["commands"].as_str().unwrap_or("-");
+		lines.push(format!("{name:<width$}  {status:<11}  {commands}"));
+	}
+	lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn classify_status_treats_only_404_as_unavailable() {
+		assert_eq!(classify_status(404), ProbeStatus::Unavailable);
+		assert_eq!(classify_status(401), ProbeStatus::Available);
+		assert_eq!(classify_status(405), ProbeStatus::Available);
+		assert_eq!(classify_status(502), ProbeStatus::Unknown);
+	}
+
+	#[test]
+	fn infer_server_version_uses_newest_available_feature() {
+		let mut statuses = vec![ProbeStatus::Available; COMPAT_TABLE.len()];
+		let backups = COMPAT_TABLE
+			.iter()
+			.position(|e| e.feature == "Backups")
+			.unwrap();
+		assert_eq!(infer_server_version(&statuses).as_deref(), Some("0.7.0"));
+
+		statuses[backups] = ProbeStatus::Unavailable;
+		assert_eq!(infer_server_version(&statuses).as_deref(), Some("0.6.0"));
+
+		let none = vec![ProbeStatus::Unavailable; COMPAT_TABLE.len()];
+		assert_eq!(infer_server_version(&none), None);
+	}
+
+	#[test]
+	fn compat_table_versions_parse() {
+		for entry in COMPAT_TABLE {
+			assert!(parse_version(entry.since).is_some(), "{}", entry.feature);
+		}
+	}
+}
@@ -0,0 +1,127 @@
+//! Ctrl-C handling. The first Ctrl-C marks the run as cancelled: in-flight requests and
+//! waits fail with [`CliError::Interrupted`], and bulk loops stop and print what they have.
+//! A second Ctrl-C, or a command that does not wind down within [`GRACE`], exits at once.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use tokio::sync::Notify;
+
+use crate::error::CliError;
+use crate::output;
+
+const GRACE: Duration = Duration::from_secs(5);
+
+static CTRL_C: Token = Token::new();
+/// Set while a pager owns the terminal; Ctrl-C then belongs to the pager (as in git).
+static PAGING: AtomicBool = AtomicBool::new(false);
+
+/// Cancellation state; the process has one ([`CTRL_C`]), tests make their own.
+struct Token {
+	cancelled: AtomicBool,
+	notify: Notify,
+}
+
+impl Token {
+	const fn new() -> Self {
+		Self {
+			cancelled: AtomicBool::new(false),
+			notify: Notify::const_new(),
+		}
+	}
+
+	fn cancel(&self) {
+		self.cancelled.store(true, Ordering::SeqCst);
+		self.notify.notify_waiters();
+	}
+
+	async fn cancelled(&self) {
+		let notified = self.notify.notified();
+		tokio::pin!(notified);
+		notified.as_mut().enable();
+		if self.cancelled.load(Ordering::SeqCst) {
+			return;
+		}
+		notified.await;
+	}
+
+	async fn guard<F: Future>(&self, future: F) -> Result<F::Output, CliError> {
+		tokio::select! {
+			biased;
+			_ = self.cancelled() => Err(CliError::Interrupted),
+			output = future => Ok(output),
+		}
+	}
+}
+
+/// Starts listening for Ctrl-C; call once from inside the runtime.
+pub(crate) fn install(quiet: bool) {
+	tokio::spawn(async move {
+		loop {
+			if tokio::signal::ctrl_c().await.is_err() {
+				return;
+			}
+			if !PAGING.load(Ordering::SeqCst) {
+				break;
+			}
+		}
+		CTRL_C.cancel();
+		// Ends a pending prompt or progress line before anything else is printed.
+		eprintln!();
+		if !quiet {
+			eprintln!("Interrupted, finishing up (Ctrl-C again to quit now)...");
+		}
+
+		tokio::select! {
+			_ = tokio::signal::ctrl_c() => {}
+			_ = tokio::time::sleep(GRACE) => {}
+		}
+		// The command never finished, so a half-written --out file must not replace the old one.
+		let _ = output::finish(false);
+		std::process::exit(CliError::Interrupted.exit_code());
+	});
+}
+
+pub(crate) fn set_paging(paging: bool) {
+	PAGING.store(paging, Ordering::SeqCst);
+}
+
+pub(crate) fn requested() -> bool {
+	CTRL_C.cancelled.load(Ordering::SeqCst)
+}
+
+/// Resolves once Ctrl-C was pressed.
+pub(crate) async fn cancelled() {
+	CTRL_C.cancelled().await
+}
+
+/// Runs `future` unless Ctrl-C comes first.
+pub(crate) async fn guard<F: Future>(future: F) -> Result<F::Output, CliError> {
+	CTRL_C.guard(future).await
+}
+
+/// `tokio::time::sleep` that ends early with [`CliError::Interrupted`] on Ctrl-C.
+pub(crate) async fn sleep(duration: Duration) -> Result<(), CliError> {
+	guard(tokio::time::sleep(duration)).await
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[tokio::test]
+	async fn guard_passes_through_until_cancelled() {
+		// A private token, so requests in concurrently running tests are never interrupted.
+		let token = Token::new();
+		assert_eq!(token.guard(async { 7 }).await.unwrap(), 7);
+
+		let waiter = token.guard(std::future::pending::<()>());
+		tokio::pin!(waiter);
+		assert!(tokio::time::timeout(Duration::from_millis(20), waiter.as_mut()).await.is_err());
+		token.cancel();
+		assert!(matches!(waiter.await, Err(CliError::Interrupted)));
+		assert!(matches!(token.guard(async {}).await, Err(CliError::Interrupted)));
+		assert!(!requested());
+	}
+}
@@ -1,31 +1,55 @@
+mod agent;
 mod api;
 mod admin;
 mod auth;
+mod bootstrap;
 mod completion;
 mod config_cmd;
+mod examples;
 mod export;
+mod help;
+mod host;
 mod network;
 mod org;
 mod planet;
+mod schema;
 mod stats;
 mod trpc;
 mod user;
+mod verify;
+mod version;
+
+use std::path::PathBuf;
+use std::time::Duration;
 
 use clap::{Args, Parser, Subcommand, ValueEnum};
 use serde::{Deserialize, Serialize};
 
+use crate::http::ResolveOverride;
+
+pub use agent::*;
 pub use api::*;
 pub use admin::*;
 pub use auth::*;
+pub use bootstrap::*;
 pub use completion::*;
 pub use config_cmd::*;
+pub use examples::*;
 pub use export::*;
+pub use help::*;
+pub use host::*;
 pub use network::*;
 pub use org::*;
 pub use planet::*;
+pub use schema::*;
 pub use stats::*;
 pub use trpc::*;
 pub use user::*;
+pub use verify::*;
+pub use version::*;
+
+pub(crate) const NETWORK_ARG_HELP: &str =
+	"Network id or name; defaults to the context network (--network, `config context set --network`)";
 
 pub(crate) const SESSION_AUTH_LONG_ABOUT: &str = "This command requires session authentication (email/password).\nRun `ztnet auth login` first.\n\nAPI tokens are not supported for this operation.";
 
@@ -33,7 +57,9 @@ pub(crate) const SESSION_AUTH_LONG_ABOUT: &str = "This command requires session
 #[command(
 	name = "ztnet",
 	version,
-	about = "ZTNet CLI — manage ZeroTier networks via ZTNet"
+	about = "ZTNet CLI — manage ZeroTier networks via ZTNet",
+	disable_help_subcommand = true,
+	infer_subcommands = true
 )]
 pub struct Cli {
 	#[command(flatten)]
@@ -71,9 +97,42 @@ pub struct GlobalOpts {
 	#[arg(short = 'o', long, value_name = "FORMAT")]
 	pub output: Option<OutputFormat>,
 
+	#[arg(long, value_name = "FILE", help = "Write command output to FILE instead of stdout")]
+	pub out: Option<PathBuf>,
+
+	#[arg(
+		long,
+		value_name = "RECIPIENT",
+		env = "ZTNET_ENCRYPT_TO",
+		value_delimiter = ',',
+		help = "Age-encrypt written files (--out, export hosts --out, backup downloads) to this age1... key (repeatable)"
+	)]
+	pub encrypt_to: Vec<String>,
+
+	#[arg(
+		long,
+		value_name = "PATH",
+		env = "ZTNET_SIGN_KEY",
+		help = "Minisign secret key; written files (--out, export hosts --out, backup downloads) get a .minisig signature"
+	)]
+	pub sign_key: Option<PathBuf>,
+
+	#[arg(
+		long,
+		value_name = "SIZE",
+		env = "ZTNET_MAX_BODY_PRINT",
+		default_value = "1MiB",
+		value_parser = parse_size,
+		help = "Truncate table output on a terminal above SIZE (0 = never)"
+	)]
+	pub max_body_print: u64,
+
 	#[arg(long, help = "Disable ANSI colors")]
 	pub no_color: bool,
 
+	#[arg(long, help = "Never pipe long output through $ZTNET_PAGER / $PAGER")]
+	pub no_pager: bool,
+
 	#[arg(long, help = "Only print machine output (no prompts)")]
 	pub quiet: bool,
 
@@ -86,11 +145,145 @@ pub struct GlobalOpts {
 	#[arg(long, value_name = "N")]
 	pub retries: Option<u32>,
 
-	#[arg(long, help = "Print the HTTP request and exit (no network calls)")]
+	#[arg(
+		long,
+		value_name = "N",
+		env = "ZTNET_CONCURRENCY",
+		value_parser = clap::value_parser!(u32).range(1..),
+		help = "Most requests a command sends at once when fetching many items (default: 8)"
+	)]
+	pub concurrency: Option<u32>,
+
+	#[arg(
+		long,
+		env = "ZTNET_READONLY",
+		value_parser = clap::builder::BoolishValueParser::new(),
+		help = "Refuse to send mutating requests (POST/PUT/PATCH/DELETE and tRPC mutations)"
+	)]
+	pub read_only: bool,
+
+	#[arg(long, value_name = "DURATION", help = "First retry delay ceiling (default: 200ms)")]
+	pub backoff_base: Option<String>,
+
+	#[arg(long, value_name = "DURATION", help = "Upper bound for retry delays (default: 5s)")]
+	pub backoff_max: Option<String>,
+
+	#[arg(
+		long,
+		value_name = "HOST:PORT:ADDR",
+		value_parser = crate::http::parse_resolve_override,
+		help = "Resolve HOST:PORT to ADDR instead of using DNS (repeatable)"
+	)]
+	pub resolve: Vec<ResolveOverride>,
+
+	#[arg(
+		long,
+		value_name = "HOST",
+		help = "Send this Host header and TLS SNI while connecting to --host's address"
+	)]
+	pub host_header: Option<String>,
+
+	#[arg(
+		long,
+		help = "Don't ask for compressed responses (also turns off --compress-requests)"
+	)]
+	pub no_compression: bool,
+
+	#[arg(
+		long,
+		env = "ZTNET_COMPRESS_REQUESTS",
+		value_parser = clap::builder::BoolishValueParser::new(),
+		help = "Gzip request bodies of 64 KiB or more (the server must accept Content-Encoding: gzip)"
+	)]
+	pub compress_requests: bool,
+
+	#[arg(long, help = "Ignore HTTPS_PROXY/HTTP_PROXY/ALL_PROXY for this invocation")]
+	pub no_proxy: bool,
+
+	#[arg(
+		long,
+		conflicts_with = "prefer_ipv6",
+		help = "Try a dual-stack host's IPv4 addresses first (IPv6 remains the fallback)"
+	)]
+	pub prefer_ipv4: bool,
+
+	#[arg(long, help = "Try a dual-stack host's IPv6 addresses first (the default)")]
+	pub prefer_ipv6: bool,
+
+	#[arg(
+		long,
+		value_name = "UA",
+		env = "ZTNET_USER_AGENT",
+		help = "User-Agent for every request (default: ztnet-cli/<version> (<os>; <arch>))"
+	)]
+	pub user_agent: Option<String>,
+
+	#[arg(long, help = "Print requests instead of sending them (session commands still run read-only lookups)")]
 	pub dry_run: bool,
 
+	#[arg(
+		long,
+		alias = "no-input-validation",
+		help = "Skip client-side input validation and send values as-is"
+	)]
+	pub force_send: bool,
+
 	#[arg(short = 'y', long, help = "Skip confirmation prompts")]
 	pub yes: bool,
+
+	#[arg(
+		long,
+		value_name = "DURATION",
+		value_parser = humantime::parse_duration,
+		env = "ZTNET_CONFIRM_TIMEOUT",
+		help = "Give up on confirmation prompts after DURATION and use --confirm-default"
+	)]
+	pub confirm_timeout: Option<Duration>,
+
+	#[arg(
+		long,
+		value_enum,
+		value_name = "ANSWER",
+		default_value_t = ConfirmDefault::No,
+		help = "Answer used for an empty reply or when --confirm-timeout expires"
+	)]
+	pub confirm_default: ConfirmDefault,
+
+	#[arg(
+		long,
+		value_enum,
+		value_name = "MODE",
+		env = "ZTNET_PROGRESS",
+		default_value_t = ProgressMode::None,
+		help = "Progress reporting for long operations: none, or json events on stderr"
+	)]
+	pub progress: ProgressMode,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ProgressMode {
+	#[default]
+	None,
+	/// Newline-delimited JSON events on stderr.
+	Json,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConfirmDefault {
+	Yes,
+	#[default]
+	No,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ErrorExitPolicy {
+	/// Exit 0 as long as the list itself was fetched.
+	#[default]
+	Never,
+	/// Exit non-zero when any item failed.
+	Any,
+	/// Exit non-zero only when every item failed.
+	All,
 }
 
 #[derive(ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, Default)]
@@ -166,5 +359,75 @@ pub enum Command {
 		#[command(subcommand)]
 		command: TrpcCommand,
 	},
+	#[command(about = "Inspect the panel host (TLS certificate)")]
+	Host {
+		#[command(subcommand)]
+		command: HostCommand,
+	},
 	Completion(CompletionArgs),
+	Version(VersionArgs),
+	#[command(about = "Print JSON Schemas for the documents the CLI itself produces")]
+	Schema(SchemaArgs),
+	#[command(about = "Turn a fresh ZTNet install into a usable profile (admin, token, session)")]
+	Bootstrap(BootstrapArgs),
+	#[command(about = "Print help for a command, or the whole command tree as JSON (--json)")]
+	Help(HelpArgs),
+	#[command(about = "Print copy-pasteable recipes for common workflows of a command group")]
+	Examples(ExamplesArgs),
+	#[command(about = "Check a file's minisign signature (as written by --sign-key)")]
+	Verify(VerifyArgs),
+	#[command(about = "Long-running agent serving a token-protected localhost HTTP/JSON API")]
+	Agent {
+		#[command(subcommand)]
+		command: AgentCommand,
+	},
+}
+
+/// Parses a byte size such as `512`, `64k`, `1MiB` or `2MB` (binary multiples throughout).
+pub(crate) fn parse_size(raw: &str) -> Result<u64, String> {
+	let raw = raw.trim();
+	let split = raw.find(|c: char| !c.is_ascii_digit()).unwrap_or(raw.len());
+	let (digits, unit) = raw.split_at(split);
+	let value: u64 = digits
+		.parse()
+		.map_err(|_| format!("invalid size '{raw}' (expected e.g. 512, 64k, 1MiB)"))?;
+	let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+		"" | "b" => 1,
+		"k" | "kb" | "kib" => 1 << 10,
+		"m" | "mb" | "mib" => 1 << 20,
+		"g" | "gb" | "gib" => 1 << 30,
+		other => return Err(format!("unknown size unit '{other}' (use k, M or G)")),
+	};
+	value
+		.checked_mul(multiplier)
+		.ok_or_else(|| format!("size '{raw}' is too large"))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use clap::CommandFactory;
+
+	#[test]
+	fn boolean_env_switches_accept_yes_on_and_1() {
+		let mut cli = Cli::command();
+		cli.build();
+		for (id, env) in [("read_only", "ZTNET_READONLY"), ("compress_requests", "ZTNET_COMPRESS_REQUESTS")] {
+			let arg = cli.get_arguments().find(|a| a.get_id() == id).unwrap();
+			assert_eq!(arg.get_env().and_then(|e| e.to_str()), Some(env));
+			// clap runs an env value through the arg's value parser; taking the value on the
+			// command line instead exercises the same parser without touching the environment.
+			let probe = clap::Command::new("probe").arg(arg.clone().env(None).action(clap::ArgAction::Set).num_args(1));
+			let parse = |raw: &str| {
+				let matches = probe.clone().try_get_matches_from(["probe", &format!("--{}", arg.get_long().unwrap()), raw]);
+				matches.map(|m| m.get_one::<bool>(id).copied().unwrap())
+			};
+			for raw in ["1", "yes", "on", "true"] {
+				assert!(parse(raw).unwrap(), "{env}={raw}");
+			}
+			for raw in ["0", "no", "off", "false"] {
+				assert!(!parse(raw).unwrap(), "{env}={raw}");
+			}
+		}
+	}
 }
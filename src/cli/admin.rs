@@ -1,4 +1,5 @@
 use std::path::PathBuf;
+use std::time::Duration;
 
 use clap::{Args, Subcommand, ValueEnum};
 
@@ -76,6 +77,19 @@ pub struct AdminUsersUpdateArgs {
 
 	#[arg(long, conflicts_with = "active")]
 	pub inactive: bool,
+
+	#[arg(long, help = "Email the user a password reset link")]
+	pub send_password_reset: bool,
+
+	#[arg(long, help = "Mark the user's email address as verified")]
+	pub verify_email: bool,
+
+	#[arg(
+		long,
+		value_name = "DATE",
+		help = "Expire the account at DATE (YYYY-MM-DD, RFC 3339, or a duration from now such as 90d); `never` clears it"
+	)]
+	pub expire: Option<String>,
 }
 
 #[derive(Subcommand, Debug)]
@@ -90,6 +104,36 @@ pub enum AdminBackupCommand {
 	Restore(AdminBackupRestoreArgs),
 	#[command(about = "Delete backup [session auth]", long_about = SESSION_AUTH_LONG_ABOUT)]
 	Delete(AdminBackupDeleteArgs),
+	#[command(about = "Delete backups outside a retention policy [session auth]", long_about = SESSION_AUTH_LONG_ABOUT)]
+	Prune(RetentionArgs),
+}
+
+/// Retention policy shared by `admin backup prune` and `admin backup create`.
+#[derive(Args, Debug, Default)]
+pub struct RetentionArgs {
+	#[arg(
+		long,
+		value_name = "N",
+		value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..),
+		help = "Keep the N newest backups"
+	)]
+	pub keep_last: Option<usize>,
+
+	#[arg(
+		long,
+		value_name = "N",
+		value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..),
+		help = "Keep the newest backup of each of the last N days"
+	)]
+	pub keep_daily: Option<usize>,
+
+	#[arg(
+		long,
+		value_name = "N",
+		value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..),
+		help = "Keep the newest backup of each of the last N weeks"
+	)]
+	pub keep_weekly: Option<usize>,
 }
 
 #[derive(Args, Debug)]
@@ -102,6 +146,34 @@ pub struct AdminBackupCreateArgs {
 
 	#[arg(long, value_name = "NAME")]
 	pub name: Option<String>,
+
+	#[arg(long, help = "Wait until the backup shows up in the backup list")]
+	pub wait: bool,
+
+	#[arg(long, value_name = "PATH", help = "Download the finished backup to PATH (implies --wait)")]
+	pub download: Option<PathBuf>,
+
+	#[arg(
+		long,
+		value_name = "DURATION",
+		default_value = "10m",
+		value_parser = humantime::parse_duration,
+		help = "Give up waiting after DURATION"
+	)]
+	pub wait_timeout: Duration,
+
+	#[arg(
+		long,
+		value_name = "DURATION",
+		default_value = "2s",
+		value_parser = humantime::parse_duration,
+		help = "Delay between backup list polls"
+	)]
+	pub poll_interval: Duration,
+
+	/// With any `--keep-*` flag, old backups are pruned once the new one is listed.
+	#[command(flatten)]
+	pub retention: RetentionArgs,
 }
 
 #[derive(Args, Debug)]
@@ -195,18 +267,27 @@ pub struct AdminMailTemplatesSetArgs {
 	#[arg(value_name = "NAME")]
 	pub name: String,
 
-	#[arg(long, value_name = "PATH")]
+	#[arg(long, value_name = "PATH", help = "Template file (- reads stdin)")]
 	pub file: PathBuf,
 }
 
 #[derive(Subcommand, Debug)]
 pub enum AdminSettingsCommand {
 	#[command(about = "Get settings [session auth]", long_about = SESSION_AUTH_LONG_ABOUT)]
-	Get,
+	Get(AdminSettingsGetArgs),
 	#[command(about = "Update settings [session auth]", long_about = SESSION_AUTH_LONG_ABOUT)]
 	Update(AdminSettingsUpdateArgs),
 }
 
+#[derive(Args, Debug)]
+pub struct AdminSettingsGetArgs {
+	#[arg(
+		long,
+		help = "Read only the public options (registration, site name, welcome message); needs no login"
+	)]
+	pub public: bool,
+}
+
 #[derive(Args, Debug)]
 pub struct AdminSettingsUpdateArgs {
 	#[arg(long, conflicts_with = "disable_registration")]
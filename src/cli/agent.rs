@@ -0,0 +1,30 @@
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use clap::{Args, Subcommand};
+
+#[derive(Subcommand, Debug)]
+pub enum AgentCommand {
+	#[command(about = "Serve the local HTTP/JSON API until interrupted")]
+	Serve(AgentServeArgs),
+	#[command(about = "Print a systemd user unit that runs `agent serve`")]
+	SystemdUnit(AgentServeArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct AgentServeArgs {
+	#[arg(
+		long,
+		value_name = "ADDR",
+		default_value = "127.0.0.1:7788",
+		help = "Loopback address and port to listen on"
+	)]
+	pub listen: SocketAddr,
+
+	#[arg(
+		long,
+		value_name = "PATH",
+		help = "Where to write the bearer token clients must send (default: <cache dir>/agent.token)"
+	)]
+	pub token_file: Option<PathBuf>,
+}
@@ -21,7 +21,7 @@ pub struct ApiRequestArgs {
 	#[arg(long, value_name = "JSON", conflicts_with = "body_file")]
 	pub body: Option<String>,
 
-	#[arg(long, value_name = "PATH", conflicts_with = "body")]
+	#[arg(long, value_name = "PATH", conflicts_with = "body", help = "JSON body file (- reads stdin)")]
 	pub body_file: Option<PathBuf>,
 
 	#[arg(long, value_name = "K:V")]
@@ -32,12 +32,35 @@ pub struct ApiRequestArgs {
 
 	#[arg(long)]
 	pub raw: bool,
+
+	#[arg(long, value_name = "PATH", conflicts_with = "raw", help = ITEMS_PATH_HELP)]
+	pub items_path: Option<String>,
 }
 
+const ITEMS_PATH_HELP: &str = "Print only the array at this dotted path in the response (e.g. data.items)";
+
 #[derive(Args, Debug)]
 pub struct ApiGetArgs {
 	#[arg(value_name = "PATH")]
 	pub path: String,
+
+	#[arg(long, value_name = "PATH", help = ITEMS_PATH_HELP)]
+	pub items_path: Option<String>,
+
+	#[arg(long, requires = "items_path", help = "Fetch successive pages and merge the arrays at --items-path")]
+	pub paginate: bool,
+
+	#[arg(
+		long,
+		value_name = "NAME",
+		default_value = "page",
+		requires = "paginate",
+		help = "Query parameter holding the page number"
+	)]
+	pub page_param: String,
+
+	#[arg(long, value_name = "N", default_value_t = 100, requires = "paginate", help = "Stop after this many pages")]
+	pub max_pages: u32,
 }
 
 #[derive(Args, Debug)]
@@ -48,7 +71,7 @@ pub struct ApiPostArgs {
 	#[arg(long, value_name = "JSON", conflicts_with = "body_file")]
 	pub body: Option<String>,
 
-	#[arg(long, value_name = "PATH", conflicts_with = "body")]
+	#[arg(long, value_name = "PATH", conflicts_with = "body", help = "JSON body file (- reads stdin)")]
 	pub body_file: Option<PathBuf>,
 }
 
@@ -1,4 +1,4 @@
-use clap::{Args, Subcommand};
+use clap::{Args, Subcommand, ValueEnum};
 
 #[derive(Subcommand, Debug)]
 pub enum AuthCommand {
@@ -71,10 +71,27 @@ pub struct AuthTestArgs {
 
 #[derive(Subcommand, Debug)]
 pub enum AuthProfilesCommand {
-	List,
+	List(AuthProfilesListArgs),
 	Use(AuthProfilesUseArgs),
 }
 
+#[derive(Args, Debug)]
+pub struct AuthProfilesListArgs {
+	#[arg(long, value_enum, value_name = "KEY", default_value_t = ProfileSortKey::Name)]
+	pub sort: ProfileSortKey,
+
+	#[arg(long, help = "Reverse the sort order")]
+	pub reverse: bool,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum ProfileSortKey {
+	Name,
+	Host,
+	#[value(help = "Soonest-expiring session first (profiles without a session last)")]
+	SessionExpires,
+}
+
 #[derive(Args, Debug)]
 pub struct AuthProfilesUseArgs {
 	#[arg(value_name = "NAME")]
@@ -83,9 +100,28 @@ pub struct AuthProfilesUseArgs {
 
 #[derive(Subcommand, Debug)]
 pub enum AuthHostsCommand {
-	List,
+	List(AuthHostsListArgs),
 	SetDefault(AuthHostsSetDefaultArgs),
 	UnsetDefault(AuthHostsUnsetDefaultArgs),
+	#[command(about = "Remove host defaults that point at missing profiles or at profiles for another host")]
+	Prune,
+}
+
+#[derive(Args, Debug)]
+pub struct AuthHostsListArgs {
+	#[arg(long, value_enum, value_name = "KEY", default_value_t = HostSortKey::Host)]
+	pub sort: HostSortKey,
+
+	#[arg(long, help = "Reverse the sort order")]
+	pub reverse: bool,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum HostSortKey {
+	Host,
+	DefaultProfile,
+	#[value(help = "Number of profiles bound to the host")]
+	Profiles,
 }
 
 #[derive(Args, Debug)]
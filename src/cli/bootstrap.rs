@@ -0,0 +1,25 @@
+use clap::Args;
+
+#[derive(Args, Debug)]
+pub struct BootstrapArgs {
+	#[arg(long, value_name = "EMAIL", env = "ZTNET_EMAIL")]
+	pub email: String,
+
+	#[arg(long, value_name = "PASSWORD", env = "ZTNET_PASSWORD", conflicts_with = "password_stdin")]
+	pub password: Option<String>,
+
+	#[arg(long, help = "Read password from STDIN (avoids shell history)", conflicts_with = "password")]
+	pub password_stdin: bool,
+
+	#[arg(long, value_name = "NAME", default_value = "admin")]
+	pub name: String,
+
+	#[arg(long, value_name = "PROFILE", help = "Profile to store host, token and session in")]
+	pub save_as: Option<String>,
+
+	#[arg(long, value_name = "NAME", help = "Also create an organization")]
+	pub create_org: Option<String>,
+
+	#[arg(long, value_name = "NAME", help = "Also create a network (inside --create-org if given)")]
+	pub create_network: Option<String>,
+}
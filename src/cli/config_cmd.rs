@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use clap::{Args, Subcommand};
 
 #[derive(Subcommand, Debug)]
@@ -6,13 +8,19 @@ pub enum ConfigCommand {
 	Get(ConfigGetArgs),
 	Set(ConfigSetArgs),
 	Unset(ConfigUnsetArgs),
-	List,
+	List(ConfigListArgs),
 	Context {
 		#[command(subcommand)]
 		command: ConfigContextCommand,
 	},
 }
 
+#[derive(Args, Debug)]
+pub struct ConfigListArgs {
+	#[arg(long, help = "Show where each value came from (flag, env var, profile key, default)")]
+	pub explain: bool,
+}
+
 #[derive(Args, Debug)]
 pub struct ConfigGetArgs {
 	#[arg(value_name = "KEY")]
@@ -21,11 +29,22 @@ pub struct ConfigGetArgs {
 
 #[derive(Args, Debug)]
 pub struct ConfigSetArgs {
-	#[arg(value_name = "KEY")]
-	pub key: String,
+	#[arg(value_name = "KEY", required_unless_present_any = ["from_file", "stdin"])]
+	pub key: Option<String>,
+
+	#[arg(value_name = "VALUE", required_unless_present_any = ["from_file", "stdin"])]
+	pub value: Option<String>,
+
+	#[arg(
+		long,
+		value_name = "PATH",
+		conflicts_with_all = ["key", "stdin"],
+		help = "Apply many keys from a TOML file (validated before a single write; - reads stdin)"
+	)]
+	pub from_file: Option<PathBuf>,
 
-	#[arg(value_name = "VALUE")]
-	pub value: String,
+	#[arg(long, conflicts_with = "key", help = "Read TOML settings from stdin")]
+	pub stdin: bool,
 
 	#[arg(long, help = "Skip host validation (format is still normalized)")]
 	pub no_validate: bool,
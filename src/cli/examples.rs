@@ -0,0 +1,7 @@
+use clap::Args;
+
+#[derive(Args, Debug)]
+pub struct ExamplesArgs {
+	#[arg(value_name = "GROUP", help = "Command group to show recipes for, e.g. `member` (omit to list groups)")]
+	pub group: Option<String>,
+}
@@ -2,6 +2,8 @@ use std::path::PathBuf;
 
 use clap::{Args, Subcommand, ValueEnum};
 
+use super::NETWORK_ARG_HELP;
+
 #[derive(Subcommand, Debug)]
 pub enum ExportCommand {
 	Hosts(ExportHostsArgs),
@@ -13,6 +15,9 @@ pub enum ExportHostsFormat {
 	Hosts,
 	Csv,
 	Json,
+	OpenwrtDhcp,
+	ReverseZone,
+	PowerdnsApi,
 }
 
 impl std::fmt::Display for ExportHostsFormat {
@@ -21,6 +26,9 @@ impl std::fmt::Display for ExportHostsFormat {
 			ExportHostsFormat::Hosts => "hosts",
 			ExportHostsFormat::Csv => "csv",
 			ExportHostsFormat::Json => "json",
+			ExportHostsFormat::OpenwrtDhcp => "openwrt-dhcp",
+			ExportHostsFormat::ReverseZone => "reverse-zone",
+			ExportHostsFormat::PowerdnsApi => "powerdns-api",
 		};
 		write!(f, "{value}")
 	}
@@ -28,14 +36,14 @@ impl std::fmt::Display for ExportHostsFormat {
 
 #[derive(Args, Debug)]
 pub struct ExportHostsArgs {
-	#[arg(value_name = "NETWORK")]
-	pub network: String,
+	#[arg(value_name = "NETWORK", help = NETWORK_ARG_HELP)]
+	pub network: Option<String>,
 
 	#[arg(long, value_name = "ORG")]
 	pub org: Option<String>,
 
-	#[arg(long, value_name = "DOMAIN")]
-	pub zone: String,
+	#[arg(long, value_name = "DOMAIN", required_unless_present = "remove")]
+	pub zone: Option<String>,
 
 	#[arg(long, value_name = "PATH")]
 	pub out: Option<PathBuf>,
@@ -46,7 +54,63 @@ pub struct ExportHostsArgs {
 	#[arg(long)]
 	pub include_unauthorized: bool,
 
+	#[arg(long, conflicts_with = "ipv6_only", help = "Export only IPv4 addresses")]
+	pub ipv4_only: bool,
+
+	#[arg(long, conflicts_with = "ipv4_only", help = "Export only IPv6 addresses")]
+	pub ipv6_only: bool,
+
+	#[arg(long, help = "List each member's IPv6 addresses before its IPv4 ones (default: IPv4 first)")]
+	pub ipv6_first: bool,
+
+	#[arg(long, help = "Export only each member's first address (after filtering and ordering)")]
+	pub one_ip_per_member: bool,
+
 	#[arg(long, value_enum, default_value_t = ExportHostsFormat::Hosts)]
 	pub format: ExportHostsFormat,
+
+	#[arg(
+		long,
+		conflicts_with = "out",
+		help = "Write a managed block into the system hosts file instead of printing"
+	)]
+	pub apply: bool,
+
+	#[arg(long, value_name = "PATH", requires = "apply", help = "Hosts file to update with --apply")]
+	pub hosts_file: Option<PathBuf>,
+
+	#[arg(long, requires = "apply", help = "Remove this network's managed block instead of updating it")]
+	pub remove: bool,
+
+	#[arg(long, value_name = "URL", help = "PowerDNS API base URL for --format powerdns-api")]
+	pub pdns_url: Option<String>,
+
+	#[arg(long, value_name = "KEY", env = "PDNS_API_KEY", hide_env_values = true)]
+	pub pdns_key: Option<String>,
+
+	#[arg(long, value_name = "ID", default_value = "localhost")]
+	pub pdns_server: String,
+
+	#[arg(long, value_name = "SECONDS", default_value_t = 300)]
+	pub pdns_ttl: u32,
+
+	#[arg(long, help = "Take over existing A/AAAA rrsets that ztnet-cli did not create")]
+	pub pdns_adopt: bool,
+
+	#[arg(
+		long,
+		conflicts_with = "apply",
+		requires = "out",
+		help = "Skip writing --out (and the --on-change hook) when the export is identical to the last run's"
+	)]
+	pub only_on_change: bool,
+
+	#[arg(
+		long,
+		value_name = "COMMAND",
+		requires = "out",
+		help = "Shell command to run after --out was written, e.g. to reload a DNS server"
+	)]
+	pub on_change: Option<String>,
 }
 
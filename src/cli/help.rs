@@ -0,0 +1,10 @@
+use clap::Args;
+
+#[derive(Args, Debug)]
+pub struct HelpArgs {
+	#[arg(value_name = "COMMAND", help = "Command path to describe, e.g. `member get`")]
+	pub command: Vec<String>,
+
+	#[arg(long, help = "Print the command tree (flags, value types, defaults) as JSON")]
+	pub json: bool,
+}
@@ -0,0 +1,20 @@
+use clap::Subcommand;
+
+#[derive(Subcommand, Debug)]
+pub enum HostCommand {
+	#[command(about = "Inspect or pin the panel's TLS certificate")]
+	Cert {
+		#[command(subcommand)]
+		command: HostCertCommand,
+	},
+	#[command(about = "Show what the panel tells visitors before login (registration, welcome message); needs no auth")]
+	PublicInfo,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum HostCertCommand {
+	#[command(about = "Print the certificate chain, expiry and SANs the panel presents")]
+	Show,
+	#[command(about = "Pin the panel's current certificate to the profile (cert_fingerprint)")]
+	Pin,
+}
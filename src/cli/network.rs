@@ -1,8 +1,9 @@
 use std::path::PathBuf;
+use std::time::Duration;
 
-use clap::{Args, Subcommand};
+use clap::{Args, Subcommand, ValueEnum};
 
-use super::SESSION_AUTH_LONG_ABOUT;
+use super::{ErrorExitPolicy, NETWORK_ARG_HELP, SESSION_AUTH_LONG_ABOUT};
 
 #[derive(Subcommand, Debug)]
 pub enum NetworkCommand {
@@ -24,20 +25,67 @@ pub enum NetworkCommand {
 	Multicast(NetworkMulticastArgs),
 	#[command(about = "Flow rules [session auth]", long_about = SESSION_AUTH_LONG_ABOUT)]
 	FlowRules(NetworkFlowRulesArgs),
+	#[command(
+		about = "Move a member to another network [session auth]",
+		long_about = SESSION_AUTH_LONG_ABOUT
+	)]
+	MoveMember(NetworkMoveMemberArgs),
+	#[command(
+		about = "Summarize settings, routes, IP pools, DNS and members [session auth]",
+		long_about = SESSION_AUTH_LONG_ABOUT
+	)]
+	Summary(NetworkSummaryArgs),
 	Member {
 		#[command(subcommand)]
 		command: NetworkMemberCommand,
 	},
 }
 
+#[derive(Args, Debug)]
+pub struct NetworkSummaryArgs {
+	#[arg(value_name = "NETWORK", help = NETWORK_ARG_HELP)]
+	pub network: Option<String>,
+
+	#[arg(long, help = "Print a Markdown report with stable section anchors (for wikis and docs commits)")]
+	pub markdown: bool,
+}
+
 #[derive(Args, Debug)]
 pub struct NetworkListArgs {
 	#[arg(long, value_name = "ORG")]
 	pub org: Option<String>,
 
+	#[arg(
+		long,
+		conflicts_with_all = ["org", "all"],
+		help = "Only personal networks, even when a context org is set"
+	)]
+	pub mine: bool,
+
+	#[arg(
+		long,
+		alias = "org-all",
+		conflicts_with = "org",
+		help = "Personal networks plus those of every organization you belong to, with an owner column"
+	)]
+	pub all: bool,
+
 	#[arg(long)]
 	pub details: bool,
 
+	#[arg(long, requires = "details", help = "Collect per-item detail errors instead of aborting")]
+	pub skip_errors: bool,
+
+	#[arg(
+		long,
+		value_enum,
+		value_name = "POLICY",
+		default_value_t = ErrorExitPolicy::Never,
+		requires = "skip_errors",
+		help = "When to exit non-zero if --skip-errors collected errors"
+	)]
+	pub error_exit: ErrorExitPolicy,
+
 	#[arg(long)]
 	pub ids_only: bool,
 
@@ -56,8 +104,8 @@ pub struct NetworkCreateArgs {
 
 #[derive(Args, Debug)]
 pub struct NetworkGetArgs {
-	#[arg(value_name = "NETWORK")]
-	pub network: String,
+	#[arg(value_name = "NETWORK", help = NETWORK_ARG_HELP)]
+	pub network: Option<String>,
 
 	#[arg(long, value_name = "ORG")]
 	pub org: Option<String>,
@@ -65,8 +113,8 @@ pub struct NetworkGetArgs {
 
 #[derive(Args, Debug)]
 pub struct NetworkUpdateArgs {
-	#[arg(value_name = "NETWORK")]
-	pub network: String,
+	#[arg(value_name = "NETWORK", help = NETWORK_ARG_HELP)]
+	pub network: Option<String>,
 
 	#[arg(long, value_name = "ORG")]
 	pub org: String,
@@ -89,7 +137,7 @@ pub struct NetworkUpdateArgs {
 	#[arg(long, value_name = "TEXT", conflicts_with = "flow_rule_file")]
 	pub flow_rule: Option<String>,
 
-	#[arg(long, value_name = "PATH", conflicts_with = "flow_rule")]
+	#[arg(long, value_name = "PATH", conflicts_with = "flow_rule", help = "Flow rules source file (- reads stdin)")]
 	pub flow_rule_file: Option<PathBuf>,
 
 	#[arg(long, value_name = "DOMAIN")]
@@ -101,12 +149,13 @@ pub struct NetworkUpdateArgs {
 	#[arg(long, value_name = "JSON", conflicts_with = "body_file")]
 	pub body: Option<String>,
 
-	#[arg(long, value_name = "PATH", conflicts_with = "body")]
+	#[arg(long, value_name = "PATH", conflicts_with = "body", help = "JSON body file (- reads stdin)")]
 	pub body_file: Option<PathBuf>,
 }
 
 #[derive(Args, Debug)]
 pub struct NetworkDeleteArgs {
+	// Deliberately not defaulted from the context: deletion must name its target.
 	#[arg(value_name = "NETWORK")]
 	pub network: String,
 
@@ -114,10 +163,50 @@ pub struct NetworkDeleteArgs {
 	pub org: Option<String>,
 }
 
+#[derive(Args, Debug)]
+pub struct NetworkMoveMemberArgs {
+	#[arg(value_name = "MEMBER", help = "Member id or name on the source network")]
+	pub member: String,
+
+	#[arg(long, value_name = "NETWORK", help = "Source network (default: the context network)")]
+	pub from: Option<String>,
+
+	#[arg(long, value_name = "NETWORK")]
+	pub to: String,
+
+	#[arg(long, value_name = "ORG")]
+	pub org: Option<String>,
+
+	#[arg(
+		long,
+		help = "Carry the member's IP assignments over instead of letting the target network assign new ones"
+	)]
+	pub keep_ips: bool,
+
+	#[arg(long, help = "Create the member on the target network without authorizing it")]
+	pub no_authorize: bool,
+
+	#[arg(
+		long,
+		value_enum,
+		value_name = "ACTION",
+		default_value_t = MoveSourceAction::Keep,
+		help = "What to do with the member on the source network afterwards"
+	)]
+	pub source: MoveSourceAction,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveSourceAction {
+	Keep,
+	Deauthorize,
+	Stash,
+}
+
 #[derive(Args, Debug)]
 pub struct NetworkRoutesArgs {
-	#[arg(value_name = "NETWORK")]
-	pub network: String,
+	#[arg(value_name = "NETWORK", help = NETWORK_ARG_HELP)]
+	pub network: Option<String>,
 
 	#[arg(long, value_name = "ORG")]
 	pub org: Option<String>,
@@ -138,11 +227,22 @@ pub enum NetworkRoutesCommand {
 
 #[derive(Args, Debug)]
 pub struct NetworkRoutesAddArgs {
-	#[arg(long, value_name = "CIDR")]
-	pub destination: String,
+	#[arg(long, value_name = "CIDR", required_unless_present = "replace_default")]
+	pub destination: Option<String>,
 
 	#[arg(long, value_name = "GATEWAY", help = "Gateway IP, or 'lan'")]
 	pub via: Option<String>,
+
+	#[arg(
+		long,
+		conflicts_with = "destination",
+		requires = "via",
+		help = "Replace the IPv4 default route (0.0.0.0/0) with one via --via"
+	)]
+	pub replace_default: bool,
+
+	#[arg(long, help = "Allow adding a default route (full tunnel for members)")]
+	pub allow_default: bool,
 }
 
 #[derive(Args, Debug)]
@@ -153,8 +253,8 @@ pub struct NetworkRoutesRemoveArgs {
 
 #[derive(Args, Debug)]
 pub struct NetworkIpPoolArgs {
-	#[arg(value_name = "NETWORK")]
-	pub network: String,
+	#[arg(value_name = "NETWORK", help = NETWORK_ARG_HELP)]
+	pub network: Option<String>,
 
 	#[arg(long, value_name = "ORG")]
 	pub org: Option<String>,
@@ -171,6 +271,33 @@ pub enum NetworkIpPoolCommand {
 	Add(NetworkIpPoolChangeArgs),
 	#[command(about = "Remove an IP pool [session auth]", long_about = SESSION_AUTH_LONG_ABOUT)]
 	Remove(NetworkIpPoolChangeArgs),
+	#[command(about = "Replace all IP pools [session auth]", long_about = SESSION_AUTH_LONG_ABOUT)]
+	Set(NetworkIpPoolSetArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct NetworkIpPoolSetArgs {
+	#[arg(
+		long,
+		value_name = "CIDR",
+		required_unless_present = "start",
+		help = "Pool covering an IPv4 CIDR (repeatable)"
+	)]
+	pub cidr: Vec<String>,
+
+	#[arg(
+		long,
+		value_name = "IP",
+		requires = "end",
+		help = "First address of a pool; pairs with the --end in the same position (repeatable)"
+	)]
+	pub start: Vec<String>,
+
+	#[arg(long, value_name = "IP", requires = "start", help = "Last address of a pool (repeatable)")]
+	pub end: Vec<String>,
+
+	#[arg(long, help = "Only report member addresses that would fall outside the new pools")]
+	pub simulate: bool,
 }
 
 #[derive(Args, Debug)]
@@ -187,8 +314,8 @@ pub struct NetworkIpPoolChangeArgs {
 
 #[derive(Args, Debug)]
 pub struct NetworkDnsArgs {
-	#[arg(value_name = "NETWORK")]
-	pub network: String,
+	#[arg(value_name = "NETWORK", help = NETWORK_ARG_HELP)]
+	pub network: Option<String>,
 
 	#[arg(long, value_name = "ORG")]
 	pub org: Option<String>,
@@ -205,8 +332,8 @@ pub struct NetworkDnsArgs {
 
 #[derive(Args, Debug)]
 pub struct NetworkIpv6Args {
-	#[arg(value_name = "NETWORK")]
-	pub network: String,
+	#[arg(value_name = "NETWORK", help = NETWORK_ARG_HELP)]
+	pub network: Option<String>,
 
 	#[arg(long, value_name = "ORG")]
 	pub org: Option<String>,
@@ -228,12 +355,18 @@ pub struct NetworkIpv6Args {
 
 	#[arg(long = "no-zt", conflicts_with = "zt")]
 	pub no_zt: bool,
+
+	#[arg(
+		long,
+		help = "Print the 6plane/RFC4193 prefixes derived from the network ID (works without toggles)"
+	)]
+	pub show_prefixes: bool,
 }
 
 #[derive(Args, Debug)]
 pub struct NetworkMulticastArgs {
-	#[arg(value_name = "NETWORK")]
-	pub network: String,
+	#[arg(value_name = "NETWORK", help = NETWORK_ARG_HELP)]
+	pub network: Option<String>,
 
 	#[arg(long, value_name = "ORG")]
 	pub org: Option<String>,
@@ -250,8 +383,8 @@ pub struct NetworkMulticastArgs {
 
 #[derive(Args, Debug)]
 pub struct NetworkFlowRulesArgs {
-	#[arg(value_name = "NETWORK")]
-	pub network: String,
+	#[arg(value_name = "NETWORK", help = NETWORK_ARG_HELP)]
+	pub network: Option<String>,
 
 	#[command(subcommand)]
 	pub command: NetworkFlowRulesCommand,
@@ -261,12 +394,26 @@ pub struct NetworkFlowRulesArgs {
 pub enum NetworkFlowRulesCommand {
 	#[command(about = "Get flow rules [session auth]", long_about = SESSION_AUTH_LONG_ABOUT)]
 	Get(NetworkFlowRulesGetArgs),
+	#[command(
+		about = "Compare live flow rules with a local file [session auth]",
+		long_about = SESSION_AUTH_LONG_ABOUT
+	)]
+	Diff(NetworkFlowRulesDiffArgs),
 }
 
 #[derive(Args, Debug)]
 pub struct NetworkFlowRulesGetArgs {
 	#[arg(long)]
 	pub reset: bool,
+
+	#[arg(long, help = "Also return the compiled rules, capabilities and tags")]
+	pub compiled: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct NetworkFlowRulesDiffArgs {
+	#[arg(long, value_name = "PATH", help = "Flow rules source file to compare against (- reads stdin)")]
+	pub file: PathBuf,
 }
 
 #[derive(Subcommand, Debug)]
@@ -278,16 +425,23 @@ pub enum NetworkMemberCommand {
 	Deauthorize(MemberDeauthorizeArgs),
 	#[command(about = "Add a member by node id [session auth]", long_about = SESSION_AUTH_LONG_ABOUT)]
 	Add(MemberAddArgs),
-	#[command(about = "Manage member tags [session auth]", long_about = SESSION_AUTH_LONG_ABOUT)]
+	#[command(
+		about = "Manage member tags [session auth]",
+		long_about = SESSION_AUTH_LONG_ABOUT,
+		subcommand_negates_reqs = true
+	)]
 	Tags(MemberTagsArgs),
-	#[command(alias = "stash")]
+	#[command(
+		alias = "stash",
+		about = "Stash a member (restorable), or remove it for good with --permanent"
+	)]
 	Delete(MemberDeleteArgs),
 }
 
 #[derive(Args, Debug)]
 pub struct MemberListArgs {
-	#[arg(value_name = "NETWORK")]
-	pub network: String,
+	#[arg(value_name = "NETWORK", help = NETWORK_ARG_HELP)]
+	pub network: Option<String>,
 
 	#[arg(long, value_name = "ORG")]
 	pub org: Option<String>,
@@ -303,15 +457,37 @@ pub struct MemberListArgs {
 
 	#[arg(long, value_name = "NODEID")]
 	pub id: Option<String>,
+
+	#[arg(
+		long,
+		value_name = "FIELDS",
+		value_delimiter = ',',
+		help = "Only keep these top-level fields (comma-separated, e.g. id,name,ipAssignments)"
+	)]
+	pub fields: Vec<String>,
+
+	#[arg(
+		long,
+		conflicts_with = "fields",
+		help = "Summarize each member's peer link (direct or relayed, latency, preferred path) [session auth]"
+	)]
+	pub peers: bool,
+
+	#[arg(
+		long,
+		conflicts_with = "fields",
+		help = "Only members reachable through a relay, which usually need NAT/firewall attention (implies --peers)"
+	)]
+	pub relayed_only: bool,
 }
 
 #[derive(Args, Debug)]
 pub struct MemberGetArgs {
-	#[arg(value_name = "NETWORK")]
-	pub network: String,
+	#[arg(value_name = "NETWORK", help = NETWORK_ARG_HELP)]
+	pub network: Option<String>,
 
 	#[arg(value_name = "MEMBER")]
-	pub member: String,
+	pub member: Option<String>,
 
 	#[arg(long, value_name = "ORG")]
 	pub org: Option<String>,
@@ -319,11 +495,11 @@ pub struct MemberGetArgs {
 
 #[derive(Args, Debug)]
 pub struct MemberUpdateArgs {
-	#[arg(value_name = "NETWORK")]
-	pub network: String,
+	#[arg(value_name = "NETWORK", help = NETWORK_ARG_HELP)]
+	pub network: Option<String>,
 
 	#[arg(value_name = "MEMBER")]
-	pub member: String,
+	pub member: Option<String>,
 
 	#[arg(long, value_name = "ORG")]
 	pub org: Option<String>,
@@ -343,17 +519,74 @@ pub struct MemberUpdateArgs {
 	#[arg(long, value_name = "JSON", conflicts_with = "body_file")]
 	pub body: Option<String>,
 
-	#[arg(long, value_name = "PATH", conflicts_with = "body")]
+	#[arg(long, value_name = "PATH", conflicts_with = "body", help = "JSON body file (- reads stdin)")]
 	pub body_file: Option<PathBuf>,
+
+	#[arg(
+		long,
+		conflicts_with_all = ["deny_bridging", "body", "body_file"],
+		help = "Let the member bridge other devices onto the network (activeBridge) [session auth]"
+	)]
+	pub allow_bridging: bool,
+
+	#[arg(
+		long,
+		alias = "deny",
+		conflicts_with_all = ["body", "body_file"],
+		help = "Stop the member from bridging (activeBridge off) [session auth]"
+	)]
+	pub deny_bridging: bool,
+
+	#[arg(
+		long,
+		conflicts_with_all = ["auto_assign_ips", "body", "body_file"],
+		help = "Don't auto-assign addresses from the network's pools (noAutoAssignIps) [session auth]"
+	)]
+	pub no_auto_assign_ips: bool,
+
+	#[arg(
+		long,
+		conflicts_with_all = ["body", "body_file"],
+		help = "Auto-assign addresses from the network's pools again [session auth]"
+	)]
+	pub auto_assign_ips: bool,
+
+	#[arg(
+		long,
+		value_name = "IP",
+		conflicts_with_all = ["clear_ips", "body", "body_file"],
+		help = "Replace the member's IP assignments (repeatable) [session auth]"
+	)]
+	pub ip: Vec<String>,
+
+	#[arg(
+		long,
+		conflicts_with_all = ["body", "body_file"],
+		help = "Remove every IP assignment [session auth]"
+	)]
+	pub clear_ips: bool,
+}
+
+impl MemberUpdateArgs {
+	/// Fields only the tRPC API can change (the REST endpoint takes name, description and
+	/// authorized).
+	pub fn has_advanced_fields(&self) -> bool {
+		self.allow_bridging
+			|| self.deny_bridging
+			|| self.no_auto_assign_ips
+			|| self.auto_assign_ips
+			|| !self.ip.is_empty()
+			|| self.clear_ips
+	}
 }
 
 #[derive(Args, Debug)]
 pub struct MemberAuthorizeArgs {
-	#[arg(value_name = "NETWORK")]
-	pub network: String,
+	#[arg(value_name = "NETWORK", help = NETWORK_ARG_HELP)]
+	pub network: Option<String>,
 
 	#[arg(value_name = "MEMBER")]
-	pub member: String,
+	pub member: Option<String>,
 
 	#[arg(long, value_name = "ORG")]
 	pub org: Option<String>,
@@ -361,47 +594,78 @@ pub struct MemberAuthorizeArgs {
 
 #[derive(Args, Debug)]
 pub struct MemberDeauthorizeArgs {
-	#[arg(value_name = "NETWORK")]
-	pub network: String,
+	#[arg(value_name = "NETWORK", help = NETWORK_ARG_HELP)]
+	pub network: Option<String>,
 
-	#[arg(value_name = "MEMBER")]
-	pub member: String,
+	#[arg(value_name = "MEMBER", conflicts_with = "all_unnamed")]
+	pub member: Option<String>,
 
 	#[arg(long, value_name = "ORG")]
 	pub org: Option<String>,
+
+	#[arg(
+		long,
+		help = "Deauthorize every authorized member without a name that joined before --age (previews, then confirms)"
+	)]
+	pub all_unnamed: bool,
+
+	#[arg(
+		long,
+		value_name = "DURATION",
+		default_value = "24h",
+		value_parser = humantime::parse_duration,
+		requires = "all_unnamed",
+		help = "Only select members that joined at least DURATION ago"
+	)]
+	pub age: Duration,
 }
 
 #[derive(Args, Debug)]
 pub struct MemberDeleteArgs {
-	#[arg(value_name = "NETWORK")]
-	pub network: String,
+	#[arg(value_name = "NETWORK", help = NETWORK_ARG_HELP)]
+	pub network: Option<String>,
 
 	#[arg(value_name = "MEMBER")]
-	pub member: String,
+	pub member: Option<String>,
 
 	#[arg(long, value_name = "ORG")]
 	pub org: Option<String>,
+
+	#[arg(
+		long,
+		help = "Remove the member record for good instead of stashing it [session auth]"
+	)]
+	pub permanent: bool,
 }
 
 #[derive(Args, Debug)]
 pub struct MemberAddArgs {
-	#[arg(value_name = "NETWORK")]
-	pub network: String,
+	#[arg(value_name = "NETWORK", help = NETWORK_ARG_HELP)]
+	pub network: Option<String>,
 
 	#[arg(value_name = "NODE_ID")]
-	pub node_id: String,
+	pub node_id: Option<String>,
 
 	#[arg(long, value_name = "ORG")]
 	pub org: Option<String>,
+
+	#[arg(long, value_name = "NAME", help = "Set the member name after creating it")]
+	pub name: Option<String>,
+
+	#[arg(long, help = "Authorize the member after creating it")]
+	pub authorize: bool,
+
+	#[arg(long, value_name = "IP", help = "Assign a static IP (repeatable)")]
+	pub ip: Vec<String>,
 }
 
 #[derive(Args, Debug)]
 pub struct MemberTagsArgs {
-	#[arg(value_name = "NETWORK")]
-	pub network: String,
+	#[arg(value_name = "NETWORK", help = NETWORK_ARG_HELP)]
+	pub network: Option<String>,
 
 	#[arg(value_name = "MEMBER")]
-	pub member: String,
+	pub member: Option<String>,
 
 	#[arg(long, value_name = "ORG")]
 	pub org: Option<String>,
@@ -416,6 +680,11 @@ pub enum MemberTagsCommand {
 	List,
 	#[command(about = "Set tags [session auth]", long_about = SESSION_AUTH_LONG_ABOUT)]
 	Set(MemberTagsSetArgs),
+	#[command(
+		about = "Apply tags to every member matching a filter [session auth]",
+		long_about = SESSION_AUTH_LONG_ABOUT
+	)]
+	Apply(MemberTagsApplyArgs),
 }
 
 #[derive(Args, Debug)]
@@ -424,6 +693,29 @@ pub struct MemberTagsSetArgs {
 	pub tags: String,
 }
 
+#[derive(Args, Debug)]
+pub struct MemberTagsApplyArgs {
+	#[arg(value_name = "NETWORK", help = NETWORK_ARG_HELP)]
+	pub network: Option<String>,
+
+	#[arg(long, value_name = "ORG")]
+	pub org: Option<String>,
+
+	#[arg(long, value_name = "EXPR", help = "Member filter, e.g. 'name~=web,authorized==true'")]
+	pub filter: String,
+
+	#[arg(
+		long,
+		value_name = "NAME=VALUE",
+		required = true,
+		help = "Tag to set, by name or id; VALUE may be an enum name or a number (repeatable)"
+	)]
+	pub tag: Vec<String>,
+
+	#[arg(long, help = "Print only failures (as JSON lines, as they happen) and a final count")]
+	pub only_errors: bool,
+}
+
 #[derive(Subcommand, Debug)]
 pub enum MemberCommand {
 	List(MemberListArgs),
@@ -433,8 +725,15 @@ pub enum MemberCommand {
 	Deauthorize(MemberDeauthorizeArgs),
 	#[command(about = "Add a member by node id [session auth]", long_about = SESSION_AUTH_LONG_ABOUT)]
 	Add(MemberAddArgs),
-	#[command(about = "Manage member tags [session auth]", long_about = SESSION_AUTH_LONG_ABOUT)]
+	#[command(
+		about = "Manage member tags [session auth]",
+		long_about = SESSION_AUTH_LONG_ABOUT,
+		subcommand_negates_reqs = true
+	)]
 	Tags(MemberTagsArgs),
-	#[command(alias = "stash")]
+	#[command(
+		alias = "stash",
+		about = "Stash a member (restorable), or remove it for good with --permanent"
+	)]
 	Delete(MemberDeleteArgs),
 }
@@ -1,6 +1,9 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
 use clap::{Args, Subcommand, ValueEnum};
 
-use super::SESSION_AUTH_LONG_ABOUT;
+use super::{ErrorExitPolicy, SESSION_AUTH_LONG_ABOUT};
 
 #[derive(Subcommand, Debug)]
 pub enum OrgCommand {
@@ -27,6 +30,11 @@ pub enum OrgCommand {
 	},
 	#[command(about = "Org logs [session auth]", long_about = SESSION_AUTH_LONG_ABOUT)]
 	Logs(OrgLogsArgs),
+	#[command(about = "Org notifications [session auth]", long_about = SESSION_AUTH_LONG_ABOUT)]
+	Notifications {
+		#[command(subcommand)]
+		command: OrgNotificationsCommand,
+	},
 }
 
 #[derive(Args, Debug)]
@@ -34,6 +42,19 @@ pub struct OrgListArgs {
 	#[arg(long)]
 	pub details: bool,
 
+	#[arg(long, requires = "details", help = "Collect per-item detail errors instead of aborting")]
+	pub skip_errors: bool,
+
+	#[arg(
+		long,
+		value_enum,
+		value_name = "POLICY",
+		default_value_t = ErrorExitPolicy::Never,
+		requires = "skip_errors",
+		help = "When to exit non-zero if --skip-errors collected errors"
+	)]
+	pub error_exit: ErrorExitPolicy,
+
 	#[arg(long)]
 	pub ids_only: bool,
 }
@@ -57,6 +78,12 @@ pub enum OrgUsersCommand {
 pub struct OrgUsersListArgs {
 	#[arg(long, value_name = "ORG")]
 	pub org: String,
+
+	#[arg(long, value_name = "ROLE", help = "Only show users with this role")]
+	pub role: Option<OrgRole>,
+
+	#[arg(long, value_name = "TEXT", help = "Only show users whose email contains TEXT")]
+	pub email: Option<String>,
 }
 
 #[derive(Args, Debug)]
@@ -93,6 +120,10 @@ pub enum OrgInviteCommand {
 	Delete(OrgInviteDeleteArgs),
 	#[command(about = "Send invite email [session auth]", long_about = SESSION_AUTH_LONG_ABOUT)]
 	Send(OrgInviteSendArgs),
+	#[command(about = "Send invite emails from a CSV file [session auth]", long_about = SESSION_AUTH_LONG_ABOUT)]
+	SendBulk(OrgInviteSendBulkArgs),
+	#[command(about = "Resend an invite email, or every stale pending one [session auth]", long_about = SESSION_AUTH_LONG_ABOUT)]
+	Resend(OrgInviteResendArgs),
 }
 
 #[derive(Args, Debug)]
@@ -119,6 +150,68 @@ pub struct OrgInviteSendArgs {
 	pub role: OrgRole,
 }
 
+#[derive(Args, Debug)]
+pub struct OrgInviteSendBulkArgs {
+	#[arg(value_name = "ORG")]
+	pub org: String,
+
+	#[arg(long, value_name = "PATH", help = "CSV with email,role columns (header row optional; - reads stdin)")]
+	pub file: PathBuf,
+
+	#[arg(long, value_name = "ROLE", default_value = "user", help = "Role for rows without a role column")]
+	pub role: OrgRole,
+
+	#[arg(
+		long,
+		value_name = "DURATION",
+		default_value = "500ms",
+		value_parser = humantime::parse_duration,
+		help = "Pause between invitations to stay under rate limits"
+	)]
+	pub delay: Duration,
+
+	#[arg(long, help = "Print only failures (as JSON lines, as they happen) and a final count")]
+	pub only_errors: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct OrgInviteResendArgs {
+	#[arg(value_name = "ORG")]
+	pub org: String,
+
+	#[arg(
+		value_name = "INVITE",
+		required_unless_present = "all_pending",
+		conflicts_with = "all_pending",
+		help = "Invitation id (see `org invite list`)"
+	)]
+	pub invite: Option<String>,
+
+	#[arg(long, help = "Resend every unused invitation older than --older-than")]
+	pub all_pending: bool,
+
+	#[arg(
+		long,
+		value_name = "DURATION",
+		default_value = "3d",
+		value_parser = humantime::parse_duration,
+		help = "With --all-pending: only invitations created at least this long ago"
+	)]
+	pub older_than: Duration,
+
+	#[arg(
+		long,
+		value_name = "DURATION",
+		default_value = "500ms",
+		value_parser = humantime::parse_duration,
+		help = "Pause between invitations to stay under rate limits"
+	)]
+	pub delay: Duration,
+
+	#[arg(long, help = "Print only failures (as JSON lines, as they happen) and a final count")]
+	pub only_errors: bool,
+}
+
 #[derive(Args, Debug)]
 pub struct OrgInviteListArgs {
 	#[arg(value_name = "ORG")]
@@ -168,6 +261,8 @@ pub enum OrgWebhooksCommand {
 	Add(OrgWebhooksAddArgs),
 	#[command(about = "Delete webhook [session auth]", long_about = SESSION_AUTH_LONG_ABOUT)]
 	Delete(OrgWebhooksDeleteArgs),
+	#[command(about = "List the event types accepted by webhook add --event")]
+	Events,
 }
 
 #[derive(Args, Debug)]
@@ -187,7 +282,7 @@ pub struct OrgWebhooksAddArgs {
 	#[arg(long, value_name = "NAME")]
 	pub name: String,
 
-	#[arg(long, value_name = "EVENT")]
+	#[arg(long, value_name = "EVENT", help = "Event type (repeatable); see `org webhooks events`")]
 	pub event: Vec<String>,
 }
 
@@ -206,6 +301,35 @@ pub struct OrgLogsArgs {
 	pub org: String,
 }
 
+#[derive(Subcommand, Debug)]
+pub enum OrgNotificationsCommand {
+	#[command(about = "Show unread notification status for your orgs [session auth]", long_about = SESSION_AUTH_LONG_ABOUT)]
+	List,
+	#[command(about = "Poll org notifications and print new messages [session auth]", long_about = SESSION_AUTH_LONG_ABOUT)]
+	Watch(OrgNotificationsWatchArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct OrgNotificationsWatchArgs {
+	#[arg(value_name = "ORG")]
+	pub org: String,
+
+	#[arg(
+		long,
+		value_name = "DURATION",
+		default_value = "15s",
+		value_parser = humantime::parse_duration,
+		help = "Time between polls"
+	)]
+	pub interval: Duration,
+
+	#[arg(long, value_name = "N", default_value_t = 0, help = "Print the last N existing messages first")]
+	pub tail: usize,
+
+	#[arg(long, help = "Mark messages as read after printing them")]
+	pub ack: bool,
+}
+
 #[derive(ValueEnum, Debug, Clone, Copy)]
 pub enum OrgRole {
 	#[value(name = "read-only")]
@@ -0,0 +1,10 @@
+use clap::Args;
+
+#[derive(Args, Debug)]
+pub struct SchemaArgs {
+	#[arg(
+		value_name = "DOCUMENT",
+		help = "Document to print the JSON Schema for (omit to list available documents)"
+	)]
+	pub document: Option<String>,
+}
@@ -10,19 +10,39 @@ pub enum TrpcCommand {
 
 #[derive(Args, Debug)]
 pub struct TrpcCallArgs {
-	#[arg(value_name = "ROUTER.PROCEDURE")]
-	pub procedure: String,
+	#[arg(value_name = "ROUTER.PROCEDURE", required_unless_present = "procedure_file")]
+	pub procedure: Option<String>,
 
 	#[arg(long, value_name = "JSON", conflicts_with = "input_file")]
 	pub input: Option<String>,
 
-	#[arg(long, value_name = "PATH", conflicts_with = "input")]
+	#[arg(long, value_name = "PATH", conflicts_with = "input", help = "JSON input file (- reads stdin)")]
 	pub input_file: Option<PathBuf>,
 
-	#[arg(long, value_name = "COOKIE", conflicts_with = "cookie_file")]
+	#[arg(
+		long,
+		value_name = "PATH",
+		conflicts_with_all = ["procedure", "input", "input_file"],
+		help = "Run a YAML/JSON script of steps; inputs may reference earlier outputs as {{steps.<id>.<path>}} (- reads stdin)"
+	)]
+	pub procedure_file: Option<PathBuf>,
+
+	#[arg(long, value_name = "COOKIE", conflicts_with_all = ["cookie_file", "cookie_from_profile"])]
 	pub cookie: Option<String>,
 
-	#[arg(long, value_name = "PATH", conflicts_with = "cookie")]
+	#[arg(
+		long,
+		value_name = "PATH",
+		conflicts_with_all = ["cookie", "cookie_from_profile"],
+		help = "File holding the Cookie header value (- reads stdin)"
+	)]
 	pub cookie_file: Option<PathBuf>,
+
+	#[arg(
+		long,
+		value_name = "NAME",
+		help = "Use the session stored in another profile (same host) without switching to it"
+	)]
+	pub cookie_from_profile: Option<String>,
 }
 
@@ -0,0 +1,25 @@
+use std::path::PathBuf;
+
+use clap::Args;
+
+#[derive(Args, Debug)]
+pub struct VerifyArgs {
+	#[arg(value_name = "FILE")]
+	pub file: PathBuf,
+
+	#[arg(long, value_name = "PATH", help = "Signature file (default: FILE.minisig)")]
+	pub signature: Option<PathBuf>,
+
+	#[arg(
+		long,
+		value_name = "KEY",
+		env = "ZTNET_VERIFY_KEY",
+		required_unless_present = "pubkey_file",
+		conflicts_with = "pubkey_file",
+		help = "Signer's minisign public key (RW...)"
+	)]
+	pub pubkey: Option<String>,
+
+	#[arg(long, value_name = "PATH", help = "Signer's minisign.pub file")]
+	pub pubkey_file: Option<PathBuf>,
+}
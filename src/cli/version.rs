@@ -0,0 +1,7 @@
+use clap::Args;
+
+#[derive(Args, Debug)]
+pub struct VersionArgs {
+	#[arg(long, help = "Probe the configured server and report feature compatibility")]
+	pub check_server: bool,
+}
@@ -14,6 +14,9 @@ pub enum ConfigError {
 	#[error("failed to determine config directory")]
 	NoConfigDir,
 
+	#[error("failed to determine cache directory")]
+	NoCacheDir,
+
 	#[error("failed to read config file: {path}")]
 	Read {
 		path: PathBuf,
@@ -46,6 +49,9 @@ pub enum ConfigError {
 
 	#[error("invalid timeout value: {0}")]
 	InvalidTimeout(String),
+
+	#[error("invalid {key} value: {value}")]
+	InvalidDuration { key: &'static str, value: String },
 }
 
 #[derive(Debug, Serialize, Deserialize, Default, Clone)]
@@ -58,6 +64,10 @@ pub struct Config {
 
 	#[serde(default)]
 	pub host_defaults: BTreeMap<String, String>,
+
+	/// `[alias]`: command aliases, e.g. `nls = "network list --output table"`.
+	#[serde(default, rename = "alias")]
+	pub aliases: BTreeMap<String, String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Default, Clone)]
@@ -71,6 +81,9 @@ pub struct ProfileConfig {
 	#[serde(default)]
 	pub session_cookie: Option<String>,
 
+	#[serde(default)]
+	pub session_expires_at: Option<i64>,
+
 	#[serde(default)]
 	pub device_cookie: Option<String>,
 
@@ -88,6 +101,28 @@ pub struct ProfileConfig {
 
 	#[serde(default)]
 	pub retries: Option<u32>,
+
+	#[serde(default)]
+	pub backoff_base: Option<String>,
+
+	#[serde(default)]
+	pub backoff_max: Option<String>,
+
+	#[serde(default)]
+	pub allow_default_route: Option<bool>,
+
+	#[serde(default)]
+	pub read_only: Option<bool>,
+
+	/// SHA-256 fingerprint the panel's TLS certificate must match.
+	#[serde(default)]
+	pub cert_fingerprint: Option<String>,
+
+	#[serde(default)]
+	pub user_agent: Option<String>,
+
+	#[serde(default)]
+	pub concurrency: Option<u32>,
 }
 
 impl Config {
@@ -132,6 +167,32 @@ fn default_config_dir() -> Result<PathBuf, ConfigError> {
 	}
 }
 
+/// Machine-local state (backups, caches). Kept apart from the config dir because on
+/// Windows `%APPDATA%` roams between machines while `%LOCALAPPDATA%` does not.
+pub fn default_cache_dir() -> Result<PathBuf, ConfigError> {
+	#[cfg(target_os = "windows")]
+	{
+		let local_app_data = env::var_os("LOCALAPPDATA").ok_or(ConfigError::NoCacheDir)?;
+		return Ok(PathBuf::from(local_app_data).join("ztnet"));
+	}
+
+	#[cfg(target_os = "macos")]
+	{
+		let home = env::var_os("HOME").ok_or(ConfigError::NoCacheDir)?;
+		return Ok(PathBuf::from(home).join("Library").join("Caches").join("ztnet"));
+	}
+
+	#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+	{
+		if let Some(xdg) = env::var_os("XDG_CACHE_HOME") {
+			return Ok(PathBuf::from(xdg).join("ztnet"));
+		}
+
+		let home = env::var_os("HOME").ok_or(ConfigError::NoCacheDir)?;
+		Ok(PathBuf::from(home).join(".cache").join("ztnet"))
+	}
+}
+
 pub fn load_config(path: &Path) -> Result<Config, ConfigError> {
 	match fs::read_to_string(path) {
 		Ok(contents) => toml::from_str(&contents).map_err(|source| ConfigError::Parse {
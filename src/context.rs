@@ -5,8 +5,12 @@ use crate::cli::{GlobalOpts, OutputFormat};
 use crate::config::{Config, ConfigError};
 use crate::error::CliError;
 use crate::host::normalize_host_input;
+use crate::http::{self, Backoff};
 use url::Url;
 
+/// Requests in flight at once for fan-out work (`--details`, `network list --all`).
+const DEFAULT_CONCURRENCY: u32 = 8;
+
 #[derive(Debug, Clone)]
 pub struct EffectiveConfig {
 	pub profile: String,
@@ -19,39 +23,59 @@ pub struct EffectiveConfig {
 	pub output: OutputFormat,
 	pub timeout: Duration,
 	pub retries: u32,
+	pub backoff: Backoff,
+	pub allow_default_route: bool,
+	pub read_only: bool,
+	pub cert_fingerprint: Option<String>,
+	pub user_agent: String,
+	/// Upper bound on requests a command has in flight at once.
+	pub concurrency: usize,
 }
 
 pub fn resolve_effective_config(
 	global: &GlobalOpts,
 	config: &Config,
 ) -> Result<EffectiveConfig, CliError> {
-	let explicit_profile = global
-		.profile
-		.clone()
-		.or_else(|| env::var("ZTNET_PROFILE").ok());
-
-	let explicit_host = global
-		.host
-		.clone()
-		.or_else(|| env::var("ZTNET_HOST").ok())
-		.or_else(|| env::var("API_ADDRESS").ok())
-		.map(|host| normalize_host_input(&host))
-		.transpose()?;
+	resolve_with_sources(global, config).map(|(effective, _)| effective)
+}
 
-	let profile = if let Some(profile) = explicit_profile.clone() {
-		profile
-	} else if let Some(ref host) = explicit_host {
+/// Where each effective setting came from (flag, environment, profile key or default),
+/// in the order `config list --explain` prints them.
+pub type Sources = Vec<(&'static str, String)>;
+
+/// [`resolve_effective_config`], also recording the layer each setting was taken from.
+pub fn resolve_with_sources(global: &GlobalOpts, config: &Config) -> Result<(EffectiveConfig, Sources), CliError> {
+	let explicit_profile = first_set([
+		("flag --profile", global.profile.clone()),
+		("env ZTNET_PROFILE", env::var("ZTNET_PROFILE").ok()),
+	]);
+
+	let explicit_host = first_set([
+		("flag --host", global.host.clone()),
+		("env ZTNET_HOST", env::var("ZTNET_HOST").ok()),
+		("env API_ADDRESS", env::var("API_ADDRESS").ok()),
+	])
+	.map(|(host, source)| normalize_host_input(&host).map(|host| (host, source)))
+	.transpose()?;
+
+	let active = || match config.active_profile.clone() {
+		Some(profile) => (profile, "active_profile".to_string()),
+		None => ("default".to_string(), "default".to_string()),
+	};
+	let (profile, profile_source) = if let Some(explicit) = explicit_profile.clone() {
+		explicit
+	} else if let Some((ref host, ref source)) = explicit_host {
 		let host_key = canonical_host_key(host)?;
-		select_profile_for_host(&host_key, config)?
-			.unwrap_or_else(|| config.active_profile.clone().unwrap_or_else(|| "default".to_string()))
+		match select_profile_for_host(&host_key, config)? {
+			Some(profile) => (profile, format!("profile matching {source}")),
+			None => active(),
+		}
 	} else {
-		config
-			.active_profile
-			.clone()
-			.unwrap_or_else(|| "default".to_string())
+		active()
 	};
 
 	let profile_cfg = config.profile(&profile);
+	let mut sources = Layers { profile: &profile, sources: vec![("profile", profile_source)] };
 
 	let profile_host_normalized = profile_cfg
 		.host
@@ -61,22 +85,20 @@ pub fn resolve_effective_config(
 		.map(normalize_host_input)
 		.transpose()?;
 
-	let host = if let Some(host) = explicit_host {
-		if explicit_profile.is_some() {
-			if let Some(ref profile_host) = profile_host_normalized {
-				let profile_key = canonical_host_key(profile_host)?;
-				let target_key = canonical_host_key(&host)?;
-				if profile_key != target_key {
-					return Err(CliError::InvalidArgument(format!(
-						"profile '{profile}' is configured for '{profile_host}', but the target host is '{host}'",
-					)));
-				}
+	let host = if let Some((host, source)) = explicit_host {
+		if explicit_profile.is_some() && let Some(ref profile_host) = profile_host_normalized {
+			let profile_key = canonical_host_key(profile_host)?;
+			let target_key = canonical_host_key(&host)?;
+			if profile_key != target_key {
+				return Err(CliError::InvalidArgument(format!(
+					"profile '{profile}' is configured for '{profile_host}', but the target host is '{host}'",
+				)));
 			}
 		}
+		sources.push("host", source);
 		host
 	} else {
-		profile_host_normalized
-			.clone()
+		sources.pick("host", None, profile_host_normalized.clone(), "host", "default")
 			.unwrap_or_else(|| "http://localhost:3000".to_string())
 	};
 
@@ -84,17 +106,16 @@ pub fn resolve_effective_config(
 	let profile_host_key = canonical_host_key_opt(profile_host_normalized.as_deref());
 	let profile_host_matches = profile_host_key.as_deref() == Some(&target_host_key);
 
-	let token_override = global
-		.token
-		.clone()
-		.or_else(|| env::var("ZTNET_API_TOKEN").ok())
-		.or_else(|| env::var("ZTNET_TOKEN").ok());
-
-	let token = if token_override.is_some() {
-		token_override
-	} else if profile_host_matches {
-		empty_to_none(profile_cfg.token.clone())
+	let token_override = first_set([
+		("flag --token", global.token.clone()),
+		("env ZTNET_API_TOKEN", env::var("ZTNET_API_TOKEN").ok()),
+		("env ZTNET_TOKEN", env::var("ZTNET_TOKEN").ok()),
+	]);
+	let profile_token = empty_to_none(profile_cfg.token.clone());
+	let token = if profile_host_matches || token_override.is_some() || profile_token.is_none() {
+		sources.pick("token", token_override, profile_token, "token", "unset")
 	} else {
+		sources.push("token", "unset (profile token is for a different host)".to_string());
 		None
 	};
 
@@ -104,50 +125,177 @@ pub fn resolve_effective_config(
 	let device_cookie = profile_host_matches
 		.then(|| empty_to_none(profile_cfg.device_cookie.clone()))
 		.flatten();
-
-	let org = global
-		.org
-		.clone()
-		.or_else(|| empty_to_none(profile_cfg.default_org.clone()));
-
-	let network = global
-		.network
-		.clone()
-		.or_else(|| empty_to_none(profile_cfg.default_network.clone()));
-
-	let output = if global.json {
-		OutputFormat::Json
-	} else if let Some(output) = global.output {
-		output
-	} else if let Ok(value) = env::var("ZTNET_OUTPUT") {
-		parse_output_format(&value)?
-	} else {
-		profile_cfg.output.unwrap_or(OutputFormat::Table)
+	sources.pick("session", None, session_cookie.as_ref(), "session_cookie", "unset");
+
+	let org = sources.pick(
+		"org",
+		first_set([("flag --org", global.org.clone())]),
+		empty_to_none(profile_cfg.default_org.clone()),
+		"default_org",
+		"unset",
+	);
+
+	let network = sources.pick(
+		"network",
+		first_set([("flag --network", global.network.clone())]),
+		empty_to_none(profile_cfg.default_network.clone()),
+		"default_network",
+		"unset",
+	);
+
+	let output_override = match first_set([
+		("flag --json", global.json.then_some(OutputFormat::Json)),
+		("flag --output", global.output),
+	]) {
+		Some(explicit) => Some(explicit),
+		None => env::var("ZTNET_OUTPUT")
+			.ok()
+			.map(|value| parse_output_format(&value).map(|format| (format, "env ZTNET_OUTPUT".to_string())))
+			.transpose()?,
 	};
-
-	let timeout_str = global
-		.timeout
-		.clone()
-		.or_else(|| empty_to_none(profile_cfg.timeout.clone()))
+	let output = sources
+		.pick("output", output_override, profile_cfg.output, "output", "default")
+		.unwrap_or(OutputFormat::Table);
+
+	let timeout_str = sources
+		.pick(
+			"timeout",
+			first_set([("flag --timeout", global.timeout.clone())]),
+			empty_to_none(profile_cfg.timeout.clone()),
+			"timeout",
+			"default",
+		)
 		.unwrap_or_else(|| "30s".to_string());
 
 	let timeout = humantime::parse_duration(&timeout_str)
 		.map_err(|_| ConfigError::InvalidTimeout(timeout_str))?;
 
-	let retries = global.retries.or(profile_cfg.retries).unwrap_or(3);
-
-	Ok(EffectiveConfig {
-		profile,
-		host,
-		token,
-		session_cookie,
-		device_cookie,
-		org,
-		network,
-		output,
-		timeout,
-		retries,
-	})
+	let retries = sources
+		.pick("retries", first_set([("flag --retries", global.retries)]), profile_cfg.retries, "retries", "default")
+		.unwrap_or(3);
+	let concurrency = sources
+		.pick(
+			"concurrency",
+			first_set([("flag --concurrency or env ZTNET_CONCURRENCY", global.concurrency)]),
+			profile_cfg.concurrency,
+			"concurrency",
+			"default",
+		)
+		.unwrap_or(DEFAULT_CONCURRENCY)
+		.max(1) as usize;
+
+	let defaults = Backoff::default();
+	let backoff_base = sources.pick(
+		"backoff_base",
+		first_set([("flag --backoff-base", global.backoff_base.clone())]),
+		empty_to_none(profile_cfg.backoff_base.clone()),
+		"backoff_base",
+		"default",
+	);
+	let backoff_max = sources.pick(
+		"backoff_max",
+		first_set([("flag --backoff-max", global.backoff_max.clone())]),
+		empty_to_none(profile_cfg.backoff_max.clone()),
+		"backoff_max",
+		"default",
+	);
+	let backoff = Backoff {
+		base: parse_duration_setting("backoff_base", backoff_base)?.unwrap_or(defaults.base),
+		max: parse_duration_setting("backoff_max", backoff_max)?.unwrap_or(defaults.max),
+	};
+	if backoff.base > backoff.max {
+		return Err(CliError::InvalidArgument(format!(
+			"backoff_base ({}) cannot exceed backoff_max ({})",
+			humantime::format_duration(backoff.base),
+			humantime::format_duration(backoff.max)
+		)));
+	}
+
+	// A read-only profile cannot be loosened from the command line.
+	let read_only = sources
+		.pick(
+			"read_only",
+			first_set([("flag --read-only or env ZTNET_READONLY", global.read_only.then_some(true))]),
+			profile_cfg.read_only,
+			"read_only",
+			"default",
+		)
+		.unwrap_or(false)
+		|| profile_cfg.read_only.unwrap_or(false);
+
+	let user_agent = sources
+		.pick(
+			"user_agent",
+			first_set([("flag --user-agent or env ZTNET_USER_AGENT", empty_to_none(global.user_agent.clone()))]),
+			empty_to_none(profile_cfg.user_agent.clone()),
+			"user_agent",
+			"default",
+		)
+		.unwrap_or_else(http::default_user_agent);
+
+	let sources = sources.sources;
+	Ok((
+		EffectiveConfig {
+			profile,
+			host,
+			token,
+			session_cookie,
+			device_cookie,
+			org,
+			network,
+			output,
+			timeout,
+			retries,
+			backoff,
+			allow_default_route: profile_cfg.allow_default_route.unwrap_or(false),
+			read_only,
+			cert_fingerprint: profile_cfg.cert_fingerprint.clone(),
+			user_agent,
+			concurrency,
+		},
+		sources,
+	))
+}
+
+/// The first layer that has a value, with the layer's name.
+fn first_set<T, const N: usize>(layers: [(&str, Option<T>); N]) -> Option<(T, String)> {
+	layers.into_iter().find_map(|(source, value)| value.map(|value| (value, source.to_string())))
+}
+
+/// Collects [`Sources`] while [`resolve_with_sources`] walks the layers.
+struct Layers<'a> {
+	profile: &'a str,
+	sources: Sources,
+}
+
+impl Layers<'_> {
+	fn push(&mut self, key: &'static str, source: String) {
+		self.sources.push((key, source));
+	}
+
+	/// `explicit` (flag or environment), then the profile's `field`, then `fallback`.
+	fn pick<T>(
+		&mut self,
+		key: &'static str,
+		explicit: Option<(T, String)>,
+		profile: Option<T>,
+		field: &str,
+		fallback: &str,
+	) -> Option<T> {
+		let (value, source) = match (explicit, profile) {
+			(Some((value, source)), _) => (Some(value), source),
+			(None, Some(value)) => (Some(value), format!("profiles.{}.{field}", self.profile)),
+			(None, None) => (None, fallback.to_string()),
+		};
+		self.push(key, source);
+		value
+	}
+}
+
+fn parse_duration_setting(key: &'static str, value: Option<String>) -> Result<Option<Duration>, ConfigError> {
+	value
+		.map(|value| humantime::parse_duration(value.trim()).map_err(|_| ConfigError::InvalidDuration { key, value }))
+		.transpose()
 }
 
 fn parse_output_format(value: &str) -> Result<OutputFormat, ConfigError> {
@@ -240,11 +388,46 @@ pub(crate) fn canonical_host_key_opt(raw: Option<&str>) -> Option<String> {
 		})
 }
 
+/// A `host_defaults` entry that can no longer be used: `select_profile_for_host` would
+/// reject it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct StaleHostDefault {
+	pub host: String,
+	pub profile: String,
+	pub reason: String,
+}
+
+/// `host_defaults` entries pointing at a missing profile or at a profile now configured
+/// for another host (or none), in host order.
+pub(crate) fn stale_host_defaults(config: &Config) -> Vec<StaleHostDefault> {
+	config
+		.host_defaults
+		.iter()
+		.filter_map(|(host, profile)| {
+			let reason = match config.profiles.get(profile) {
+				None => format!("profile '{profile}' does not exist"),
+				Some(cfg) => match cfg.host.as_deref().map(str::trim).filter(|h| !h.is_empty()) {
+					None => format!("profile '{profile}' has no host"),
+					Some(profile_host) if canonical_host_key_opt(Some(profile_host)).as_deref() != Some(host) => {
+						format!("profile '{profile}' is configured for {profile_host}")
+					}
+					Some(_) => return None,
+				},
+			};
+			Some(StaleHostDefault {
+				host: host.clone(),
+				profile: profile.clone(),
+				reason,
+			})
+		})
+		.collect()
+}
+
 fn select_profile_for_host(host_key: &str, config: &Config) -> Result<Option<String>, CliError> {
 	if let Some(profile) = config.host_defaults.get(host_key).cloned() {
 		if !config.profiles.contains_key(&profile) {
 			return Err(CliError::InvalidArgument(format!(
-				"host_defaults maps '{host_key}' to unknown profile '{profile}'"
+				"host_defaults maps '{host_key}' to unknown profile '{profile}' (run `ztnet auth hosts prune`)"
 			)));
 		}
 
@@ -252,7 +435,7 @@ fn select_profile_for_host(host_key: &str, config: &Config) -> Result<Option<Str
 		let profile_host_key = canonical_host_key_opt(cfg.host.as_deref());
 		if profile_host_key.as_deref() != Some(host_key) {
 			return Err(CliError::InvalidArgument(format!(
-				"host_defaults for '{host_key}' points to profile '{profile}', but that profile's host does not match"
+				"host_defaults for '{host_key}' points to profile '{profile}', but that profile's host does not match (run `ztnet auth hosts prune`)"
 			)));
 		}
 
@@ -284,13 +467,34 @@ mod tests {
 			network: None,
 			json: false,
 			output: Some(OutputFormat::Json),
+			out: None,
+			encrypt_to: Vec::new(),
+			sign_key: None,
+			max_body_print: 1 << 20,
 			no_color: true,
+			no_pager: true,
 			quiet: true,
 			verbose: 0,
 			timeout: Some("30s".to_string()),
 			retries: Some(3),
+			concurrency: None,
+			backoff_base: None,
+			backoff_max: None,
+			read_only: false,
+			resolve: Vec::new(),
+			host_header: None,
+			no_compression: false,
+			compress_requests: false,
+			no_proxy: false,
+			prefer_ipv4: false,
+			prefer_ipv6: false,
+			user_agent: None,
 			dry_run: false,
+			force_send: false,
 			yes: false,
+			confirm_timeout: None,
+			confirm_default: crate::cli::ConfirmDefault::No,
+			progress: crate::cli::ProgressMode::None,
 		}
 	}
 
@@ -314,18 +518,62 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn stale_host_defaults_flags_missing_profiles_and_moved_hosts() {
+		let mut cfg = Config::default();
+		for (name, host) in [("prod", Some("https://ztnet.example.com/api")), ("lab", Some("https://lab.example.com")), ("bare", None)] {
+			cfg.profiles.insert(
+				name.to_string(),
+				ProfileConfig {
+					host: host.map(str::to_string),
+					..Default::default()
+				},
+			);
+		}
+		for (host, profile) in [
+			("https://ztnet.example.com", "prod"),
+			("https://old-lab.example.com", "lab"),
+			("https://gone.example.com", "renamed"),
+			("https://bare.example.com", "bare"),
+		] {
+			cfg.host_defaults.insert(host.to_string(), profile.to_string());
+		}
+
+		let stale: Vec<(String, String)> =
+			stale_host_defaults(&cfg).into_iter().map(|entry| (entry.host, entry.reason)).collect();
+		assert_eq!(
+			stale,
+			[
+				("https://bare.example.com".to_string(), "profile 'bare' has no host".to_string()),
+				("https://gone.example.com".to_string(), "profile 'renamed' does not exist".to_string()),
+				(
+					"https://old-lab.example.com".to_string(),
+					"profile 'lab' is configured for https://lab.example.com".to_string()
+				),
+			]
+		);
+	}
+
 	#[test]
 	fn canonical_host_key_normalizes_ipv6() {
 		assert_eq!(
 			canonical_host_key("http://[2001:db8::1]:3000/").unwrap(),
 			"http://[2001:db8::1]:3000"
 		);
+		// Spelled differently, same host: one key, so host defaults and sessions match.
+		assert_eq!(
+			canonical_host_key("https://[FD00:0:0:0::A]:443/api").unwrap(),
+			"https://[fd00::a]"
+		);
+		assert_eq!(canonical_host_key_opt(Some("http://[::ffff:10.0.0.1]")).as_deref(), Some("http://[::ffff:10.0.0.1]"));
 	}
 
 	#[test]
 	fn resolve_effective_config_selects_host_default_profile() {
-		let mut cfg = Config::default();
-		cfg.active_profile = Some("default".to_string());
+		let mut cfg = Config {
+			active_profile: Some("default".to_string()),
+			..Default::default()
+		};
 		cfg.profiles.insert(
 			"prod".to_string(),
 			ProfileConfig {
@@ -377,8 +625,10 @@ mod tests {
 
 	#[test]
 	fn resolve_effective_config_drops_stored_creds_when_host_mismatch() {
-		let mut cfg = Config::default();
-		cfg.active_profile = Some("default".to_string());
+		let mut cfg = Config {
+			active_profile: Some("default".to_string()),
+			..Default::default()
+		};
 		cfg.profiles.insert(
 			"default".to_string(),
 			ProfileConfig {
@@ -418,5 +668,110 @@ mod tests {
 			other => panic!("expected InvalidArgument, got {other:?}"),
 		}
 	}
-}
 
+	#[test]
+	fn resolve_effective_config_layers_backoff_settings() {
+		let mut cfg = Config::default();
+		cfg.profiles.insert(
+			"default".to_string(),
+			ProfileConfig {
+				host: Some("https://ztnet.example.com".to_string()),
+				backoff_base: Some("1s".to_string()),
+				backoff_max: Some("20s".to_string()),
+				..Default::default()
+			},
+		);
+
+		let mut global = base_global();
+		let effective = resolve_effective_config(&global, &cfg).unwrap();
+		assert_eq!(effective.backoff.base, Duration::from_secs(1));
+		assert_eq!(effective.backoff.max, Duration::from_secs(20));
+
+		global.backoff_max = Some("500ms".to_string());
+		assert!(resolve_effective_config(&global, &cfg).is_err());
+	}
+
+	#[test]
+	fn resolve_with_sources_reports_flag_profile_and_default() {
+		let mut cfg = Config::default();
+		cfg.profiles.insert(
+			"default".to_string(),
+			ProfileConfig {
+				host: Some("https://host-a.example.com".to_string()),
+				token: Some("a-token".to_string()),
+				retries: Some(5),
+				concurrency: Some(2),
+				..Default::default()
+			},
+		);
+
+		let mut global = base_global();
+		global.timeout = Some("5s".to_string());
+		global.retries = None;
+		let (effective, sources) = resolve_with_sources(&global, &cfg).unwrap();
+		assert_eq!(effective.concurrency, 2);
+		let sources: std::collections::HashMap<_, _> = sources.into_iter().collect();
+		assert_eq!(sources["concurrency"], "profiles.default.concurrency");
+		assert_eq!(sources["host"], "profiles.default.host");
+		assert_eq!(sources["token"], "profiles.default.token");
+		assert_eq!(sources["retries"], "profiles.default.retries");
+		assert_eq!(sources["timeout"], "flag --timeout");
+		assert_eq!(sources["backoff_max"], "default");
+		assert_eq!(sources["org"], "unset");
+
+		global.host = Some("https://host-b.example.com".to_string());
+		let sources: std::collections::HashMap<_, _> =
+			resolve_with_sources(&global, &cfg).unwrap().1.into_iter().collect();
+		assert_eq!(sources["host"], "flag --host");
+		assert!(sources["token"].contains("different host"));
+
+		global.concurrency = Some(16);
+		assert_eq!(resolve_effective_config(&global, &cfg).unwrap().concurrency, 16);
+	}
+
+	#[test]
+	fn resolve_with_sources_follows_flag_profile_default_precedence() {
+		let mut cfg = Config { active_profile: Some("default".to_string()), ..Default::default() };
+		cfg.profiles.insert(
+			"default".to_string(),
+			ProfileConfig { host: Some("https://host-a.example.com".to_string()), ..Default::default() },
+		);
+		cfg.profiles.insert(
+			"lab".to_string(),
+			ProfileConfig {
+				host: Some("https://host-b.example.com".to_string()),
+				token: Some("lab-token".to_string()),
+				default_org: Some("lab-org".to_string()),
+				timeout: Some("7s".to_string()),
+				read_only: Some(true),
+				..Default::default()
+			},
+		);
+
+		let mut global = base_global();
+		global.host = Some("https://host-b.example.com".to_string());
+		global.output = None;
+		global.timeout = None;
+		global.retries = Some(9);
+		let (effective, sources) = resolve_with_sources(&global, &cfg).unwrap();
+		let sources: std::collections::HashMap<_, _> = sources.into_iter().collect();
+		assert_eq!((effective.profile.as_str(), sources["profile"].as_str()), ("lab", "profile matching flag --host"));
+		assert_eq!((effective.token.as_deref(), sources["token"].as_str()), (Some("lab-token"), "profiles.lab.token"));
+		assert_eq!((effective.org.as_deref(), sources["org"].as_str()), (Some("lab-org"), "profiles.lab.default_org"));
+		assert_eq!((effective.timeout, sources["timeout"].as_str()), (Duration::from_secs(7), "profiles.lab.timeout"));
+		assert_eq!((effective.retries, sources["retries"].as_str()), (9, "flag --retries"));
+		assert!(matches!(effective.output, OutputFormat::Table));
+		assert_eq!(sources["output"], "default");
+		assert_eq!((effective.read_only, sources["read_only"].as_str()), (true, "profiles.lab.read_only"));
+
+		global.org = Some("flag-org".to_string());
+		global.timeout = Some("2s".to_string());
+		global.json = true;
+		let (effective, sources) = resolve_with_sources(&global, &cfg).unwrap();
+		let sources: std::collections::HashMap<_, _> = sources.into_iter().collect();
+		assert_eq!((effective.org.as_deref(), sources["org"].as_str()), (Some("flag-org"), "flag --org"));
+		assert_eq!((effective.timeout, sources["timeout"].as_str()), (Duration::from_secs(2), "flag --timeout"));
+		assert!(matches!(effective.output, OutputFormat::Json));
+		assert_eq!(sources["output"], "flag --json");
+	}
+}
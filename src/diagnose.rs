@@ -0,0 +1,136 @@
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+
+use futures_util::future::join_all;
+use tokio::net::{TcpStream, lookup_host};
+use tokio::time::timeout_at;
+use url::Url;
+
+use crate::error::CliError;
+use crate::http::ConnectOverrides;
+use crate::output;
+use crate::proxy;
+use crate::tls;
+
+/// Upper bound for the whole report, so a dead host only delays the error briefly.
+/// The TCP probes run in parallel and share whatever the DNS step left.
+const BUDGET: Duration = Duration::from_secs(2);
+const MAX_ADDRS: usize = 3;
+
+/// Turns a final connect/timeout failure into an error carrying a short DNS / TCP / proxy
+/// report for `url`; every other request error passes through unchanged.
+pub(crate) async fn connection_error(err: reqwest::Error, url: &Url, connect: &ConnectOverrides) -> CliError {
+	if tls::pin_mismatch(&err).is_some() || !(err.is_connect() || err.is_timeout()) {
+		return tls::request_error(err, url);
+	}
+	let diagnostics = diagnose(url, connect).await;
+	CliError::Unreachable { source: err, diagnostics }
+}
+
+async fn diagnose(url: &Url, connect: &ConnectOverrides) -> String {
+	let host = url
+		.host_str()
+		.unwrap_or_default()
+		.trim_start_matches('[')
+		.trim_end_matches(']')
+		.to_ascii_lowercase();
+	let port = url.port_or_known_default().unwrap_or(443);
+	let started = Instant::now();
+	let deadline = tokio::time::Instant::from_std(started + BUDGET);
+
+	let mut lines = Vec::new();
+	let addrs: Vec<SocketAddr> = if let Ok(ip) = host.parse::<IpAddr>() {
+		lines.push("dns:   skipped (host is an IP address)".to_string());
+		vec![SocketAddr::new(ip, port)]
+	} else if let Some(entry) = connect.resolve.iter().find(|e| e.host == host && e.port == port) {
+		lines.push(format!("dns:   {host} -> {} (from --resolve)", entry.addr));
+		vec![SocketAddr::new(entry.addr, entry.port)]
+	} else if connect.host_header.as_deref() == Some(host.as_str()) {
+		lines.push(format!("dns:   skipped ({host} is pinned by --host-header)"));
+		Vec::new()
+	} else {
+		match timeout_at(deadline, lookup_host((host.as_str(), port))).await {
+			Ok(Ok(found)) => {
+				// Same order the request tried, so a dual-stack host gets one probe per family.
+				let addrs = connect.prefer.order(found);
+				let shown: Vec<String> = addrs.iter().map(|a| a.ip().to_string()).collect();
+				lines.push(format!(
+					"dns:   {host} -> {} ({})",
+					shown.join(", "),
+					output::format_duration(started.elapsed())
+				));
+				addrs
+			}
+			Ok(Err(err)) => {
+				lines.push(format!("dns:   {host} did not resolve: {err}"));
+				Vec::new()
+			}
+			Err(_) => {
+				lines.push(format!("dns:   lookup of {host} timed out after {}", output::format_duration(BUDGET)));
+				Vec::new()
+			}
+		}
+	};
+
+	let probes = addrs.iter().take(MAX_ADDRS).map(|addr| async move {
+		let started = Instant::now();
+		match timeout_at(deadline, TcpStream::connect(addr)).await {
+			Ok(Ok(_)) => format!(
+				"tcp:   connected to {addr} in {} (the port is open; suspect TLS, a proxy or --timeout)",
+				output::format_duration(started.elapsed())
+			),
+			Ok(Err(err)) => format!("tcp:   {addr} failed: {err}"),
+			Err(_) => format!(
+				"tcp:   {addr} did not answer within {} (firewall or wrong port?)",
+				output::format_duration(started.elapsed())
+			),
+		}
+	});
+	lines.extend(join_all(probes).await);
+
+	lines.push(proxy_line(url.scheme(), &host, connect));
+	lines
+		.iter()
+		.map(|line| format!("    {line}"))
+		.collect::<Vec<_>>()
+		.join("\n")
+}
+
+/// Describes the proxy (if any) the request went through. The TCP check above always
+/// connects directly, so a proxy in use is called out.
+fn proxy_line(scheme: &str, host: &str, connect: &ConnectOverrides) -> String {
+	let selection = connect.proxy_selection(scheme, host);
+	match selection {
+		proxy::Selection::Proxy { .. } => format!("{} (the tcp check is direct)", selection.describe(host)),
+		_ => selection.describe(host),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[tokio::test]
+	async fn diagnose_reports_closed_ports_within_the_budget() {
+		let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+		let port = listener.local_addr().unwrap().port();
+		drop(listener);
+
+		// 192.0.2.0/24 is TEST-NET-1: the probe either fails fast or runs into the budget.
+		let url = Url::parse(&format!("http://panel.test:{port}")).unwrap();
+		let connect = ConnectOverrides {
+			resolve: vec![crate::http::parse_resolve_override(&format!("panel.test:{port}:192.0.2.1")).unwrap()],
+			no_proxy: true,
+			..Default::default()
+		};
+		let started = Instant::now();
+		let report = diagnose(&url, &connect).await;
+		assert!(started.elapsed() < BUDGET + Duration::from_millis(500), "{report}");
+		assert!(report.contains("(from --resolve)"), "{report}");
+
+		let url = Url::parse(&format!("http://127.0.0.1:{port}")).unwrap();
+		let report = diagnose(&url, &connect).await;
+		assert!(report.contains("dns:   skipped (host is an IP address)"), "{report}");
+		assert!(report.contains(&format!("tcp:   127.0.0.1:{port} failed")), "{report}");
+	}
+}
@@ -0,0 +1,60 @@
+//! `--encrypt-to`: age encryption of the files the CLI writes (`--out`, `export hosts --out`,
+//! backup downloads), so exported inventory can sit in shared artifact stores. Decrypt with
+//! `age -d -i key.txt FILE`.
+
+use std::io::Write;
+
+use age::x25519::Recipient;
+
+use crate::error::CliError;
+
+pub(crate) fn parse_recipients(raw: &[String]) -> Result<Vec<Recipient>, CliError> {
+	raw.iter()
+		.map(|text| text.trim())
+		.filter(|text| !text.is_empty())
+		.map(|text| {
+			text.parse::<Recipient>().map_err(|err| {
+				CliError::InvalidArgument(format!("invalid --encrypt-to recipient '{text}': {err} (expected an age1... public key)"))
+			})
+		})
+		.collect()
+}
+
+/// Encrypts `plaintext` to every recipient in the binary age format.
+pub(crate) fn encrypt(plaintext: &[u8], recipients: &[Recipient]) -> Result<Vec<u8>, CliError> {
+	let encryptor = age::Encryptor::with_recipients(recipients.iter().map(|r| r as &dyn age::Recipient))
+		.map_err(|err| CliError::InvalidArgument(format!("age encryption failed: {err}")))?;
+	let mut out = Vec::with_capacity(plaintext.len() + 256);
+	let mut writer = encryptor.wrap_output(&mut out)?;
+	writer.write_all(plaintext)?;
+	writer.finish()?;
+	Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::io::Read;
+
+	#[test]
+	fn encrypts_to_every_recipient() {
+		let (alice, bob) = (age::x25519::Identity::generate(), age::x25519::Identity::generate());
+		let recipients =
+			parse_recipients(&[alice.to_public().to_string(), format!(" {} ", bob.to_public())]).unwrap();
+		let sealed = encrypt(b"10.0.0.1 web.zt\n", &recipients).unwrap();
+		assert!(sealed.starts_with(b"age-encryption.org/v1"));
+
+		for identity in [&alice, &bob] {
+			let decryptor = age::Decryptor::new(&sealed[..]).unwrap();
+			let mut plain = String::new();
+			decryptor
+				.decrypt(std::iter::once(identity as &dyn age::Identity))
+				.unwrap()
+				.read_to_string(&mut plain)
+				.unwrap();
+			assert_eq!(plain, "10.0.0.1 web.zt\n");
+		}
+
+		assert!(parse_recipients(&["ssh-ed25519 AAAA".to_string()]).is_err());
+	}
+}
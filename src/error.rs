@@ -16,15 +16,44 @@ pub enum CliError {
 	#[error("this command requires session authentication\n\n  Run: ztnet auth login --email <EMAIL> --password <PASSWORD>\n\n  This command uses a tRPC endpoint that requires user credentials,\n  not an API token. See: ztnet auth login --help")]
 	SessionRequired,
 
+	#[error("this command requires {required}, but only {available} is configured\n\n  Run: {fix}")]
+	AuthMethodMissing {
+		required: AuthMethod,
+		available: AuthMethod,
+		fix: String,
+	},
+
 	#[error("invalid argument: {0}")]
 	InvalidArgument(String),
 
+	#[error("read-only mode: refusing to send {0}\n\n  Read-only mode is set by --read-only, ZTNET_READONLY or the profile's read_only key.")]
+	ReadOnly(String),
+
 	#[error("dry-run: request printed")]
 	DryRunPrinted,
 
 	#[error("request failed: {0}")]
 	Request(#[from] reqwest::Error),
 
+	#[error("request failed: {source}\n\n  diagnostics:\n{diagnostics}")]
+	Unreachable {
+		source: reqwest::Error,
+		diagnostics: String,
+	},
+
+	#[error("certificate for {host} does not match the pinned fingerprint\n\n  pinned: {expected}\n  server: {actual}\n\n  If the certificate was replaced on purpose, check it with `ztnet host cert show`\n  and update the profile's cert_fingerprint.")]
+	CertPinMismatch {
+		host: String,
+		expected: String,
+		actual: String,
+	},
+
+	#[error("login failed at {step}: {source}")]
+	LoginStep {
+		step: &'static str,
+		source: Box<CliError>,
+	},
+
 	#[error("http {status}: {message}")]
 	HttpStatus {
 		status: StatusCode,
@@ -32,9 +61,30 @@ pub enum CliError {
 		body: Option<String>,
 	},
 
+	#[error("the server rejected the input{}:\n{}", procedure.as_deref().map(|p| format!(" to {p}")).unwrap_or_default(), render_issues(issues))]
+	InputRejected {
+		procedure: Option<String>,
+		issues: Vec<FieldIssue>,
+	},
+
+	#[error("signature check failed: {0}")]
+	SignatureInvalid(String),
+
+	#[error("drift detected: {0}")]
+	Drift(String),
+
+	#[error("{failed} of {total} requests failed")]
+	PartialFailure { failed: usize, total: usize },
+
+	#[error("timed out: {0}")]
+	Timeout(String),
+
 	#[error("rate limited (429) after retries exhausted")]
 	RateLimited,
 
+	#[error("interrupted")]
+	Interrupted,
+
 	#[error("I/O error: {0}")]
 	Io(#[from] io::Error),
 
@@ -45,13 +95,56 @@ pub enum CliError {
 	Url(#[from] url::ParseError),
 }
 
+/// One field of a tRPC input that failed the server's validation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldIssue {
+	/// Field path as written in the input, e.g. `updateParams.routes[0].target`.
+	pub path: String,
+	pub message: String,
+	/// The CLI flag that most likely produced the field.
+	pub flag: Option<&'static str>,
+}
+
+fn render_issues(issues: &[FieldIssue]) -> String {
+	let lines: Vec<String> = issues
+		.iter()
+		.map(|issue| {
+			let path = if issue.path.is_empty() { "(input)" } else { issue.path.as_str() };
+			match issue.flag {
+				Some(flag) => format!("  {path}: {} (check {flag})", issue.message),
+				None => format!("  {path}: {}", issue.message),
+			}
+		})
+		.collect();
+	lines.join("\n")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthMethod {
+	Token,
+	Session,
+}
+
+impl std::fmt::Display for AuthMethod {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let value = match self {
+			AuthMethod::Token => "an API token",
+			AuthMethod::Session => "a session (email/password login)",
+		};
+		write!(f, "{value}")
+	}
+}
+
 impl CliError {
 	pub fn exit_code(&self) -> i32 {
 		match self {
 			CliError::DryRunPrinted => 0,
 			CliError::MissingConfig(_) | CliError::InvalidArgument(_) => 2,
-			CliError::SessionRequired => 3,
+			CliError::SessionRequired | CliError::AuthMethodMissing { .. } | CliError::ReadOnly(_) => 3,
+			CliError::InputRejected { .. } => 5,
 			CliError::RateLimited => 6,
+			CliError::Interrupted => 130,
+			CliError::LoginStep { source, .. } => source.exit_code(),
 			CliError::HttpStatus { status, .. } => match *status {
 				StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => 3,
 				StatusCode::NOT_FOUND => 4,
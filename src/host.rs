@@ -1,3 +1,5 @@
+use std::net::Ipv6Addr;
+
 use url::Url;
 
 use crate::error::CliError;
@@ -8,10 +10,11 @@ pub(crate) fn normalize_host_input(raw: &str) -> Result<String, CliError> {
 		return Err(CliError::InvalidArgument("host cannot be empty".to_string()));
 	}
 
+	let trimmed = bracket_bare_ipv6(trimmed);
 	let with_scheme = if trimmed.contains("://") {
-		trimmed.to_string()
+		trimmed
 	} else {
-		let scheme = infer_default_scheme(trimmed);
+		let scheme = infer_default_scheme(&trimmed);
 		format!("{scheme}://{trimmed}")
 	};
 
@@ -69,6 +72,21 @@ pub(crate) fn api_base_candidates(base: &str) -> Vec<String> {
 	out
 }
 
+/// `fd00::1/api` or `http://fd00::1` is unambiguous even without the brackets URLs need
+/// around IPv6 literals; a port requires them (`[fd00::1]:3000`).
+fn bracket_bare_ipv6(raw: &str) -> String {
+	let (scheme, rest) = match raw.split_once("://") {
+		Some((scheme, rest)) => (format!("{scheme}://"), rest),
+		None => (String::new(), raw),
+	};
+	let (host, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+	if host.parse::<Ipv6Addr>().is_ok() {
+		format!("{scheme}[{host}]{path}")
+	} else {
+		raw.to_string()
+	}
+}
+
 fn infer_default_scheme(raw: &str) -> &'static str {
 	let before_slash = raw.split('/').next().unwrap_or(raw);
 
@@ -151,6 +169,14 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn normalize_host_input_brackets_bare_ipv6_literals() {
+		assert_eq!(normalize_host_input("fd00:1234::5/api").unwrap(), "https://[fd00:1234::5]/api");
+		assert_eq!(normalize_host_input("http://FD00::A").unwrap(), "http://[fd00::a]");
+		assert_eq!(normalize_host_input("::1").unwrap(), "http://[::1]");
+		assert_eq!(normalize_host_input("[fd00::5]:3000").unwrap(), "https://[fd00::5]:3000");
+	}
+
 	#[test]
 	fn normalize_host_input_trims_and_removes_trailing_slash() {
 		assert_eq!(
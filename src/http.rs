@@ -1,16 +1,26 @@
+use std::io::Write;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::time::Duration;
 
 use bytes::Bytes;
+use flate2::write::GzEncoder;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
 use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 use reqwest::{Method, StatusCode};
 use serde_json::Value;
 use url::Url;
 
+use crate::cancel;
 use crate::cli::GlobalOpts;
 use crate::context::EffectiveConfig;
-use crate::error::CliError;
+use crate::diagnose;
+use crate::error::{AuthMethod, CliError};
 use crate::multi_base::{self, BaseCandidate};
+use crate::proxy;
+use crate::redact;
+use crate::tls;
 
 const AUTH_HEADER: &str = "x-ztnet-auth";
 
@@ -19,6 +29,361 @@ pub(crate) struct ClientUi {
 	pub quiet: bool,
 	pub no_color: bool,
 	pub profile: Option<String>,
+	pub has_session: bool,
+	pub connect: ConnectOverrides,
+	pub backoff: Backoff,
+	pub read_only: bool,
+}
+
+/// Retry delay policy: the ceiling doubles from `base` up to `max`, and each delay is
+/// drawn uniformly from `[0, ceiling]` ("full jitter") so many clients failing at once
+/// don't retry in lockstep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Backoff {
+	pub base: Duration,
+	pub max: Duration,
+}
+
+impl Default for Backoff {
+	fn default() -> Self {
+		Self {
+			base: Duration::from_millis(200),
+			max: Duration::from_secs(5),
+		}
+	}
+}
+
+impl Backoff {
+	fn ceiling(&self, attempt: u32) -> Duration {
+		let factor = 1u32.checked_shl(attempt).unwrap_or(u32::MAX);
+		self.base.saturating_mul(factor).min(self.max)
+	}
+
+	pub(crate) fn delay(&self, attempt: u32) -> Duration {
+		let ceiling = self.ceiling(attempt).as_nanos() as u64;
+		if ceiling == 0 {
+			return Duration::ZERO;
+		}
+		Duration::from_nanos(random_u64() % (ceiling + 1))
+	}
+}
+
+/// Non-cryptographic randomness for jitter; `RandomState` is seeded per process and
+/// its keys advance with every instance.
+fn random_u64() -> u64 {
+	use std::hash::{BuildHasher, Hasher};
+
+	let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+	let now = std::time::SystemTime::now()
+		.duration_since(std::time::UNIX_EPOCH)
+		.unwrap_or_default();
+	hasher.write_u128(now.as_nanos());
+	hasher.finish()
+}
+
+/// A curl-style `--resolve host:port:addr` entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolveOverride {
+	pub host: String,
+	pub port: u16,
+	pub addr: IpAddr,
+}
+
+pub(crate) fn parse_resolve_override(value: &str) -> Result<ResolveOverride, String> {
+	let mut parts = value.splitn(3, ':');
+	let (Some(host), Some(port), Some(addr)) = (parts.next(), parts.next(), parts.next()) else {
+		return Err("expected HOST:PORT:ADDR".to_string());
+	};
+
+	let host = host.trim().to_ascii_lowercase();
+	if host.is_empty() {
+		return Err("host cannot be empty".to_string());
+	}
+	let port = port
+		.trim()
+		.parse::<u16>()
+		.map_err(|_| format!("invalid port: {port}"))?;
+	let addr = addr.trim().trim_start_matches('[').trim_end_matches(']');
+	let addr = addr
+		.parse::<IpAddr>()
+		.map_err(|_| format!("invalid address: {addr}"))?;
+
+	Ok(ResolveOverride { host, port, addr })
+}
+
+/// DNS, Host/SNI and certificate-pin overrides applied to every client the CLI builds.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ConnectOverrides {
+	pub resolve: Vec<ResolveOverride>,
+	pub host_header: Option<String>,
+	pub no_compression: bool,
+	/// `--compress-requests`: gzip large request bodies.
+	pub compress_requests: bool,
+	/// SHA-256 fingerprint the server's leaf certificate must match.
+	pub cert_pin: Option<String>,
+	/// `None` sends [`default_user_agent`].
+	pub user_agent: Option<String>,
+	/// `--no-proxy`: ignore the proxy environment variables.
+	pub no_proxy: bool,
+	/// Log the proxy choice for each host (`-v`).
+	pub verbose: bool,
+	/// Address family tried first for dual-stack hosts.
+	pub prefer: AddressFamily,
+}
+
+/// `--prefer-ipv4` / `--prefer-ipv6`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum AddressFamily {
+	#[default]
+	V6,
+	V4,
+}
+
+impl AddressFamily {
+	/// Happy-Eyeballs order (RFC 8305): alternate families, starting with the preferred one,
+	/// keeping the resolver's order within each family. The connector tries the first
+	/// address's family and starts the other after a short delay, so a host whose IPv6 (or
+	/// IPv4) addresses are unreachable still connects, just a little later.
+	pub(crate) fn order(self, addrs: impl IntoIterator<Item = SocketAddr>) -> Vec<SocketAddr> {
+		let (mut v6, mut v4): (Vec<SocketAddr>, Vec<SocketAddr>) = addrs.into_iter().partition(SocketAddr::is_ipv6);
+		v6.dedup();
+		v4.dedup();
+		let (first, second) = match self {
+			AddressFamily::V6 => (v6, v4),
+			AddressFamily::V4 => (v4, v6),
+		};
+		let mut ordered = Vec::with_capacity(first.len() + second.len());
+		let (mut first, mut second) = (first.into_iter(), second.into_iter());
+		loop {
+			match (first.next(), second.next()) {
+				(None, None) => return ordered,
+				(a, b) => ordered.extend(a.into_iter().chain(b)),
+			}
+		}
+	}
+}
+
+/// `(--host-header name, original host)`: the name the URL carries and the host to look up for it.
+type HostAlias = (String, String);
+
+/// System DNS with the answers put in [`AddressFamily::order`]. `alias` maps the
+/// `--host-header` name to the host it stands in for, so that host is looked up instead.
+struct OrderedResolver {
+	prefer: AddressFamily,
+	alias: Option<HostAlias>,
+}
+
+impl Resolve for OrderedResolver {
+	fn resolve(&self, name: Name) -> Resolving {
+		let prefer = self.prefer;
+		let lookup = match &self.alias {
+			Some((from, to)) if from.eq_ignore_ascii_case(name.as_str()) => to.clone(),
+			_ => name.as_str().to_string(),
+		};
+		Box::pin(async move {
+			let found = tokio::net::lookup_host((lookup.as_str(), 0)).await?;
+			let addrs: Addrs = Box::new(prefer.order(found).into_iter());
+			Ok(addrs)
+		})
+	}
+}
+
+impl ConnectOverrides {
+	pub fn from_global(global: &GlobalOpts) -> Self {
+		Self {
+			resolve: global.resolve.clone(),
+			host_header: global
+				.host_header
+				.as_deref()
+				.map(str::trim)
+				.filter(|h| !h.is_empty())
+				.map(str::to_ascii_lowercase),
+			no_compression: global.no_compression,
+			compress_requests: global.compress_requests,
+			cert_pin: None,
+			user_agent: global.user_agent.clone().filter(|ua| !ua.trim().is_empty()),
+			no_proxy: global.no_proxy,
+			verbose: global.verbose > 0 && !global.quiet,
+			prefer: if global.prefer_ipv4 { AddressFamily::V4 } else { AddressFamily::V6 },
+		}
+	}
+
+	/// [`Self::from_global`] plus the profile's `cert_fingerprint` pin and User-Agent.
+	pub fn from_context(global: &GlobalOpts, effective: &EffectiveConfig) -> Self {
+		Self {
+			cert_pin: effective.cert_fingerprint.clone(),
+			user_agent: Some(effective.user_agent.clone()),
+			..Self::from_global(global)
+		}
+	}
+
+	/// Builds the client and returns the base URL requests should use.
+	///
+	/// With `--host-header`, the base URL's host is swapped for the header value and pinned to
+	/// the original address, so both the Host header and TLS SNI carry the overridden name.
+	pub(crate) fn build_client(
+		&self,
+		base_url: &str,
+		builder: reqwest::ClientBuilder,
+	) -> Result<(String, reqwest::Client), CliError> {
+		let mut builder = builder.user_agent(self.user_agent.clone().unwrap_or_else(default_user_agent));
+		if self.no_compression {
+			builder = builder.no_gzip().no_deflate();
+		}
+		// Like curl, an entry only applies to connections to its HOST on its PORT.
+		let port = Url::parse(base_url)?.port_or_known_default().unwrap_or(443);
+		for entry in self.resolve.iter().filter(|e| e.port == port) {
+			builder = builder.resolve(&entry.host, SocketAddr::new(entry.addr, port));
+		}
+		if let Some(pin) = self.cert_pin.as_deref() {
+			builder = builder.use_preconfigured_tls(tls::pinned_client_config(pin)?);
+		}
+
+		let (base_url, builder, alias) = self.apply_host_header(base_url, builder)?;
+		let builder = builder.dns_resolver(Arc::new(OrderedResolver { prefer: self.prefer, alias }));
+		let builder = self.apply_proxy(&base_url, builder)?;
+		Ok((base_url, builder.build()?))
+	}
+
+	/// Also returns the resolver alias when the original host is a name that still needs DNS.
+	fn apply_host_header(
+		&self,
+		base_url: &str,
+		builder: reqwest::ClientBuilder,
+	) -> Result<(String, reqwest::ClientBuilder, Option<HostAlias>), CliError> {
+		let Some(host_header) = self.host_header.as_deref() else {
+			return Ok((base_url.to_string(), builder, None));
+		};
+
+		let mut url = Url::parse(base_url)?;
+		let original = url
+			.host_str()
+			.ok_or_else(|| CliError::InvalidArgument(format!("host has no hostname: {base_url}")))?
+			.trim_start_matches('[')
+			.trim_end_matches(']')
+			.to_ascii_lowercase();
+		if original == host_header {
+			return Ok((base_url.to_string(), builder, None));
+		}
+
+		let port = url.port_or_known_default().unwrap_or(443);
+		url.set_host(Some(host_header)).map_err(|_| {
+			CliError::InvalidArgument(format!("invalid --host-header value: {host_header}"))
+		})?;
+		let rewritten = url.as_str().trim_end_matches('/').to_string();
+
+		match self.fixed_addr(&original, port) {
+			Some(addr) => Ok((rewritten, builder.resolve(host_header, addr), None)),
+			None => Ok((rewritten, builder, Some((host_header.to_string(), original)))),
+		}
+	}
+
+	/// Replaces reqwest's implicit environment proxy with the one [`proxy::select`] picks.
+	fn apply_proxy(&self, base_url: &str, builder: reqwest::ClientBuilder) -> Result<reqwest::ClientBuilder, CliError> {
+		let url = Url::parse(base_url)?;
+		let host = url.host_str().unwrap_or_default().to_ascii_lowercase();
+		let selection = self.proxy_selection(url.scheme(), &host);
+		if self.verbose {
+			proxy::log_once(selection.describe(&host));
+		}
+
+		let builder = builder.no_proxy();
+		let proxy::Selection::Proxy { var, url, no_proxy } = selection else {
+			return Ok(builder);
+		};
+		if let Some((scheme, _)) = url.split_once("://")
+			&& !scheme.eq_ignore_ascii_case("http")
+			&& !scheme.eq_ignore_ascii_case("https")
+		{
+			// Typically a socks5:// ALL_PROXY meant for other tools; connect directly.
+			proxy::log_once(format!(
+				"warning: ignoring {var} ({}): only http:// and https:// proxies are supported; connecting directly",
+				redact::url(&url)
+			));
+			return Ok(builder);
+		}
+		let proxy = reqwest::Proxy::all(url.as_str()).map_err(|err| {
+			CliError::InvalidArgument(format!("invalid proxy in {var} ({}): {err}", redact::url(&url)))
+		})?;
+		Ok(builder.proxy(proxy.no_proxy(no_proxy.as_deref().and_then(reqwest::NoProxy::from_string))))
+	}
+
+	pub(crate) fn proxy_selection(&self, scheme: &str, host: &str) -> proxy::Selection {
+		if self.no_proxy {
+			return proxy::Selection::Disabled;
+		}
+		proxy::select(scheme, host, |key| std::env::var(key).ok())
+	}
+
+	/// The `--resolve` entry for `host:port`, or `host` itself when it is an IP address.
+	pub(crate) fn fixed_addr(&self, host: &str, port: u16) -> Option<SocketAddr> {
+		if let Some(entry) = self.resolve.iter().find(|e| e.host == host && e.port == port) {
+			return Some(SocketAddr::new(entry.addr, port));
+		}
+		host.parse::<IpAddr>().ok().map(|ip| SocketAddr::new(ip, port))
+	}
+
+	/// Where a connection to `host:port` goes: [`Self::fixed_addr`], else the preferred DNS answer.
+	pub(crate) async fn target_addr(&self, host: &str, port: u16) -> Result<SocketAddr, CliError> {
+		if let Some(addr) = self.fixed_addr(host, port) {
+			return Ok(addr);
+		}
+		self.prefer
+			.order(tokio::net::lookup_host((host, port)).await?)
+			.into_iter()
+			.next()
+			.ok_or_else(|| CliError::InvalidArgument(format!("could not resolve host: {host}")))
+	}
+}
+
+/// `ztnet-cli/<version> (<os>; <arch>)`, so server logs can tell CLI traffic apart.
+pub(crate) fn default_user_agent() -> String {
+	format!(
+		"ztnet-cli/{} ({}; {})",
+		env!("CARGO_PKG_VERSION"),
+		std::env::consts::OS,
+		std::env::consts::ARCH
+	)
+}
+
+/// With `--compress-requests`, request bodies at least this large are sent gzip-compressed.
+const COMPRESS_MIN_BYTES: usize = 64 * 1024;
+
+/// Opt-in gzip for large request bodies. ZTNet's API routes may not decode
+/// `Content-Encoding`, so a 415 for a compressed body turns compression off for the rest of
+/// the client's life and the body is re-sent uncompressed. Other errors are never retried
+/// this way: a 400 is as likely to be the server rejecting the payload itself.
+#[derive(Debug)]
+pub(crate) struct RequestCompression {
+	enabled: AtomicBool,
+}
+
+impl RequestCompression {
+	pub(crate) fn new(connect: &ConnectOverrides) -> Self {
+		Self {
+			enabled: AtomicBool::new(connect.compress_requests && !connect.no_compression),
+		}
+	}
+
+	/// The gzipped body, when compression is on and actually makes the body smaller.
+	pub(crate) fn encode(&self, body: &[u8]) -> Option<Bytes> {
+		if body.len() < COMPRESS_MIN_BYTES || !self.enabled.load(Ordering::Relaxed) {
+			return None;
+		}
+		let mut encoder = GzEncoder::new(Vec::with_capacity(body.len() / 4), flate2::Compression::fast());
+		encoder.write_all(body).ok()?;
+		let out = encoder.finish().ok()?;
+		(out.len() < body.len()).then(|| Bytes::from(out))
+	}
+
+	/// Whether `status` is the server refusing a compressed body; disables compression if so.
+	pub(crate) fn rejected(&self, status: StatusCode) -> bool {
+		let rejected = status == StatusCode::UNSUPPORTED_MEDIA_TYPE;
+		if rejected {
+			self.enabled.store(false, Ordering::Relaxed);
+		}
+		rejected
+	}
 }
 
 impl ClientUi {
@@ -27,27 +392,95 @@ impl ClientUi {
 			quiet,
 			no_color,
 			profile,
+			has_session: false,
+			connect: ConnectOverrides::default(),
+			backoff: Backoff::default(),
+			read_only: false,
 		}
 	}
 
 	pub fn from_context(global: &GlobalOpts, effective: &EffectiveConfig) -> Self {
-		Self::new(
-			global.quiet,
-			global.no_color,
-			Some(effective.profile.clone()),
-		)
+		Self {
+			has_session: effective.session_cookie.is_some(),
+			connect: ConnectOverrides::from_context(global, effective),
+			backoff: effective.backoff,
+			read_only: effective.read_only,
+			..Self::new(
+				global.quiet,
+				global.no_color,
+				Some(effective.profile.clone()),
+			)
+		}
+	}
+
+	/// Fails unless `method` is safe to send in read-only mode.
+	pub(crate) fn check_writable(&self, method: &Method, target: &str) -> Result<(), CliError> {
+		if self.read_only && !matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) {
+			return Err(CliError::ReadOnly(format!("{method} {target}")));
+		}
+		Ok(())
 	}
 
 	fn fix_command(&self, host: &str) -> String {
-		match self.profile.as_deref() {
-			Some(profile) if profile != "default" => {
-				format!("ztnet --profile {profile} config set host {host}")
-			}
-			_ => format!("ztnet config set host {host}"),
+		format!("{} config set host {host}", command_prefix(self.profile.as_deref()))
+	}
+
+	fn missing_token_error(&self) -> CliError {
+		if !self.has_session {
+			return CliError::MissingConfig("token");
+		}
+
+		CliError::AuthMethodMissing {
+			required: AuthMethod::Token,
+			available: AuthMethod::Session,
+			fix: format!("{} auth set-token <TOKEN>", command_prefix(self.profile.as_deref())),
+		}
+	}
+}
+
+impl ClientUi {
+	fn failure_message(&self, status: StatusCode, path: &str) -> String {
+		match status_hint(status, path, self.profile.as_deref()) {
+			Some(hint) => format!("request failed\n\n  hint: {hint}"),
+			None => "request failed".to_string(),
 		}
 	}
 }
 
+/// Endpoint-aware guidance for common REST failures.
+fn status_hint(status: StatusCode, path: &str, profile: Option<&str>) -> Option<String> {
+	let rest = path.find("/api/v1/").map(|idx| &path[idx + "/api/v1/".len()..])?;
+	let segments: Vec<&str> = rest.split('/').filter(|s| !s.is_empty()).collect();
+	match status {
+		StatusCode::UNAUTHORIZED => Some(format!(
+			"API token invalid or missing; run: {} auth set-token <TOKEN>",
+			command_prefix(profile)
+		)),
+		StatusCode::FORBIDDEN if segments.first() == Some(&"org") => Some(
+			"the token lacks access to this organization (its user must be a member of the org)"
+				.to_string(),
+		),
+		StatusCode::NOT_FOUND => match segments.as_slice() {
+			[.., "network", network, "member", member] => {
+				Some(format!("member '{member}' is not in network {network}"))
+			}
+			[.., "network", network] => Some(format!(
+				"network '{network}' not found (or not visible to this token)"
+			)),
+			["org", org, ..] => Some(format!("organization '{org}' not found")),
+			_ => None,
+		},
+		_ => None,
+	}
+}
+
+pub(crate) fn command_prefix(profile: Option<&str>) -> String {
+	match profile {
+		Some(profile) if profile != "default" => format!("ztnet --profile {profile}"),
+		_ => "ztnet".to_string(),
+	}
+}
+
 #[derive(Debug)]
 pub struct HttpClient {
 	bases: Vec<BaseCandidate>,
@@ -57,6 +490,7 @@ pub struct HttpClient {
 	retries: u32,
 	dry_run: bool,
 	client: reqwest::Client,
+	compression: RequestCompression,
 	ui: ClientUi,
 }
 
@@ -69,9 +503,11 @@ impl HttpClient {
 		dry_run: bool,
 		ui: ClientUi,
 	) -> Result<Self, CliError> {
-		let bases = multi_base::build_base_candidates(base_url)?;
+		let (base_url, client) = ui
+			.connect
+			.build_client(base_url, reqwest::Client::builder().timeout(timeout))?;
+		let bases = multi_base::build_base_candidates(&base_url)?;
 
-		let client = reqwest::Client::builder().timeout(timeout).build()?;
 		Ok(Self {
 			bases,
 			active_base: AtomicUsize::new(0),
@@ -80,10 +516,12 @@ impl HttpClient {
 			retries,
 			dry_run,
 			client,
+			compression: RequestCompression::new(&ui.connect),
 			ui,
 		})
 	}
 
+	#[cfg(test)]
 	pub fn build_url(&self, path: &str) -> Result<Url, CliError> {
 		let idx = self.active_base.load(Ordering::Relaxed);
 		self.build_url_for_base(idx, path)
@@ -121,15 +559,17 @@ impl HttpClient {
 		if self.dry_run {
 			let base_idx = self.active_base.load(Ordering::Relaxed);
 			let url = self.build_url_for_base(base_idx, path)?;
-			print_dry_run(
+			write_dry_run(
+				&mut crate::output::writer(),
 				&method,
 				&url,
-				include_auth.then(|| self.token.as_deref()).flatten(),
+				include_auth.then_some(self.token.as_deref()).flatten(),
 				&headers,
 				body_bytes.as_deref(),
-			);
+			)?;
 			return Err(CliError::DryRunPrinted);
 		}
+		self.ui.check_writable(&method, path)?;
 
 		multi_base::try_with_base_fallback(
 			&self.bases,
@@ -159,15 +599,17 @@ impl HttpClient {
 		if self.dry_run {
 			let base_idx = self.active_base.load(Ordering::Relaxed);
 			let url = self.build_url_for_base(base_idx, path)?;
-			print_dry_run(
+			write_dry_run(
+				&mut crate::output::writer(),
 				&method,
 				&url,
-				include_auth.then(|| self.token.as_deref()).flatten(),
+				include_auth.then_some(self.token.as_deref()).flatten(),
 				&headers,
 				body_bytes.as_deref(),
-			);
+			)?;
 			return Err(CliError::DryRunPrinted);
 		}
+		self.ui.check_writable(&method, path)?;
 
 		multi_base::try_with_base_fallback(
 			&self.bases,
@@ -198,13 +640,13 @@ impl HttpClient {
 		headers: &HeaderMap,
 		include_auth: bool,
 	) -> Result<Value, CliError> {
-		let mut backoff = Duration::from_millis(200);
+		let gzipped = body_bytes.as_deref().and_then(|bytes| self.compression.encode(bytes));
 		for attempt in 0..=self.retries {
 			let mut request_headers = headers.clone();
 			request_headers.insert("accept", HeaderValue::from_static("application/json"));
 
 			if include_auth {
-				let token = self.token.as_deref().ok_or(CliError::MissingConfig("token"))?;
+				let token = self.token.as_deref().ok_or_else(|| self.ui.missing_token_error())?;
 				request_headers.insert(
 					HeaderName::from_static(AUTH_HEADER),
 					HeaderValue::from_str(token).map_err(|_| {
@@ -218,26 +660,31 @@ impl HttpClient {
 				.request(method.clone(), url.clone())
 				.headers(request_headers);
 			if let Some(ref bytes) = body_bytes {
-				request = request
-					.header("content-type", "application/json")
-					.body(bytes.clone());
+				request = request.header("content-type", "application/json");
+				request = match gzipped {
+					Some(ref gzipped) => request.header("content-encoding", "gzip").body(gzipped.clone()),
+					None => request.body(bytes.clone()),
+				};
 			}
 
-			match request.send().await {
+			match cancel::guard(request.send()).await? {
 				Ok(resp) => {
 					let status = resp.status();
 					if status.is_success() {
 						return Ok(resp.json::<Value>().await?);
 					}
+					if gzipped.is_some() && self.compression.rejected(status) {
+						return Box::pin(self.request_json_with_url(method, url, body_bytes, headers, include_auth))
+							.await;
+					}
 
 					if should_retry_status(status) && attempt < self.retries {
 						if status == StatusCode::TOO_MANY_REQUESTS {
 							let retry_after = parse_retry_after(&resp);
-							tokio::time::sleep(retry_after.unwrap_or(backoff)).await;
+							cancel::sleep(retry_after.unwrap_or_else(|| self.ui.backoff.delay(attempt))).await?;
 						} else {
-							tokio::time::sleep(backoff).await;
+							cancel::sleep(self.ui.backoff.delay(attempt)).await?;
 						}
-						backoff = (backoff * 2).min(Duration::from_secs(5));
 						continue;
 					}
 
@@ -245,20 +692,19 @@ impl HttpClient {
 						return Err(CliError::RateLimited);
 					}
 
-					let body = resp.text().await.ok();
+					let body = resp.text().await.ok().map(|text| redact::body(&text));
 					return Err(CliError::HttpStatus {
 						status,
-						message: "request failed".to_string(),
+						message: self.ui.failure_message(status, url.path()),
 						body,
 					});
 				}
 				Err(err) => {
 					if attempt < self.retries && should_retry_error(&err) {
-						tokio::time::sleep(backoff).await;
-						backoff = (backoff * 2).min(Duration::from_secs(5));
+						cancel::sleep(self.ui.backoff.delay(attempt)).await?;
 						continue;
 					}
-					return Err(CliError::Request(err));
+					return Err(diagnose::connection_error(err, &url, &self.ui.connect).await);
 				}
 			}
 		}
@@ -275,13 +721,13 @@ impl HttpClient {
 		include_auth: bool,
 		content_type: Option<&str>,
 	) -> Result<Vec<u8>, CliError> {
-		let mut backoff = Duration::from_millis(200);
+		let gzipped = body.as_deref().and_then(|bytes| self.compression.encode(bytes));
 		for attempt in 0..=self.retries {
 			let mut request_headers = headers.clone();
 			request_headers.insert("accept", HeaderValue::from_static("*/*"));
 
 			if include_auth {
-				let token = self.token.as_deref().ok_or(CliError::MissingConfig("token"))?;
+				let token = self.token.as_deref().ok_or_else(|| self.ui.missing_token_error())?;
 				request_headers.insert(
 					HeaderName::from_static(AUTH_HEADER),
 					HeaderValue::from_str(token).map_err(|_| {
@@ -298,24 +744,37 @@ impl HttpClient {
 				if let Some(content_type) = content_type {
 					request = request.header("content-type", content_type);
 				}
-				request = request.body(bytes.clone());
+				request = match gzipped {
+					Some(ref gzipped) => request.header("content-encoding", "gzip").body(gzipped.clone()),
+					None => request.body(bytes.clone()),
+				};
 			}
 
-			match request.send().await {
+			match cancel::guard(request.send()).await? {
 				Ok(resp) => {
 					let status = resp.status();
 					if status.is_success() {
 						return Ok(resp.bytes().await?.to_vec());
 					}
+					if gzipped.is_some() && self.compression.rejected(status) {
+						return Box::pin(self.request_bytes_with_url(
+							method,
+							url,
+							body,
+							headers,
+							include_auth,
+							content_type,
+						))
+						.await;
+					}
 
 					if should_retry_status(status) && attempt < self.retries {
 						if status == StatusCode::TOO_MANY_REQUESTS {
 							let retry_after = parse_retry_after(&resp);
-							tokio::time::sleep(retry_after.unwrap_or(backoff)).await;
+							cancel::sleep(retry_after.unwrap_or_else(|| self.ui.backoff.delay(attempt))).await?;
 						} else {
-							tokio::time::sleep(backoff).await;
+							cancel::sleep(self.ui.backoff.delay(attempt)).await?;
 						}
-						backoff = (backoff * 2).min(Duration::from_secs(5));
 						continue;
 					}
 
@@ -323,20 +782,19 @@ impl HttpClient {
 						return Err(CliError::RateLimited);
 					}
 
-					let body = resp.text().await.ok();
+					let body = resp.text().await.ok().map(|text| redact::body(&text));
 					return Err(CliError::HttpStatus {
 						status,
-						message: "request failed".to_string(),
+						message: self.ui.failure_message(status, url.path()),
 						body,
 					});
 				}
 				Err(err) => {
 					if attempt < self.retries && should_retry_error(&err) {
-						tokio::time::sleep(backoff).await;
-						backoff = (backoff * 2).min(Duration::from_secs(5));
+						cancel::sleep(self.ui.backoff.delay(attempt)).await?;
 						continue;
 					}
-					return Err(CliError::Request(err));
+					return Err(diagnose::connection_error(err, &url, &self.ui.connect).await);
 				}
 			}
 		}
@@ -374,12 +832,12 @@ pub(crate) fn print_host_autofix_banner(ui: &ClientUi, configured: &str, using:
 	eprintln!("{yellow}{bold}======================================================{reset}");
 }
 
-fn should_retry_status(status: StatusCode) -> bool {
+pub(crate) fn should_retry_status(status: StatusCode) -> bool {
 	status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
 }
 
-fn should_retry_error(err: &reqwest::Error) -> bool {
-	err.is_timeout() || err.is_connect() || err.is_request()
+pub(crate) fn should_retry_error(err: &reqwest::Error) -> bool {
+	(err.is_timeout() || err.is_connect() || err.is_request()) && tls::pin_mismatch(err).is_none()
 }
 
 fn parse_retry_after(resp: &reqwest::Response) -> Option<Duration> {
@@ -388,10 +846,196 @@ fn parse_retry_after(resp: &reqwest::Response) -> Option<Duration> {
 	Some(Duration::from_secs(secs))
 }
 
+/// Writes the request a `--dry-run` would have sent: request line, redacted headers and the
+/// body, pretty-printed when it is JSON.
+pub(crate) fn write_dry_run(
+	out: &mut dyn Write,
+	method: &Method,
+	url: &Url,
+	token: Option<&str>,
+	headers: &HeaderMap,
+	body: Option<&[u8]>,
+) -> std::io::Result<()> {
+	writeln!(out, "{method} {url}")?;
+
+	for (name, value) in headers.iter() {
+		if let Ok(value) = value.to_str() {
+			writeln!(out, "{name}: {}", redact::header(name.as_str(), value))?;
+		}
+	}
+
+	if let Some(token) = token {
+		writeln!(out, "{AUTH_HEADER}: {}", redact::header(AUTH_HEADER, token))?;
+	}
+
+	if let Some(body) = body {
+		if let Ok(json) = serde_json::from_slice::<Value>(body)
+			&& let Ok(pretty) = serde_json::to_string_pretty(&json)
+		{
+			return writeln!(out, "\n{pretty}");
+		}
+
+		if let Ok(text) = std::str::from_utf8(body) {
+			writeln!(out, "\n{text}")?;
+		}
+	}
+	Ok(())
+}
+
+/// Skew beyond this breaks TOTP codes (30s steps) and makes cookie expiry unreliable.
+const CLOCK_SKEW_WARN_SECS: i64 = 30;
+
+/// Returns a warning when the response's `Date` header is more than
+/// [`CLOCK_SKEW_WARN_SECS`] away from the local clock.
+pub(crate) fn clock_skew_warning(headers: &HeaderMap) -> Option<String> {
+	let server = headers
+		.get(reqwest::header::DATE)
+		.and_then(|v| v.to_str().ok())
+		.and_then(parse_http_date)?;
+	let local = std::time::SystemTime::now()
+		.duration_since(std::time::UNIX_EPOCH)
+		.ok()?
+		.as_secs() as i64;
+	let skew = server - local;
+	if skew.abs() <= CLOCK_SKEW_WARN_SECS {
+		return None;
+	}
+	let direction = if skew > 0 { "behind" } else { "ahead of" };
+	Some(format!(
+		"local clock is {} {direction} the server; TOTP codes and session expiry may fail (sync the clock, e.g. enable NTP)",
+		humantime::format_duration(Duration::from_secs(skew.unsigned_abs()))
+	))
+}
+
+/// Parses an RFC 7231 IMF-fixdate (`Sun, 06 Nov 1994 08:49:37 GMT`) into Unix seconds.
+pub(crate) fn parse_http_date(value: &str) -> Option<i64> {
+	let mut parts = value.split_whitespace().skip(1);
+	let day: i64 = parts.next()?.parse().ok()?;
+	let month = match parts.next()? {
+		"Jan" => 1,
+		"Feb" => 2,
+		"Mar" => 3,
+		"Apr" => 4,
+		"May" => 5,
+		"Jun" => 6,
+		"Jul" => 7,
+		"Aug" => 8,
+		"Sep" => 9,
+		"Oct" => 10,
+		"Nov" => 11,
+		"Dec" => 12,
+		_ => return None,
+	};
+	let year: i64 = parts.next()?.parse().ok()?;
+	let mut time = parts.next()?.split(':').map(|p| p.parse::<i64>().ok());
+	let (h, m, s) = (time.next()??, time.next()??, time.next()??);
+	if parts.next()? != "GMT" {
+		return None;
+	}
+
+	// Days since 1970-01-01 (proleptic Gregorian), after Howard Hinnant's days_from_civil.
+	let y = if month <= 2 { year - 1 } else { year };
+	let era = y.div_euclid(400);
+	let yoe = y - era * 400;
+	let mp = (month + 9) % 12;
+	let doy = (153 * mp + 2) / 5 + day - 1;
+	let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+	let days = era * 146_097 + doe - 719_468;
+	Some(days * 86_400 + h * 3_600 + m * 60 + s)
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
 
+	#[test]
+	fn dry_run_preview_is_written_to_the_given_sink_redacted() {
+		let url = Url::parse("https://panel.test/api/v1/network").unwrap();
+		let mut headers = HeaderMap::new();
+		headers.insert("cookie", HeaderValue::from_static("next-auth.session-token=abc"));
+		let mut out = Vec::new();
+		write_dry_run(&mut out, &Method::POST, &url, Some("sk_1234567890abcd"), &headers, Some(br#"{"a":1}"#))
+			.unwrap();
+		assert_eq!(
+			String::from_utf8(out).unwrap(),
+			format!(
+				"POST https://panel.test/api/v1/network\ncookie: {}\nx-ztnet-auth: sk_1…abcd\n\n{{\n  \"a\": 1\n}}\n",
+				redact::REDACTED
+			)
+		);
+
+		let mut out = Vec::new();
+		write_dry_run(&mut out, &Method::GET, &url, None, &HeaderMap::new(), None).unwrap();
+		assert_eq!(String::from_utf8(out).unwrap(), "GET https://panel.test/api/v1/network\n");
+	}
+
+	#[test]
+	fn read_only_blocks_mutating_methods() {
+		let mut ui = ClientUi::new(true, true, None);
+		assert!(ui.check_writable(&Method::POST, "/api/v1/network").is_ok());
+
+		ui.read_only = true;
+		assert!(ui.check_writable(&Method::GET, "/api/v1/network").is_ok());
+		assert!(ui.check_writable(&Method::HEAD, "/api/v1/network").is_ok());
+		for method in [Method::POST, Method::PUT, Method::PATCH, Method::DELETE] {
+			assert!(matches!(
+				ui.check_writable(&method, "/api/v1/network"),
+				Err(CliError::ReadOnly(_))
+			));
+		}
+	}
+
+	#[test]
+	fn backoff_delay_is_jittered_below_ceiling() {
+		let backoff = Backoff {
+			base: Duration::from_millis(100),
+			max: Duration::from_secs(1),
+		};
+		assert_eq!(backoff.ceiling(0), Duration::from_millis(100));
+		assert_eq!(backoff.ceiling(2), Duration::from_millis(400));
+		assert_eq!(backoff.ceiling(10), Duration::from_secs(1));
+		assert_eq!(backoff.ceiling(64), Duration::from_secs(1));
+		for attempt in 0..8 {
+			assert!(backoff.delay(attempt) <= backoff.ceiling(attempt));
+		}
+	}
+
+	#[test]
+	fn status_hint_is_endpoint_aware() {
+		let hint = status_hint(StatusCode::UNAUTHORIZED, "/api/v1/network", Some("prod")).unwrap();
+		assert!(hint.contains("ztnet --profile prod auth set-token"));
+
+		assert!(status_hint(StatusCode::FORBIDDEN, "/api/v1/org/o1/network", None)
+			.unwrap()
+			.contains("organization"));
+		assert!(status_hint(StatusCode::FORBIDDEN, "/api/v1/network", None).is_none());
+
+		let hint = status_hint(StatusCode::NOT_FOUND, "/api/v1/network/n1/member/abc", None).unwrap();
+		assert_eq!(hint, "member 'abc' is not in network n1");
+		assert!(status_hint(StatusCode::UNAUTHORIZED, "/api/trpc/x", None).is_none());
+	}
+
+	#[test]
+	fn parse_http_date_handles_imf_fixdate() {
+		assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"), Some(784_111_777));
+		assert_eq!(parse_http_date("Thu, 01 Jan 1970 00:00:00 GMT"), Some(0));
+		assert_eq!(parse_http_date("Thu, 29 Feb 2024 12:00:00 GMT"), Some(1_709_208_000));
+		assert_eq!(parse_http_date("Sunday, 06-Nov-94 08:49:37 GMT"), None);
+	}
+
+	#[test]
+	fn clock_skew_warning_ignores_small_skew() {
+		let mut headers = HeaderMap::new();
+		assert!(clock_skew_warning(&headers).is_none());
+
+		headers.insert(
+			reqwest::header::DATE,
+			HeaderValue::from_static("Thu, 01 Jan 1970 00:00:00 GMT"),
+		);
+		let warning = clock_skew_warning(&headers).unwrap();
+		assert!(warning.contains("ahead of the server"));
+	}
+
 	#[test]
 	fn build_url_preserves_base_path_prefix() {
 		let client = HttpClient::new(
@@ -437,59 +1081,125 @@ mod tests {
 		let url = client.build_url("https://other.example.com/x").unwrap();
 		assert_eq!(url.as_str(), "https://other.example.com/x");
 	}
-}
 
-fn print_dry_run(
-	method: &Method,
-	url: &Url,
-	token: Option<&str>,
-	headers: &HeaderMap,
-	body: Option<&[u8]>,
-) {
-	println!("{method} {url}");
+	#[test]
+	fn parse_resolve_override_accepts_ipv4_and_bracketed_ipv6() {
+		let entry = parse_resolve_override("Panel.example.com:443:10.0.0.5").unwrap();
+		assert_eq!(entry.host, "panel.example.com");
+		assert_eq!(entry.port, 443);
+		assert_eq!(entry.addr, "10.0.0.5".parse::<IpAddr>().unwrap());
 
-	for (name, value) in headers.iter() {
-		if name.as_str().eq_ignore_ascii_case("cookie") {
-			println!("{name}: REDACTED");
-			continue;
-		}
-		if let Ok(value) = value.to_str() {
-			println!("{name}: {value}");
-		}
+		let entry = parse_resolve_override("panel.example.com:8443:[::1]").unwrap();
+		assert_eq!(entry.addr, "::1".parse::<IpAddr>().unwrap());
+
+		assert!(parse_resolve_override("panel.example.com:443").is_err());
+		assert!(parse_resolve_override("panel.example.com:https:10.0.0.5").is_err());
 	}
 
-	if let Some(token) = token {
-		println!("{AUTH_HEADER}: {}", redact_token(token));
+	#[test]
+	fn address_order_alternates_families_starting_with_the_preferred_one() {
+		let addrs: Vec<SocketAddr> = ["10.0.0.1:443", "10.0.0.2:443", "[fd00::1]:443", "[fd00::2]:443", "[fd00::3]:443"]
+			.iter()
+			.map(|a| a.parse().unwrap())
+			.collect();
+		let shown = |order: Vec<SocketAddr>| order.iter().map(|a| a.ip().to_string()).collect::<Vec<_>>();
+		assert_eq!(
+			shown(AddressFamily::V6.order(addrs.clone())),
+			["fd00::1", "10.0.0.1", "fd00::2", "10.0.0.2", "fd00::3"]
+		);
+		assert_eq!(
+			shown(AddressFamily::V4.order(addrs)),
+			["10.0.0.1", "fd00::1", "10.0.0.2", "fd00::2", "fd00::3"]
+		);
 	}
 
-	if let Some(body) = body {
-		if let Ok(json) = serde_json::from_slice::<Value>(body) {
-			if let Ok(pretty) = serde_json::to_string_pretty(&json) {
-				println!();
-				println!("{pretty}");
-				return;
-			}
-		}
+	#[test]
+	fn host_header_rewrites_base_url_host() {
+		let connect = ConnectOverrides {
+			resolve: Vec::new(),
+			host_header: Some("panel.example.com".to_string()),
+			no_compression: false,
+			compress_requests: false,
+			cert_pin: None,
+			user_agent: None,
+			no_proxy: true,
+			verbose: false,
+			prefer: AddressFamily::V6,
+		};
+		let (base, _client) = connect
+			.build_client("https://10.0.0.5:8443/api", reqwest::Client::builder())
+			.unwrap();
+		assert_eq!(base, "https://panel.example.com:8443/api");
 
-		if let Ok(text) = std::str::from_utf8(body) {
-			println!();
-			println!("{text}");
-		}
+		let (base, _client) = connect
+			.build_client("https://panel.example.com", reqwest::Client::builder())
+			.unwrap();
+		assert_eq!(base, "https://panel.example.com");
 	}
-}
 
-fn redact_token(token: &str) -> String {
-	const KEEP: usize = 4;
-	let char_count = token.chars().count();
-	if char_count <= KEEP * 2 {
-		return "REDACTED".to_string();
+	#[tokio::test]
+	async fn resolve_overrides_match_host_and_port_and_host_header_resolves_lazily() {
+		let server = crate::mock_server::MockServer::start(|_| crate::mock_server::Response::json("{}")).await;
+		let port = server.addr.port();
+		let last_host = || server.requests().pop().unwrap().header("host").map(str::to_string);
+
+		let entry = |port: u16| parse_resolve_override(&format!("panel.test:{port}:127.0.0.1")).unwrap();
+		let connect = ConnectOverrides {
+			resolve: vec![entry(port), entry(8443)],
+			no_proxy: true,
+			..Default::default()
+		};
+		let loopback = "127.0.0.1".parse::<IpAddr>().unwrap();
+		assert_eq!(connect.fixed_addr("panel.test", 8443), Some(SocketAddr::new(loopback, 8443)));
+		assert_eq!(connect.fixed_addr("panel.test", 443), None);
+		assert_eq!(connect.target_addr("panel.test", port).await.unwrap(), SocketAddr::new(loopback, port));
+		assert!(connect.target_addr("localhost", 443).await.unwrap().ip().is_loopback());
+
+		let (base, client) = connect
+			.build_client(&format!("http://panel.test:{port}"), reqwest::Client::builder())
+			.unwrap();
+		client.get(&base).send().await.unwrap();
+		assert_eq!(last_host(), Some(format!("panel.test:{port}")));
+
+		// The --host-header name is looked up as the original host when the request is sent.
+		let connect = ConnectOverrides {
+			host_header: Some("panel.example.com".to_string()),
+			no_proxy: true,
+			..Default::default()
+		};
+		let (base, client) = connect
+			.build_client(&format!("http://localhost:{port}"), reqwest::Client::builder())
+			.unwrap();
+		assert_eq!(base, format!("http://panel.example.com:{port}"));
+		client.get(&base).send().await.unwrap();
+		assert_eq!(last_host(), Some(format!("panel.example.com:{port}")));
 	}
 
-	let prefix: String = token.chars().take(KEEP).collect();
+	#[test]
+	fn request_compression_only_for_large_bodies_until_rejected() {
+		let large = "{\"name\":\"member\"},".repeat(COMPRESS_MIN_BYTES / 8);
+		assert!(RequestCompression::new(&ConnectOverrides::default()).encode(large.as_bytes()).is_none());
+
+		let compression = RequestCompression::new(&ConnectOverrides {
+			compress_requests: true,
+			..Default::default()
+		});
+		assert!(compression.encode(b"{\"small\":true}").is_none());
+		let gzipped = compression.encode(large.as_bytes()).unwrap();
+		assert!(gzipped.len() < large.len());
 
-	let mut suffix_chars: Vec<char> = token.chars().rev().take(KEEP).collect();
-	suffix_chars.reverse();
-	let suffix: String = suffix_chars.into_iter().collect();
+		assert!(!compression.rejected(StatusCode::INTERNAL_SERVER_ERROR));
+		// A 400 is a validation error as often as not; re-sending a mutation would repeat it.
+		assert!(!compression.rejected(StatusCode::BAD_REQUEST));
+		assert!(compression.encode(large.as_bytes()).is_some());
+		assert!(compression.rejected(StatusCode::UNSUPPORTED_MEDIA_TYPE));
+		assert!(compression.encode(large.as_bytes()).is_none());
 
-	format!("{prefix}…{suffix}")
+		let disabled = RequestCompression::new(&ConnectOverrides {
+			compress_requests: true,
+			no_compression: true,
+			..Default::default()
+		});
+		assert!(disabled.encode(large.as_bytes()).is_none());
+	}
 }
@@ -1,19 +1,37 @@
+mod alias;
 mod app;
+mod cancel;
 mod cli;
 mod config;
 mod context;
+mod diagnose;
+mod encrypt;
 mod error;
 mod host;
 mod http;
+#[cfg(test)]
+mod mock_server;
 mod multi_base;
 mod output;
+mod progress;
+mod proxy;
+mod redact;
+mod sign;
+mod tls;
 
 use clap::Parser;
 
 #[tokio::main]
 async fn main() {
 	dotenvy::dotenv().ok();
-	let cli = cli::Cli::parse();
+	let args = match alias::expand(std::env::args_os().collect()) {
+		Ok(args) => args,
+		Err(err) => {
+			eprintln!("{err}");
+			std::process::exit(err.exit_code());
+		}
+	};
+	let cli = cli::Cli::parse_from(args);
 
 	if let Err(err) = app::run(cli).await {
 		let code = err.exit_code();
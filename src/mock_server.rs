@@ -0,0 +1,175 @@
+//! A loopback HTTP server for tests: every request is answered by a closure and recorded,
+//! so a test can check both what the client did with the response and what it sent.
+
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use reqwest::StatusCode;
+use serde_json::Value;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// One request as the server saw it.
+#[derive(Debug, Clone)]
+pub(crate) struct Request {
+	pub(crate) method: String,
+	/// Path and query, as sent on the request line.
+	pub(crate) target: String,
+	pub(crate) head: String,
+	pub(crate) body: String,
+}
+
+impl Request {
+	pub(crate) fn path(&self) -> &str {
+		self.target.split('?').next().unwrap_or_default()
+	}
+
+	pub(crate) fn query(&self, key: &str) -> Option<&str> {
+		let (_, query) = self.target.split_once('?')?;
+		query.split('&').find_map(|pair| pair.strip_prefix(key)?.strip_prefix('='))
+	}
+
+	pub(crate) fn header(&self, name: &str) -> Option<&str> {
+		self.head.lines().skip(1).find_map(|line| {
+			let (key, value) = line.split_once(':')?;
+			key.trim().eq_ignore_ascii_case(name).then(|| value.trim())
+		})
+	}
+
+	/// The procedure of a tRPC request (`/api/trpc/<procedure>`).
+	pub(crate) fn procedure(&self) -> Option<&str> {
+		self.path().strip_prefix("/api/trpc/")
+	}
+
+	/// The input of a tRPC mutation (`{"0":{"json":<input>}}`).
+	pub(crate) fn trpc_input(&self) -> Value {
+		serde_json::from_str::<Value>(&self.body).map(|body| body["0"]["json"].clone()).unwrap_or(Value::Null)
+	}
+}
+
+pub(crate) struct Response {
+	status: StatusCode,
+	body: String,
+}
+
+impl Response {
+	pub(crate) fn json(body: impl Into<String>) -> Self {
+		Self::status(StatusCode::OK, body)
+	}
+
+	pub(crate) fn status(status: StatusCode, body: impl Into<String>) -> Self {
+		Self { status, body: body.into() }
+	}
+
+	/// A successful tRPC response carrying `data`.
+	pub(crate) fn trpc(data: &Value) -> Self {
+		Self::json(serde_json::json!({ "result": { "data": { "json": data } } }).to_string())
+	}
+}
+
+type Handler = dyn Fn(&Request) -> Response + Send + Sync;
+
+pub(crate) struct MockServer {
+	pub(crate) url: String,
+	pub(crate) addr: SocketAddr,
+	requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl MockServer {
+	pub(crate) async fn start(handler: impl Fn(&Request) -> Response + Send + Sync + 'static) -> Self {
+		Self::spawn(None, Arc::new(handler)).await
+	}
+
+	/// Like [`MockServer::start`], but speaks HTTPS through `acceptor`.
+	pub(crate) async fn start_tls(
+		acceptor: tokio_rustls::TlsAcceptor,
+		handler: impl Fn(&Request) -> Response + Send + Sync + 'static,
+	) -> Self {
+		Self::spawn(Some(acceptor), Arc::new(handler)).await
+	}
+
+	async fn spawn(acceptor: Option<tokio_rustls::TlsAcceptor>, handler: Arc<Handler>) -> Self {
+		let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+		let addr = listener.local_addr().unwrap();
+		let requests = Arc::new(Mutex::new(Vec::new()));
+		let log = Arc::clone(&requests);
+		tokio::spawn(async move {
+			while let Ok((stream, _)) = listener.accept().await {
+				let (handler, log) = (Arc::clone(&handler), Arc::clone(&log));
+				match &acceptor {
+					Some(acceptor) => {
+						if let Ok(stream) = acceptor.accept(stream).await {
+							serve(stream, &*handler, &log).await;
+						}
+					}
+					None => serve(stream, &*handler, &log).await,
+				}
+			}
+		});
+		Self {
+			url: format!("http://{addr}"),
+			addr,
+			requests,
+		}
+	}
+
+	pub(crate) fn requests(&self) -> Vec<Request> {
+		self.requests.lock().unwrap().clone()
+	}
+
+	/// `"METHOD target"` for every request so far.
+	pub(crate) fn request_lines(&self) -> Vec<String> {
+		self.requests().iter().map(|r| format!("{} {}", r.method, r.target)).collect()
+	}
+
+	pub(crate) fn clear(&self) {
+		self.requests.lock().unwrap().clear();
+	}
+}
+
+async fn serve<S: AsyncRead + AsyncWrite + Unpin>(mut stream: S, handler: &Handler, log: &Mutex<Vec<Request>>) {
+	let Some(request) = read_request(&mut stream).await else {
+		return;
+	};
+	// Recorded before answering, so a client that got its response always finds it logged.
+	let response = handler(&request);
+	log.lock().unwrap().push(request);
+	let reply = format!(
+		"HTTP/1.1 {} {}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+		response.status.as_u16(),
+		response.status.canonical_reason().unwrap_or_default(),
+		response.body.len(),
+		response.body
+	);
+	let _ = stream.write_all(reply.as_bytes()).await;
+	let _ = stream.shutdown().await;
+}
+
+async fn read_request<S: AsyncRead + Unpin>(stream: &mut S) -> Option<Request> {
+	let mut raw = Vec::new();
+	let mut buf = [0u8; 4096];
+	loop {
+		let read = stream.read(&mut buf).await.ok()?;
+		raw.extend_from_slice(&buf[..read]);
+		if let Some(end) = raw.windows(4).position(|w| w == b"\r\n\r\n") {
+			let head = String::from_utf8_lossy(&raw[..end]).to_string();
+			let length = head
+				.lines()
+				.find_map(|line| {
+					let (key, value) = line.split_once(':')?;
+					key.trim().eq_ignore_ascii_case("content-length").then(|| value.trim().parse::<usize>().ok())?
+				})
+				.unwrap_or(0);
+			if raw.len() - end - 4 >= length || read == 0 {
+				let body = String::from_utf8_lossy(&raw[end + 4..]).to_string();
+				let mut line = head.split_whitespace();
+				let method = line.next()?.to_string();
+				let target = line.next()?.to_string();
+				return Some(Request { method, target, head, body });
+			}
+		}
+		if read == 0 {
+			return None;
+		}
+	}
+}
@@ -1,18 +1,301 @@
-use std::io::{self, Write};
+use std::fs::File;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
 
+use age::x25519::Recipient;
 use comfy_table::{presets, Cell, Table};
 use serde_json::Value;
 
+use crate::cancel;
 use crate::cli::OutputFormat;
+use crate::encrypt;
 use crate::error::CliError;
+use crate::sign::Signer;
 
-pub fn print_value(value: &Value, format: OutputFormat, no_color: bool) -> Result<(), CliError> {
-	let mut stdout = io::stdout().lock();
-	write_value(&mut stdout, value, format, no_color)?;
-	writeln!(&mut stdout)?;
+/// Where command output goes (`--out`), how much of it a terminal gets
+/// (`--max-body-print`) and whether a pager may show it. Set once per process by [`init`].
+struct Sink {
+	out: Option<Out>,
+	/// `(temporary, final)` paths of the `--out` file until [`finish`] settles it.
+	pending: Mutex<Option<(PathBuf, PathBuf)>>,
+	max_print: u64,
+	/// `--encrypt-to` recipients; when set, every file written through this module is
+	/// age-encrypted.
+	recipients: Vec<Recipient>,
+	/// `--sign-key`; when set, every file written through this module gets a
+	/// `<file>.minisig` signature.
+	signer: Option<Signer>,
+	paging: bool,
+}
+
+/// The `--out` target until [`finish`]: a temporary file next to it, or, when the output is
+/// age-encrypted, memory, so the plaintext never touches the disk.
+enum Out {
+	File(File),
+	Sealed(Mutex<Vec<u8>>),
+}
+
+static SINK: OnceLock<Sink> = OnceLock::new();
+
+/// `--out` is written to a temporary file next to the target, so a failed run never leaves
+/// a truncated or half-written file in place of a previous good one.
+pub fn init(
+	out: Option<&Path>,
+	max_body_print: u64,
+	recipients: Vec<Recipient>,
+	signer: Option<Signer>,
+	paging: bool,
+) -> Result<(), CliError> {
+	let _ = SINK.set(Sink::new(out, max_body_print, recipients, signer, paging)?);
+	Ok(())
+}
+
+/// Whether anything was written to the `--out` file so far.
+pub fn has_output() -> bool {
+	SINK.get().is_some_and(Sink::has_output)
+}
+
+/// Moves the `--out` file into place when `keep` is set, discards it otherwise. Safe to
+/// call more than once.
+pub fn finish(keep: bool) -> Result<(), CliError> {
+	SINK.get().map_or(Ok(()), |sink| sink.finish(keep))
+}
+
+impl Sink {
+	fn new(
+		out: Option<&Path>,
+		max_print: u64,
+		recipients: Vec<Recipient>,
+		signer: Option<Signer>,
+		paging: bool,
+	) -> Result<Self, CliError> {
+		let (out, pending) = match out {
+			Some(path) => {
+				if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+					std::fs::create_dir_all(parent)?;
+				}
+				let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("out");
+				let tmp = path.with_file_name(format!(".{file_name}.ztnet.tmp"));
+				let out = if recipients.is_empty() {
+					Out::File(File::create(&tmp)?)
+				} else {
+					Out::Sealed(Mutex::new(Vec::new()))
+				};
+				(Some(out), Some((tmp, path.to_path_buf())))
+			}
+			None => (None, None),
+		};
+		Ok(Self {
+			out,
+			pending: Mutex::new(pending),
+			max_print,
+			recipients,
+			signer,
+			paging,
+		})
+	}
+
+	fn has_output(&self) -> bool {
+		match &self.out {
+			Some(Out::File(file)) => file.metadata().is_ok_and(|meta| meta.len() > 0),
+			Some(Out::Sealed(buffer)) => buffer.lock().is_ok_and(|buffer| !buffer.is_empty()),
+			None => false,
+		}
+	}
+
+	fn finish(&self, keep: bool) -> Result<(), CliError> {
+		let pending = self.pending.lock().map(|mut pending| pending.take()).unwrap_or(None);
+		let Some((tmp, path)) = pending else {
+			return Ok(());
+		};
+		match &self.out {
+			Some(Out::File(file)) => file.sync_all()?,
+			Some(Out::Sealed(buffer)) if keep => {
+				let plaintext = buffer.lock().map(|mut buffer| std::mem::take(&mut *buffer)).unwrap_or_default();
+				std::fs::write(&tmp, encrypt::encrypt(&plaintext, &self.recipients)?)?;
+			}
+			_ => {}
+		}
+		if keep {
+			std::fs::rename(&tmp, &path)?;
+			if let Some(signer) = self.signer.as_ref() {
+				signer.sign_file(&path)?;
+			}
+		} else {
+			let _ = std::fs::remove_file(&tmp);
+		}
+		Ok(())
+	}
+
+	fn writer(&self) -> Option<Box<dyn Write + '_>> {
+		match self.out.as_ref()? {
+			Out::File(file) => Some(Box::new(file)),
+			Out::Sealed(buffer) => Some(Box::new(SealedWriter(buffer))),
+		}
+	}
+}
+
+/// Collects `--out` output in memory until [`finish`] encrypts it.
+struct SealedWriter<'a>(&'a Mutex<Vec<u8>>);
+
+impl Write for SealedWriter<'_> {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		self.0
+			.lock()
+			.map_err(|_| io::Error::other("output buffer poisoned"))?
+			.extend_from_slice(buf);
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		Ok(())
+	}
+}
+
+/// Writes `contents` to `path` (creating parent directories), age-encrypted when
+/// `--encrypt-to` is set and signed when `--sign-key` is. Returns the number of bytes
+/// written.
+pub fn write_file(path: &Path, contents: &[u8]) -> Result<u64, CliError> {
+	if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+		std::fs::create_dir_all(parent)?;
+	}
+	let size = match SINK.get().map(|sink| &sink.recipients).filter(|r| !r.is_empty()) {
+		Some(recipients) => {
+			let sealed = encrypt::encrypt(contents, recipients)?;
+			std::fs::write(path, &sealed)?;
+			sealed.len() as u64
+		}
+		None => {
+			std::fs::write(path, contents)?;
+			contents.len() as u64
+		}
+	};
+	if let Some(signer) = SINK.get().and_then(|sink| sink.signer.as_ref()) {
+		signer.sign_file(path)?;
+	}
+	Ok(size)
+}
+
+/// Whether files are age-encrypted (`--encrypt-to`).
+pub fn encrypting() -> bool {
+	SINK.get().is_some_and(|sink| !sink.recipients.is_empty())
+}
+
+/// Whether files are signed (`--sign-key`).
+pub fn signing() -> bool {
+	SINK.get().is_some_and(|sink| sink.signer.is_some())
+}
+
+/// The `--out` file when one was given, stdout otherwise.
+pub fn writer() -> Box<dyn Write> {
+	match SINK.get().and_then(Sink::writer) {
+		Some(writer) => writer,
+		None => Box::new(io::stdout().lock()),
+	}
+}
+
+/// Writes one rendered block and a newline. On a terminal, a block taller than the window
+/// goes through the pager (unless `--no-pager`); otherwise human-readable blocks larger
+/// than `--max-body-print` are cut at a line boundary.
+pub fn emit(text: &str, human: bool) -> Result<(), CliError> {
+	let max_print = SINK.get().map_or(0, |sink| sink.max_print);
+	let to_terminal = SINK.get().is_none_or(|sink| sink.out.is_none()) && io::stdout().is_terminal();
+	if to_terminal
+		&& SINK.get().is_some_and(|sink| sink.paging)
+		&& taller_than_terminal(text)
+		&& let Some(pager) = pager_command()
+		&& page(&pager, text)
+	{
+		return Ok(());
+	}
+	let mut writer = writer();
+	if human && to_terminal && let Some(head) = truncate_for_print(text, max_print) {
+		writeln!(writer, "{head}")?;
+		eprintln!(
+			"… output truncated at {} of {}; use --output json --out file.json for the full output",
+			format_size(head.len() as u64),
+			format_size(text.len() as u64)
+		);
+		return Ok(());
+	}
+	writeln!(writer, "{text}")?;
 	Ok(())
 }
 
+/// `$ZTNET_PAGER`, then `$PAGER`, then `less`; an empty value or `cat` turns paging off.
+fn pager_command() -> Option<String> {
+	let command = std::env::var("ZTNET_PAGER")
+		.or_else(|_| std::env::var("PAGER"))
+		.unwrap_or_else(|_| "less".to_string());
+	let command = command.trim();
+	(!command.is_empty() && command != "cat").then(|| command.to_string())
+}
+
+fn taller_than_terminal(text: &str) -> bool {
+	crossterm::terminal::size().is_ok_and(|(_, rows)| text.lines().count() >= usize::from(rows))
+}
+
+/// Pipes `text` into the pager and waits for it to exit. The command is split with shell
+/// quoting rules (`less -R "+/some text"`). Returns false when the pager could not be
+/// started, so the caller prints normally instead.
+fn page(command: &str, text: &str) -> bool {
+	let Ok(words) = crate::alias::split_words(command) else {
+		return false;
+	};
+	let Some((program, args)) = words.split_first() else {
+		return false;
+	};
+	let mut pager = Command::new(program);
+	pager.args(args).stdin(Stdio::piped());
+	// Like git: quit if it fits one screen, keep colors, don't clear the screen on exit.
+	if std::env::var_os("LESS").is_none() {
+		pager.env("LESS", "FRX");
+	}
+	let Ok(mut child) = pager.spawn() else {
+		return false;
+	};
+	cancel::set_paging(true);
+	let mut feed = move || {
+		if let Some(mut stdin) = child.stdin.take() {
+			// Quitting the pager early closes the pipe; that is not an error.
+			let _ = writeln!(stdin, "{text}");
+		}
+		let _ = child.wait();
+	};
+	// The user can keep the pager open indefinitely: hand this worker's other tasks
+	// (the Ctrl-C listener among them) to the rest of the runtime while we block.
+	match tokio::runtime::Handle::try_current() {
+		Ok(handle) if handle.runtime_flavor() == tokio::runtime::RuntimeFlavor::MultiThread => {
+			tokio::task::block_in_place(feed)
+		}
+		_ => feed(),
+	}
+	cancel::set_paging(false);
+	true
+}
+
+fn truncate_for_print(text: &str, max: u64) -> Option<&str> {
+	let max = usize::try_from(max).unwrap_or(usize::MAX);
+	if max == 0 || text.len() <= max {
+		return None;
+	}
+	let mut end = max;
+	while !text.is_char_boundary(end) {
+		end -= 1;
+	}
+	let end = text[..end].rfind('\n').unwrap_or(end);
+	Some(&text[..end])
+}
+
+pub fn print_value(value: &Value, format: OutputFormat, no_color: bool) -> Result<(), CliError> {
+	let mut rendered = Vec::new();
+	write_value(&mut rendered, value, format, no_color)?;
+	emit(&String::from_utf8_lossy(&rendered), matches!(format, OutputFormat::Table))
+}
+
 pub fn write_value<W: Write>(
 	mut writer: W,
 	value: &Value,
@@ -44,6 +327,22 @@ pub fn write_value<W: Write>(
 	Ok(())
 }
 
+/// Prints `rows` as a table with exactly `columns`, for listings whose fields are not in
+/// the generic preferred-column set.
+pub fn print_columns(rows: &[Value], columns: &[&str]) -> Result<(), CliError> {
+	let mut table = Table::new();
+	table.load_preset(presets::UTF8_FULL);
+	table.set_header(columns.iter().copied());
+	for row in rows {
+		table.add_row(
+			columns
+				.iter()
+				.map(|col| Cell::new(row.get(*col).map(|v| field_text(col, v)).unwrap_or_default())),
+		);
+	}
+	emit(&table.to_string(), true)
+}
+
 fn write_table<W: Write>(mut writer: W, value: &Value, _no_color: bool) -> Result<bool, CliError> {
 	let Some(rows) = value.as_array() else {
 		return Ok(false);
@@ -58,11 +357,15 @@ fn write_table<W: Write>(mut writer: W, value: &Value, _no_color: bool) -> Resul
 		"orgName",
 		"nwid",
 		"nwname",
+		"owner",
 		"authorized",
 		"memberCount",
+		"size",
 		"host",
 		"default_profile",
 		"profiles",
+		"status",
+		"error",
 	];
 
 	let mut columns: Vec<&'static str> = Vec::new();
@@ -80,7 +383,7 @@ fn write_table<W: Write>(mut writer: W, value: &Value, _no_color: bool) -> Resul
 	for row in rows {
 		let mut cells = Vec::with_capacity(columns.len());
 		for col in &columns {
-			let text = row.get(*col).map(value_to_cell).unwrap_or_default();
+			let text = row.get(*col).map(|v| field_text(col, v)).unwrap_or_default();
 			cells.push(Cell::new(text));
 		}
 		table.add_row(cells);
@@ -90,6 +393,47 @@ fn write_table<W: Write>(mut writer: W, value: &Value, _no_color: bool) -> Resul
 	Ok(true)
 }
 
+/// Table-mode text for field `key`. Byte counts (`size`, `bytes`, `*Size`, `*_bytes`) and
+/// millisecond values (`*_ms`, `*Ms`) are humanized; JSON and YAML keep the raw numbers.
+pub fn field_text(key: &str, value: &Value) -> String {
+	if let Some(n) = value.as_u64() {
+		let lower = key.to_ascii_lowercase();
+		if lower == "size" || lower.ends_with("bytes") || key.ends_with("Size") || lower.ends_with("_size") {
+			return format_size(n);
+		}
+		if lower.ends_with("_ms") || key.ends_with("Ms") {
+			return format_duration(Duration::from_millis(n));
+		}
+	}
+	value_to_cell(value)
+}
+
+/// Binary units with one decimal (`1.5 KiB`); counts below 1 KiB stay exact (`512 B`).
+pub fn format_size(bytes: u64) -> String {
+	const UNITS: [&str; 5] = ["KiB", "MiB", "GiB", "TiB", "PiB"];
+	if bytes < 1024 {
+		return format!("{bytes} B");
+	}
+	let mut value = bytes as f64 / 1024.0;
+	let mut unit = 0;
+	while value >= 1024.0 && unit < UNITS.len() - 1 {
+		value /= 1024.0;
+		unit += 1;
+	}
+	format!("{value:.1} {}", UNITS[unit])
+}
+
+/// `350ms` below a second, `4.2s` below a minute, whole-second humantime (`2m 5s`) above.
+pub fn format_duration(duration: Duration) -> String {
+	if duration < Duration::from_secs(1) {
+		format!("{}ms", duration.as_millis())
+	} else if duration < Duration::from_secs(60) {
+		format!("{:.1}s", duration.as_secs_f64())
+	} else {
+		humantime::format_duration(Duration::from_secs(duration.as_secs())).to_string()
+	}
+}
+
 fn value_to_cell(value: &Value) -> String {
 	match value {
 		Value::Null => String::new(),
@@ -99,3 +443,76 @@ fn value_to_cell(value: &Value) -> String {
 		_ => serde_json::to_string(value).unwrap_or_default(),
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::io::Read;
+
+	#[test]
+	fn encrypted_out_never_writes_plaintext() {
+		let dir = std::env::temp_dir().join(format!("ztnet-sealed-out-{}", std::process::id()));
+		let path = dir.join("hosts.age");
+		let tmp = dir.join(".hosts.age.ztnet.tmp");
+		let identity = age::x25519::Identity::generate();
+		let sink = Sink::new(Some(&path), 0, vec![identity.to_public()], None, false).unwrap();
+
+		writeln!(sink.writer().unwrap(), "10.0.0.1\tweb.zt").unwrap();
+		assert!(sink.has_output());
+		assert!(!tmp.exists() && !path.exists());
+
+		sink.finish(true).unwrap();
+		let sealed = std::fs::read(&path).unwrap();
+		assert!(sealed.starts_with(b"age-encryption.org/v1"));
+		let mut plain = String::new();
+		age::Decryptor::new(&sealed[..])
+			.unwrap()
+			.decrypt(std::iter::once(&identity as &dyn age::Identity))
+			.unwrap()
+			.read_to_string(&mut plain)
+			.unwrap();
+		assert_eq!(plain, "10.0.0.1\tweb.zt\n");
+		assert!(!tmp.exists());
+		let _ = std::fs::remove_dir_all(&dir);
+	}
+
+	#[test]
+	fn truncation_cuts_at_a_line_boundary_and_only_above_the_limit() {
+		let text = "header\nrow one\nrow two\n";
+		assert_eq!(truncate_for_print(text, 0), None);
+		assert_eq!(truncate_for_print(text, 100), None);
+		assert_eq!(truncate_for_print(text, 12), Some("header"));
+		assert_eq!(truncate_for_print("äöü", 3), Some("ä"));
+	}
+
+	#[test]
+	fn sizes_and_durations_are_humanized_by_field_name() {
+		assert_eq!(format_size(512), "512 B");
+		assert_eq!(format_size(1536), "1.5 KiB");
+		assert_eq!(format_size(3 << 30), "3.0 GiB");
+		assert_eq!(format_duration(Duration::from_millis(350)), "350ms");
+		assert_eq!(format_duration(Duration::from_millis(4_250)), "4.2s");
+		assert_eq!(format_duration(Duration::from_millis(125_900)), "2m 5s");
+
+		assert_eq!(field_text("fileSize", &Value::from(2048)), "2.0 KiB");
+		assert_eq!(field_text("bytes", &Value::from(10)), "10 B");
+		assert_eq!(field_text("elapsed_ms", &Value::from(1500)), "1.5s");
+		assert_eq!(field_text("memberCount", &Value::from(2048)), "2048");
+	}
+
+	#[cfg(unix)]
+	#[tokio::test(flavor = "multi_thread")]
+	async fn page_splits_the_command_with_shell_quoting() {
+		let dir = std::env::temp_dir().join(format!("ztnet-pager-{}", std::process::id()));
+		std::fs::create_dir_all(&dir).unwrap();
+		let target = dir.join("paged text.txt");
+		let command = format!("sh -c 'cat > \"$0\"' '{}'", target.display());
+
+		assert!(page(&command, "line one\nline two"));
+		assert_eq!(std::fs::read_to_string(&target).unwrap(), "line one\nline two\n");
+
+		assert!(!page("ztnet-no-such-pager --flag", "text"));
+		assert!(!page("less 'unterminated", "text"));
+		std::fs::remove_dir_all(&dir).unwrap();
+	}
+}
@@ -0,0 +1,132 @@
+//! `--progress json`: newline-delimited JSON progress events on stderr for long-running
+//! operations (bulk updates, invite batches, detail fetches), so GUIs and CI dashboards
+//! wrapping the CLI can show progress without scraping the human output.
+
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+use serde_json::{Value, json};
+
+use crate::cli::ProgressMode;
+
+static JSON: AtomicBool = AtomicBool::new(false);
+
+pub(crate) fn install(mode: ProgressMode) {
+	JSON.store(mode == ProgressMode::Json, Ordering::SeqCst);
+}
+
+/// One operation's progress: `start` on creation, `item_started`/`item_finished` per
+/// item, and `finish` when dropped (also when the operation ends early with an error).
+pub(crate) struct Progress {
+	operation: &'static str,
+	total: Option<usize>,
+	begun: Cell<usize>,
+	done: Cell<usize>,
+	failed: Cell<usize>,
+	started: Instant,
+}
+
+impl Progress {
+	pub(crate) fn start(operation: &'static str, total: Option<usize>) -> Self {
+		let progress = Self {
+			operation,
+			total,
+			begun: Cell::new(0),
+			done: Cell::new(0),
+			failed: Cell::new(0),
+			started: Instant::now(),
+		};
+		emit(progress.event("start", json!({})));
+		progress
+	}
+
+	pub(crate) fn item_started(&self, item: &str) {
+		let index = self.begun.replace(self.begun.get() + 1);
+		emit(self.event("item_started", json!({ "item": item, "index": index })));
+	}
+
+	pub(crate) fn item_finished(&self, item: &str, ok: bool) {
+		self.done.set(self.done.get() + 1);
+		if !ok {
+			self.failed.set(self.failed.get() + 1);
+		}
+		emit(self.finished_event(item, ok));
+	}
+
+	fn finished_event(&self, item: &str, ok: bool) -> Value {
+		let done = self.done.get();
+		let (percent, eta) = match self.total {
+			Some(total) if total > 0 && done > 0 => {
+				let elapsed = self.started.elapsed().as_secs_f64();
+				let remaining = total.saturating_sub(done) as f64;
+				(json!(round1(done as f64 * 100.0 / total as f64)), json!(round1(elapsed / done as f64 * remaining)))
+			}
+			_ => (Value::Null, Value::Null),
+		};
+		self.event(
+			"item_finished",
+			json!({ "item": item, "ok": ok, "done": done, "percent": percent, "eta_secs": eta }),
+		)
+	}
+
+	fn event(&self, kind: &str, fields: Value) -> Value {
+		let mut event = json!({ "event": kind, "operation": self.operation, "total": self.total });
+		if let (Some(event), Value::Object(fields)) = (event.as_object_mut(), fields) {
+			event.extend(fields);
+		}
+		event
+	}
+}
+
+impl Drop for Progress {
+	fn drop(&mut self) {
+		emit(self.event(
+			"finish",
+			json!({
+				"done": self.done.get(),
+				"failed": self.failed.get(),
+				"elapsed_secs": round1(self.started.elapsed().as_secs_f64()),
+			}),
+		));
+	}
+}
+
+fn emit(event: Value) {
+	if JSON.load(Ordering::SeqCst) {
+		eprintln!("{event}");
+	}
+}
+
+fn round1(value: f64) -> f64 {
+	(value * 10.0).round() / 10.0
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::time::Duration;
+
+	#[test]
+	fn finished_events_carry_percentage_and_eta() {
+		let progress = Progress {
+			operation: "member tags",
+			total: Some(4),
+			begun: Cell::new(0),
+			done: Cell::new(0),
+			failed: Cell::new(0),
+			started: Instant::now() - Duration::from_secs(2),
+		};
+		progress.done.set(1);
+		let event = progress.finished_event("abc", true);
+		assert_eq!(event["event"], "item_finished");
+		assert_eq!(event["operation"], "member tags");
+		assert_eq!(event["percent"], 25.0);
+		// One item took ~2s, three remain.
+		assert!((event["eta_secs"].as_f64().unwrap() - 6.0).abs() < 0.5);
+
+		let unknown = Progress::start("fetch", None);
+		unknown.done.set(1);
+		assert!(unknown.finished_event("x", false)["percent"].is_null());
+	}
+}
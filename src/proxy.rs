@@ -0,0 +1,171 @@
+//! Proxy selection from `HTTPS_PROXY`/`HTTP_PROXY`/`ALL_PROXY` and `NO_PROXY`. The CLI
+//! makes the choice itself and hands reqwest an explicit proxy (or none), so clients, the
+//! `-v` log and the connection diagnostics always agree on it.
+
+use std::collections::HashSet;
+use std::net::IpAddr;
+use std::sync::{Mutex, OnceLock};
+
+use crate::redact;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Selection {
+	/// No proxy variable applies to the scheme.
+	Direct,
+	/// `--no-proxy` was given.
+	Disabled,
+	/// A proxy is configured, but `NO_PROXY` lists the host.
+	Excluded { var: &'static str, no_proxy_var: &'static str },
+	Proxy { var: &'static str, url: String, no_proxy: Option<String> },
+}
+
+/// Picks the proxy for a request to `host`: the scheme's variable (lowercase first, as
+/// curl does), then `ALL_PROXY`, unless `NO_PROXY` matches the host.
+pub(crate) fn select(scheme: &str, host: &str, env: impl Fn(&str) -> Option<String>) -> Selection {
+	let lookup = |names: &[&'static str]| {
+		names
+			.iter()
+			.find_map(|name| env(name).filter(|v| !v.trim().is_empty()).map(|v| (*name, v.trim().to_string())))
+	};
+	let scheme_vars: &[&'static str] = if scheme == "https" {
+		&["https_proxy", "HTTPS_PROXY"]
+	} else {
+		&["http_proxy", "HTTP_PROXY"]
+	};
+
+	let Some((var, url)) = lookup(scheme_vars).or_else(|| lookup(&["all_proxy", "ALL_PROXY"])) else {
+		return Selection::Direct;
+	};
+	let no_proxy = lookup(&["no_proxy", "NO_PROXY"]);
+	if let Some((no_proxy_var, list)) = &no_proxy
+		&& no_proxy_matches(list, host)
+	{
+		return Selection::Excluded { var, no_proxy_var };
+	}
+	Selection::Proxy { var, url, no_proxy: no_proxy.map(|(_, list)| list) }
+}
+
+/// `NO_PROXY` entries match the host itself or any subdomain (`example.com`,
+/// `.example.com` and `*.example.com` are equivalent); `*` matches everything. A `:port`
+/// suffix on an entry is ignored, and `10.0.0.0/8`-style entries match IP hosts in the
+/// network. The same list is also handed to reqwest as a [`reqwest::NoProxy`].
+pub(crate) fn no_proxy_matches(no_proxy: &str, host: &str) -> bool {
+	let host = host.trim_start_matches('[').trim_end_matches(']').to_ascii_lowercase();
+	let host_ip = host.parse::<IpAddr>().ok();
+	no_proxy.split(',').map(str::trim).filter(|e| !e.is_empty()).any(|entry| {
+		if let Some(ip) = host_ip
+			&& let Some((network, prefix)) = entry.split_once('/')
+		{
+			return cidr_contains(network, prefix, ip);
+		}
+		let entry = entry.trim_start_matches("*.").trim_start_matches('.').to_ascii_lowercase();
+		let entry = match entry.strip_prefix('[') {
+			Some(bracketed) => bracketed.split(']').next().unwrap_or_default(),
+			None => match entry.rsplit_once(':') {
+				Some((name, port)) if !name.contains(':') && port.chars().all(|c| c.is_ascii_digit()) => name,
+				_ => entry.as_str(),
+			},
+		};
+		entry == "*" || host == entry || host.ends_with(&format!(".{entry}"))
+	})
+}
+
+fn cidr_contains(network: &str, prefix: &str, ip: IpAddr) -> bool {
+	let (Ok(network), Ok(prefix)) = (network.parse::<IpAddr>(), prefix.parse::<u32>()) else {
+		return false;
+	};
+	let (network, ip, bits) = match (network, ip) {
+		(IpAddr::V4(n), IpAddr::V4(i)) => (u32::from(n) as u128, u32::from(i) as u128, 32),
+		(IpAddr::V6(n), IpAddr::V6(i)) => (u128::from(n), u128::from(i), 128),
+		_ => return false,
+	};
+	if prefix > bits {
+		return false;
+	}
+	let shift = bits - prefix;
+	shift == bits || network >> shift == ip >> shift
+}
+
+impl Selection {
+	/// One line for `-v` and diagnostics; proxy credentials are masked.
+	pub(crate) fn describe(&self, host: &str) -> String {
+		match self {
+			Selection::Direct => format!("proxy: none configured for {host} (direct connection)"),
+			Selection::Disabled => format!("proxy: disabled by --no-proxy for {host} (direct connection)"),
+			Selection::Excluded { var, no_proxy_var } => {
+				format!("proxy: {var} is set, but {no_proxy_var} excludes {host} (direct connection)")
+			}
+			Selection::Proxy { var, url, .. } => format!("proxy: {var}={} for {host}", redact::url(url)),
+		}
+	}
+}
+
+/// Prints `line` on stderr the first time it comes up; commands build several clients for
+/// the same host and one line each is enough.
+pub(crate) fn log_once(line: String) {
+	static SEEN: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+	let mut seen = SEEN.get_or_init(Default::default).lock().unwrap_or_else(|e| e.into_inner());
+	if seen.insert(line.clone()) {
+		eprintln!("{line}");
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn env(vars: &'static [(&'static str, &'static str)]) -> impl Fn(&str) -> Option<String> {
+		move |key: &str| vars.iter().find(|(k, _)| *k == key).map(|(_, v)| v.to_string())
+	}
+
+	#[test]
+	fn select_honors_scheme_all_proxy_and_no_proxy() {
+		assert_eq!(select("https", "ztnet.example.com", env(&[])), Selection::Direct);
+		assert_eq!(
+			select("https", "ztnet.example.com", env(&[("HTTP_PROXY", "http://p:3128")])),
+			Selection::Direct
+		);
+		assert_eq!(
+			select(
+				"https",
+				"ztnet.example.com",
+				env(&[("HTTPS_PROXY", "http://upper:3128"), ("https_proxy", "http://lower:3128")])
+			),
+			Selection::Proxy { var: "https_proxy", url: "http://lower:3128".to_string(), no_proxy: None }
+		);
+		assert_eq!(
+			select(
+				"http",
+				"ztnet.example.com",
+				env(&[("ALL_PROXY", "http://p:1080"), ("NO_PROXY", "localhost,.example.com")])
+			),
+			Selection::Excluded { var: "ALL_PROXY", no_proxy_var: "NO_PROXY" }
+		);
+
+		let line = Selection::Proxy {
+			var: "HTTPS_PROXY",
+			url: "http://user:secret@p:3128".to_string(),
+			no_proxy: None,
+		}
+		.describe("ztnet.example.com");
+		assert!(line.contains("HTTPS_PROXY=http://***@p:3128/"));
+		assert!(!line.contains("secret"));
+	}
+
+	#[test]
+	fn no_proxy_matches_suffixes_ports_and_wildcard() {
+		assert!(no_proxy_matches("example.com", "ztnet.example.com"));
+		assert!(no_proxy_matches("*.example.com", "example.com"));
+		assert!(no_proxy_matches("ztnet.example.com:443", "ztnet.example.com"));
+		assert!(no_proxy_matches("10.0.0.5", "10.0.0.5"));
+		assert!(no_proxy_matches("[::1]:3000", "[::1]"));
+		assert!(no_proxy_matches("*", "anything"));
+		assert!(!no_proxy_matches("ample.com", "ztnet.example.com"));
+		assert!(no_proxy_matches("localhost, 10.0.0.0/8", "10.20.30.40"));
+		assert!(no_proxy_matches("fd00::/8", "[fd12::1]"));
+		assert!(no_proxy_matches("0.0.0.0/0", "192.0.2.1"));
+		assert!(!no_proxy_matches("10.0.0.0/8", "11.0.0.1"));
+		assert!(!no_proxy_matches("10.0.0.0/8", "fd00::1"));
+		assert!(!no_proxy_matches("10.0.0.0/8", "ten.example.com"));
+	}
+}
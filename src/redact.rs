@@ -0,0 +1,125 @@
+//! Masking of secrets before they reach the terminal: dry-run previews, error bodies,
+//! diagnostics and `auth show` / `config list` all go through these helpers.
+
+use serde_json::Value;
+use url::Url;
+
+pub(crate) const REDACTED: &str = "REDACTED";
+
+/// JSON keys whose values are never printed (matched case-insensitively as substrings).
+const SECRET_KEY_MARKERS: &[&str] = &["password", "secret", "token", "apikey", "api_key", "totp", "cookie"];
+
+/// Headers printed as `REDACTED`; the API token header keeps its first and last characters.
+const SECRET_HEADERS: &[&str] = &["cookie", "set-cookie", "authorization", "proxy-authorization", "x-api-key"];
+const TOKEN_HEADER: &str = "x-ztnet-auth";
+
+/// Shows enough of a token to tell two apart (`sk_1…abcd`); short tokens are hidden fully.
+pub(crate) fn token(token: &str) -> String {
+	const KEEP: usize = 4;
+	let chars: Vec<char> = token.chars().collect();
+	if chars.len() <= KEEP * 2 {
+		return REDACTED.to_string();
+	}
+	let prefix: String = chars[..KEEP].iter().collect();
+	let suffix: String = chars[chars.len() - KEEP..].iter().collect();
+	format!("{prefix}…{suffix}")
+}
+
+/// Display value for a request header.
+pub(crate) fn header(name: &str, value: &str) -> String {
+	if name.eq_ignore_ascii_case(TOKEN_HEADER) {
+		return token(value);
+	}
+	if SECRET_HEADERS.iter().any(|h| name.eq_ignore_ascii_case(h)) {
+		return REDACTED.to_string();
+	}
+	value.to_string()
+}
+
+/// Replaces values of secret-looking keys (passwords, tokens, ...) anywhere in `value`.
+pub(crate) fn json(value: &mut Value) {
+	match value {
+		Value::Object(map) => {
+			for (key, child) in map.iter_mut() {
+				let key = key.to_ascii_lowercase();
+				if SECRET_KEY_MARKERS.iter().any(|m| key.contains(m)) && !child.is_null() {
+					*child = Value::String(REDACTED.to_string());
+				} else {
+					json(child);
+				}
+			}
+		}
+		Value::Array(items) => items.iter_mut().for_each(json),
+		_ => {}
+	}
+}
+
+/// A response body as kept on errors: JSON bodies get their secret keys masked, anything
+/// else is passed through.
+pub(crate) fn body(text: &str) -> String {
+	match serde_json::from_str::<Value>(text) {
+		Ok(mut value) if value.is_object() || value.is_array() => {
+			json(&mut value);
+			value.to_string()
+		}
+		_ => text.to_string(),
+	}
+}
+
+/// Hides the userinfo of a URL such as a proxy setting (`http://***@proxy:3128/`).
+pub(crate) fn url(value: &str) -> String {
+	match Url::parse(value) {
+		Ok(mut url) if !url.username().is_empty() || url.password().is_some() => {
+			let _ = url.set_username("***");
+			let _ = url.set_password(None);
+			url.to_string()
+		}
+		_ => value.to_string(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+
+	#[test]
+	fn token_keeps_edges_and_is_char_safe() {
+		assert_eq!(token("sk_1234567890abcd"), "sk_1…abcd");
+		assert_eq!(token("short"), REDACTED);
+		assert_eq!(token("ääää-xx-üüüü"), "ääää…üüüü");
+	}
+
+	#[test]
+	fn headers_are_masked_by_name() {
+		assert_eq!(header("Cookie", "next-auth.session-token=abc"), REDACTED);
+		assert_eq!(header("X-API-Key", "pdns"), REDACTED);
+		assert_eq!(header("x-ztnet-auth", "sk_1234567890abcd"), "sk_1…abcd");
+		assert_eq!(header("accept", "application/json"), "application/json");
+	}
+
+	#[test]
+	fn json_masks_nested_secret_keys() {
+		let mut value = json!({
+			"0": { "json": {
+				"updateParams": { "password": "hunter2", "name": "ann" },
+				"smtpPassword": "x",
+				"items": [{ "apiToken": "abc" }]
+			}}
+		});
+		json(&mut value);
+		let input = &value["0"]["json"];
+		assert_eq!(input["updateParams"]["password"], REDACTED);
+		assert_eq!(input["updateParams"]["name"], "ann");
+		assert_eq!(input["smtpPassword"], REDACTED);
+		assert_eq!(input["items"][0]["apiToken"], REDACTED);
+	}
+
+	#[test]
+	fn body_and_url_mask_credentials() {
+		assert_eq!(body(r#"{"token":"abc","ok":false}"#), r#"{"ok":false,"token":"REDACTED"}"#);
+		assert_eq!(body("<html>oops</html>"), "<html>oops</html>");
+		assert_eq!(url("http://user:pw@p:3128"), "http://***@p:3128/");
+		assert_eq!(url("http://p:3128"), "http://p:3128");
+	}
+}
@@ -0,0 +1,351 @@
+//! `--sign-key` and `ztnet verify`: minisign signatures for the files the CLI writes
+//! (`--out`, `export hosts --out`, backup downloads), so artifacts passed between teams
+//! carry provenance. Signatures are written next to the file as `<file>.minisig` and can
+//! also be checked with `minisign -Vm FILE -p minisign.pub`.
+//!
+//! Keys are regular minisign keys (`minisign -G`); encrypted secret keys are unlocked with
+//! `ZTNET_SIGN_PASSWORD`.
+
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use blake2::digest::consts::U32;
+use blake2::{Blake2b, Blake2b512, Digest};
+use ring::signature::{self, Ed25519KeyPair, UnparsedPublicKey};
+
+use crate::error::CliError;
+
+const PASSWORD_ENV: &str = "ZTNET_SIGN_PASSWORD";
+/// Signature over the BLAKE2b-512 hash of the file (minisign's default).
+const ALG_HASHED: &[u8; 2] = b"ED";
+/// Signature over the file itself (legacy minisign signatures).
+const ALG_LEGACY: &[u8; 2] = b"Ed";
+/// Bounds for the scrypt limits stored in an encrypted key, which come from the key file and
+/// would otherwise let it demand any amount of memory and time. `minisign -G` writes 2^25 and
+/// 2^30 (scrypt N = 2^20, r = 8, p = 1, i.e. 1 GiB); the ops cap allows p up to 4.
+const MAX_OPSLIMIT: u64 = 1 << 27;
+const MAX_MEMLIMIT: u64 = 1 << 30;
+
+pub(crate) struct Signer {
+	key_id: [u8; 8],
+	pair: Ed25519KeyPair,
+}
+
+impl Signer {
+	/// Reads a minisign secret key, decrypting it with `ZTNET_SIGN_PASSWORD` when needed.
+	pub(crate) fn load(path: &Path) -> Result<Self, CliError> {
+		let text = std::fs::read_to_string(path)
+			.map_err(|err| CliError::InvalidArgument(format!("cannot read --sign-key {}: {err}", path.display())))?;
+		let password = std::env::var(PASSWORD_ENV).ok();
+		Self::from_secret_key(&text, password.as_deref())
+			.map_err(|reason| CliError::InvalidArgument(format!("--sign-key {}: {reason}", path.display())))
+	}
+
+	fn from_secret_key(text: &str, password: Option<&str>) -> Result<Self, String> {
+		let raw = decode_box(text)?;
+		if raw.len() != 158 || &raw[..2] != ALG_LEGACY || &raw[4..6] != b"B2" {
+			return Err("not a minisign secret key".to_string());
+		}
+		let mut keynum_sk = raw[54..].to_vec();
+		match &raw[2..4] {
+			[0, 0] => {}
+			b"Sc" => {
+				let password = password.ok_or_else(|| format!("the key is encrypted; set {PASSWORD_ENV}"))?;
+				let opslimit = u64::from_le_bytes(raw[38..46].try_into().unwrap_or_default());
+				let memlimit = u64::from_le_bytes(raw[46..54].try_into().unwrap_or_default());
+				if opslimit > MAX_OPSLIMIT || memlimit > MAX_MEMLIMIT {
+					return Err(format!(
+						"the key's scrypt limits (opslimit {opslimit}, memlimit {memlimit}) exceed what minisign uses"
+					));
+				}
+				let (log_n, r, p) = scrypt_params(opslimit, memlimit);
+				// The length only matters for password hashes; the output buffer sets it here.
+				let params = scrypt::Params::new(log_n, r, p, scrypt::Params::RECOMMENDED_LEN)
+					.map_err(|err| format!("unsupported key parameters: {err}"))?;
+				let mut stream = vec![0u8; keynum_sk.len()];
+				scrypt::scrypt(password.as_bytes(), &raw[6..38], &params, &mut stream)
+					.map_err(|err| format!("key derivation failed: {err}"))?;
+				keynum_sk.iter_mut().zip(stream).for_each(|(byte, key)| *byte ^= key);
+			}
+			_ => return Err("unsupported key encryption".to_string()),
+		}
+
+		let (key_id, secret, checksum) = (&keynum_sk[..8], &keynum_sk[8..72], &keynum_sk[72..]);
+		let mut hasher = Blake2b::<U32>::new();
+		hasher.update(ALG_LEGACY);
+		hasher.update(key_id);
+		hasher.update(secret);
+		if hasher.finalize().as_slice() != checksum {
+			return Err(if raw[2..4] == *b"Sc" {
+				format!("wrong password ({PASSWORD_ENV})")
+			} else {
+				"the key is corrupted".to_string()
+			});
+		}
+		let pair = Ed25519KeyPair::from_seed_and_public_key(&secret[..32], &secret[32..])
+			.map_err(|_| "the key is corrupted".to_string())?;
+		Ok(Self {
+			key_id: key_id.try_into().unwrap_or_default(),
+			pair,
+		})
+	}
+
+	/// The `.minisig` contents for `data`, with a trusted comment naming `file_name`.
+	pub(crate) fn sign(&self, data: &[u8], file_name: &str) -> String {
+		let signature = self.pair.sign(&Blake2b512::digest(data));
+		let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default();
+		let trusted = format!("timestamp:{timestamp}\tfile:{file_name}\thashed");
+
+		let mut sig_box = Vec::with_capacity(74);
+		sig_box.extend_from_slice(ALG_HASHED);
+		sig_box.extend_from_slice(&self.key_id);
+		sig_box.extend_from_slice(signature.as_ref());
+		let global = self.pair.sign(&[signature.as_ref(), trusted.as_bytes()].concat());
+		format!(
+			"untrusted comment: signature from ztnet secret key\n{}\ntrusted comment: {trusted}\n{}\n",
+			STANDARD.encode(sig_box),
+			STANDARD.encode(global.as_ref())
+		)
+	}
+
+	/// Writes `<path>.minisig` for the file at `path`.
+	pub(crate) fn sign_file(&self, path: &Path) -> Result<(), CliError> {
+		let data = std::fs::read(path)?;
+		let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+		std::fs::write(signature_path(path), self.sign(&data, name))?;
+		Ok(())
+	}
+}
+
+pub(crate) fn signature_path(path: &Path) -> std::path::PathBuf {
+	let mut name = path.as_os_str().to_owned();
+	name.push(".minisig");
+	name.into()
+}
+
+pub(crate) struct PublicKey {
+	key_id: [u8; 8],
+	key: [u8; 32],
+}
+
+impl PublicKey {
+	/// A public key as printed by `minisign -G` (`RW...`), or the contents of a
+	/// `minisign.pub` file.
+	pub(crate) fn parse(text: &str) -> Result<Self, CliError> {
+		let raw = decode_box(text).map_err(|reason| CliError::InvalidArgument(format!("invalid public key: {reason}")))?;
+		if raw.len() != 42 || &raw[..2] != ALG_LEGACY {
+			return Err(CliError::InvalidArgument("invalid public key: not a minisign public key".to_string()));
+		}
+		Ok(Self {
+			key_id: raw[2..10].try_into().unwrap_or_default(),
+			key: raw[10..].try_into().unwrap_or_default(),
+		})
+	}
+
+	/// Checks a `.minisig` signature of `data` and returns its trusted comment.
+	pub(crate) fn verify(&self, data: &[u8], minisig: &str) -> Result<String, CliError> {
+		let invalid = |reason: &str| CliError::InvalidArgument(format!("invalid signature file: {reason}"));
+		let mut lines = minisig.lines().filter(|line| !line.trim().is_empty());
+		let _untrusted = lines.next().ok_or_else(|| invalid("empty"))?;
+		let sig_box = decode_line(lines.next().ok_or_else(|| invalid("missing signature"))?).map_err(|r| invalid(&r))?;
+		let trusted = lines
+			.next()
+			.and_then(|line| line.strip_prefix("trusted comment: "))
+			.ok_or_else(|| invalid("missing trusted comment"))?;
+		let global = decode_line(lines.next().ok_or_else(|| invalid("missing global signature"))?).map_err(|r| invalid(&r))?;
+		if sig_box.len() != 74 || global.len() != 64 {
+			return Err(invalid("unexpected signature length"));
+		}
+		if sig_box[2..10] != self.key_id {
+			return Err(CliError::SignatureInvalid(format!(
+				"signed with key {}, not {}",
+				key_id_hex(&sig_box[2..10]),
+				key_id_hex(&self.key_id)
+			)));
+		}
+
+		let key = UnparsedPublicKey::new(&signature::ED25519, &self.key);
+		let signature = &sig_box[10..];
+		let checked = match &sig_box[..2] {
+			alg if alg == ALG_HASHED => key.verify(&Blake2b512::digest(data), signature),
+			alg if alg == ALG_LEGACY => key.verify(data, signature),
+			_ => return Err(invalid("unsupported signature algorithm")),
+		};
+		checked.map_err(|_| CliError::SignatureInvalid("the file does not match its signature".to_string()))?;
+		key.verify(&[signature, trusted.as_bytes()].concat(), &global)
+			.map_err(|_| CliError::SignatureInvalid("the trusted comment was altered".to_string()))?;
+		Ok(trusted.to_string())
+	}
+}
+
+/// Key ids are shown the way `minisign` prints them: the little-endian id in hex.
+fn key_id_hex(id: &[u8]) -> String {
+	id.iter().rev().map(|b| format!("{b:02X}")).collect()
+}
+
+/// The base64 payload of a minisign key file (after its comment line) or of a bare key.
+fn decode_box(text: &str) -> Result<Vec<u8>, String> {
+	let line = text
+		.lines()
+		.map(str::trim)
+		.find(|line| !line.is_empty() && !line.starts_with("untrusted comment:"))
+		.ok_or_else(|| "empty key".to_string())?;
+	decode_line(line)
+}
+
+fn decode_line(line: &str) -> Result<Vec<u8>, String> {
+	STANDARD.decode(line.trim()).map_err(|err| format!("bad base64: {err}"))
+}
+
+/// libsodium's `crypto_pwhash_scryptsalsa208sha256` mapping from ops/mem limits to scrypt
+/// parameters, as used by minisign.
+fn scrypt_params(opslimit: u64, memlimit: u64) -> (u8, u32, u32) {
+	let opslimit = opslimit.max(32_768);
+	let r = 8u32;
+	let max_n = |limit: u64| {
+		let mut log_n = 1u8;
+		while log_n < 63 && (1u64 << log_n) <= limit / 2 {
+			log_n += 1;
+		}
+		log_n
+	};
+	if opslimit < memlimit / 32 {
+		(max_n(opslimit / (u64::from(r) * 4)), r, 1)
+	} else {
+		let log_n = max_n(memlimit / (u64::from(r) * 128));
+		let max_rp = ((opslimit / 4) / (1u64 << log_n)).min(0x3fff_ffff);
+		(log_n, r, (max_rp / u64::from(r)) as u32)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ring::signature::KeyPair;
+
+	/// A minisign secret key file for `seed`, encrypted when `password` is given (with
+	/// cheap scrypt limits so the test stays fast).
+	fn secret_key_file(seed: [u8; 32], password: Option<&str>) -> (String, String) {
+		secret_key_file_with_limits(seed, password, 32_768, 1 << 24)
+	}
+
+	fn secret_key_file_with_limits(
+		seed: [u8; 32],
+		password: Option<&str>,
+		opslimit: u64,
+		memlimit: u64,
+	) -> (String, String) {
+		let pair = Ed25519KeyPair::from_seed_unchecked(&seed).unwrap();
+		let key_id = [7u8, 6, 5, 4, 3, 2, 1, 0];
+		let mut secret = seed.to_vec();
+		secret.extend_from_slice(pair.public_key().as_ref());
+		let mut hasher = Blake2b::<U32>::new();
+		hasher.update(ALG_LEGACY);
+		hasher.update(key_id);
+		hasher.update(&secret);
+		let mut keynum_sk = [&key_id[..], &secret, hasher.finalize().as_slice()].concat();
+
+		let salt = [9u8; 32];
+		let kdf: &[u8] = if let Some(password) = password {
+			let (log_n, r, p) = scrypt_params(opslimit, memlimit);
+			let mut stream = vec![0u8; keynum_sk.len()];
+			scrypt::scrypt(password.as_bytes(), &salt, &scrypt::Params::new(log_n, r, p, 32).unwrap(), &mut stream).unwrap();
+			keynum_sk.iter_mut().zip(stream).for_each(|(byte, key)| *byte ^= key);
+			b"Sc"
+		} else {
+			&[0, 0]
+		};
+		let raw = [
+			&ALG_LEGACY[..],
+			kdf,
+			b"B2",
+			&salt,
+			&opslimit.to_le_bytes(),
+			&memlimit.to_le_bytes(),
+			&keynum_sk,
+		]
+		.concat();
+		let public = [&ALG_LEGACY[..], &key_id, pair.public_key().as_ref()].concat();
+		(
+			format!("untrusted comment: minisign encrypted secret key\n{}\n", STANDARD.encode(raw)),
+			format!("untrusted comment: minisign public key\n{}\n", STANDARD.encode(public)),
+		)
+	}
+
+	#[test]
+	fn signatures_round_trip_and_detect_tampering() {
+		let (secret, public) = secret_key_file([1; 32], None);
+		let signer = Signer::from_secret_key(&secret, None).unwrap();
+		let public = PublicKey::parse(&public).unwrap();
+
+		let minisig = signer.sign(b"10.0.0.1\tweb.zt\n", "hosts");
+		let trusted = public.verify(b"10.0.0.1\tweb.zt\n", &minisig).unwrap();
+		assert!(trusted.starts_with("timestamp:") && trusted.ends_with("\tfile:hosts\thashed"));
+
+		assert!(matches!(public.verify(b"10.0.0.2\tweb.zt\n", &minisig), Err(CliError::SignatureInvalid(_))));
+		let forged = minisig.replace("file:hosts", "file:other");
+		assert!(matches!(public.verify(b"10.0.0.1\tweb.zt\n", &forged), Err(CliError::SignatureInvalid(_))));
+		let (_, other) = secret_key_file([2; 32], None);
+		let other = PublicKey::parse(other.lines().nth(1).unwrap()).unwrap();
+		assert!(other.verify(b"10.0.0.1\tweb.zt\n", &minisig).is_err());
+	}
+
+	#[test]
+	fn encrypted_keys_need_the_password() {
+		let (secret, _) = secret_key_file([3; 32], Some("hunter2"));
+		assert!(Signer::from_secret_key(&secret, None).err().unwrap().contains(PASSWORD_ENV));
+		assert!(Signer::from_secret_key(&secret, Some("wrong")).err().unwrap().contains("wrong password"));
+		assert!(Signer::from_secret_key(&secret, Some("hunter2")).is_ok());
+		// minisign's defaults (opslimit 2^25, memlimit 2^30) map to N = 2^20, r = 8, p = 1.
+		assert_eq!(scrypt_params(1 << 25, 1 << 30), (20, 8, 1));
+
+		// Limits from the file are untrusted: anything above minisign's is refused before scrypt
+		// runs. The keys are marked encrypted without running the (huge) scrypt to build them.
+		for (opslimit, memlimit) in [(1 << 40, 1 << 24), (32_768, 1 << 40)] {
+			let (plain, _) = secret_key_file_with_limits([3; 32], None, opslimit, memlimit);
+			let mut raw = decode_box(&plain).unwrap();
+			raw[2..4].copy_from_slice(b"Sc");
+			let greedy = STANDARD.encode(raw);
+			assert!(Signer::from_secret_key(&greedy, Some("hunter2")).err().unwrap().contains("exceed"));
+		}
+	}
+
+	/// Public key and signatures of the file `test`, produced by minisign itself (they are the
+	/// fixtures of the minisign-verify crate): one prehashed (`ED`) and one legacy (`Ed`).
+	const MINISIGN_PUBKEY: &str = "RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3";
+	const MINISIGN_HASHED: &str = "untrusted comment: signature from minisign secret key
+RUQf6LRCGA9i559r3g7V1qNyJDApGip8MfqcadIgT9CuhV3EMhHoN1mGTkUidF/z7SrlQgXdy8ofjb7bNJJylDOocrCo8KLzZwo=
+trusted comment: timestamp:1556193335\tfile:test
+y/rUw2y8/hOUYjZU71eHp/Wo1KZ40fGy2VJEDl34XMJM+TX48Ss/17u3IvIfbVR1FkZZSNCisQbuQY+bHwhEBg==
+";
+	const MINISIGN_LEGACY: &str = "untrusted comment: signature from minisign secret key
+RWQf6LRCGA9i59SLOFxz6NxvASXDJeRtuZykwQepbDEGt87ig1BNpWaVWuNrm73YiIiJbq71Wi+dP9eKL8OC351vwIasSSbXxwA=
+trusted comment: timestamp:1555779966\tfile:test
+QtKMXWyYcwdpZAlPF7tE2ENJkRd1ujvKjlj1m9RtHTBnZPa5WKU5uWRs5GoP5M/VqE81QFuMKI5k/SfNQUaOAA==
+";
+
+	#[test]
+	fn verifies_signatures_made_by_minisign() {
+		let file = format!("untrusted comment: minisign public key E7620F1842B4E81F\n{MINISIGN_PUBKEY}\n");
+		let public = PublicKey::parse(&file).unwrap();
+		assert_eq!(public.verify(b"test", MINISIGN_HASHED).unwrap(), "timestamp:1556193335\tfile:test");
+		assert_eq!(public.verify(b"test", MINISIGN_LEGACY).unwrap(), "timestamp:1555779966\tfile:test");
+		assert!(matches!(public.verify(b"Test", MINISIGN_HASHED), Err(CliError::SignatureInvalid(_))));
+		let forged = MINISIGN_HASHED.replace("file:test", "file:best");
+		assert!(matches!(public.verify(b"test", &forged), Err(CliError::SignatureInvalid(_))));
+	}
+
+	#[test]
+	fn minisign_verifies_our_signatures() {
+		let (secret, public) = secret_key_file([4; 32], None);
+		let minisig = Signer::from_secret_key(&secret, None).unwrap().sign(b"10.0.0.1\tweb.zt\n", "hosts");
+
+		let public = minisign_verify::PublicKey::decode(public.trim_end()).unwrap();
+		let signature = minisign_verify::Signature::decode(&minisig).unwrap();
+		public.verify(b"10.0.0.1\tweb.zt\n", &signature, false).unwrap();
+		assert!(public.verify(b"10.0.0.2\tweb.zt\n", &signature, false).is_err());
+		assert!(signature.trusted_comment().ends_with("\tfile:hosts\thashed"));
+	}
+}
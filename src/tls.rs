@@ -0,0 +1,465 @@
+//! TLS certificate inspection (`host cert`) and per-profile certificate pinning
+//! (`profiles.<name>.cert_fingerprint`).
+
+use std::error::Error as StdError;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::client::WebPkiServerVerifier;
+use rustls::crypto::{self, CryptoProvider};
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{CertificateError, ClientConfig, DigitallySignedStruct, OtherError, RootCertStore, SignatureScheme};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+use url::Url;
+use x509_parser::prelude::{FromDer, GeneralName, X509Certificate};
+
+use crate::error::CliError;
+
+/// SHA-256 fingerprint of a DER certificate as uppercase colon-separated hex, the format
+/// `openssl x509 -fingerprint -sha256` prints.
+pub(crate) fn fingerprint(der: &[u8]) -> String {
+	Sha256::digest(der)
+		.iter()
+		.map(|b| format!("{b:02X}"))
+		.collect::<Vec<_>>()
+		.join(":")
+}
+
+/// Accepts a SHA-256 fingerprint with or without colons, in any case, optionally prefixed
+/// with `sha256:` / `SHA256=`, and returns it in [`fingerprint`] form.
+pub(crate) fn normalize_fingerprint(raw: &str) -> Result<String, CliError> {
+	let trimmed = raw.trim();
+	let lower = trimmed.to_ascii_lowercase();
+	let body = lower
+		.strip_prefix("sha256:")
+		.or_else(|| lower.strip_prefix("sha256="))
+		.unwrap_or(&lower);
+	let hex: String = body.chars().filter(|c| *c != ':').collect();
+	if hex.len() != 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+		return Err(CliError::InvalidArgument(format!(
+			"invalid certificate fingerprint: {trimmed} (expected 32 hex bytes, e.g. AB:CD:...)"
+		)));
+	}
+	Ok(hex
+		.to_ascii_uppercase()
+		.as_bytes()
+		.chunks(2)
+		.map(|pair| String::from_utf8_lossy(pair).into_owned())
+		.collect::<Vec<_>>()
+		.join(":"))
+}
+
+/// The leaf certificate did not match the profile's pin.
+#[derive(Debug)]
+pub(crate) struct PinMismatch {
+	pub expected: String,
+	pub actual: String,
+}
+
+impl std::fmt::Display for PinMismatch {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "certificate fingerprint {} does not match the pinned {}", self.actual, self.expected)
+	}
+}
+
+impl StdError for PinMismatch {}
+
+/// Finds a [`PinMismatch`] anywhere in a request error's source chain.
+pub(crate) fn pin_mismatch<'a>(err: &'a (dyn StdError + 'static)) -> Option<&'a PinMismatch> {
+	let mut current = Some(err);
+	while let Some(err) = current {
+		if let Some(found) = err.downcast_ref::<PinMismatch>() {
+			return Some(found);
+		}
+		if let Some(rustls::Error::InvalidCertificate(CertificateError::Other(OtherError(inner)))) =
+			err.downcast_ref::<rustls::Error>()
+			&& let Some(found) = inner.downcast_ref::<PinMismatch>()
+		{
+			return Some(found);
+		}
+		// io::Error::source() skips the wrapped error itself, so look inside explicitly.
+		if let Some(io) = err.downcast_ref::<std::io::Error>()
+			&& let Some(inner) = io.get_ref()
+			&& let Some(found) = pin_mismatch(inner)
+		{
+			return Some(found);
+		}
+		current = err.source();
+	}
+	None
+}
+
+/// A failed request as a [`CliError`]: pin mismatches become [`CliError::CertPinMismatch`].
+pub(crate) fn request_error(err: reqwest::Error, url: &Url) -> CliError {
+	match pin_mismatch(&err) {
+		Some(mismatch) => CliError::CertPinMismatch {
+			host: url.host_str().unwrap_or_default().to_string(),
+			expected: mismatch.expected.clone(),
+			actual: mismatch.actual.clone(),
+		},
+		None => CliError::Request(err),
+	}
+}
+
+fn provider() -> Arc<CryptoProvider> {
+	Arc::new(crypto::ring::default_provider())
+}
+
+fn webpki_verifier(provider: Arc<CryptoProvider>) -> Result<Arc<WebPkiServerVerifier>, CliError> {
+	let roots = RootCertStore {
+		roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+	};
+	WebPkiServerVerifier::builder_with_provider(Arc::new(roots), provider)
+		.build()
+		.map_err(|err| CliError::InvalidArgument(format!("TLS setup failed: {err}")))
+}
+
+fn client_config(verifier: Arc<dyn ServerCertVerifier>, provider: Arc<CryptoProvider>) -> Result<ClientConfig, CliError> {
+	Ok(ClientConfig::builder_with_provider(provider)
+		.with_safe_default_protocol_versions()
+		.map_err(|err| CliError::InvalidArgument(format!("TLS setup failed: {err}")))?
+		.dangerous()
+		.with_custom_certificate_verifier(verifier)
+		.with_no_client_auth())
+}
+
+/// TLS settings for the HTTP clients when the profile pins a certificate: the leaf's SHA-256
+/// fingerprint must equal `pin`. A matching leaf is trusted on its own (like an SSH known
+/// host), so self-signed panels can be pinned; anything else is rejected.
+pub(crate) fn pinned_client_config(pin: &str) -> Result<ClientConfig, CliError> {
+	let provider = provider();
+	let verifier = PinnedVerifier {
+		provider: provider.clone(),
+		pin: normalize_fingerprint(pin)?,
+	};
+	client_config(Arc::new(verifier), provider)
+}
+
+#[derive(Debug)]
+struct PinnedVerifier {
+	provider: Arc<CryptoProvider>,
+	pin: String,
+}
+
+impl ServerCertVerifier for PinnedVerifier {
+	fn verify_server_cert(
+		&self,
+		end_entity: &CertificateDer<'_>,
+		_intermediates: &[CertificateDer<'_>],
+		_server_name: &ServerName<'_>,
+		_ocsp_response: &[u8],
+		_now: UnixTime,
+	) -> Result<ServerCertVerified, rustls::Error> {
+		let actual = fingerprint(end_entity);
+		if actual != self.pin {
+			return Err(rustls::Error::InvalidCertificate(CertificateError::Other(OtherError(Arc::new(
+				PinMismatch {
+					expected: self.pin.clone(),
+					actual,
+				},
+			)))));
+		}
+		Ok(ServerCertVerified::assertion())
+	}
+
+	fn verify_tls12_signature(
+		&self,
+		message: &[u8],
+		cert: &CertificateDer<'_>,
+		dss: &DigitallySignedStruct,
+	) -> Result<HandshakeSignatureValid, rustls::Error> {
+		crypto::verify_tls12_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+	}
+
+	fn verify_tls13_signature(
+		&self,
+		message: &[u8],
+		cert: &CertificateDer<'_>,
+		dss: &DigitallySignedStruct,
+	) -> Result<HandshakeSignatureValid, rustls::Error> {
+		crypto::verify_tls13_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+	}
+
+	fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+		self.provider.signature_verification_algorithms.supported_schemes()
+	}
+}
+
+/// Accepts any certificate but keeps the presented chain and the outcome of normal
+/// validation, so `host cert show` can report on broken or self-signed setups too.
+#[derive(Debug)]
+struct RecordingVerifier {
+	inner: Arc<WebPkiServerVerifier>,
+	provider: Arc<CryptoProvider>,
+	seen: Mutex<Option<PeerChain>>,
+}
+
+impl ServerCertVerifier for RecordingVerifier {
+	fn verify_server_cert(
+		&self,
+		end_entity: &CertificateDer<'_>,
+		intermediates: &[CertificateDer<'_>],
+		server_name: &ServerName<'_>,
+		ocsp_response: &[u8],
+		now: UnixTime,
+	) -> Result<ServerCertVerified, rustls::Error> {
+		let verdict = self
+			.inner
+			.verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now)
+			.err()
+			.map(|err| err.to_string());
+		let certificates = std::iter::once(end_entity)
+			.chain(intermediates)
+			.map(|cert| cert.as_ref().to_vec())
+			.collect();
+		if let Ok(mut seen) = self.seen.lock() {
+			*seen = Some(PeerChain {
+				certificates,
+				verify_error: verdict,
+			});
+		}
+		Ok(ServerCertVerified::assertion())
+	}
+
+	fn verify_tls12_signature(
+		&self,
+		message: &[u8],
+		cert: &CertificateDer<'_>,
+		dss: &DigitallySignedStruct,
+	) -> Result<HandshakeSignatureValid, rustls::Error> {
+		crypto::verify_tls12_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+	}
+
+	fn verify_tls13_signature(
+		&self,
+		message: &[u8],
+		cert: &CertificateDer<'_>,
+		dss: &DigitallySignedStruct,
+	) -> Result<HandshakeSignatureValid, rustls::Error> {
+		crypto::verify_tls13_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+	}
+
+	fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+		self.provider.signature_verification_algorithms.supported_schemes()
+	}
+}
+
+/// What the server presented during a handshake.
+#[derive(Debug)]
+pub(crate) struct PeerChain {
+	/// DER certificates, leaf first.
+	pub certificates: Vec<Vec<u8>>,
+	/// Why web-PKI validation failed, if it did.
+	pub verify_error: Option<String>,
+}
+
+/// Completes a TLS handshake with `addr`, presenting `server_name` as SNI, and returns the
+/// certificate chain the server sent.
+pub(crate) async fn fetch_chain(addr: SocketAddr, server_name: &str, timeout: Duration) -> Result<PeerChain, CliError> {
+	let provider = provider();
+	let verifier = Arc::new(RecordingVerifier {
+		inner: webpki_verifier(provider.clone())?,
+		provider: provider.clone(),
+		seen: Mutex::new(None),
+	});
+	let config = client_config(verifier.clone(), provider)?;
+	let name = match server_name.parse::<IpAddr>() {
+		Ok(ip) => ServerName::IpAddress(ip.into()),
+		Err(_) => ServerName::try_from(server_name.to_string())
+			.map_err(|_| CliError::InvalidArgument(format!("invalid TLS server name: {server_name}")))?,
+	};
+
+	let handshake = async {
+		let stream = TcpStream::connect(addr).await?;
+		TlsConnector::from(Arc::new(config)).connect(name, stream).await
+	};
+	match tokio::time::timeout(timeout, handshake).await {
+		Ok(Ok(_)) => {}
+		Ok(Err(err)) => {
+			return Err(CliError::InvalidArgument(format!("TLS handshake with {addr} failed: {err}")));
+		}
+		Err(_) => {
+			return Err(CliError::Timeout(format!("TLS handshake with {addr}")));
+		}
+	}
+
+	verifier
+		.seen
+		.lock()
+		.ok()
+		.and_then(|mut seen| seen.take())
+		.ok_or_else(|| CliError::InvalidArgument(format!("{addr} presented no certificate")))
+}
+
+/// Subject, issuer, validity, fingerprint and (for `with_sans`) the subject alternative
+/// names of a DER certificate. Fields that fail to parse are left out.
+pub(crate) fn describe(der: &[u8], with_sans: bool) -> Value {
+	let mut out = json!({ "sha256": fingerprint(der) });
+	let Ok((_, cert)) = X509Certificate::from_der(der) else {
+		return out;
+	};
+
+	let validity = cert.validity();
+	let not_before = validity.not_before.timestamp();
+	let not_after = validity.not_after.timestamp();
+	let now = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|d| d.as_secs() as i64)
+		.unwrap_or_default();
+	out["subject"] = Value::String(cert.subject().to_string());
+	out["issuer"] = Value::String(cert.issuer().to_string());
+	out["not_before"] = Value::String(format_timestamp(not_before));
+	out["not_after"] = Value::String(format_timestamp(not_after));
+	out["expires_in_days"] = Value::from((not_after - now).div_euclid(86_400));
+	out["serial"] = Value::String(cert.raw_serial_as_string());
+
+	if with_sans {
+		let sans: Vec<Value> = cert
+			.subject_alternative_name()
+			.ok()
+			.flatten()
+			.map(|ext| {
+				ext.value
+					.general_names
+					.iter()
+					.filter_map(|name| match name {
+						GeneralName::DNSName(dns) => Some(dns.to_string()),
+						GeneralName::IPAddress(bytes) => ip_from_bytes(bytes).map(|ip| ip.to_string()),
+						_ => None,
+					})
+					.map(Value::String)
+					.collect()
+			})
+			.unwrap_or_default();
+		out["sans"] = Value::Array(sans);
+	}
+	out
+}
+
+fn ip_from_bytes(bytes: &[u8]) -> Option<IpAddr> {
+	match bytes.len() {
+		4 => <[u8; 4]>::try_from(bytes).ok().map(IpAddr::from),
+		16 => <[u8; 16]>::try_from(bytes).ok().map(IpAddr::from),
+		_ => None,
+	}
+}
+
+fn format_timestamp(secs: i64) -> String {
+	let time = if secs >= 0 {
+		UNIX_EPOCH + Duration::from_secs(secs as u64)
+	} else {
+		UNIX_EPOCH - Duration::from_secs(secs.unsigned_abs())
+	};
+	humantime::format_rfc3339_seconds(time).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn fingerprints_normalize_to_colon_hex() {
+		let colon = "ab:".repeat(31) + "ab";
+		let expected = "AB:".repeat(31) + "AB";
+		assert_eq!(normalize_fingerprint(&colon).unwrap(), expected);
+		assert_eq!(normalize_fingerprint(&format!("sha256:{}", "ab".repeat(32))).unwrap(), expected);
+		assert!(normalize_fingerprint("abcd").is_err());
+		assert_eq!(fingerprint(b"").len(), 32 * 3 - 1);
+	}
+
+	#[test]
+	fn pin_mismatch_is_found_through_io_and_rustls_wrappers() {
+		let mismatch = PinMismatch {
+			expected: "AA".to_string(),
+			actual: "BB".to_string(),
+		};
+		let rustls_err = rustls::Error::InvalidCertificate(CertificateError::Other(OtherError(Arc::new(mismatch))));
+		let io = std::io::Error::new(std::io::ErrorKind::InvalidData, rustls_err);
+		let found = pin_mismatch(&io).expect("mismatch in chain");
+		assert_eq!(found.actual, "BB");
+	}
+
+	/// Self-signed P-256 certificate for `panel.test` (valid until 2126) and its PKCS#8 key.
+	const PANEL_CERT: &str = concat!(
+		"MIIBmTCCAT6gAwIBAgIUVbu4NGX6vHe6hv+KMZYFJV42LTgwCgYIKoZIzj0EAwIwFTETMBEGA1UEAwwKcGFuZWwudGVzdDAg",
+		"Fw0yNjEwMTYxNDA2MjZaGA8yMTI2MDkyMjE0MDYyNlowFTETMBEGA1UEAwwKcGFuZWwudGVzdDBZMBMGByqGSM49AgEGCCqG",
+		"SM49AwEHA0IABP16g/Qxli317b9bZUJ0GY6epA4+KXGZusC/874Ai2IUiKa1uYIb061Pkb0hmVDb5KSzg4sNolGUFX5H0PRn",
+		"EuejajBoMB0GA1UdDgQWBBQXuCfrmDMJk1BLnaRr0L8IhxVUiDAfBgNVHSMEGDAWgBQXuCfrmDMJk1BLnaRr0L8IhxVUiDAP",
+		"BgNVHRMBAf8EBTADAQH/MBUGA1UdEQQOMAyCCnBhbmVsLnRlc3QwCgYIKoZIzj0EAwIDSQAwRgIhAN6RJomPLK7q7IA+Xlk2",
+		"Mzovznqe3pLZWS2t2BjnG+u0AiEAzXQiFRvXjuCDKKyQSivsADnkMnZEgSuxnXblQ7iIDzM=",
+	);
+	const PANEL_KEY: &str = concat!(
+		"MIGHAgEAMBMGByqGSM49AgEGCCqGSM49AwEHBG0wawIBAQQgjmIltyUGcYyrCXNg3QBlK6h71Cv9uGq7BO0zgdX1KMyhRANC",
+		"AAT9eoP0MZYt9e2/W2VCdBmOnqQOPilxmbrAv/O+AItiFIimtbmCG9OtT5G9IZlQ2+Sks4OLDaJRlBV+R9D0ZxLn",
+	);
+
+	fn decode(b64: &str) -> Vec<u8> {
+		use base64::Engine;
+		base64::engine::general_purpose::STANDARD.decode(b64).unwrap()
+	}
+
+	fn verify_with_pin(pin: &str) -> Result<ServerCertVerified, rustls::Error> {
+		let verifier = PinnedVerifier {
+			provider: provider(),
+			pin: normalize_fingerprint(pin).unwrap(),
+		};
+		let cert = CertificateDer::from(decode(PANEL_CERT));
+		let name = ServerName::try_from("panel.test").unwrap();
+		verifier.verify_server_cert(&cert, &[], &name, &[], UnixTime::now())
+	}
+
+	#[test]
+	fn pinned_verifier_accepts_the_pinned_leaf_and_reports_a_mismatch() {
+		let pin = fingerprint(&decode(PANEL_CERT));
+		assert!(verify_with_pin(&pin.to_ascii_lowercase()).is_ok());
+
+		let other = "00:".repeat(31) + "00";
+		let err = verify_with_pin(&other).unwrap_err();
+		let found = pin_mismatch(&err).expect("mismatch in error");
+		assert_eq!(found.expected, other);
+		assert_eq!(found.actual, pin);
+	}
+
+	#[tokio::test]
+	async fn pinned_handshake_succeeds_only_with_the_matching_pin() {
+		use rustls::pki_types::{PrivateKeyDer, PrivatePkcs8KeyDer};
+		use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+		use crate::mock_server::{MockServer, Response};
+
+		let server = rustls::ServerConfig::builder_with_provider(provider())
+			.with_safe_default_protocol_versions()
+			.unwrap()
+			.with_no_client_auth()
+			.with_single_cert(
+				vec![CertificateDer::from(decode(PANEL_CERT))],
+				PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(decode(PANEL_KEY))),
+			)
+			.unwrap();
+		let acceptor = tokio_rustls::TlsAcceptor::from(Arc::new(server));
+		let panel = MockServer::start_tls(acceptor, |_| Response::json("ok")).await;
+		let addr = panel.addr;
+
+		let connect = |pin: String| async move {
+			let config = pinned_client_config(&pin).unwrap();
+			let stream = TcpStream::connect(addr).await?;
+			let name = ServerName::try_from("panel.test").unwrap();
+			let mut tls = TlsConnector::from(Arc::new(config)).connect(name, stream).await?;
+			tls.write_all(b"GET / HTTP/1.1\r\nhost: panel.test\r\n\r\n").await?;
+			let mut response = String::new();
+			tls.read_to_string(&mut response).await?;
+			Ok::<_, std::io::Error>(response.rsplit("\r\n").next().unwrap_or_default().to_string())
+		};
+
+		let pin = fingerprint(&decode(PANEL_CERT));
+		assert_eq!(connect(pin.clone()).await.unwrap(), "ok");
+
+		let err = connect("11:".repeat(31) + "11").await.unwrap_err();
+		assert_eq!(pin_mismatch(&err).expect("mismatch in error").actual, pin);
+	}
+}